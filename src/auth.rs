@@ -0,0 +1,160 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use git2::Repository;
+use parking_lot::Mutex;
+
+use crate::{
+    gitinfo::{self, AuthFailed},
+    throttle::FetchLimits,
+    util::unix_seconds,
+};
+
+/// How long fetches from a host are skipped after its authentication failed.
+pub const RETRY_AFTER: Duration = Duration::from_hours(24);
+
+/// Remembers the hosts that rejected the credentials of a fetch, so that their repositories
+/// are skipped for `RETRY_AFTER` instead of failing one by one on every run.
+///
+/// Repositories are grouped by the host of their remote, which is what credentials are
+/// usually tied to. A successful fetch from a host forgets its failure again.
+#[derive(Debug, Default)]
+pub struct AuthFailures {
+    /// Where the failures are kept between runs, if anywhere.
+    path: Option<PathBuf>,
+    /// Fetch from every host, even those that failed recently.
+    retry: bool,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// When the authentication with a host last failed, in seconds since the Unix epoch.
+    failed_at: BTreeMap<String, u64>,
+    /// How many repositories were not fetched, per host.
+    skipped: BTreeMap<String, usize>,
+    /// Whether `failed_at` differs from the file it was read from.
+    changed: bool,
+}
+
+impl AuthFailures {
+    /// Reads the failures remembered at `path`. A missing or unreadable file is taken as
+    /// no failures.
+    /// # Arguments
+    /// * `path` - The file the failures are kept in between runs, `None` to only
+    ///   remember them during this run.
+    /// * `retry` - Fetch from every host, even those that failed recently.
+    #[must_use]
+    pub fn load(path: Option<&Path>, retry: bool) -> Self {
+        let failed_at = path
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self {
+            path: path.map(Path::to_path_buf),
+            retry,
+            state: Mutex::new(State {
+                failed_at,
+                ..State::default()
+            }),
+        }
+    }
+
+    /// Returns where the failures are kept by default: `git-statuses/auth-failures.json`
+    /// in `$XDG_CACHE_HOME`, or in `~/.cache` if that is not set.
+    #[must_use]
+    pub fn default_path() -> Option<PathBuf> {
+        env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+            .map(|cache| cache.join("git-statuses").join("auth-failures.json"))
+    }
+
+    /// Fetches like `gitinfo::fetch_origin`, unless the authentication with the host of the
+    /// remote failed less than `RETRY_AFTER` ago. Skipped fetches are counted for `finish`.
+    /// The fetch waits for a slot in `limits`, a skipped one does not.
+    ///
+    /// # Errors
+    /// Returns the error of the fetch. A skipped fetch is no error.
+    pub fn fetch(
+        &self,
+        repo: &Repository,
+        prune: bool,
+        limits: &FetchLimits,
+    ) -> anyhow::Result<()> {
+        let host = gitinfo::get_remote_url(repo)
+            .and_then(|url| gitinfo::remote_host_and_owner(&url))
+            .map(|(host, _)| host);
+        let Some(host) = host else {
+            let _permit = limits.acquire(None);
+            return gitinfo::fetch_origin(repo, prune);
+        };
+
+        let now = unix_seconds(SystemTime::now());
+        let mut state = self.state.lock();
+        let recent = state
+            .failed_at
+            .get(&host)
+            .is_some_and(|&at| now.saturating_sub(at) < RETRY_AFTER.as_secs());
+        if recent && !self.retry {
+            *state.skipped.entry(host).or_default() += 1;
+            return Ok(());
+        }
+        drop(state);
+
+        let permit = limits.acquire(Some(&host));
+        let result = gitinfo::fetch_origin(repo, prune);
+        drop(permit);
+        let mut state = self.state.lock();
+        match &result {
+            Err(e) if e.is::<AuthFailed>() => {
+                state.failed_at.insert(host, now);
+                state.changed = true;
+            }
+            Ok(()) => {
+                state.changed |= state.failed_at.remove(&host).is_some();
+            }
+            Err(_) => {}
+        }
+        result
+    }
+
+    /// Logs how many repositories were skipped per host and saves the failures.
+    ///
+    /// Saving is best-effort: a cache that cannot be written only means the next run
+    /// tries again.
+    pub fn finish(self) {
+        for (host, count) in self.skipped() {
+            log::warn!(
+                "{count} {} skipped: authentication failed for {host} (--retry-auth fetches them anyway)",
+                if count == 1 {
+                    "repository"
+                } else {
+                    "repositories"
+                },
+            );
+        }
+
+        let state = self.state.into_inner();
+        let Some(path) = self.path.filter(|_| state.changed) else {
+            return;
+        };
+        let saved = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&path, serde_json::json!(state.failed_at).to_string()));
+        if let Err(e) = saved {
+            log::debug!("Failed to write {}: {e}", path.display());
+        }
+    }
+
+    /// Returns how many repositories were not fetched so far, per host.
+    #[must_use]
+    pub fn skipped(&self) -> BTreeMap<String, usize> {
+        self.state.lock().skipped.clone()
+    }
+}
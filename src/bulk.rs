@@ -0,0 +1,434 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io::Write,
+    path::Path,
+    process::Command,
+};
+
+use anyhow::Context as _;
+use git2::Repository;
+use rayon::{
+    ThreadPoolBuilder,
+    iter::{IntoParallelRefIterator as _, ParallelIterator as _},
+};
+
+use crate::{
+    gitinfo::{
+        self,
+        repoinfo::{Housekeeping, RepoInfo},
+        status::Status,
+    },
+    util,
+};
+
+/// What a bulk operation did to a repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The operation succeeded, with what it did.
+    Done(String),
+    /// `--dry-run`: what the operation would have done.
+    WouldDo(String),
+    /// The repository was left alone, with the reason.
+    Skipped(String),
+    /// The operation failed, with the error.
+    Failed(String),
+}
+
+impl Outcome {
+    /// Returns the color the outcome is shown in.
+    #[must_use]
+    pub const fn comfy_color(&self) -> comfy_table::Color {
+        match self {
+            Self::Done(_) => comfy_table::Color::Green,
+            Self::WouldDo(_) => comfy_table::Color::Cyan,
+            Self::Skipped(_) => comfy_table::Color::Yellow,
+            Self::Failed(_) => comfy_table::Color::Red,
+        }
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Done(what) => f.write_str(what),
+            Self::WouldDo(what) => write!(f, "would {what}"),
+            Self::Skipped(why) => write!(f, "skipped: {why}"),
+            Self::Failed(error) => write!(f, "failed: {error}"),
+        }
+    }
+}
+
+/// The outcome of a bulk operation for one repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BulkResult {
+    /// The relative path of the repository.
+    pub repo_path: String,
+    /// What happened.
+    pub outcome: Outcome,
+}
+
+/// Pulls every repository that is behind its upstream, with the git executable.
+///
+/// A repository that is only behind is fast-forwarded. One that has diverged is only
+/// pulled if its configuration says how (`pull.rebase`, `pull.ff` or
+/// `branch.<name>.rebase`), and one with changes in its working directory is skipped.
+/// # Arguments
+/// * `repos` - The scanned repositories. Those not behind are left out of the result.
+/// * `dry_run` - Only report what would be done.
+/// # Returns
+/// One result per repository that is behind, in the order of `repos`.
+pub fn pull(repos: &[RepoInfo], dry_run: bool) -> Vec<BulkResult> {
+    repos
+        .par_iter()
+        .filter(|repo| repo.behind > 0)
+        .map(|repo| BulkResult {
+            repo_path: repo.repo_path.clone(),
+            outcome: pull_one(repo, dry_run),
+        })
+        .collect()
+}
+
+/// Pulls a single repository that is behind, see `pull`.
+fn pull_one(repo: &RepoInfo, dry_run: bool) -> Outcome {
+    if !has_clean_working_tree(&repo.status) {
+        return Outcome::Skipped(format!("the status is {}", repo.status));
+    }
+    if upstream(&repo.path, &repo.branch).is_some_and(|(remote, _)| remote == ".") {
+        return Outcome::Skipped("the upstream is a local branch".to_owned());
+    }
+    let fast_forward = repo.ahead == 0;
+    if !fast_forward && !pull_configured(&repo.path, &repo.branch) {
+        return Outcome::Skipped(
+            "diverged, set pull.rebase or pull.ff to say how to pull".to_owned(),
+        );
+    }
+    let (what, done) = if fast_forward {
+        (
+            format!("fast-forward {} commits", repo.behind),
+            format!("fast-forwarded {} commits", repo.behind),
+        )
+    } else {
+        (
+            format!(
+                "pull {} commits onto {} local ones",
+                repo.behind, repo.ahead
+            ),
+            format!(
+                "pulled {} commits onto {} local ones",
+                repo.behind, repo.ahead
+            ),
+        )
+    };
+    if dry_run {
+        return Outcome::WouldDo(what);
+    }
+    let mut pull = Command::new("git");
+    pull.arg("pull").arg("--quiet");
+    if fast_forward {
+        pull.arg("--ff-only");
+    }
+    match run_git(&mut pull, &repo.path) {
+        Ok(()) => Outcome::Done(done),
+        Err(error) => Outcome::Failed(error),
+    }
+}
+
+/// Pushes every repository that is ahead of its upstream to it, with the git executable.
+///
+/// Only the current branch is pushed, to the remote branch it tracks. A repository with
+/// changes in its working directory or whose branch has diverged is skipped.
+/// # Arguments
+/// * `repos` - The scanned repositories. Those not ahead are left out of the result.
+/// * `dry_run` - Only report what would be done.
+/// # Returns
+/// One result per repository that is ahead, in the order of `repos`.
+pub fn push(repos: &[RepoInfo], dry_run: bool) -> Vec<BulkResult> {
+    repos
+        .par_iter()
+        .filter(|repo| repo.ahead > 0)
+        .map(|repo| BulkResult {
+            repo_path: repo.repo_path.clone(),
+            outcome: push_one(repo, dry_run),
+        })
+        .collect()
+}
+
+/// Pushes a single repository that is ahead, see `push`.
+fn push_one(repo: &RepoInfo, dry_run: bool) -> Outcome {
+    if !has_clean_working_tree(&repo.status) {
+        return Outcome::Skipped(format!("the status is {}", repo.status));
+    }
+    if repo.behind > 0 {
+        return Outcome::Skipped(format!(
+            "diverged, pull the {} new commits first",
+            repo.behind
+        ));
+    }
+    let Some((remote, merge)) = upstream(&repo.path, &repo.branch) else {
+        return Outcome::Skipped("no upstream is configured".to_owned());
+    };
+    if remote == "." {
+        return Outcome::Skipped("the upstream is a local branch".to_owned());
+    }
+    let target = format!(
+        "{remote}/{}",
+        merge.strip_prefix("refs/heads/").unwrap_or(&merge)
+    );
+    if dry_run {
+        return Outcome::WouldDo(format!("push {} commits to {target}", repo.ahead));
+    }
+    let mut push = Command::new("git");
+    push.arg("push")
+        .arg("--quiet")
+        .arg("--")
+        .arg(&remote)
+        .arg(format!("HEAD:{merge}"));
+    match run_git(&mut push, &repo.path) {
+        Ok(()) => Outcome::Done(format!("pushed {} commits to {target}", repo.ahead)),
+        Err(error) => Outcome::Failed(error),
+    }
+}
+
+/// Runs `git gc` in every repository, with the git executable, which packs the loose
+/// objects and packfiles and drops unreachable objects once they expired.
+///
+/// Repositories another git process is working on, with stale locks or that are corrupt
+/// are skipped, as `git gc` would fail on them or could make matters worse.
+/// # Arguments
+/// * `repos` - The repositories to clean up.
+/// * `dry_run` - Only report what would be done.
+/// # Returns
+/// One result per repository, in the order of `repos`.
+pub fn gc(repos: &[RepoInfo], dry_run: bool) -> Vec<BulkResult> {
+    repos
+        .par_iter()
+        .map(|repo| BulkResult {
+            repo_path: repo.repo_path.clone(),
+            outcome: gc_one(repo, dry_run),
+        })
+        .collect()
+}
+
+/// Runs `git gc` in a single repository, see `gc`.
+fn gc_one(repo: &RepoInfo, dry_run: bool) -> Outcome {
+    if matches!(
+        repo.status,
+        Status::Busy | Status::StaleLock | Status::Corrupt
+    ) {
+        return Outcome::Skipped(format!("the status is {}", repo.status));
+    }
+    let Some(before) = repo.housekeeping.or_else(|| housekeeping(&repo.path)) else {
+        return Outcome::Failed("Failed to open the repository".to_owned());
+    };
+    if dry_run {
+        return Outcome::WouldDo(format!(
+            "pack {} loose objects and {} packs, {} in total",
+            before.loose_objects,
+            before.packs,
+            util::format_size(before.git_size)
+        ));
+    }
+    let mut gc = Command::new("git");
+    gc.arg("gc").arg("--quiet");
+    if let Err(error) = run_git(&mut gc, &repo.path) {
+        return Outcome::Failed(error);
+    }
+    let after = housekeeping(&repo.path).unwrap_or_default();
+    Outcome::Done(format!(
+        "packed, {} → {}",
+        util::format_size(before.git_size),
+        util::format_size(after.git_size)
+    ))
+}
+
+/// Opens a repository to look at its object store, see `gitinfo::get_housekeeping`.
+fn housekeeping(path: &Path) -> Option<Housekeeping> {
+    Repository::open(path)
+        .ok()
+        .map(|repo| gitinfo::get_housekeeping(&repo))
+}
+
+/// Runs a shell command in every repository, `sh -c` (`cmd /C` on Windows) with the
+/// repository as working directory.
+///
+/// `{name}`, `{branch}` and `{path}` in the command are replaced with the name, the branch
+/// and the absolute path of each repository, quoted for the shell. The output of the
+/// commands is collected and written per repository once all are done, so that the
+/// output of commands running at the same time does not interleave.
+/// # Arguments
+/// * `repos` - The repositories to run the command in.
+/// * `command` - The command, with placeholders.
+/// * `jobs` - How many commands to run at the same time, `0` for one per CPU.
+/// * `out` - Where to write the output of the commands to, below a header per repository.
+/// # Returns
+/// The exit code per repository, in the order of `repos`.
+///
+/// # Errors
+/// Returns an error if the threads cannot be started or writing to `out` fails.
+pub fn exec(
+    repos: &[RepoInfo],
+    command: &str,
+    jobs: usize,
+    out: &mut impl Write,
+) -> anyhow::Result<Vec<BulkResult>> {
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build()
+        .context("Failed to start the threads to run the command")?;
+    let runs: Vec<(BulkResult, Vec<u8>)> = pool.install(|| {
+        repos
+            .par_iter()
+            .map(|repo| {
+                let (outcome, output) = exec_one(repo, command);
+                let result = BulkResult {
+                    repo_path: repo.repo_path.clone(),
+                    outcome,
+                };
+                (result, output)
+            })
+            .collect()
+    });
+    let mut results = Vec::with_capacity(runs.len());
+    for (result, output) in runs {
+        if !output.is_empty() {
+            writeln!(out, "==> {} <==", result.repo_path)?;
+            out.write_all(&output)?;
+            if !output.ends_with(b"\n") {
+                writeln!(out)?;
+            }
+        }
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// Runs the command of `exec` in a single repository.
+/// # Returns
+/// The exit code as outcome, and what the command wrote to stdout followed by stderr.
+fn exec_one(repo: &RepoInfo, command: &str) -> (Outcome, Vec<u8>) {
+    let command = substitute(command, repo);
+    match shell(&command).current_dir(&repo.path).output() {
+        Ok(output) => {
+            let outcome = match output.status.code() {
+                Some(0) => Outcome::Done("exit code 0".to_owned()),
+                Some(code) => Outcome::Failed(format!("exit code {code}")),
+                None => Outcome::Failed("killed by a signal".to_owned()),
+            };
+            let mut text = output.stdout;
+            text.extend(output.stderr);
+            (outcome, text)
+        }
+        Err(e) => (
+            Outcome::Failed(format!("Failed to run the command: {e}")),
+            Vec::new(),
+        ),
+    }
+}
+
+/// Substitutes the placeholders `{name}`, `{branch}` and `{path}` of `command` with the
+/// quoted values of `repo`. The command is read once from left to right, so that a
+/// placeholder within a substituted value stays as it is.
+fn substitute(command: &str, repo: &RepoInfo) -> String {
+    let full_path = repo.path.to_string_lossy();
+    let placeholders = [
+        ("{name}", repo.name.as_str()),
+        ("{branch}", repo.branch.as_str()),
+        ("{path}", full_path.as_ref()),
+    ];
+    let mut substituted = String::with_capacity(command.len());
+    let mut rest = command;
+    while let Some(start) = rest.find('{') {
+        substituted.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some((placeholder, value)) = placeholders
+            .iter()
+            .find(|(placeholder, _)| rest.starts_with(placeholder))
+        {
+            substituted.push_str(&shell_quote(value));
+            rest = &rest[placeholder.len()..];
+        } else {
+            substituted.push('{');
+            rest = &rest[1..];
+        }
+    }
+    substituted.push_str(rest);
+    substituted
+}
+
+/// Builds the process running `command` in the shell, `sh -c` (`cmd /C` on Windows).
+pub fn shell(command: &str) -> Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command);
+    shell
+}
+
+/// Quotes a value to be substituted into a shell command as a single word.
+fn shell_quote(value: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+/// Checks whether the working directory of a repository has no changes. The statuses of
+/// operations in progress, and those without an upstream, count as not clean.
+const fn has_clean_working_tree(status: &Status) -> bool {
+    match status {
+        Status::Clean | Status::Unpushed => true,
+        Status::Dirty(_)
+        | Status::SubmoduleDirty
+        | Status::Merge
+        | Status::Revert
+        | Status::Rebase
+        | Status::Bisect
+        | Status::CherryPick
+        | Status::Unpublished
+        | Status::Detached
+        | Status::Busy
+        | Status::StaleLock
+        | Status::Corrupt
+        | Status::Unknown => false,
+    }
+}
+
+/// Checks whether the configuration of a repository says how to pull a diverged branch.
+fn pull_configured(path: &Path, branch: &str) -> bool {
+    let Ok(config) = Repository::open(path).and_then(|repo| repo.config()) else {
+        return false;
+    };
+    ["pull.rebase", "pull.ff", &format!("branch.{branch}.rebase")]
+        .iter()
+        .any(|name| config.get_entry(name).is_ok())
+}
+
+/// Reads the remote and the remote branch a local branch tracks, from `branch.<name>.remote`
+/// and `branch.<name>.merge`. The remote is `.` for a branch that tracks a local branch.
+fn upstream(path: &Path, branch: &str) -> Option<(String, String)> {
+    let config = Repository::open(path).and_then(|repo| repo.config()).ok()?;
+    let remote = config.get_string(&format!("branch.{branch}.remote")).ok()?;
+    let merge = config.get_string(&format!("branch.{branch}.merge")).ok()?;
+    Some((remote, merge))
+}
+
+/// Runs a git command in `dir` without prompting for credentials.
+fn run_git(command: &mut Command, dir: &Path) -> Result<(), String> {
+    let output = command
+        .current_dir(dir)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|e| format!("Failed to run git: {e}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_owned())
+    }
+}
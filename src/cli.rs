@@ -1,17 +1,46 @@
-use std::{borrow::Cow, ffi::OsStr, path::PathBuf, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    error::Error,
+    ffi::OsStr,
+    fmt::{self, Display, Formatter},
+    fs,
+    io::{self, BufRead, IsTerminal as _},
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-use clap::Parser;
+use anyhow::Context as _;
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
 use clap_complete::Shell;
-use parking_lot::RwLock;
-use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
-use walkdir::WalkDir;
 
+#[cfg(feature = "daemon")]
+use crate::daemon;
 use crate::{
-    gitinfo::{repoinfo::RepoInfo, status::Status},
-    util::GitPathExt as _,
+    auth::AuthFailures,
+    disk_usage::DiskUsageCache,
+    forge::{self, Forge},
+    gitinfo::{
+        self,
+        repoinfo::{FailedRepo, RepoInfo, RepoListing},
+        status::Status,
+    },
+    lint::CommitPolicy,
+    porcelain::PorcelainVersion,
+    printer::Style,
+    registry,
+    remotes::Conversion,
+    report::{GroupBy, Rollup},
+    scanner::{
+        FailureBudget, FetchSelector, HostLimit, IntegrityCheck, ScanEvent, ScanResult, Scanner,
+    },
+    theme::{Palette, StatusColor, Theme},
+    webhook::WebhookFormat,
+    workspace::Editor,
 };
 
-/// Scan the given directory for Git repositories and display their status.
+/// Scan the given directories for Git repositories and display their status.
 /// A Repository turns red if it has unpushed changes.
 #[expect(
     clippy::struct_excessive_bools,
@@ -19,10 +48,21 @@ use crate::{
 )]
 #[derive(Parser, Debug, Default)]
 #[command(author, version, about, long_about = None)]
+// A subcommand may follow the directories, e.g. `git-statuses ~/work report`.
+#[command(subcommand_precedence_over_arg = true)]
+#[command(group(
+    ArgGroup::new("changes").args(["prune_merged", "convert_remotes", "remove_stale_locks"])
+))]
+#[command(group(ArgGroup::new("bulk").args(["pull", "push", "gc"]).multiple(true)))]
+#[command(group(ArgGroup::new("confirmed").args(["push", "gc"]).multiple(true)))]
+#[command(group(ArgGroup::new("confirmation").args(["yes", "dry_run"]).multiple(true)))]
 pub struct Args {
-    /// Directory to scan
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Directories to scan. With more than one, the path of every repository starts with
+    /// the name of the directory it was found in
     #[arg(default_value = ".")]
-    pub dir: PathBuf,
+    pub dirs: Vec<PathBuf>,
     /// Recursively scan all subdirectories to the given depth.
     /// If set to 1, only the current directory is scanned.
     /// If set to a negative value, all subdirectories are scanned. (this may take a while)
@@ -34,19 +74,190 @@ pub struct Args {
     /// Use a condensed layout
     #[arg(short, long)]
     pub condensed: bool,
-    /// Show a summary of the scan
+    /// The colors to show the statuses in
+    #[arg(long, value_name = "PALETTE", default_value = "default")]
+    pub theme: Palette,
+    /// Show STATUS in COLOR instead of the color of the `--theme`, e.g. `dirty=yellow` or
+    /// `unpushed=#e69f00`. STATUS is one of clean, dirty, submodule-dirty, merge, revert,
+    /// rebase, bisect, cherry-pick, unpushed, unpublished, detached, busy, stale-lock,
+    /// corrupt and unknown; COLOR a name like `dark-yellow` or `#rrggbb`. Can be repeated
+    #[arg(long, value_name = "STATUS=COLOR")]
+    pub status_color: Vec<StatusColor>,
+    /// Draw tables, arrows and other symbols with ASCII characters only, e.g. `^2 v1` for
+    /// ahead and behind, for terminals, CI logs and ticketing systems that mangle Unicode
+    #[arg(long)]
+    pub ascii: bool,
+    /// Show every column of the table in full, even if the terminal is too narrow for it.
+    /// Otherwise narrow terminals hide the Commits column and shorten the Local column and
+    /// long paths
+    #[arg(long)]
+    pub wide: bool,
+    /// Show a summary of the scan. With `--json` it is added as a `summary` field
     #[arg(short = 's', long)]
     pub summary: bool,
+    /// After the scan, post its summary to this URL, e.g. an incoming webhook of Slack.
+    /// Like the summary, it counts every repository, whatever the filters
+    #[arg(
+        long,
+        value_name = "URL",
+        conflicts_with_all = ["list_only", "watch", "exec", "changes", "remotes"]
+    )]
+    pub report_webhook: Option<String>,
+    /// What to post to `--report-webhook`
+    #[arg(long, value_enum, default_value_t, requires = "report_webhook")]
+    pub webhook_format: WebhookFormat,
+    /// Also list the repositories that are not clean in the `--report-webhook` payload
+    #[arg(long, requires = "report_webhook")]
+    pub webhook_repos: bool,
+    /// Scan a repository reached through several paths, e.g. through a symlink, once per
+    /// path instead of once under the shortest of them
+    #[arg(long)]
+    pub no_dedupe: bool,
+    /// Instead of the table, list the remotes that more than one repository is a checkout
+    /// of, with the state of each checkout. Checkouts without changes, unpushed commits or
+    /// stashes are marked disposable, as deleting them loses nothing
+    #[arg(long, conflicts_with_all = ["list_only", "watch", "diff", "stream", "exec"])]
+    pub duplicates: bool,
+    /// Instead of the table, draw the repositories nested under the directories they live
+    /// in, like the `tree` command, with their branch and status
+    #[arg(
+        long,
+        conflicts_with_all = ["list_only", "watch", "diff", "stream", "exec", "duplicates"]
+    )]
+    pub tree: bool,
+    /// Split the table into one section per group, each followed by how many of its
+    /// repositories are dirty, behind and ahead
+    #[arg(
+        long,
+        value_enum,
+        conflicts_with_all = ["list_only", "watch", "diff", "stream", "exec", "duplicates", "tree"]
+    )]
+    pub group_by: Option<GroupBy>,
+    /// Do not count the commits of every repository, which walks their whole history and is
+    /// the slowest part of scanning large repositories. The Commits column is left out. The
+    /// history is still walked down to the root commit with `--snapshot`, `--diff` and
+    /// `--changes-only`, which recognize moved repositories by it
+    #[arg(long)]
+    pub no_commit_count: bool,
+    /// Record how long opening, fetching, walking the history and gathering the status took
+    /// for every repository, and print the N slowest repositories after the table (10
+    /// without N). With `--json` it is added to every repository as a `timing` field
+    #[arg(
+        long,
+        value_name = "N",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "10"
+    )]
+    pub timing: Option<usize>,
     /// Run a fetch before scanning to update the repository state
     /// Note: This may take a while for large repositories.
-    #[arg(short, long)]
-    pub fetch: bool,
+    /// `--fetch=behind` only fetches the repositories already behind their upstream,
+    /// `--fetch=remote:github.com/acme/*` those whose remote matches the pattern.
+    /// The other repositories are scanned without fetching, and `--ff` skips them as well.
+    /// The selector has to follow an `=`: in `--fetch behind`, `behind` is a directory
+    #[arg(
+        short,
+        long,
+        value_name = "SELECTOR",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "all"
+    )]
+    pub fetch: Option<FetchSelector>,
     /// Run a fast-forward merge after fetching
     #[arg(short = 'F', long = "ff")]
     pub fast_forward: bool,
-    /// Print a legend explaining the color codes and statuses used in the output
+    /// Remove remote-tracking branches that were deleted on the remote while fetching, like
+    /// `git fetch --prune`, so branches whose upstream is gone are recognized
+    #[arg(long)]
+    pub prune: bool,
+    /// After the scan, pull every repository that is behind its upstream and print what
+    /// happened to each. Repositories with changes are skipped, and diverged ones are only
+    /// pulled if `pull.rebase` or `pull.ff` is configured. Implies `--fetch`
+    #[arg(long, conflicts_with_all = ["list_only", "watch"])]
+    pub pull: bool,
+    /// After the scan, push every repository that is ahead of its upstream to it and print
+    /// what happened to each. Repositories with changes or whose branch has diverged are
+    /// skipped. Needs `--yes`, or `--dry-run` to only see what would be pushed
+    #[arg(long, requires = "confirmation", conflicts_with_all = ["list_only", "watch"])]
+    pub push: bool,
+    /// After the scan, run `git gc` in every repository that passes the filters, to pack its
+    /// loose objects and packfiles, and print how the size of its git directory changed.
+    /// Busy, locked and corrupt repositories are skipped. Implies `--maintenance`. Needs
+    /// `--yes`, or `--dry-run` to only see what would be collected
+    #[arg(long, requires = "confirmation", conflicts_with_all = ["list_only", "watch"])]
+    pub gc: bool,
+    /// With `--push` or `--gc`, go ahead without further confirmation
+    #[arg(long, requires = "confirmed")]
+    pub yes: bool,
+    /// With `--pull`, `--push` or `--gc`, only print what would be done
+    #[arg(long, requires = "bulk")]
+    pub dry_run: bool,
+    /// Run a shell command in every repository that passes the filters instead of printing
+    /// the table, then print its output and exit code per repository. `{name}`, `{branch}`
+    /// and `{path}` in the command are replaced with those of the repository
+    #[arg(
+        long,
+        value_name = "COMMAND",
+        conflicts_with_all = ["list_only", "watch", "snapshot", "diff", "bulk"]
+    )]
+    pub exec: Option<String>,
+    /// With `--exec`, how many commands to run at the same time. 0 runs one per CPU
+    #[arg(long, value_name = "N", default_value_t = 0, requires = "exec")]
+    pub jobs: usize,
+    /// Instead of the table, pick one of the repositories that pass the filters with `fzf`
+    /// and print only its path, e.g. for `cd "$(git-statuses --pick --non-clean)"`.
+    /// Everything else is logged to stderr
+    #[arg(
+        long,
+        conflicts_with_all = ["list_only", "watch", "snapshot", "diff", "bulk", "exec", "stream"]
+    )]
+    pub pick: bool,
+    /// With `--pick`, the shell command to pick with. It reads one line per repository,
+    /// starting with its relative path, and writes the picked line
+    #[arg(long, value_name = "COMMAND", default_value = "fzf", requires = "pick")]
+    pub picker: String,
+    /// Count ahead/behind against the branch of the same name on this remote, e.g.
+    /// `upstream` in a fork, instead of the configured upstream branch. Branches the remote
+    /// does not have fall back to their upstream. Cannot be combined with `--pull` or
+    /// `--push`, which go by the configured upstream branch
+    #[arg(long, value_name = "REMOTE", conflicts_with_all = ["pull", "push"])]
+    pub upstream_remote: Option<String>,
+    /// Show how many commits the default branch of `origin` is behind that of `upstream`,
+    /// for forks with both remotes, as of their last fetch
+    #[arg(long)]
+    pub forks: bool,
+    /// Count the local branches whose upstream branch is gone from the remote, as of the
+    /// last fetch, e.g. after it was merged and deleted
+    #[arg(long)]
+    pub gone: bool,
+    /// With fetching, run at most N fetches at the same time, however many repositories are
+    /// scanned in parallel
+    #[arg(long, value_name = "N")]
+    pub fetch_jobs: Option<NonZeroUsize>,
+    /// With fetching, run at most N fetches from the hosts matching HOST at the same time,
+    /// e.g. `github.com=4`. `*` in HOST stands for any run of characters. May be given
+    /// several times, the first matching limit applies
+    #[arg(long, value_name = "HOST=N")]
+    pub fetch_host_limit: Vec<HostLimit>,
+    /// Fetch from hosts whose authentication failed within the last day as well. Otherwise
+    /// their repositories are not fetched, and one line per host says how many were skipped
+    #[arg(long)]
+    pub retry_auth: bool,
+    /// Seconds between progress lines while fetching, printed only when stderr is not a
+    /// terminal (e.g. in CI). 0 disables them
+    #[arg(long, value_name = "SECS", default_value = "10")]
+    pub progress_interval: u64,
+    /// Print a legend explaining the statuses and their colors, the columns and the symbols
+    /// used in the output
     #[arg(short, long)]
     pub legend: bool,
+    /// Print a one-line summary of the repository the directory is in, e.g.
+    /// `main ↑2 ↓0 ✚3 ⚑1` (changed files and stashes), for a shell prompt. Nothing else is
+    /// scanned and nothing is printed outside of a repository
+    #[arg(long)]
+    pub prompt: bool,
     /// Look in a specific subdir if it exists for each folder
     /// This can be useful, if you don't checkout in a folder directly
     /// but in a subfolder like `repo-name/checkout`
@@ -58,118 +269,954 @@ pub struct Args {
     /// Show the path to the repository
     #[arg(short, long)]
     pub path: bool,
+    /// Show whether a repository is a shallow or partial clone, whose counts are incomplete
+    #[arg(long)]
+    pub clones: bool,
+    /// Show the `user.email` each repository commits with. `--identity=*@acme.com` marks
+    /// the repositories whose email does not match the pattern, in which `*` stands for
+    /// anything
+    #[arg(
+        long,
+        value_name = "PATTERN",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "*"
+    )]
+    pub identity: Option<String>,
+    /// Show whether the Git LFS files of a repository were downloaded, or are only pointers
+    #[arg(long)]
+    pub lfs: bool,
+    /// Show how active a repository is: the date of its first commit, its commits of the
+    /// last 30 days and its number of authors. Walks the whole history of every repository.
+    /// With `--json` it is added to every repository as an `activity` field
+    #[arg(long)]
+    pub activity: bool,
+    /// Check every repository for corruption and show a corrupt one as `Corrupt` rather than
+    /// failing on it. `refs` checks that HEAD, the refs and the index can be read and that
+    /// the objects they point to exist, `fsck` also runs the slower `git fsck --no-full`
+    #[arg(
+        long,
+        value_name = "CHECK",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "refs"
+    )]
+    pub check_integrity: Option<IntegrityCheck>,
+    /// Show what `git gc` would clean up: the number of loose objects and packfiles, and
+    /// the size of the git directory. With `--json` it is added to every repository as a
+    /// `housekeeping` field
+    #[arg(long)]
+    pub maintenance: bool,
+    /// Show how much disk space the working directory, including ignored files, and the
+    /// git directory of every repository take. Reads every file, so the sizes are kept
+    /// for an hour. With `--json` they are added to every repository as a `disk_usage` field
+    #[arg(long)]
+    pub disk_usage: bool,
+    /// Show how many files and directories of every repository are ignored, e.g. build
+    /// artifacts. An ignored directory counts once. Ignored files never make a repository
+    /// dirty
+    #[arg(long)]
+    pub show_ignored: bool,
+    /// Show how many files are marked with `git update-index --assume-unchanged` or
+    /// `--skip-worktree`, whose changes neither `git status` nor the status column show.
+    /// Files left out by a sparse checkout are not counted
+    #[arg(long)]
+    pub hidden_files: bool,
+    /// Check whether the HEAD commit, or the last COMMITS commits, of every repository are
+    /// signed and whether their GPG, SSH or X.509 signatures verify with the keys git is set
+    /// up with. Adds a column and, with `--summary`, the number of repositories with commits
+    /// that are not signed with a good signature
+    #[arg(
+        long,
+        value_name = "COMMITS",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "1"
+    )]
+    pub verify_signatures: Option<NonZeroUsize>,
+    /// Show the open pull requests and the CI status of the default branch of the
+    /// repositories on github.com. Needs curl, and a token in `GH_TOKEN` or `GITHUB_TOKEN`
+    /// for private repositories
+    #[arg(long)]
+    pub github: bool,
+    /// Like `--github`, for the repositories on HOST, whose forge is of KIND `github`,
+    /// `gitlab`, `gitea` or `forgejo`. The API is expected at the usual path on HOST unless
+    /// given, e.g. `git.acme.com=gitea:https://git.acme.com:3000/api/v1`. The token is read
+    /// from `GIT_STATUSES_TOKEN_<HOST>` (e.g. `GIT_STATUSES_TOKEN_GIT_ACME_COM`), or
+    /// `GITLAB_TOKEN` or `GITEA_TOKEN`. Can be given several times
+    #[arg(long, value_name = "HOST=KIND[:API_URL]")]
+    pub forge: Vec<Forge>,
     /// Only show non clean repositories
     #[arg(short = 'n', long)]
     pub non_clean: bool,
-    /// Output in JSON format
-    #[arg(long)]
+    /// Only show repositories at least N commits ahead of their upstream
+    #[arg(long, value_name = "N")]
+    pub min_ahead: Option<usize>,
+    /// Only show repositories at least N commits behind their upstream
+    #[arg(long, value_name = "N")]
+    pub min_behind: Option<usize>,
+    /// Only show repositories with at least N stashes
+    #[arg(long, value_name = "N")]
+    pub min_stashes: Option<usize>,
+    /// Only show repositories with at least N changed files
+    #[arg(long, value_name = "N")]
+    pub min_dirty: Option<usize>,
+    /// Output in JSON format, short for `--output json`
+    #[arg(long, conflicts_with = "output")]
     pub json: bool,
+    /// Output a tmux status line, short for `--output tmux`
+    #[arg(long, conflicts_with_all = ["output", "json"])]
+    pub tmux: bool,
+    /// Output JSON in a versioned format for scripts, whose fields only ever change with a
+    /// new VERSION, unlike those of `--json`. `--json-schema` prints its schema
+    #[arg(
+        long,
+        value_enum,
+        value_name = "VERSION",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "v1",
+        conflicts_with_all = ["output", "json", "tmux", "list_only", "stream", "duplicates", "tree", "group_by"]
+    )]
+    pub porcelain: Option<PorcelainVersion>,
+    /// Print the JSON schema of the `--porcelain` output of VERSION and exit
+    #[arg(
+        long,
+        value_enum,
+        value_name = "VERSION",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "v1"
+    )]
+    pub json_schema: Option<PorcelainVersion>,
+    /// The output format. `dot` draws a Graphviz graph of the repositories grouped by
+    /// remote host and owner, colored by status. `html` ignores the display options and
+    /// always includes the summary
+    #[arg(short, long, value_enum, default_value_t)]
+    pub output: OutputFormat,
+    /// With `--output plain` or `--output ndjson`, print every repository as soon as it is
+    /// processed, in the order they finish, instead of all of them sorted once the scan is done
+    #[arg(long, conflicts_with_all = ["list_only", "watch", "diff", "exec", "github", "forge"])]
+    pub stream: bool,
+    /// The Jinja template `--output template` renders. It sees `repositories`, `failed` and
+    /// `summary`, with the fields of the JSON output
+    #[cfg(feature = "template")]
+    #[arg(long, value_name = "FILE", required_if_eq("output", "template"))]
+    pub template: Option<PathBuf>,
+    /// End every line of `--output plain` and `--list-only` with a NUL instead of a newline,
+    /// for `xargs -0` and other tools that cope with any path
+    #[arg(short = '0', long)]
+    pub null: bool,
+    /// Suggest .gitignore entries for untracked build artifacts (`target/`, `node_modules/`, ...)
+    #[arg(long)]
+    pub gitignore_advice: bool,
+    /// Abort without printing the table if more repositories than this fail to process,
+    /// given as a count (`5`) or a percentage of the repositories found (`10%`).
+    /// The exit code is then 3
+    #[arg(long, value_name = "N[%]")]
+    pub max_failures: Option<FailureBudget>,
+    /// Only list the repositories found, without computing their status.
+    /// Much faster, and meant as input for other tools (one repository per line)
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "non_clean",
+            "min_ahead",
+            "min_behind",
+            "min_stashes",
+            "min_dirty",
+            "fetch",
+            "fast_forward"
+        ]
+    )]
+    pub list_only: bool,
+    /// Keep rescanning every SECS seconds until interrupted, printing the result each time
+    #[arg(long, value_name = "SECS", conflicts_with = "list_only")]
+    pub watch: Option<u64>,
+    /// Keep rescanning and serve gauges of the latest results, e.g. the dirty repositories
+    /// and the unpushed commits, to Prometheus on `http://ADDR/metrics`
+    #[cfg(feature = "metrics")]
+    #[arg(
+        long,
+        value_name = "ADDR",
+        conflicts_with_all = ["list_only", "watch", "exec", "stream"]
+    )]
+    pub serve_metrics: Option<String>,
+    /// With `--serve-metrics`, seconds to wait between two scans
+    #[cfg(feature = "metrics")]
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = 300,
+        requires = "serve_metrics"
+    )]
+    pub metrics_interval: u64,
+    /// With `--watch`, print the full result only once and afterwards just one timestamped
+    /// line per repository that changed
+    #[arg(long, requires = "watch")]
+    pub changes_only: bool,
+    /// Save the scan result to FILE, to compare a later scan against with `--diff`
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["list_only", "watch"])]
+    pub snapshot: Option<PathBuf>,
+    /// After the scan, write a workspace for EDITOR with one folder per repository that
+    /// passes the filters, e.g. `--emit-workspace code --non-clean` to open everything in
+    /// flight in one window
+    #[arg(
+        long,
+        value_enum,
+        value_name = "EDITOR",
+        conflicts_with_all = ["list_only", "watch"]
+    )]
+    pub emit_workspace: Option<Editor>,
+    /// With `--emit-workspace`, the file to write the workspace to [default: one named after
+    /// the scanned directory in it, `.idea/vcs.xml` for `idea`]
+    #[arg(long, value_name = "FILE", requires = "emit_workspace")]
+    pub workspace_file: Option<PathBuf>,
+    /// Compare the scan against a `--snapshot` saved earlier and only print the
+    /// repositories that were added, removed, moved or changed, with their old and new values
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["list_only", "watch", "json", "output"]
+    )]
+    pub diff: Option<PathBuf>,
+    /// Append every scan to the SQLite database at DB, which is created if needed
+    #[cfg(feature = "history")]
+    #[arg(long, value_name = "DB", conflicts_with = "list_only")]
+    pub history: Option<PathBuf>,
+    /// Print how the repositories recorded in the `--history` database developed instead of
+    /// scanning: how long they have not been clean and how their ahead/behind counts changed
+    #[cfg(feature = "history")]
+    #[arg(long, requires = "history", conflicts_with_all = ["watch", "snapshot", "diff"])]
+    pub history_report: bool,
+    /// List the local branches that are merged into the default branch or whose upstream
+    /// is gone, instead of scanning. Nothing is deleted without `--apply`, and like with
+    /// `git branch -d` a branch whose upstream is gone is kept unless HEAD has its commits
+    #[arg(long, conflicts_with_all = ["list_only", "watch", "snapshot", "diff"])]
+    pub prune_merged: bool,
+    /// Write the name, relative path and remote URL of every repository found to FILE as
+    /// JSON instead of scanning, for `clone-missing` to recreate the directory elsewhere
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["list_only", "watch", "snapshot", "diff"]
+    )]
+    pub export_manifest: Option<PathBuf>,
+    /// List every remote with its protocol (ssh, https, http, git or file) and URL instead
+    /// of scanning
+    #[arg(long, conflicts_with_all = ["list_only", "watch", "snapshot", "diff"])]
+    pub remotes: bool,
+    /// List the remotes that use the protocol FROM with the URL they would have with the
+    /// protocol TO, e.g. `ssh:https`, instead of scanning. Nothing is rewritten without
+    /// `--apply`
+    #[arg(
+        long,
+        value_name = "FROM:TO",
+        conflicts_with_all = ["list_only", "watch", "snapshot", "diff", "remotes"]
+    )]
+    pub convert_remotes: Option<Conversion>,
+    /// List the lock files that crashed git processes left behind, which make every git
+    /// command that changes the repository fail, instead of scanning. Nothing is removed
+    /// without `--apply`
+    #[arg(long, conflicts_with_all = ["list_only", "watch", "snapshot", "diff"])]
+    pub remove_stale_locks: bool,
+    /// List the unpushed commits, those on no remote, whose subject breaks POLICY instead of
+    /// scanning, i.e. the commits a server-side hook enforcing it would reject. POLICY is
+    /// `conventional` for Conventional Commits with the types of commitlint's preset, or a
+    /// regular expression every subject has to match. Merge commits are not checked
+    #[arg(
+        long,
+        value_name = "POLICY",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "conventional",
+        conflicts_with_all = ["list_only", "watch", "snapshot", "diff", "changes", "remotes"]
+    )]
+    pub lint_commits: Option<CommitPolicy>,
+    /// List which of HOOKS, a comma-separated list of hook names, every repository has
+    /// installed in its hooks directory (`core.hooksPath` or `.git/hooks`) and which it is
+    /// missing instead of scanning. A hook only counts if git runs it: `.sample` files and,
+    /// on Unix, files that are not executable do not
+    #[arg(
+        long,
+        value_name = "HOOKS",
+        num_args = 0..=1,
+        require_equals = true,
+        value_delimiter = ',',
+        default_missing_value = "pre-commit",
+        conflicts_with_all = ["list_only", "watch", "snapshot", "diff", "changes", "remotes", "lint_commits"]
+    )]
+    pub hooks: Option<Vec<String>>,
+    /// Carry out what `--prune-merged`, `--convert-remotes` or `--remove-stale-locks` lists
+    /// instead of only listing it
+    #[arg(long, requires = "changes")]
+    pub apply: bool,
+    /// Read the repositories to scan from stdin, one path per line, instead of walking the
+    /// directory. Relative paths are relative to the directory; tab-separated columns after
+    /// the path (as `--list-only` prints them) and a trailing `/.git` are ignored. Paths
+    /// separated by NUL characters, e.g. by `fd -0`, are taken as they are
+    #[arg(long)]
+    pub stdin: bool,
+    /// Scan the repositories of the registry instead of walking the directory, which is
+    /// then only used for the relative paths. Every scan adds the repositories it finds to
+    /// the registry, so walking once is enough
+    #[arg(long, conflicts_with = "stdin")]
+    pub registered: bool,
+    /// Scan the repositories registered with `git maintenance register` (the
+    /// `maintenance.repo` entries of the git configuration) instead of walking the directory
+    #[arg(long, conflicts_with_all = ["stdin", "registered"])]
+    pub from_git_maintenance: bool,
+    /// Read the repositories to scan from a file, or from stdin for `-`, instead of walking
+    /// the directory. The paths are read like those of `--stdin`
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["stdin", "registered", "from_git_maintenance"]
+    )]
+    pub paths_from: Option<PathBuf>,
+    /// The file the registry of found repositories is kept in
+    /// [default: `$XDG_DATA_HOME/git-statuses/registry.json`]
+    #[arg(long, global = true, value_name = "FILE")]
+    pub registry: Option<PathBuf>,
+    /// Show the latest results of a running `git-statuses daemon` instead of scanning.
+    /// The scan options of the daemon apply, not the ones given here.
+    #[cfg(feature = "daemon")]
+    #[arg(long, conflicts_with_all = ["list_only", "stdin"])]
+    pub from_daemon: bool,
+    /// The unix socket the daemon listens on [default: `$XDG_RUNTIME_DIR/git-statuses.sock`]
+    #[cfg(feature = "daemon")]
+    #[arg(long, global = true, value_name = "PATH")]
+    pub socket: Option<PathBuf>,
+}
+
+/// The formats the scan result can be printed in, see `--output`.
+#[derive(ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// A table, followed by the failed repositories and the requested extras
+    #[default]
+    Table,
+    /// The repositories and failed repositories as a JSON object
+    Json,
+    /// One line per repository with tab-separated fields and no colors or padding: the
+    /// relative directory, branch, status, ahead, behind, commits and stashes, followed by
+    /// the path and the remote URL if requested
+    Plain,
+    /// One JSON object per line and repository, as in the `repositories` of `json`. Failed
+    /// repositories are only logged
+    Ndjson,
+    /// A Graphviz graph
+    Dot,
+    /// A single line counting the dirty, unpushed, behind and failed repositories, colored
+    /// with tmux style tags for `status-right`. Combine it with `--from-daemon` to not
+    /// rescan every time tmux asks
+    Tmux,
+    /// A self-contained HTML report with the summary and a sortable, searchable table
+    #[cfg(feature = "html")]
+    Html,
+    /// The repositories rendered through the Jinja template given with `--template`
+    #[cfg(feature = "template")]
+    Template,
+    /// The Graphviz graph, laid out and rendered as SVG
+    #[cfg(feature = "svg")]
+    Svg,
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.to_possible_value()
+            .map_or(Ok(()), |value| write!(f, "{}", value.get_name()))
+    }
 }
 
+impl OutputFormat {
+    /// Checks that the format is one of `supported`, the formats `what` can be printed in.
+    ///
+    /// # Errors
+    /// Returns an error saying that `what` cannot be combined with the format otherwise.
+    pub fn require(self, supported: &[Self], what: &str) -> anyhow::Result<Self> {
+        if !supported.contains(&self) {
+            anyhow::bail!("{what} cannot be combined with --output {self}");
+        }
+        Ok(self)
+    }
+}
+
+/// Subcommands besides the default scan.
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Keep rescanning in the background and serve the latest results over a unix socket.
+    /// Clients may send a JSON-RPC request on a line instead: `list`, `summary`, `rescan`,
+    /// or `refresh` with the `repo` to scan again. The scan options given before the
+    /// subcommand are used for every scan.
+    #[cfg(feature = "daemon")]
+    Daemon {
+        /// Seconds to wait between two scans
+        #[arg(long, default_value = "300", global = true)]
+        interval: u64,
+        #[command(subcommand)]
+        action: Option<DaemonAction>,
+    },
+    /// Scan and print one row per group of repositories: how many there are, how many of
+    /// them are clean, their unpushed commits and the one most in need of attention
+    Report {
+        /// What to group the repositories by
+        #[arg(long, value_enum, default_value_t)]
+        rollup: Rollup,
+    },
+    /// Clone the repositories of a manifest written by `--export-manifest` that are missing
+    /// from the directory, each to its path relative to it
+    CloneMissing {
+        /// The manifest to read
+        manifest: PathBuf,
+    },
+}
+
+/// Subcommands of `daemon`.
+#[cfg(feature = "daemon")]
+#[derive(Subcommand, Debug, Clone)]
+pub enum DaemonAction {
+    /// Install a user-level service running the daemon with the given scan options
+    /// (a systemd user unit, or a launchd agent on macOS)
+    Install {
+        /// Print the service file instead of installing it
+        #[arg(long)]
+        print: bool,
+    },
+    /// Send a request to the running daemon and print its result as JSON
+    Request {
+        /// The method to call: `list`, `summary`, `rescan` or `refresh`
+        method: String,
+        /// The repository to `refresh`, as a path or as the relative path it is listed under
+        #[arg(long)]
+        repo: Option<String>,
+    },
+}
+
+/// The error a scan ends with when more repositories failed than `--max-failures` allows.
+#[derive(Debug)]
+pub struct TooManyFailures {
+    /// The number of repositories that failed before the scan was given up.
+    pub failed: usize,
+    /// The number of repositories processed until then.
+    pub processed: usize,
+}
+
+impl TooManyFailures {
+    /// The exit code of a run that was given up, distinct from the generic failure code.
+    pub const EXIT_CODE: u8 = 3;
+}
+
+impl Display for TooManyFailures {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Giving up: {} of {} repositories failed to process, more than --max-failures allows",
+            self.failed, self.processed
+        )
+    }
+}
+
+impl Error for TooManyFailures {}
+
 impl Args {
+    /// Returns the output format, taking `--json` into account.
+    #[must_use]
+    pub const fn output_format(&self) -> OutputFormat {
+        if self.json {
+            OutputFormat::Json
+        } else if self.tmux {
+            OutputFormat::Tmux
+        } else {
+            self.output
+        }
+    }
+
+    /// Checks whether the remote URL has to be read, either to be shown or because the
+    /// output groups repositories by their remote.
+    const fn needs_remote(&self) -> bool {
+        self.remote
+            || self.duplicates
+            || matches!(self.command, Some(Command::Report { .. }))
+            || !matches!(
+                self.output_format(),
+                OutputFormat::Table
+                    | OutputFormat::Json
+                    | OutputFormat::Plain
+                    | OutputFormat::Ndjson
+            )
+    }
+
+    /// Checks whether the repositories have to be identified (see `Scanner::identify`),
+    /// because the scan is diffed against another, now or later.
+    const fn needs_identity(&self) -> bool {
+        #[cfg(feature = "daemon")]
+        if matches!(self.command, Some(Command::Daemon { .. })) {
+            return true;
+        }
+        self.snapshot.is_some() || self.diff.is_some() || self.changes_only
+    }
+
+    /// Returns the socket the daemon listens on, see `--socket`.
+    #[cfg(feature = "daemon")]
+    pub fn socket_path(&self) -> PathBuf {
+        self.socket
+            .clone()
+            .unwrap_or_else(daemon::default_socket_path)
+    }
+
+    /// Returns the first directory to scan, which relative paths given to `--stdin` and
+    /// `--paths-from` are resolved against.
+    #[must_use]
+    pub fn first_dir(&self) -> &Path {
+        self.dirs
+            .first()
+            .map_or_else(|| Path::new("."), PathBuf::as_path)
+    }
+
+    /// Checks whether the repositories to scan are read from stdin, with `--stdin` or
+    /// `--paths-from -`.
+    #[must_use]
+    pub fn reads_stdin(&self) -> bool {
+        self.stdin || self.paths_from.as_deref() == Some(Path::new("-"))
+    }
+
+    /// Describes the scanned directories for messages, separated by spaces.
+    #[must_use]
+    pub fn dirs_display(&self) -> String {
+        self.dirs
+            .iter()
+            .map(|dir| dir.display().to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Checks whether only completions, the legend, the JSON schema or the prompt line are
+    /// asked for, which are printed without scanning or logging.
+    #[must_use]
+    pub const fn is_informational(&self) -> bool {
+        self.completions.is_some() || self.legend || self.prompt || self.json_schema.is_some()
+    }
+
+    /// Builds the scanner for the scan options given on the command line, with the
+    /// repositories of `--stdin`, `--paths-from`, `--registered` or `--from-git-maintenance`.
+    ///
+    /// Reads those sources every time, so a scan that repeats has to keep the scanner
+    /// rather than call this again: stdin can only be read once.
+    ///
+    /// # Errors
+    /// Returns an error if the file of `--paths-from` cannot be read, or if a directory
+    /// that does not exist looks like the selector of a bare `--fetch`.
+    pub fn scanner(&self) -> anyhow::Result<Scanner> {
+        if matches!(self.fetch, Some(FetchSelector::All))
+            && let Some(dir) = self.dirs.iter().find(|dir| {
+                dir.to_str().is_some_and(|dir| {
+                    matches!(dir, "all" | "behind") || dir.starts_with("remote:")
+                }) && !dir.exists()
+            })
+        {
+            anyhow::bail!(
+                "There is no directory {dir}, did you mean --fetch={dir}? The selector of \
+                 --fetch has to follow an `=`",
+                dir = dir.display()
+            );
+        }
+        let first = self.first_dir();
+        let mut scanner = self.options_scanner();
+        if self.stdin {
+            scanner = scanner.repositories(repository_paths(io::stdin().lock(), first));
+        }
+        if let Some(source) = &self.paths_from {
+            scanner = scanner.repositories(paths_from(source, first)?);
+        }
+        if self.registered {
+            scanner = scanner.repositories(self.registered_repositories());
+        }
+        if self.from_git_maintenance {
+            let config = git2::Config::open_default();
+            if let Err(e) = &config {
+                log::warn!("Failed to read the git configuration: {}", e.message());
+            }
+            scanner = scanner.repositories(
+                config
+                    .map(|config| gitinfo::maintenance_repositories(&config))
+                    .unwrap_or_default(),
+            );
+        }
+        Ok(scanner)
+    }
+
+    /// Returns the command line options that make another process scan like this one, e.g.
+    /// the daemon a service runs, without the directories to scan.
+    ///
+    /// Has to carry every option `scanner`, `options_scanner` and `forges` read. Paths are
+    /// made absolute, as the other process may start in another directory.
+    #[must_use]
+    pub fn scan_arguments(&self) -> Vec<String> {
+        let absolute = |path: &Path| {
+            std::path::absolute(path)
+                .unwrap_or_else(|_| path.to_owned())
+                .display()
+                .to_string()
+        };
+        let flags = [
+            (self.remote, "--remote"),
+            (self.fast_forward, "--ff"),
+            (self.prune, "--prune"),
+            (self.retry_auth, "--retry-auth"),
+            (self.no_commit_count, "--no-commit-count"),
+            (self.activity, "--activity"),
+            (self.maintenance, "--maintenance"),
+            (self.disk_usage, "--disk-usage"),
+            (self.show_ignored, "--show-ignored"),
+            (self.hidden_files, "--hidden-files"),
+            (self.lfs, "--lfs"),
+            (self.forks, "--forks"),
+            (self.gone, "--gone"),
+            (self.clones, "--clones"),
+            (self.no_dedupe, "--no-dedupe"),
+            (self.registered, "--registered"),
+            (self.from_git_maintenance, "--from-git-maintenance"),
+            (self.github, "--github"),
+        ];
+        let mut arguments = vec![format!("--depth={}", self.depth)];
+        arguments.extend(
+            flags
+                .into_iter()
+                .filter(|&(set, _)| set)
+                .map(|(_, flag)| flag.to_owned()),
+        );
+        match &self.fetch {
+            Some(FetchSelector::All) => arguments.push("--fetch".to_owned()),
+            Some(selector) => arguments.push(format!("--fetch={selector}")),
+            None => {}
+        }
+        arguments.push(format!("--progress-interval={}", self.progress_interval));
+        let options = [
+            self.subdir
+                .as_ref()
+                .map(|subdir| format!("--subdir={subdir}")),
+            self.upstream_remote
+                .as_ref()
+                .map(|remote| format!("--upstream-remote={remote}")),
+            self.timing.map(|count| format!("--timing={count}")),
+            self.identity
+                .as_ref()
+                .map(|pattern| format!("--identity={pattern}")),
+            self.check_integrity
+                .map(|check| format!("--check-integrity={check}")),
+            self.verify_signatures
+                .map(|commits| format!("--verify-signatures={commits}")),
+            self.max_failures
+                .map(|budget| format!("--max-failures={budget}")),
+            self.fetch_jobs.map(|jobs| format!("--fetch-jobs={jobs}")),
+            self.paths_from
+                .as_deref()
+                .map(|path| format!("--paths-from={}", absolute(path))),
+            self.registry
+                .as_deref()
+                .map(|path| format!("--registry={}", absolute(path))),
+        ];
+        arguments.extend(options.into_iter().flatten());
+        arguments.extend(
+            self.fetch_host_limit
+                .iter()
+                .map(|limit| format!("--fetch-host-limit={limit}")),
+        );
+        arguments.extend(self.forge.iter().map(|forge| format!("--forge={forge}")));
+        arguments
+    }
+
+    /// Builds the scanner for the scan options given on the command line, walking the
+    /// directories rather than reading the repositories from anywhere.
+    fn options_scanner(&self) -> Scanner {
+        let first = self.first_dir();
+        let mut scanner = Scanner::new(first)
+            .depth(self.depth)
+            .remote(self.needs_remote())
+            .fetch(self.fetch.is_some() || self.pull)
+            .fetch_only(self.fetch.clone().unwrap_or_default())
+            .fast_forward(self.fast_forward)
+            .prune(self.prune)
+            .retry_auth(self.retry_auth)
+            .timing(self.timing.is_some())
+            .count_commits(!self.no_commit_count)
+            .identify(self.needs_identity())
+            .activity(self.activity)
+            .check_integrity(self.check_integrity)
+            .maintenance(self.maintenance || self.gc)
+            .disk_usage(self.disk_usage)
+            .show_ignored(self.show_ignored)
+            .hidden_files(self.hidden_files)
+            .lfs(self.lfs)
+            .forks(self.forks)
+            .gone_branches(self.gone)
+            .clones(self.clones)
+            .user_email(self.identity.is_some())
+            .verify_signatures(self.verify_signatures.map(NonZeroUsize::get))
+            .dedupe(!self.no_dedupe);
+        for dir in self.dirs.iter().skip(1) {
+            scanner = scanner.root(dir);
+        }
+        if let Some(path) = AuthFailures::default_path() {
+            scanner = scanner.auth_failures(path);
+        }
+        if let Some(path) = DiskUsageCache::default_path() {
+            scanner = scanner.disk_usage_cache(path);
+        }
+        if let Some(subdir) = &self.subdir {
+            scanner = scanner.subdir(subdir);
+        }
+        if let Some(remote) = &self.upstream_remote {
+            scanner = scanner.upstream_remote(remote);
+        }
+        if let Some(interval) = self.progress_interval() {
+            scanner = scanner.progress_interval(interval);
+        }
+        if let Some(budget) = self.max_failures {
+            scanner = scanner.max_failures(budget);
+        }
+        if let Some(jobs) = self.fetch_jobs {
+            scanner = scanner.fetch_jobs(jobs.get());
+        }
+        for limit in &self.fetch_host_limit {
+            scanner = scanner.fetch_host_limit(limit.clone());
+        }
+        scanner
+    }
+
+    /// Fills in the default `--registry`. `Args::default()` leaves it empty, so that scans
+    /// run through the library do not touch the registry of the user.
+    #[must_use]
+    pub fn with_default_registry(mut self) -> Self {
+        if self.registry.is_none() {
+            self.registry = registry::default_path();
+        }
+        self
+    }
+
+    /// Reads the repositories of the registry for `--registered`, leaving out the ones that
+    /// were deleted since. A registry that cannot be read holds no repositories, with a
+    /// warning.
+    fn registered_repositories(&self) -> BTreeSet<PathBuf> {
+        let Some(path) = &self.registry else {
+            log::warn!("There is no registry to read the repositories from");
+            return BTreeSet::new();
+        };
+        let mut repos = registry::load(path).unwrap_or_else(|e| {
+            log::warn!("{e:#}");
+            BTreeSet::new()
+        });
+        repos.retain(|repo| repo.exists());
+        if repos.is_empty() {
+            log::info!("The registry is empty, scan without --registered first");
+        }
+        repos
+    }
+
+    /// Returns the forges to query, from `--forge` and `--github`.
+    #[must_use]
+    pub fn forges(&self) -> Vec<Forge> {
+        let mut forges = self.forge.clone();
+        if self.github {
+            forges.push(Forge::github());
+        }
+        forges
+    }
+
     /// Scans the given directory (recursively if requested) for Git repositories and collects their status information.
     ///
-    /// The repositories are collected in parallel, so both returned vectors are sorted
-    /// before they are handed back. Every consumer (table, JSON, warnings) therefore sees
-    /// the same, reproducible order.
+    /// See `Scanner::scan`.
     ///
     /// # Returns
     /// A tuple containing:
     /// - A vector of `RepoInfo` containing details about each found repository.
-    /// - A vector of strings of failed repositories (those that could not be opened or processed).
-    #[expect(
-        clippy::cast_sign_loss,
-        reason = "We check i32 to be non-negative, so casting to usize is safe"
-    )]
-    pub fn find_repositories(&self) -> (Vec<RepoInfo>, Vec<String>) {
-        let walker = {
-            let mut walk = WalkDir::new(&self.dir).min_depth(0).follow_links(false);
-
-            // Any negative depth means "no limit"; `-1` is just the documented spelling.
-            // A depth of 0 would find nothing at all, so it is treated like 1.
-            if self.depth >= 0 {
-                walk = walk.max_depth(self.depth.max(1) as usize);
-            }
+    /// - A vector of the failed repositories (those that could not be opened or processed).
+    ///
+    /// # Errors
+    /// Returns an error if the repositories to scan cannot be read, see `Args::scanner`.
+    pub fn find_repositories(&self) -> anyhow::Result<(Vec<RepoInfo>, Vec<FailedRepo>)> {
+        self.find_repositories_with(|_| {})
+    }
 
-            // Never descend into a repository's own git directory. Nothing inside it is a
-            // repository the user asked about - it holds git's bookkeeping, including the
-            // `worktrees/<name>` metadata directories - and on a deep scan it is a lot of
-            // entries to walk and stat for nothing.
-            walk.into_iter()
-                .filter_entry(|e| e.depth() == 0 || e.file_name() != OsStr::new(".git"))
-                .filter_map(Result::ok)
-                .collect::<Vec<_>>()
-        };
+    /// Finds the Git repositories like `find_repositories`, and passes every repository to
+    /// `on_repo` as soon as it is processed, from the worker threads. That is before the
+    /// forges are queried, so `RepoInfo::forge` is not set yet.
+    ///
+    /// # Errors
+    /// Returns an error if the repositories to scan cannot be read, see `Args::scanner`.
+    pub fn find_repositories_with(
+        &self,
+        on_repo: impl Fn(&RepoInfo) + Sync,
+    ) -> anyhow::Result<(Vec<RepoInfo>, Vec<FailedRepo>)> {
+        Ok(self.scan_repositories(&self.scanner()?, on_repo))
+    }
 
-        let repos: Arc<RwLock<Vec<RepoInfo>>> = Arc::new(RwLock::new(Vec::new()));
-        let failed_repos: Arc<RwLock<Vec<String>>> = Arc::new(RwLock::new(Vec::new()));
-
-        walker.par_iter().for_each(|entry| {
-            let orig_path = entry.path();
-            let repo_name = orig_path.dir_name();
-            let path_buf = {
-                if orig_path.is_git_directory() || orig_path.is_git_worktree() {
-                    orig_path.to_path_buf()
-                } else if let Some(subdir) = &self.subdir {
-                    let subdir_path = orig_path.join(subdir);
-                    if subdir_path.is_git_directory() || subdir_path.is_git_worktree() {
-                        subdir_path
-                    } else {
-                        // If the subdir does not exist, skip this directory
-                        return;
-                    }
-                } else {
-                    // If no subdir is specified and the path is not a git directory, skip it
-                    return;
-                }
-            };
-            match git2::Repository::open(path_buf.as_path()) {
-                Ok(mut git_repo) => {
-                    if let Ok(repo) = RepoInfo::new(
-                        &mut git_repo,
-                        &repo_name,
-                        self.remote,
-                        self.fetch,
-                        self.fast_forward,
-                        &self.dir,
-                    ) {
-                        repos.write().push(repo);
-                    } else {
-                        failed_repos.write().push(repo_name);
-                    }
-                }
-                Err(e) => {
-                    log::debug!("Failed to open repository at {}: {}", path_buf.display(), e);
-                    failed_repos.write().push(path_buf.dir_name());
-                }
+    /// Finds the Git repositories like `find_repositories_with`, but with a scanner built
+    /// by `Args::scanner` before, for scans that repeat.
+    pub fn scan_repositories(
+        &self,
+        scanner: &Scanner,
+        on_repo: impl Fn(&RepoInfo) + Sync,
+    ) -> (Vec<RepoInfo>, Vec<FailedRepo>) {
+        let ScanResult { mut repos, failed } = scanner.scan_with_events(|event| {
+            if let ScanEvent::RepoProcessed(repo) = event {
+                on_repo(&repo);
             }
         });
+        self.annotate_forges(&mut repos);
+        (repos, failed)
+    }
+
+    /// Scans the repository at `path` again on its own, e.g. after the watcher saw its
+    /// HEAD or index change, with the same columns as the scan it was found by.
+    ///
+    /// Never fetches, even with `--fetch`: a refresh is about local changes, and
+    /// fetching on every change would keep the network busy.
+    #[must_use]
+    pub fn rescan(&self, path: &Path) -> ScanResult {
+        let mut result = self
+            .options_scanner()
+            .fetch(false)
+            .fast_forward(false)
+            .repositories([path])
+            .scan();
+        self.annotate_forges(&mut result.repos);
+        result
+    }
 
-        let mut repos = repos.read().to_vec();
-        let mut failed_repos = failed_repos.read().to_vec();
-        repos.sort_by_key(|r| r.repo_path.to_lowercase());
-        failed_repos.sort_by_key(|r| r.to_lowercase());
-        (repos, failed_repos)
+    /// Adds what the forges of `--forge` and `--github` know to the repositories.
+    fn annotate_forges(&self, repos: &mut [RepoInfo]) {
+        let forges = self.forges();
+        if !forges.is_empty() {
+            forge::annotate(repos, &forges);
+        }
     }
 
-    /// Applies the output filters (currently only `--non-clean`) to a scan result.
+    /// Returns how the output draws its tables and symbols, see `--condensed`, `--ascii`,
+    /// `--theme` and `--status-color`.
+    #[must_use]
+    pub fn style(&self) -> Style {
+        Style {
+            condensed: self.condensed,
+            ascii: self.ascii,
+            theme: Theme::new(self.theme, &self.status_color),
+        }
+    }
+
+    /// Finds the Git repositories like `find_repositories`, but only opens them instead of
+    /// computing their status. This is what `--list-only` shows.
+    ///
+    /// # Returns
+    /// A tuple containing the found repositories and the names of those that could not be
+    /// opened, both sorted.
+    ///
+    /// # Errors
+    /// Returns an error if the repositories to scan cannot be read, see `Args::scanner`.
+    pub fn list_repositories(&self) -> anyhow::Result<(Vec<RepoListing>, Vec<FailedRepo>)> {
+        let ScanResult { repos, failed } = self.scanner()?.list();
+        Ok((repos, failed))
+    }
+
+    /// Returns how often to print a progress line during the scan, if at all.
+    ///
+    /// Progress is only reported while fetching, as that is what makes a scan slow, and
+    /// only when stderr is not a terminal: in CI logs a periodic line shows the run is
+    /// alive, while on a terminal it would just push the table out of view.
+    fn progress_interval(&self) -> Option<Duration> {
+        let fetching = self.fetch.is_some() || self.fast_forward || self.pull;
+        (fetching && self.progress_interval > 0 && !io::stderr().is_terminal())
+            .then(|| Duration::from_secs(self.progress_interval))
+    }
+
+    /// Applies the output filters (`--non-clean` and the `--min-*` thresholds) to a scan
+    /// result. A repository has to pass all of them.
     ///
     /// Every output format has to go through this, otherwise the formats disagree about
     /// which repositories the user asked to see.
     ///
     /// # Returns
     /// The repositories to display. Borrows the input when no filter is active.
+    #[must_use]
     pub fn filter_repos<'a>(&self, repos: &'a [RepoInfo]) -> Cow<'a, [RepoInfo]> {
-        if self.non_clean {
-            Cow::Owned(
-                repos
-                    .iter()
-                    .filter(|r| r.status != Status::Clean)
-                    .cloned()
-                    .collect(),
-            )
-        } else {
-            Cow::Borrowed(repos)
+        let thresholds = [
+            self.min_ahead,
+            self.min_behind,
+            self.min_stashes,
+            self.min_dirty,
+        ];
+        if !self.non_clean && thresholds.iter().all(Option::is_none) {
+            return Cow::Borrowed(repos);
         }
+        Cow::Owned(
+            repos
+                .iter()
+                .filter(|r| !self.non_clean || r.status != Status::Clean)
+                .filter(|r| {
+                    let values = [r.ahead, r.behind, r.stash_count, r.status.changed_files()];
+                    thresholds
+                        .iter()
+                        .zip(values)
+                        .all(|(min, value)| min.is_none_or(|min| value >= min))
+                })
+                .cloned()
+                .collect(),
+        )
     }
 }
+
+/// Reads the repositories of `--paths-from`.
+/// # Arguments
+/// * `source` - The file to read, `-` for stdin.
+/// * `dir` - The directory relative paths are resolved against.
+/// # Errors
+/// Returns an error if the file cannot be opened, rather than scanning no repositories.
+fn paths_from(source: &Path, dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    if source == Path::new("-") {
+        return Ok(repository_paths(io::stdin().lock(), dir));
+    }
+    let file =
+        fs::File::open(source).with_context(|| format!("Failed to read {}", source.display()))?;
+    Ok(repository_paths(io::BufReader::new(file), dir))
+}
+
+/// Reads the repository paths given to `--stdin` or `--paths-from`.
+///
+/// # Arguments
+/// * `input` - One path per line. Anything after a tab is ignored, and so are empty lines.
+///   If the input contains a NUL character, the paths are separated by NUL characters
+///   instead and taken as they are, tabs and surrounding spaces included.
+/// * `dir` - The directory relative paths are resolved against.
+///
+/// # Returns
+/// The repository paths, with a trailing `.git` component removed so that the output of
+/// `fd -H '^.git$'` can be piped in as is.
+pub fn repository_paths(mut input: impl BufRead, dir: &Path) -> Vec<PathBuf> {
+    let mut bytes = Vec::new();
+    if let Err(e) = input.read_to_end(&mut bytes) {
+        log::warn!("Failed to read the repository paths: {e}");
+    }
+    let text = String::from_utf8_lossy(&bytes);
+    let nul_separated = text.contains('\0');
+    let entries: Vec<&str> = if nul_separated {
+        text.split('\0').collect()
+    } else {
+        text.lines()
+            .map(|line| line.split('\t').next().unwrap_or_default().trim())
+            .collect()
+    };
+    entries
+        .into_iter()
+        .filter_map(|path| {
+            if path.is_empty() {
+                return None;
+            }
+            let path = Path::new(path);
+            let path = if path.file_name() == Some(OsStr::new(".git")) {
+                path.parent().unwrap_or(path)
+            } else {
+                path
+            };
+            Some(dir.join(path))
+        })
+        .collect()
+}
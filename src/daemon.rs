@@ -0,0 +1,387 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+#[cfg(unix)]
+use parking_lot::RwLock;
+#[cfg(unix)]
+use serde_json::{Value, json};
+
+use crate::{
+    cli::Args,
+    gitinfo::repoinfo::{FailedRepo, RepoInfo},
+};
+#[cfg(unix)]
+use crate::{printer::Summary, scanner::ScanResult, watcher::GitWatcher};
+
+/// The file name of the daemon's socket inside the runtime directory.
+const SOCKET_NAME: &str = "git-statuses.sock";
+
+/// How long the daemon waits for the request of a client before it answers with the
+/// latest scan result.
+#[cfg(unix)]
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Returns the socket path used when `--socket` is not given.
+///
+/// Prefers `$XDG_RUNTIME_DIR`, which is private to the user, and falls back to the
+/// temporary directory.
+pub fn default_socket_path() -> PathBuf {
+    env::var_os("XDG_RUNTIME_DIR")
+        .map_or_else(env::temp_dir, PathBuf::from)
+        .join(SOCKET_NAME)
+}
+
+/// Runs the daemon: rescans every `interval` and answers the requests of the clients
+/// connecting to `socket`.
+///
+/// A client sends one JSON-RPC 2.0 request on a line and gets one response back, see
+/// `respond`. A client that sends nothing and closes its end, or sends nothing for
+/// `REQUEST_TIMEOUT`, gets the latest scan result as JSON, like `--json` prints it.
+///
+/// The first scan completes before the socket is bound, so a client never sees an empty
+/// result just because it connected early. Every client is answered on its own thread, so
+/// `list` and `summary` never wait for a `refresh` or `rescan` of another client. Every
+/// scan updates the `--registry`, so that `--registered` finds what the daemon found.
+///
+/// Between two scans, a repository whose HEAD, refs or index change is scanned again on
+/// its own right away, see `GitWatcher` and `Args::rescan`, so `interval` can be long.
+///
+/// # Arguments
+/// * `args` - The scan options, used for every rescan.
+/// * `socket` - The unix socket to listen on.
+/// * `interval` - The time between the end of one scan and the start of the next.
+///
+/// # Errors
+/// Returns an error if another daemon is already listening on `socket`, or if the socket
+/// cannot be bound. Does not return otherwise.
+#[cfg(unix)]
+pub fn serve(args: &Args, socket: &Path, interval: Duration) -> anyhow::Result<()> {
+    use std::{
+        fs,
+        os::unix::net::{UnixListener, UnixStream},
+        thread,
+        time::Instant,
+    };
+
+    use anyhow::Context as _;
+
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            anyhow::bail!("A daemon is already listening on {}", socket.display());
+        }
+        // Left behind by a daemon that did not shut down cleanly.
+        fs::remove_file(socket)
+            .with_context(|| format!("Failed to remove stale socket {}", socket.display()))?;
+    }
+
+    let latest = RwLock::new(Latest::scan(args)?);
+    let listener = UnixListener::bind(socket)
+        .with_context(|| format!("Failed to bind {}", socket.display()))?;
+    log::info!("Serving scan results on {}", socket.display());
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            let mut watcher = GitWatcher::new();
+            loop {
+                watcher.watch(&latest.read().0.repos);
+                let next_scan = Instant::now() + interval;
+                loop {
+                    let changed = watcher.wait(next_scan);
+                    if changed.is_empty() {
+                        break;
+                    }
+                    for path in &changed {
+                        let result = args.rescan(path);
+                        latest.write().0.replace(path, result);
+                        log::debug!("Refreshed {}", path.display());
+                    }
+                }
+                match Latest::scan(args) {
+                    Ok(scanned) => {
+                        *latest.write() = scanned;
+                        log::debug!("Rescanned {}", args.dirs_display());
+                    }
+                    Err(e) => log::warn!("Failed to rescan, keeping the last scan: {e:#}"),
+                }
+            }
+        });
+
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let latest = &latest;
+                    s.spawn(move || {
+                        if let Err(e) = answer(stream, args, latest) {
+                            log::warn!("Failed to answer a client: {e}");
+                        }
+                    });
+                }
+                Err(e) => log::warn!("Failed to accept a client: {e}"),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// The latest scan result of the daemon.
+#[cfg(unix)]
+pub struct Latest(ScanResult);
+
+#[cfg(unix)]
+impl Latest {
+    /// Scans with the given options and adds the repositories found to the registry.
+    ///
+    /// # Errors
+    /// Returns an error if the repositories to scan cannot be read, see `Args::scanner`.
+    pub fn scan(args: &Args) -> anyhow::Result<Self> {
+        let (repos, failed) = args.find_repositories()?;
+        if let Some(path) = &args.registry
+            && let Err(e) = crate::registry::update(path, &repos)
+        {
+            log::warn!("Failed to update the registry: {e:#}");
+        }
+        Ok(Self(ScanResult { repos, failed }))
+    }
+
+    /// Renders the result like `--json` does.
+    fn json(&self) -> Value {
+        crate::printer::json_value(&self.0.repos, &self.0.failed)
+    }
+
+    /// Counts the result like the `summary` of `--json --summary` does.
+    fn summary(&self) -> Value {
+        json!(Summary::new(&self.0.repos, self.0.failed.len()))
+    }
+}
+
+/// Reads the request of a client and writes the response.
+#[cfg(unix)]
+fn answer(
+    mut stream: std::os::unix::net::UnixStream,
+    args: &Args,
+    latest: &RwLock<Latest>,
+) -> std::io::Result<()> {
+    use std::io::{BufRead as _, BufReader, ErrorKind, Write as _};
+
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut request = String::new();
+    match BufReader::new(&stream).read_line(&mut request) {
+        Ok(_) => {}
+        // A client that only reads, e.g. `nc -U`, is answered like one that sent nothing.
+        Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+        Err(e) => return Err(e),
+    }
+    let response = if request.trim().is_empty() {
+        latest.read().json()
+    } else {
+        respond(request.trim(), args, latest)
+    };
+    stream.write_all(response.to_string().as_bytes())?;
+    stream.write_all(b"\n")
+}
+
+/// Answers a JSON-RPC 2.0 request to the daemon.
+///
+/// The methods are:
+/// * `list` - The latest scan result, like `--json` prints it.
+/// * `summary` - The counts of the latest scan, like the `summary` of `--json --summary`.
+/// * `refresh` - Scans the repository given as `repo`, a path or the relative path it is
+///   listed under, again, and answers with it.
+/// * `rescan` - Scans everything again, and answers with the new summary.
+///
+/// # Arguments
+/// * `request` - The request, e.g. `{"jsonrpc": "2.0", "id": 1, "method": "list"}`.
+/// * `args` - The scan options, for `refresh` and `rescan`.
+/// * `latest` - The latest scan result, updated by `refresh` and `rescan`.
+/// # Returns
+/// The response, with the `result` or the `error` of the request.
+#[cfg(unix)]
+pub fn respond(request: &str, args: &Args, latest: &RwLock<Latest>) -> Value {
+    let Ok(request) = serde_json::from_str::<Value>(request) else {
+        return rpc_error(&Value::Null, -32700, "Parse error");
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let result = match request.get("method").and_then(Value::as_str) {
+        Some("list") => Ok(latest.read().json()),
+        Some("summary") => Ok(latest.read().summary()),
+        Some("rescan") => match Latest::scan(args) {
+            Ok(scanned) => {
+                let summary = scanned.summary();
+                *latest.write() = scanned;
+                Ok(summary)
+            }
+            Err(e) => Err((-32000, format!("{e:#}"))),
+        },
+        Some("refresh") => refresh(&request, args, latest),
+        Some(_) => Err((-32601, "Method not found".to_owned())),
+        None => Err((-32600, "Invalid request".to_owned())),
+    };
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => rpc_error(&id, code, &message),
+    }
+}
+
+/// Scans the repository of a `refresh` request again, see `Args::rescan`.
+#[cfg(unix)]
+fn refresh(request: &Value, args: &Args, latest: &RwLock<Latest>) -> Result<Value, (i64, String)> {
+    let repo = request
+        .pointer("/params/repo")
+        .and_then(Value::as_str)
+        .ok_or_else(|| (-32602, "Invalid params, expected a `repo`".to_owned()))?;
+    let path = latest
+        .read()
+        .0
+        .repos
+        .iter()
+        .find(|r| r.repo_path == repo)
+        .map_or_else(|| args.first_dir().join(repo), |r| r.path.clone());
+    let result = args.rescan(&path);
+    let answer = match (result.repos.first(), result.failed.first()) {
+        (Some(repo), _) => Ok(json!(repo)),
+        (None, Some(failed)) => Err((-32000, failed.error.clone())),
+        (None, None) => Err((-32000, format!("{} is no repository", path.display()))),
+    };
+    latest.write().0.replace(&path, result);
+    answer
+}
+
+/// Builds the response to a request that failed.
+#[cfg(unix)]
+fn rpc_error(id: &Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// Daemon mode relies on unix sockets and is not available on this platform.
+///
+/// # Errors
+/// Always.
+#[cfg(not(unix))]
+pub fn serve(_args: &Args, _socket: &Path, _interval: Duration) -> anyhow::Result<()> {
+    anyhow::bail!("The daemon is only supported on unix platforms")
+}
+
+/// Fetches the latest scan result from a running daemon.
+///
+/// # Arguments
+/// * `socket` - The unix socket the daemon listens on.
+///
+/// # Returns
+/// The repositories and failed repositories of the daemon's latest scan, in the order
+/// `Args::find_repositories` returns them.
+///
+/// # Errors
+/// Returns an error if no daemon is listening on `socket` or its answer cannot be parsed.
+#[cfg(unix)]
+pub fn query(socket: &Path) -> anyhow::Result<(Vec<RepoInfo>, Vec<FailedRepo>)> {
+    use std::{io::Read as _, net::Shutdown, os::unix::net::UnixStream};
+
+    use anyhow::Context as _;
+
+    use crate::snapshot::Snapshot;
+
+    let mut stream = UnixStream::connect(socket)
+        .with_context(|| format!("No daemon is listening on {}", socket.display()))?;
+    // Sending no request asks for the latest scan result.
+    stream.shutdown(Shutdown::Write)?;
+    let mut body = String::new();
+    stream.read_to_string(&mut body)?;
+    let snapshot = Snapshot::parse(&body).context("Failed to parse the daemon's answer")?;
+
+    Ok((snapshot.repositories, snapshot.failed))
+}
+
+/// Sends a JSON-RPC request to a running daemon, see `respond` for the methods.
+///
+/// # Arguments
+/// * `socket` - The unix socket the daemon listens on.
+/// * `method` - The method to call, e.g. `summary`.
+/// * `params` - The parameters of the method, `Value::Null` for none.
+///
+/// # Returns
+/// The `result` of the response.
+///
+/// # Errors
+/// Returns an error if no daemon is listening on `socket`, its answer cannot be parsed,
+/// or it answers with an error.
+#[cfg(unix)]
+pub fn call(socket: &Path, method: &str, params: Value) -> anyhow::Result<Value> {
+    use std::{
+        io::{BufRead as _, BufReader, Write as _},
+        os::unix::net::UnixStream,
+    };
+
+    use anyhow::Context as _;
+
+    let mut stream = UnixStream::connect(socket)
+        .with_context(|| format!("No daemon is listening on {}", socket.display()))?;
+    let mut request = json!({ "jsonrpc": "2.0", "id": 1, "method": method });
+    if !params.is_null() {
+        request["params"] = params;
+    }
+    writeln!(stream, "{request}")?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    let mut response: Value =
+        serde_json::from_str(&line).context("Failed to parse the daemon's answer")?;
+    if let Some(error) = response.get("error") {
+        anyhow::bail!(
+            "The daemon failed to {method}: {}",
+            error["message"].as_str().unwrap_or("unknown error")
+        );
+    }
+    Ok(response["result"].take())
+}
+
+/// Daemon mode relies on unix sockets and is not available on this platform.
+///
+/// # Errors
+/// Always.
+#[cfg(not(unix))]
+pub fn query(_socket: &Path) -> anyhow::Result<(Vec<RepoInfo>, Vec<FailedRepo>)> {
+    anyhow::bail!("The daemon is only supported on unix platforms")
+}
+
+/// Sends a request to a running daemon, see `call`, and writes its result as JSON.
+///
+/// # Arguments
+/// * `socket` - The unix socket the daemon listens on.
+/// * `method` - The method to call, e.g. `summary`.
+/// * `repo` - The repository to `refresh`, if any.
+/// * `out` - Where to write the result to.
+///
+/// # Errors
+/// Returns an error if the request fails or the result cannot be written.
+#[cfg(unix)]
+pub fn request(
+    socket: &Path,
+    method: &str,
+    repo: Option<&str>,
+    out: &mut impl std::io::Write,
+) -> anyhow::Result<()> {
+    let params = repo.map_or(Value::Null, |repo| json!({ "repo": repo }));
+    writeln!(out, "{}", call(socket, method, params)?)?;
+    Ok(())
+}
+
+/// Daemon mode relies on unix sockets and is not available on this platform.
+///
+/// # Errors
+/// Always.
+#[cfg(not(unix))]
+pub fn request(
+    _socket: &Path,
+    _method: &str,
+    _repo: Option<&str>,
+    _out: &mut impl std::io::Write,
+) -> anyhow::Result<()> {
+    anyhow::bail!("The daemon is only supported on unix platforms")
+}
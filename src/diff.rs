@@ -0,0 +1,157 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::gitinfo::repoinfo::RepoInfo;
+
+/// How a repository differs between two scans.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoChange {
+    /// The relative path of the repository, as of the newer scan unless it was removed.
+    pub repo_path: String,
+    /// What changed.
+    pub kind: ChangeKind,
+}
+
+/// The kinds of changes `diff` reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The repository is new.
+    Added,
+    /// The repository is gone.
+    Removed,
+    /// The repository was found at another path, recognized by its identity.
+    Moved {
+        /// The relative path in the older scan.
+        from: String,
+        /// Whatever else changed.
+        fields: Vec<FieldChange>,
+    },
+    /// The state of the repository changed.
+    Changed(Vec<FieldChange>),
+}
+
+/// A single value that changed between two scans.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    /// What changed, e.g. `status`.
+    pub field: &'static str,
+    /// The value in the older scan.
+    pub old: String,
+    /// The value in the newer scan.
+    pub new: String,
+}
+
+impl Display for FieldChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} → {}", self.field, self.old, self.new)
+    }
+}
+
+impl Display for RepoChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let fields = |fields: &[FieldChange]| {
+            fields
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match &self.kind {
+            ChangeKind::Added => write!(f, "{}: added", self.repo_path),
+            ChangeKind::Removed => write!(f, "{}: removed", self.repo_path),
+            ChangeKind::Moved {
+                from,
+                fields: changed,
+            } => {
+                write!(f, "{}: moved from {from}", self.repo_path)?;
+                if changed.is_empty() {
+                    Ok(())
+                } else {
+                    write!(f, ", {}", fields(changed))
+                }
+            }
+            ChangeKind::Changed(changed) => write!(f, "{}: {}", self.repo_path, fields(changed)),
+        }
+    }
+}
+
+/// Compares two scans.
+///
+/// Repositories are matched by their relative path first. A repository that only shows up
+/// at a new path is reported as moved if a vanished one had the same identity (see
+/// `RepoInfo::identity`), and as added otherwise.
+/// # Arguments
+/// * `old` - The older scan.
+/// * `new` - The newer scan.
+/// # Returns
+/// The repositories that were added, removed, moved or changed their branch, status,
+/// ahead/behind counts or stash count, sorted by relative path.
+pub fn diff(old: &[RepoInfo], new: &[RepoInfo]) -> Vec<RepoChange> {
+    let mut unmatched: HashMap<&str, &RepoInfo> =
+        old.iter().map(|r| (r.repo_path.as_str(), r)).collect();
+    let mut changes = Vec::new();
+    let mut appeared = Vec::new();
+
+    for repo in new {
+        if let Some(previous) = unmatched.remove(repo.repo_path.as_str()) {
+            let fields = field_changes(previous, repo);
+            if !fields.is_empty() {
+                changes.push(RepoChange {
+                    repo_path: repo.repo_path.clone(),
+                    kind: ChangeKind::Changed(fields),
+                });
+            }
+        } else {
+            appeared.push(repo);
+        }
+    }
+
+    for repo in appeared {
+        let moved_from = repo.identity.as_ref().and_then(|identity| {
+            unmatched
+                .iter()
+                .filter(|(_, r)| r.identity.as_ref() == Some(identity))
+                .map(|(path, _)| *path)
+                .min()
+        });
+        let kind = moved_from.and_then(|path| unmatched.remove(path)).map_or(
+            ChangeKind::Added,
+            |previous| ChangeKind::Moved {
+                from: previous.repo_path.clone(),
+                fields: field_changes(previous, repo),
+            },
+        );
+        changes.push(RepoChange {
+            repo_path: repo.repo_path.clone(),
+            kind,
+        });
+    }
+
+    changes.extend(unmatched.into_values().map(|repo| RepoChange {
+        repo_path: repo.repo_path.clone(),
+        kind: ChangeKind::Removed,
+    }));
+    changes.sort_by(|a, b| a.repo_path.cmp(&b.repo_path));
+    changes
+}
+
+/// Lists the values that differ between two scans of the same repository.
+fn field_changes(old: &RepoInfo, new: &RepoInfo) -> Vec<FieldChange> {
+    [
+        ("branch", old.branch.clone(), new.branch.clone()),
+        ("status", old.status.to_string(), new.status.to_string()),
+        ("ahead", old.ahead.to_string(), new.ahead.to_string()),
+        ("behind", old.behind.to_string(), new.behind.to_string()),
+        (
+            "stashes",
+            old.stash_count.to_string(),
+            new.stash_count.to_string(),
+        ),
+    ]
+    .into_iter()
+    .filter(|(_, old, new)| old != new)
+    .map(|(field, old, new)| FieldChange { field, old, new })
+    .collect()
+}
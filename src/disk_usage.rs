@@ -0,0 +1,121 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use parking_lot::Mutex;
+
+use crate::{
+    gitinfo::repoinfo::DiskUsage,
+    util::{self, unix_seconds},
+};
+
+/// How long a measured disk usage is reused before the repository is measured again.
+pub const MAX_AGE: Duration = Duration::from_hours(1);
+
+/// A disk usage with when it was measured.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug)]
+struct Entry {
+    /// When the repository was measured, in seconds since the Unix epoch.
+    measured_at: u64,
+    #[serde(flatten)]
+    usage: DiskUsage,
+}
+
+/// Remembers the disk usage of repositories, as walking every file of a large checkout
+/// takes a while and its size rarely changes much within `MAX_AGE`.
+#[derive(Debug, Default)]
+pub struct DiskUsageCache {
+    /// Where the disk usages are kept between runs, if anywhere.
+    path: Option<PathBuf>,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// The disk usage per repository, by `util::path_key`.
+    entries: BTreeMap<String, Entry>,
+    /// Whether `entries` differs from the file it was read from.
+    changed: bool,
+}
+
+impl DiskUsageCache {
+    /// Reads the disk usages remembered at `path`. A missing or unreadable file is taken as
+    /// none remembered.
+    /// # Arguments
+    /// * `path` - The file the disk usages are kept in between runs, `None` to not keep
+    ///   them at all.
+    #[must_use]
+    pub fn load(path: Option<&Path>) -> Self {
+        let entries = path
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self {
+            path: path.map(Path::to_path_buf),
+            state: Mutex::new(State {
+                entries,
+                changed: false,
+            }),
+        }
+    }
+
+    /// Returns where the disk usages are kept by default: `git-statuses/disk-usage.json`
+    /// in `$XDG_CACHE_HOME`, or in `~/.cache` if that is not set.
+    #[must_use]
+    pub fn default_path() -> Option<PathBuf> {
+        env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+            .map(|cache| cache.join("git-statuses").join("disk-usage.json"))
+    }
+
+    /// Returns the disk usage of the repository at `repo`, measured with `measure` unless
+    /// it was measured less than `MAX_AGE` ago.
+    pub fn get(&self, repo: &Path, measure: impl FnOnce() -> DiskUsage) -> DiskUsage {
+        let key = util::path_key(repo);
+        let now = unix_seconds(SystemTime::now());
+        let cached = self.state.lock().entries.get(&key).copied();
+        if let Some(entry) = cached
+            && now.saturating_sub(entry.measured_at) < MAX_AGE.as_secs()
+        {
+            return entry.usage;
+        }
+        // Measured without the lock, so that repositories are measured in parallel.
+        let usage = measure();
+        let mut state = self.state.lock();
+        state.entries.insert(
+            key,
+            Entry {
+                measured_at: now,
+                usage,
+            },
+        );
+        state.changed = true;
+        usage
+    }
+
+    /// Saves the disk usages, leaving out those that expired.
+    ///
+    /// Saving is best-effort: a cache that cannot be written only means the next run
+    /// measures again.
+    pub fn finish(self) {
+        let mut state = self.state.into_inner();
+        let Some(path) = self.path.filter(|_| state.changed) else {
+            return;
+        };
+        let now = unix_seconds(SystemTime::now());
+        state
+            .entries
+            .retain(|_, entry| now.saturating_sub(entry.measured_at) < MAX_AGE.as_secs());
+        let saved = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&path, serde_json::json!(state.entries).to_string()));
+        if let Err(e) = saved {
+            log::debug!("Failed to write {}: {e}", path.display());
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use std::{
+    env,
+    fmt::{self, Display, Formatter},
+    io::Write as _,
+    process::{Command, Stdio},
+    str::FromStr,
+};
+
+use anyhow::Context as _;
+use rayon::iter::{IntoParallelRefMutIterator as _, ParallelIterator as _};
+use serde_json::Value;
+
+use crate::gitinfo::{self, repoinfo::RepoInfo};
+
+/// Where the API of github.com lives.
+const GITHUB_API: &str = "https://api.github.com";
+
+/// What `--github` or `--forge` found out about a repository on its forge.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForgeStatus {
+    /// Number of open pull requests (merge requests on GitLab), counted up to 100, or 50
+    /// on Gitea.
+    pub open_pulls: usize,
+    /// The outcome of the checks of the latest commit on the default branch, `None` if it
+    /// has none.
+    pub ci: Option<CiStatus>,
+}
+
+/// The combined outcome of the checks of a commit.
+#[derive(
+    serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum CiStatus {
+    /// Every check passed or was skipped.
+    Success,
+    /// Some checks are still running, none failed.
+    Pending,
+    /// At least one check failed, was cancelled or timed out.
+    Failure,
+}
+
+impl CiStatus {
+    /// Combines the check runs of a commit, as returned by GitHub's
+    /// `/commits/{ref}/check-runs`.
+    /// # Arguments
+    /// * `response` - The response of the API.
+    /// # Returns
+    /// The worst outcome of the runs, or `None` if there are none.
+    pub fn from_check_runs(response: &Value) -> Option<Self> {
+        response["check_runs"]
+            .as_array()?
+            .iter()
+            .map(|run| match run["conclusion"].as_str() {
+                None => Self::Pending,
+                Some("success" | "neutral" | "skipped") => Self::Success,
+                Some(_) => Self::Failure,
+            })
+            .max()
+    }
+
+    /// Reads the status of the latest pipeline, as returned by GitLab's `/pipelines`.
+    /// # Arguments
+    /// * `response` - The response of the API, the latest pipeline first.
+    /// # Returns
+    /// The outcome of the pipeline, or `None` if there is none.
+    pub fn from_pipelines(response: &Value) -> Option<Self> {
+        match response.get(0)?["status"].as_str()? {
+            "success" | "skipped" => Some(Self::Success),
+            "failed" | "canceled" => Some(Self::Failure),
+            _ => Some(Self::Pending),
+        }
+    }
+
+    /// Reads the combined status of a commit, as returned by Gitea's `/commits/{ref}/status`.
+    /// # Arguments
+    /// * `response` - The response of the API.
+    /// # Returns
+    /// The combined outcome, or `None` if the commit has no statuses.
+    pub fn from_combined_status(response: &Value) -> Option<Self> {
+        if response["total_count"].as_u64() == Some(0) {
+            return None;
+        }
+        match response["state"].as_str()? {
+            "success" | "warning" => Some(Self::Success),
+            "pending" => Some(Self::Pending),
+            _ => Some(Self::Failure),
+        }
+    }
+
+    /// Returns the color the status is shown in.
+    #[must_use]
+    pub const fn comfy_color(self) -> comfy_table::Color {
+        match self {
+            Self::Success => comfy_table::Color::Green,
+            Self::Pending => comfy_table::Color::Yellow,
+            Self::Failure => comfy_table::Color::Red,
+        }
+    }
+}
+
+impl Display for CiStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Success => "success",
+            Self::Pending => "pending",
+            Self::Failure => "failure",
+        })
+    }
+}
+
+/// The kinds of forges whose API git-statuses speaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForgeKind {
+    /// github.com or GitHub Enterprise.
+    GitHub,
+    /// GitLab, hosted or self-managed.
+    GitLab,
+    /// Gitea or its fork Forgejo.
+    Gitea,
+}
+
+impl ForgeKind {
+    /// The API of the forge at `host`, if the forge does not live elsewhere.
+    fn default_api(self, host: &str) -> String {
+        match self {
+            Self::GitHub if host.eq_ignore_ascii_case("github.com") => GITHUB_API.to_owned(),
+            Self::GitHub => format!("https://{host}/api/v3"),
+            Self::GitLab => format!("https://{host}/api/v4"),
+            Self::Gitea => format!("https://{host}/api/v1"),
+        }
+    }
+}
+
+impl FromStr for ForgeKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "github" => Ok(Self::GitHub),
+            "gitlab" => Ok(Self::GitLab),
+            "gitea" | "forgejo" => Ok(Self::Gitea),
+            _ => Err(format!(
+                "`{s}` is not a forge, use `github`, `gitlab`, `gitea` or `forgejo`"
+            )),
+        }
+    }
+}
+
+impl Display for ForgeKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::GitHub => "github",
+            Self::GitLab => "gitlab",
+            Self::Gitea => "gitea",
+        })
+    }
+}
+
+/// A forge to query for the repositories whose remote is on `host`, given to `--forge` as
+/// `HOST=KIND` or `HOST=KIND:API_URL`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forge {
+    /// The host of the remotes, e.g. `gitlab.acme.com`.
+    pub host: String,
+    /// Which API the forge speaks.
+    pub kind: ForgeKind,
+    /// The base URL of the API, e.g. `https://gitlab.acme.com/api/v4`.
+    pub api: String,
+}
+
+impl Forge {
+    /// The forge of `--github`.
+    #[must_use]
+    pub fn github() -> Self {
+        Self {
+            host: "github.com".to_owned(),
+            kind: ForgeKind::GitHub,
+            api: GITHUB_API.to_owned(),
+        }
+    }
+
+    /// The environment variable holding the token for this host only, e.g.
+    /// `GIT_STATUSES_TOKEN_GITLAB_ACME_COM` for `gitlab.acme.com`.
+    #[must_use]
+    pub fn token_variable(&self) -> String {
+        let host: String = self
+            .host
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_uppercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        format!("GIT_STATUSES_TOKEN_{host}")
+    }
+
+    /// Reads the token for this forge: the one for its host, else `GITLAB_TOKEN` or
+    /// `GITEA_TOKEN` for its kind, or for github.com `GH_TOKEN` or `GITHUB_TOKEN` like the
+    /// `gh` CLI. A github.com token is never sent to GitHub Enterprise.
+    fn token(&self) -> Option<String> {
+        let mut variables = vec![self.token_variable()];
+        match self.kind {
+            ForgeKind::GitHub if self.host.eq_ignore_ascii_case("github.com") => {
+                variables.extend(["GH_TOKEN".to_owned(), "GITHUB_TOKEN".to_owned()]);
+            }
+            ForgeKind::GitHub => {}
+            ForgeKind::GitLab => variables.push("GITLAB_TOKEN".to_owned()),
+            ForgeKind::Gitea => variables.push("GITEA_TOKEN".to_owned()),
+        }
+        variables
+            .into_iter()
+            .find_map(|name| env::var(name).ok().filter(|token| !token.is_empty()))
+    }
+
+    /// Finds the project a remote URL points to on this forge.
+    /// # Arguments
+    /// * `url` - The remote URL.
+    /// # Returns
+    /// The path of the project, e.g. `acme/tool` or on GitLab `acme/tools/tool`, or `None`
+    /// if the remote is not on this forge.
+    #[must_use]
+    pub fn project(&self, url: &str) -> Option<String> {
+        let (host, owner) = gitinfo::remote_host_and_owner(url)?;
+        let name = gitinfo::repo_name_from_url(url)?;
+        let nested = owner.contains('/') && self.kind != ForgeKind::GitLab;
+        (host.eq_ignore_ascii_case(&self.host) && !owner.is_empty() && !nested)
+            .then(|| format!("{owner}/{name}"))
+    }
+
+    /// Asks the forge for the open pull requests and the checks of the default branch.
+    fn status(&self, project: &str, token: Option<&str>) -> anyhow::Result<ForgeStatus> {
+        let api = self.api.trim_end_matches('/');
+        let (base, pulls) = match self.kind {
+            ForgeKind::GitHub => (
+                format!("{api}/repos/{project}"),
+                "pulls?state=open&per_page=100",
+            ),
+            ForgeKind::GitLab => (
+                format!("{api}/projects/{}", project.replace('/', "%2F")),
+                "merge_requests?state=opened&per_page=100",
+            ),
+            ForgeKind::Gitea => (
+                format!("{api}/repos/{project}"),
+                "pulls?state=open&limit=50",
+            ),
+        };
+        let repository = get_json(&base, token)?;
+        let branch = repository["default_branch"]
+            .as_str()
+            .context("The repository has no default branch")?;
+        let pulls = get_json(&format!("{base}/{pulls}"), token)?;
+        let ci = match self.kind {
+            ForgeKind::GitHub => CiStatus::from_check_runs(&get_json(
+                &format!("{base}/commits/{branch}/check-runs?per_page=100"),
+                token,
+            )?),
+            ForgeKind::GitLab => CiStatus::from_pipelines(&get_json(
+                &format!("{base}/pipelines?ref={branch}&per_page=1"),
+                token,
+            )?),
+            ForgeKind::Gitea => CiStatus::from_combined_status(&get_json(
+                &format!("{base}/commits/{branch}/status"),
+                token,
+            )?),
+        };
+        Ok(ForgeStatus {
+            open_pulls: pulls.as_array().map_or(0, Vec::len),
+            ci,
+        })
+    }
+}
+
+impl FromStr for Forge {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((host, kind)) = s.split_once('=').filter(|(host, _)| !host.is_empty()) else {
+            return Err(format!(
+                "`{s}` is not a forge, use e.g. `gitlab.acme.com=gitlab`"
+            ));
+        };
+        let (kind, api) = kind
+            .split_once(':')
+            .map_or((kind, None), |(kind, api)| (kind, Some(api.to_owned())));
+        let kind: ForgeKind = kind.parse()?;
+        Ok(Self {
+            host: host.to_owned(),
+            kind,
+            api: api.unwrap_or_else(|| kind.default_api(host)),
+        })
+    }
+}
+
+impl Display for Forge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}:{}", self.host, self.kind, self.api)
+    }
+}
+
+/// Queries the forges for every repository whose remote is on one of them and stores the
+/// result in `RepoInfo::forge`.
+///
+/// Without a token (see `Forge::token_variable`) only public repositories can be queried,
+/// and github.com allows only 60 requests an hour. A repository that cannot be queried is
+/// left without a forge status and a warning.
+/// # Arguments
+/// * `repos` - The repositories to query.
+/// * `forges` - The forges to query, the first one matching a remote is used.
+pub fn annotate(repos: &mut [RepoInfo], forges: &[Forge]) {
+    let tokens: Vec<Option<String>> = forges.iter().map(Forge::token).collect();
+    for (forge, token) in forges.iter().zip(&tokens) {
+        if token.is_none() {
+            log::warn!(
+                "No token for {} in {}, only public repositories can be queried",
+                forge.host,
+                forge.token_variable()
+            );
+        }
+    }
+    repos.par_iter_mut().for_each(|repo| {
+        let Some(url) = repo.remote_url.as_deref() else {
+            return;
+        };
+        let Some((forge, token, project)) = forges
+            .iter()
+            .zip(&tokens)
+            .find_map(|(forge, token)| Some((forge, token, forge.project(url)?)))
+        else {
+            return;
+        };
+        match forge.status(&project, token.as_deref()) {
+            Ok(status) => repo.forge = Some(status),
+            Err(e) => log::warn!(
+                "Failed to query {} for `{}`: {e:#}",
+                forge.host,
+                repo.repo_path
+            ),
+        }
+    });
+}
+
+/// Fetches `url` with the curl executable, which keeps TLS out of git-statuses just like
+/// fetching through the git executable does. The token is handed over on stdin so that it
+/// does not show up in the process list.
+fn get_json(url: &str, token: Option<&str>) -> anyhow::Result<Value> {
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location"])
+        .args(["--max-time", "30"])
+        .args(["--header", "Accept: application/json"])
+        .args(["--header", "@-"])
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run curl")?;
+    if let (Some(mut stdin), Some(token)) = (child.stdin.take(), token) {
+        writeln!(stdin, "Authorization: Bearer {token}")?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    serde_json::from_slice(&output.stdout).with_context(|| format!("{url} did not return JSON"))
+}
@@ -1,14 +1,28 @@
 use std::{
-    path::{self},
+    collections::{BTreeSet, HashSet},
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs,
+    path::{self, Path, PathBuf},
     process::Command,
+    thread,
+    time::{Duration, Instant, SystemTime},
 };
 
-use git2::{Branch, Repository, StatusOptions};
+use git2::{
+    Branch, Oid, Repository, RepositoryState, StatusOptions, SubmoduleIgnore, SubmoduleStatus,
+};
 
-use crate::gitinfo::status::Status;
+use crate::gitinfo::{
+    repoinfo::{
+        Activity, DiskUsage, HiddenFiles, Housekeeping, LfsStatus, OperationProgress, Signatures,
+    },
+    status::Status,
+};
 
 pub mod repoinfo;
 pub mod status;
+pub mod symbol;
 
 /// The status bits that make a working directory count as changed.
 ///
@@ -27,6 +41,35 @@ pub const CHANGED: git2::Status = git2::Status::WT_NEW
     .union(git2::Status::INDEX_RENAMED)
     .union(git2::Status::CONFLICTED);
 
+/// Upper bound on the untracked entries looked at when suggesting `.gitignore` entries.
+///
+/// Untracked directories are reported as a single entry, so this is only ever reached by
+/// repositories with a lot of loose untracked files at their top levels.
+const UNTRACKED_SAMPLE: usize = 1000;
+
+/// Directory names that are build output or dependency caches and belong in `.gitignore`.
+const ARTIFACT_DIRS: &[&str] = &["target", "node_modules", "__pycache__"];
+
+/// Lock files in the git directory that git holds while it changes the index or `HEAD`.
+const GIT_DIR_LOCKS: &[&str] = &["index.lock", "HEAD.lock", "ORIG_HEAD.lock"];
+
+/// Lock files in the common git directory, shared by all worktrees. Ref locks below `refs/`
+/// are looked for separately.
+const COMMON_DIR_LOCKS: &[&str] = &["config.lock", "packed-refs.lock", "shallow.lock"];
+
+/// Lock files older than this are assumed to be left behind by a crashed git process
+/// rather than belonging to an operation in progress.
+const LOCK_MAX_AGE: Duration = Duration::from_mins(10);
+
+/// How often `wait_until_idle` looks at the lock files again.
+const IDLE_POLL: Duration = Duration::from_millis(100);
+
+/// File names that operating systems drop into every directory they touch.
+const ARTIFACT_FILES: &[&str] = &[".DS_Store", "Thumbs.db"];
+
+/// File extensions of compiled artifacts that are never meant to be committed.
+const ARTIFACT_EXTENSIONS: &[&str] = &["pyc"];
+
 /// Gets the first available remote name, preferring "origin".
 /// If "origin" doesn't exist, it returns the first available remote.
 /// # Arguments
@@ -89,6 +132,38 @@ pub fn repo_name_from_url(url: &str) -> Option<String> {
     (!name.is_empty()).then(|| name.to_owned())
 }
 
+/// Extracts the host and the owner (user, organization or group path) from a remote URL.
+///
+/// Understands the same shapes as `repo_name_from_url`, plus `scheme://` URLs carrying a
+/// user or a port. The owner is empty for a repository directly below the host.
+///
+/// # Arguments
+/// * `url` - The remote URL to parse.
+/// # Returns
+/// The host and owner, or `None` for a local path.
+pub fn remote_host_and_owner(url: &str) -> Option<(String, String)> {
+    let (host, path) = if let Some((_, rest)) = url.split_once("://") {
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let host = authority.rsplit('@').next().unwrap_or(authority);
+        // A port is not part of the host a user thinks of.
+        (host.split(':').next().unwrap_or(host), path)
+    } else {
+        let (authority, path) = url.split_once(':')?;
+        // `C:\repo` is a Windows path, and a slash before the colon makes it a local path.
+        if authority.len() < 2 || authority.contains(['/', '\\']) {
+            return None;
+        }
+        (authority.rsplit('@').next().unwrap_or(authority), path)
+    };
+    if host.is_empty() {
+        return None;
+    }
+
+    let path = path.trim_matches('/');
+    let owner = path.rsplit_once('/').map_or("", |(owner, _)| owner);
+    Some((host.to_owned(), owner.to_owned()))
+}
+
 /// Gets the name of the repository from the remote URL.
 /// If the remote URL is not available, it returns `None`.
 /// # Arguments
@@ -104,7 +179,8 @@ fn get_repo_name(repo: &Repository) -> Option<String> {
 }
 
 /// Returns the current branch name or a fallback if not available.
-/// If the HEAD is detached, it returns "N/A".
+/// If the HEAD is detached, it returns the short commit id, preceded by the tag pointing
+/// at it if there is one (`v1.2.0 (1a2b3c4)`).
 /// If not pointing to a branch, it returns the symbolic target of HEAD or "(no branch)" if no commits exist.
 /// # Arguments
 /// * `repo` - The Git repository to check for the branch name.
@@ -118,7 +194,11 @@ pub fn get_branch_name(repo: &Repository) -> String {
             }
         } else {
             // Detached HEAD
-            return "N/A".to_owned();
+            return match (get_head_tag(repo), get_head_sha(repo)) {
+                (Some(tag), Some(sha)) => format!("{tag} ({sha})"),
+                (None, Some(sha)) => sha,
+                (_, None) => "N/A".to_owned(),
+            };
         }
         if let Ok(Some(target)) = head.symbolic_target()
             && let Some(branch) = target.rsplit('/').next()
@@ -134,31 +214,299 @@ pub fn get_branch_name(repo: &Repository) -> String {
     "(no branch)".to_owned()
 }
 
+/// Returns the abbreviated id of the commit `HEAD` points to, as short as it can be while
+/// staying unique (at least 7 characters), or `None` if there are no commits.
+pub fn get_head_sha(repo: &Repository) -> Option<String> {
+    let commit = repo.head().ok()?.peel_to_commit().ok()?;
+    let short = commit.as_object().short_id().ok()?;
+    short.as_str().ok().map(ToOwned::to_owned)
+}
+
+/// Returns the first tag, by name, that points at the commit `HEAD` points to.
+fn get_head_tag(repo: &Repository) -> Option<String> {
+    let head = repo.head().ok()?.peel_to_commit().ok()?.id();
+    let tags = repo.tag_names(None).ok()?;
+    let mut matching: Vec<&str> = tags
+        .iter()
+        .flatten()
+        .flatten()
+        .filter(|tag| {
+            repo.find_reference(&format!("refs/tags/{tag}"))
+                .and_then(|r| r.peel_to_commit())
+                .is_ok_and(|commit| commit.id() == head)
+        })
+        .collect();
+    matching.sort_unstable();
+    matching.first().map(|tag| (*tag).to_owned())
+}
+
 /// Get the number of commits ahead and behind the upstream branch, and whether the branch is local-only.
 /// If the current branch has no upstream, it returns (0, 0, true).
+///
+/// With `remote`, the branch is compared with the branch of the same name on that remote
+/// instead, e.g. `upstream/main` in a fork. Without such a branch, as of the last fetch,
+/// it falls back to the configured upstream.
 /// # Arguments
 /// * `repo` - The Git repository to check for ahead/behind status.
+/// * `remote` - The remote to compare with, if not the upstream.
 /// # Returns
 /// A tuple containing the number of commits ahead, behind, and whether the branch is local-only.
-pub fn get_ahead_behind_and_local_status(repo: &Repository) -> (usize, usize, bool) {
+pub fn get_ahead_behind_and_local_status(
+    repo: &Repository,
+    remote: Option<&str>,
+) -> (usize, usize, bool) {
     let Ok(head) = repo.head() else {
         return (0, 0, true);
     };
-    let branch = head
-        .shorthand()
+    let Some(local) = head.target() else {
+        return (0, 0, true);
+    };
+    let name = head.is_branch().then(|| head.shorthand().ok()).flatten();
+    let on_remote = remote
+        .zip(name)
+        .and_then(|(remote, name)| {
+            repo.find_reference(&format!("refs/remotes/{remote}/{name}"))
+                .ok()
+        })
+        .and_then(|reference| reference.target());
+    let upstream = on_remote.or_else(|| {
+        let branch = repo.find_branch(name?, git2::BranchType::Local).ok()?;
+        branch.upstream().ok()?.get().target()
+    });
+    upstream.map_or((0, 0, true), |up| {
+        let (ahead, behind) = repo.graph_ahead_behind(local, up).unwrap_or((0, 0));
+        (ahead, behind, false)
+    })
+}
+
+/// Returns the `user.email` commits in the repository are made with: the one of the
+/// repository's own config, or else the global or system one.
+pub fn get_user_email(repo: &Repository) -> Option<String> {
+    repo.config()
+        .and_then(|mut config| config.snapshot())
+        .and_then(|config| config.get_string("user.email"))
         .ok()
-        .and_then(|name| repo.find_branch(name, git2::BranchType::Local).ok());
-    if let Some(branch) = branch
-        && let Ok(upstream) = branch.upstream()
-    {
-        let local_oid = branch.get().target();
-        let upstream_oid = upstream.get().target();
-        if let (Some(local), Some(up)) = (local_oid, upstream_oid) {
-            let (ahead, behind) = repo.graph_ahead_behind(local, up).unwrap_or((0, 0));
-            return (ahead, behind, false);
+}
+
+/// How far back a commit counts as recent for `Activity::recent_commits`.
+const RECENT: Duration = Duration::from_hours(30 * 24);
+
+/// Walks the history of the current branch to find out how active the repository is.
+/// # Arguments
+/// * `repo` - The Git repository to inspect.
+/// # Returns
+/// The dates of the first and last commits, the commits of the last 30 days and the
+/// number of authors, or `None` without commits.
+pub fn get_activity(repo: &Repository) -> Option<Activity> {
+    let head = repo.head().ok()?.target()?;
+    let mut revwalk = repo.revwalk().ok()?;
+    revwalk.push(head).ok()?;
+    let recent_since = SystemTime::now()
+        .checked_sub(RECENT)
+        .map_or(0, crate::util::unix_seconds)
+        .try_into()
+        .unwrap_or(i64::MAX);
+    let mut first_commit = i64::MAX;
+    let mut last_commit = i64::MIN;
+    let mut recent_commits = 0;
+    let mut authors = HashSet::new();
+    for commit in revwalk.filter_map(|oid| repo.find_commit(oid.ok()?).ok()) {
+        let time = commit.time().seconds();
+        first_commit = first_commit.min(time);
+        last_commit = last_commit.max(time);
+        if time >= recent_since {
+            recent_commits += 1;
+        }
+        if let Ok(email) = commit.author().email() {
+            authors.insert(email.to_lowercase());
+        }
+    }
+    (last_commit >= first_commit).then_some(Activity {
+        first_commit,
+        last_commit,
+        recent_commits,
+        contributors: authors.len(),
+    })
+}
+
+/// Returns the repositories registered for background maintenance with
+/// `git maintenance register`, i.e. the values of `maintenance.repo`.
+/// # Arguments
+/// * `config` - The configuration to read, usually `git2::Config::open_default()`.
+/// # Returns
+/// The paths of the repositories, in the order they were registered.
+pub fn maintenance_repositories(config: &git2::Config) -> Vec<PathBuf> {
+    let mut repos = Vec::new();
+    if let Ok(entries) = config.multivar("maintenance.repo", None) {
+        let _ = entries.for_each(|entry| {
+            if let Ok(value) = entry.value() {
+                repos.push(PathBuf::from(value));
+            }
+        });
+    }
+    repos
+}
+
+/// Pointer files are tiny, so larger blobs need not be read to rule them out.
+const LFS_POINTER_MAX_SIZE: usize = 1024;
+
+/// Checks the Git LFS objects of the checked out commit.
+///
+/// A repository uses Git LFS if its `.gitattributes` routes files through the `lfs` filter
+/// or it has a `.lfsconfig`. Every file of the commit that is stored as an LFS pointer is
+/// an object, and it is missing if its content is not in the local LFS store.
+/// # Arguments
+/// * `repo` - The Git repository to check.
+/// # Returns
+/// The number of objects and of missing ones, or `None` if the repository does not use
+/// Git LFS.
+pub fn get_lfs_status(repo: &Repository) -> Option<LfsStatus> {
+    let workdir = repo.workdir()?;
+    let uses_lfs = fs::read_to_string(workdir.join(".gitattributes"))
+        .is_ok_and(|attributes| attributes.contains("filter=lfs"))
+        || workdir.join(".lfsconfig").exists();
+    if !uses_lfs {
+        return None;
+    }
+
+    let mut status = LfsStatus::default();
+    let (Ok(odb), Ok(tree)) = (repo.odb(), repo.head().and_then(|h| h.peel_to_tree())) else {
+        return Some(status);
+    };
+    let store = repo.commondir().join("lfs").join("objects");
+    let _ = tree.walk(git2::TreeWalkMode::PreOrder, |_, entry| {
+        if entry.kind() == Some(git2::ObjectType::Blob)
+            && odb
+                .read_header(entry.id())
+                .is_ok_and(|(size, _)| size <= LFS_POINTER_MAX_SIZE)
+            && let Ok(blob) = repo.find_blob(entry.id())
+            && let Some(oid) = lfs_pointer_oid(blob.content())
+        {
+            status.objects += 1;
+            if !store.join(&oid[..2]).join(&oid[2..4]).join(oid).exists() {
+                status.missing += 1;
+            }
         }
+        git2::TreeWalkResult::Ok
+    });
+    Some(status)
+}
+
+/// Returns the SHA-256 of the object a Git LFS pointer file refers to, `None` unless it
+/// is 64 hexadecimal digits, as the path of the object is built from it.
+fn lfs_pointer_oid(content: &[u8]) -> Option<&str> {
+    let text = str::from_utf8(content).ok()?;
+    if !text.starts_with("version https://git-lfs.github.com/spec/") {
+        return None;
     }
-    (0, 0, true)
+    text.lines()
+        .find_map(|line| line.strip_prefix("oid sha256:"))
+        .filter(|oid| oid.len() == 64 && oid.bytes().all(|byte| byte.is_ascii_hexdigit()))
+}
+
+/// Checks whether the repository is a partial clone, i.e. one cloned with `--filter` that
+/// fetches blobs or trees from a promisor remote only once they are needed.
+pub fn is_partial_clone(repo: &Repository) -> bool {
+    let Ok(config) = repo.config().and_then(|mut c| c.snapshot()) else {
+        return false;
+    };
+    config.get_str("extensions.partialclone").is_ok()
+        || repo.remotes().is_ok_and(|remotes| {
+            remotes.iter().flatten().flatten().any(|remote| {
+                config
+                    .get_bool(&format!("remote.{remote}.promisor"))
+                    .unwrap_or(false)
+            })
+        })
+}
+
+/// Lists the local branches that track a remote branch which no longer exists, typically
+/// because it was deleted after its pull request was merged. Whether a remote branch
+/// exists is known as of the last fetch, and only a fetch that prunes forgets deleted ones.
+/// # Arguments
+/// * `repo` - The Git repository to check.
+/// # Returns
+/// The names of the branches, sorted.
+pub fn get_gone_branches(repo: &Repository) -> Vec<String> {
+    let Ok(branches) = repo.branches(Some(git2::BranchType::Local)) else {
+        return Vec::new();
+    };
+    let mut gone: Vec<String> = branches
+        .filter_map(Result::ok)
+        .filter_map(|(branch, _)| {
+            let refname = branch.get().name().ok()?;
+            let upstream = repo.branch_upstream_name(refname).ok()?;
+            let upstream = upstream.as_str().ok()?;
+            // A branch tracking another local branch has nothing to lose on a remote.
+            (upstream.starts_with("refs/remotes/") && repo.find_reference(upstream).is_err())
+                .then(|| branch.name().ok().flatten().map(ToOwned::to_owned))
+                .flatten()
+        })
+        .collect();
+    gone.sort();
+    gone
+}
+
+/// Counts the commits the default branch of `origin` lacks compared to that of
+/// `upstream`, the conventional remote names of a fork and the repository it was forked
+/// from. The default branch is the one `upstream/HEAD` points to, or else `main` or
+/// `master`. The remote branches are as of their last fetch.
+/// # Arguments
+/// * `repo` - The Git repository to check.
+/// # Returns
+/// How far `origin` is behind, or `None` if the repository lacks either remote or their
+/// default branch.
+pub fn get_fork_behind(repo: &Repository) -> Option<usize> {
+    let branch = remote_default_branch(repo, "upstream")?;
+    let tip = |remote: &str| {
+        repo.find_reference(&format!("refs/remotes/{remote}/{branch}"))
+            .ok()?
+            .target()
+    };
+    let (_, behind) = repo
+        .graph_ahead_behind(tip("origin")?, tip("upstream")?)
+        .ok()?;
+    Some(behind)
+}
+
+/// Finds the default branch of the repository: the branch `HEAD` of the first remote
+/// (preferring "origin") points to, as of the last fetch, or else a local `main` or
+/// `master`.
+/// # Arguments
+/// * `repo` - The Git repository to check.
+/// # Returns
+/// The name of the branch and its tip, the remote one if there is a remote.
+pub fn get_default_branch(repo: &Repository) -> Option<(String, git2::Oid)> {
+    if let Some(remote) = get_remote_name(repo)
+        && let Some(branch) = remote_default_branch(repo, &remote)
+        && let Ok(reference) = repo.find_reference(&format!("refs/remotes/{remote}/{branch}"))
+        && let Some(tip) = reference.target()
+    {
+        return Some((branch, tip));
+    }
+    ["main", "master"].into_iter().find_map(|branch| {
+        let tip = repo
+            .find_branch(branch, git2::BranchType::Local)
+            .ok()?
+            .get()
+            .target()?;
+        Some((branch.to_owned(), tip))
+    })
+}
+
+/// Returns the name of the default branch of `remote`, see `get_fork_behind`.
+fn remote_default_branch(repo: &Repository, remote: &str) -> Option<String> {
+    let prefix = format!("refs/remotes/{remote}/");
+    let head = repo.find_reference(&format!("{prefix}HEAD")).ok();
+    if let Some(Ok(Some(target))) = head.as_ref().map(git2::Reference::symbolic_target)
+        && let Some(branch) = target.strip_prefix(&prefix)
+    {
+        return Some(branch.to_owned());
+    }
+    ["main", "master"]
+        .into_iter()
+        .find(|branch| repo.find_reference(&format!("{prefix}{branch}")).is_ok())
+        .map(ToOwned::to_owned)
 }
 
 /// Gets the total number of commits in the current branch.
@@ -178,6 +526,35 @@ pub fn get_total_commits(repo: &Repository) -> anyhow::Result<usize> {
     Ok(revwalk.count())
 }
 
+/// Returns an identifier of the repository that survives moving or renaming its checkout.
+///
+/// This is the OID of the root commit reached by following first parents from HEAD, so
+/// every clone of a project shares it. Repositories without commits fall back to their
+/// remote URL, without a trailing slash or `.git` suffix.
+/// # Arguments
+/// * `repo` - The Git repository to identify.
+/// # Returns
+/// The identity, or `None` for a repository with neither commits nor a remote.
+pub fn get_repo_identity(repo: &Repository) -> Option<String> {
+    let root = repo
+        .head()
+        .ok()
+        .and_then(|head| head.target())
+        .and_then(|oid| {
+            let mut revwalk = repo.revwalk().ok()?;
+            revwalk.simplify_first_parent().ok()?;
+            revwalk.push(oid).ok()?;
+            revwalk.filter_map(Result::ok).last()
+        });
+    if let Some(root) = root {
+        return Some(root.to_string());
+    }
+
+    let url = get_remote_url(repo)?;
+    let url = url.trim_end_matches('/');
+    Some(url.strip_suffix(".git").unwrap_or(url).to_owned())
+}
+
 /// Returns the number of changed (unstaged, staged or untracked) files.
 pub fn get_changed_count(repo: &Repository) -> usize {
     let mut opts = StatusOptions::new();
@@ -190,6 +567,188 @@ pub fn get_changed_count(repo: &Repository) -> usize {
     })
 }
 
+/// Returns the number of ignored entries of the working directory. An ignored directory,
+/// e.g. `target/`, counts once, without descending into it.
+pub fn get_ignored_count(repo: &Repository) -> usize {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false)
+        .include_ignored(true)
+        .recurse_ignored_dirs(false)
+        .exclude_submodules(true);
+    repo.statuses(Some(&mut opts)).map_or(0, |statuses| {
+        statuses.iter().filter(|e| e.status().is_ignored()).count()
+    })
+}
+
+/// Counts the files of the index whose changes `git status` does not show, because they
+/// are marked with `git update-index --assume-unchanged` or `--skip-worktree`.
+///
+/// With sparse checkout, skip-worktree marks the files that are left out of the working
+/// directory on purpose, so they are not counted.
+pub fn get_hidden_files(repo: &Repository) -> HiddenFiles {
+    // The index cached by the repository may predate `git update-index`.
+    let Some(index) = repo
+        .index()
+        .ok()
+        .and_then(|mut index| index.read(false).is_ok().then_some(index))
+    else {
+        return HiddenFiles::default();
+    };
+    let sparse = repo
+        .config()
+        .and_then(|config| config.get_bool("core.sparseCheckout"))
+        .unwrap_or(false);
+    let mut hidden = HiddenFiles::default();
+    for entry in index.iter() {
+        if git2::IndexEntryFlag::from_bits_truncate(entry.flags).is_valid() {
+            hidden.assume_unchanged += 1;
+        }
+        if !sparse
+            && git2::IndexEntryExtendedFlag::from_bits_truncate(entry.flags_extended)
+                .is_skip_worktree()
+        {
+            hidden.skip_worktree += 1;
+        }
+    }
+    hidden
+}
+
+/// Returns the number of conflicted files, left behind by a merge, rebase, revert,
+/// cherry-pick or stash that stopped on a conflict.
+pub fn get_conflict_count(repo: &Repository) -> usize {
+    // Reading the index is cheap, walking the working directory is not. The index is
+    // read again in case git changed it since the repository was opened.
+    if !repo
+        .index()
+        .is_ok_and(|mut index| index.read(false).is_ok() && index.has_conflicts())
+    {
+        return 0;
+    }
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(false).include_ignored(false);
+    repo.statuses(Some(&mut opts)).map_or(0, |statuses| {
+        statuses
+            .iter()
+            .filter(|e| e.status().is_conflicted())
+            .count()
+    })
+}
+
+/// Reads how far the rebase, cherry-pick or revert in progress got, from the files git
+/// keeps in `rebase-merge`, `rebase-apply` or `sequencer` in the git directory.
+/// # Returns
+/// The progress, or `None` if no such operation is in progress or its files cannot be read.
+pub fn get_progress(repo: &Repository) -> Option<OperationProgress> {
+    let git_dir = repo.path();
+    let read = |path: PathBuf| fs::read_to_string(path).ok().map(|s| s.trim().to_owned());
+    match repo.state() {
+        RepositoryState::Rebase
+        | RepositoryState::RebaseInteractive
+        | RepositoryState::RebaseMerge => {
+            // `git rebase --apply` numbers its steps in other files than the merge backend.
+            let (dir, step, total) = if git_dir.join("rebase-merge").is_dir() {
+                (git_dir.join("rebase-merge"), "msgnum", "end")
+            } else {
+                (git_dir.join("rebase-apply"), "next", "last")
+            };
+            let branch = read(dir.join("head-name"))
+                .filter(|name| name != "detached HEAD")
+                .map(|name| name.trim_start_matches("refs/heads/").to_owned());
+            let onto = read(dir.join("onto"))
+                .and_then(|onto| Oid::from_str(&onto).ok())
+                .and_then(|onto| describe_commit(repo, onto));
+            Some(OperationProgress {
+                step: read(dir.join(step))?.parse().ok()?,
+                total: read(dir.join(total))?.parse().ok()?,
+                branch,
+                onto,
+            })
+        }
+        RepositoryState::CherryPick
+        | RepositoryState::CherryPickSequence
+        | RepositoryState::Revert
+        | RepositoryState::RevertSequence => {
+            // A single commit is picked without a sequencer. Otherwise, the todo list holds
+            // the current commit and those after it, and every commit done so far is one on
+            // top of the HEAD the sequence started from.
+            let sequencer = git_dir.join("sequencer");
+            let remaining = read(sequencer.join("todo")).map_or(1, |todo| {
+                todo.lines()
+                    .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+                    .count()
+                    .max(1)
+            });
+            let done = read(sequencer.join("head"))
+                .and_then(|start| Oid::from_str(&start).ok())
+                .zip(repo.head().ok().and_then(|head| head.target()))
+                .and_then(|(start, head)| repo.graph_ahead_behind(head, start).ok())
+                .map_or(0, |(ahead, _)| ahead);
+            Some(OperationProgress {
+                step: done + 1,
+                total: done + remaining,
+                branch: None,
+                onto: None,
+            })
+        }
+        RepositoryState::Clean
+        | RepositoryState::Merge
+        | RepositoryState::Bisect
+        | RepositoryState::ApplyMailbox
+        | RepositoryState::ApplyMailboxOrRebase => None,
+    }
+}
+
+/// Names a commit by a branch pointing at it, local branches first, or else by its
+/// abbreviated id.
+fn describe_commit(repo: &Repository, id: Oid) -> Option<String> {
+    let branch = repo.branches(None).ok().and_then(|branches| {
+        let mut names: Vec<(bool, String)> = branches
+            .flatten()
+            .filter(|(branch, _)| branch.get().target() == Some(id))
+            .filter_map(|(branch, kind)| {
+                let name = branch.name().ok()??.to_owned();
+                Some((kind == git2::BranchType::Remote, name))
+            })
+            .collect();
+        names.sort();
+        names.into_iter().next().map(|(_, name)| name)
+    });
+    branch.or_else(|| {
+        let short = repo.find_object(id, None).ok()?.short_id().ok()?;
+        short.as_str().ok().map(ToOwned::to_owned)
+    })
+}
+
+/// Lists the submodules that have changes or untracked files of their own, while still
+/// being checked out at the commit the repository records for them.
+/// # Arguments
+/// * `repo` - The Git repository whose submodules to check.
+/// # Returns
+/// The paths of the submodules, relative to the working directory.
+pub fn get_dirty_submodule_paths(repo: &Repository) -> BTreeSet<String> {
+    let moved = SubmoduleStatus::WD_MODIFIED
+        | SubmoduleStatus::WD_ADDED
+        | SubmoduleStatus::WD_DELETED
+        | SubmoduleStatus::INDEX_MODIFIED
+        | SubmoduleStatus::INDEX_ADDED
+        | SubmoduleStatus::INDEX_DELETED;
+    let dirty = SubmoduleStatus::WD_INDEX_MODIFIED
+        | SubmoduleStatus::WD_WD_MODIFIED
+        | SubmoduleStatus::WD_UNTRACKED;
+    repo.submodules()
+        .unwrap_or_default()
+        .iter()
+        .filter(|submodule| {
+            submodule
+                .name()
+                .ok()
+                .and_then(|name| repo.submodule_status(name, SubmoduleIgnore::None).ok())
+                .is_some_and(|status| status.intersects(dirty) && !status.intersects(moved))
+        })
+        .map(|submodule| submodule.path().to_string_lossy().into_owned())
+        .collect()
+}
+
 /// Returns the remote URL for the first available remote (preferring "origin"), if available.
 pub fn get_remote_url(repo: &Repository) -> Option<String> {
     let remote_name = get_remote_name(repo)?;
@@ -198,8 +757,311 @@ pub fn get_remote_url(repo: &Repository) -> Option<String> {
         .and_then(|r| r.url().map(ToOwned::to_owned).ok())
 }
 
+/// Looks at the object store of a repository: how many loose objects and packfiles it
+/// has, and how large the git directory is. Both grow until `git gc` packs the objects.
+pub fn get_housekeeping(repo: &Repository) -> Housekeeping {
+    let common = repo.commondir();
+    let objects = common.join("objects");
+    let pack_dir = objects.join("pack");
+    let mut housekeeping = Housekeeping::default();
+    for entry in walkdir::WalkDir::new(common)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+    {
+        housekeeping.git_size += entry.metadata().map_or(0, |m| m.len());
+        let Some(dir) = entry.path().parent() else {
+            continue;
+        };
+        if dir == pack_dir {
+            housekeeping.packs +=
+                usize::from(entry.path().extension().is_some_and(|e| e == "pack"));
+        } else if dir.parent() == Some(objects.as_path())
+            && dir
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.len() == 2 && name.chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            housekeeping.loose_objects += 1;
+        }
+    }
+    housekeeping
+}
+
+/// Measures how much disk space a repository takes: its working directory, including
+/// ignored files and nested repositories, and its git directory. Both are walked at the
+/// same time, without following symlinks.
+pub fn get_disk_usage(repo: &Repository) -> DiskUsage {
+    let size = |dir: &Path, skip: Option<&Path>| -> u64 {
+        walkdir::WalkDir::new(dir)
+            .into_iter()
+            .filter_entry(|e| Some(e.path()) != skip)
+            .filter_map(Result::ok)
+            .filter(|e| !e.file_type().is_dir())
+            .filter_map(|e| e.metadata().ok())
+            .map(|m| m.len())
+            .sum()
+    };
+    let git_dir = repo.commondir();
+    // Skips the `.git` of the repository itself, whether a directory or the file of a
+    // linked worktree or submodule.
+    let workdir = repo
+        .workdir()
+        .map(|workdir| (workdir, workdir.join(".git")));
+    let (worktree, git) = rayon::join(
+        || {
+            workdir
+                .as_ref()
+                .map_or(0, |(workdir, dot_git)| size(workdir, Some(dot_git)))
+        },
+        || size(git_dir, None),
+    );
+    DiskUsage { worktree, git }
+}
+
+/// How many problems `check_integrity` reports per check at most.
+const MAX_INTEGRITY_ERRORS: usize = 5;
+
+/// Checks a repository for corruption: whether HEAD, the refs and the index can be read,
+/// and whether the objects HEAD and the refs point to exist.
+/// # Arguments
+/// * `repo` - The Git repository to check.
+/// * `fsck` - Whether to also run `git fsck --no-full`, which checks the connectivity of
+///   the history and the loose objects, but is much slower.
+/// # Returns
+/// The problems found, none for an intact repository.
+pub fn check_integrity(repo: &Repository, fsck: bool) -> Vec<String> {
+    let mut errors = Vec::new();
+    match repo.head().and_then(|head| head.peel_to_commit()) {
+        Ok(_) => {}
+        // A repository without commits has nothing to resolve yet.
+        Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {}
+        Err(e) => errors.push(format!("HEAD: {}", e.message())),
+    }
+    match repo.odb().and_then(|odb| Ok((odb, repo.references()?))) {
+        Ok((odb, references)) => {
+            let broken = references.filter_map(|reference| match reference {
+                Ok(reference) => {
+                    let id = reference.target().filter(|id| !odb.exists(*id))?;
+                    let name = String::from_utf8_lossy(reference.name_bytes()).into_owned();
+                    Some(format!("{name}: missing object {id}"))
+                }
+                Err(e) => Some(format!("refs: {}", e.message())),
+            });
+            errors.extend(broken.take(MAX_INTEGRITY_ERRORS));
+        }
+        Err(e) => errors.push(format!("refs: {}", e.message())),
+    }
+    if let Err(e) = repo.index() {
+        errors.push(format!("index: {}", e.message()));
+    }
+    if fsck {
+        errors.extend(run_fsck(repo));
+    }
+    errors
+}
+
+/// Checks the signatures of the last commits of HEAD with `git log --format=%G?`, which
+/// verifies GPG, SSH and X.509 signatures with the keys and allowed signers git is set up
+/// with.
+/// # Arguments
+/// * `repo` - The Git repository to check.
+/// * `count` - How many commits to check, starting at HEAD.
+/// # Returns
+/// How many of the commits are signed and whether the signatures verify, `None` if the
+/// repository has no commits or git could not check them.
+pub fn get_signatures(repo: &Repository, count: usize) -> Option<Signatures> {
+    if repo.head().is_err() {
+        return None;
+    }
+    let output = Command::new("git")
+        .arg("--git-dir")
+        .arg(repo.path())
+        .args([
+            "log",
+            "--format=%G?",
+            &format!("--max-count={count}"),
+            "HEAD",
+        ])
+        .output()
+        .inspect_err(|e| log::debug!("Failed to run git log: {e}"))
+        .ok()
+        .filter(|output| output.status.success())?;
+    let mut signatures = Signatures::default();
+    for code in String::from_utf8_lossy(&output.stdout).lines() {
+        signatures.checked += 1;
+        match code.trim() {
+            // A good signature, with a key of unknown trust for `U`.
+            "G" | "U" => signatures.good += 1,
+            "B" => signatures.bad += 1,
+            "N" => signatures.unsigned += 1,
+            // Expired or revoked keys, expired signatures, or keys that are missing.
+            _ => signatures.unverified += 1,
+        }
+    }
+    Some(signatures)
+}
+
+/// Runs `git fsck --no-full` on a repository.
+/// # Returns
+/// The problems it reported, or why it could not run.
+fn run_fsck(repo: &Repository) -> Vec<String> {
+    let output = match Command::new("git")
+        .arg("--git-dir")
+        .arg(repo.path())
+        .args(["fsck", "--no-full", "--no-dangling", "--no-progress"])
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => return vec![format!("fsck: failed to run git: {e}")],
+    };
+    if output.status.success() {
+        return Vec::new();
+    }
+    let mut errors: Vec<String> = [&output.stdout, &output.stderr]
+        .into_iter()
+        .flat_map(|bytes| {
+            String::from_utf8_lossy(bytes)
+                .lines()
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        })
+        .filter(|line| !line.trim().is_empty())
+        .take(MAX_INTEGRITY_ERRORS)
+        .map(|line| format!("fsck: {line}"))
+        .collect();
+    if errors.is_empty() {
+        errors.push(format!("fsck: failed with {}", output.status));
+    }
+    errors
+}
+
+/// Lists the lock files git holds while it changes the repository, as far as they exist.
+fn lock_files(repo: &Repository) -> Vec<PathBuf> {
+    let common = repo.commondir();
+    let ref_locks = walkdir::WalkDir::new(common.join("refs"))
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| {
+            e.file_type().is_file() && e.path().extension().is_some_and(|ext| ext == "lock")
+        })
+        .map(walkdir::DirEntry::into_path);
+    GIT_DIR_LOCKS
+        .iter()
+        .map(|lock| repo.path().join(lock))
+        .chain(COMMON_DIR_LOCKS.iter().map(|lock| common.join(lock)))
+        .filter(|path| path.is_file())
+        .chain(ref_locks)
+        .collect()
+}
+
+/// Returns whether a lock file is older than `LOCK_MAX_AGE`. A lock whose age cannot be
+/// told, e.g. one modified in the future, is not.
+fn is_stale_lock(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .is_ok_and(|modified| {
+            SystemTime::now()
+                .duration_since(modified)
+                .is_ok_and(|age| age >= LOCK_MAX_AGE)
+        })
+}
+
+/// The lock files of a repository, see `get_locks`.
+#[derive(Debug, Default)]
+pub struct Locks {
+    /// The locks younger than `LOCK_MAX_AGE`, held by a git operation in progress.
+    pub fresh: Vec<PathBuf>,
+    /// The locks older than `LOCK_MAX_AGE`, see `get_stale_locks`.
+    pub stale: Vec<PathBuf>,
+}
+
+/// Lists the lock files of the repository, split into fresh and stale ones with a single
+/// look at the repository.
+///
+/// A fresh lock means another process, e.g. an IDE running a background fetch, is in the
+/// middle of a git operation on the repository. Only locks younger than `LOCK_MAX_AGE`
+/// count as such, so a lock left behind by a crashed process does not mark the repository
+/// busy forever.
+pub fn get_locks(repo: &Repository) -> Locks {
+    let (stale, fresh) = lock_files(repo)
+        .into_iter()
+        .partition(|lock| is_stale_lock(lock));
+    Locks { fresh, stale }
+}
+
+/// Lists the lock files older than `LOCK_MAX_AGE`, which a crashed or killed git process
+/// left behind. Until they are removed, every git command that changes the repository
+/// fails.
+/// # Returns
+/// The paths of the lock files.
+pub fn get_stale_locks(repo: &Repository) -> Vec<PathBuf> {
+    get_locks(repo).stale
+}
+
+/// Waits for the git operation another process is running on the repository to finish,
+/// looking at the lock files again only while a fresh one exists.
+/// # Arguments
+/// * `repo` - The repository to watch.
+/// * `timeout` - How long to wait at most.
+/// # Returns
+/// The lock files as of the last look. The repository is idle if none of them is fresh,
+/// and still busy after `timeout` otherwise.
+pub fn wait_until_idle(repo: &Repository, timeout: Duration) -> Locks {
+    let start = Instant::now();
+    loop {
+        let locks = get_locks(repo);
+        if locks.fresh.is_empty() || start.elapsed() >= timeout {
+            return locks;
+        }
+        thread::sleep(IDLE_POLL);
+    }
+}
+
+/// Phrases in the output of `git fetch` that mean the credentials were missing or rejected,
+/// as opposed to the remote being unreachable or the repository missing.
+const AUTH_FAILURE_MESSAGES: &[&str] = &[
+    "Authentication failed",
+    "Permission denied (publickey",
+    "could not read Username",
+    "could not read Password",
+    "terminal prompts disabled",
+];
+
+/// The error `fetch_origin` fails with when the remote rejected the credentials.
+#[derive(Debug)]
+pub struct AuthFailed {
+    /// The remote that was fetched.
+    pub remote: String,
+    /// What git printed.
+    pub stderr: String,
+}
+
+impl Display for AuthFailed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Authentication failed for {}: {}",
+            self.remote,
+            self.stderr.trim_end()
+        )
+    }
+}
+
+impl Error for AuthFailed {}
+
 /// Executes a fetch operation for the first available remote (preferring "origin") to update upstream information.
-pub fn fetch_origin(repo: &Repository) -> anyhow::Result<()> {
+///
+/// Git is not allowed to prompt for credentials, since the fetches run in parallel and
+/// unattended.
+///
+/// With `prune`, remote-tracking branches whose remote branch was deleted are removed,
+/// like `git fetch --prune` does.
+///
+/// # Errors
+/// Returns an `AuthFailed` if the remote rejected the credentials, and another error if
+/// there is no remote or the fetch failed otherwise.
+pub fn fetch_origin(repo: &Repository, prune: bool) -> anyhow::Result<()> {
     let remote_name = get_remote_name(repo).ok_or_else(|| anyhow::anyhow!("No remotes found"))?;
     // `repo.path()` is the git directory. For a worktree that is
     // `<main>/.git/worktrees/<name>`, whose parent is not a working directory at all, so
@@ -210,16 +1072,22 @@ pub fn fetch_origin(repo: &Repository) -> anyhow::Result<()> {
         .ok_or_else(|| anyhow::anyhow!("No working directory found"))?;
     let output = Command::new("git")
         .arg("fetch")
+        .args(prune.then_some("--prune"))
         .arg(&remote_name)
         .current_dir(path)
+        .env("GIT_TERMINAL_PROMPT", "0")
         .output()?;
 
     if !output.status.success() {
-        anyhow::bail!(
-            "Failed to fetch from {}: {}",
-            remote_name,
-            String::from_utf8_lossy(&output.stderr)
-        )
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if AUTH_FAILURE_MESSAGES.iter().any(|m| stderr.contains(m)) {
+            return Err(AuthFailed {
+                remote: remote_name,
+                stderr: stderr.into_owned(),
+            }
+            .into());
+        }
+        anyhow::bail!("Failed to fetch from {remote_name}: {stderr}")
     }
 
     Ok(())
@@ -303,3 +1171,54 @@ pub fn get_stash_count(repo: &mut Repository) -> usize {
     });
     count
 }
+
+/// Maps an untracked path to the `.gitignore` entry that would hide it, if it is an
+/// obvious artifact.
+/// # Arguments
+/// * `path` - The untracked path as reported by git, with a trailing slash for directories.
+/// # Returns
+/// The suggested `.gitignore` entry, or `None` if the path does not look like an artifact.
+fn gitignore_entry_for(path: &str) -> Option<String> {
+    let is_dir = path.ends_with('/');
+    let path = Path::new(path.trim_end_matches('/'));
+    let name = path.file_name()?.to_str()?;
+
+    if is_dir {
+        return ARTIFACT_DIRS.contains(&name).then(|| format!("{name}/"));
+    }
+    if ARTIFACT_FILES.contains(&name) {
+        return Some(name.to_owned());
+    }
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .filter(|ext| ARTIFACT_EXTENSIONS.contains(ext))
+        .map(|ext| format!("*.{ext}"))
+}
+
+/// Suggests `.gitignore` entries for untracked build artifacts and OS clutter.
+///
+/// Untracked directories are not descended into, so a `node_modules` with thousands of
+/// files costs a single entry. At most `UNTRACKED_SAMPLE` untracked entries are looked at.
+/// # Arguments
+/// * `repo` - The Git repository to analyze.
+/// # Returns
+/// The suggested entries, sorted and without duplicates.
+pub fn suggest_gitignore_entries(repo: &Repository) -> Vec<String> {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(false)
+        .include_ignored(false)
+        .exclude_submodules(true);
+    let Ok(statuses) = repo.statuses(Some(&mut opts)) else {
+        return Vec::new();
+    };
+
+    statuses
+        .iter()
+        .filter(|e| e.status().is_wt_new())
+        .take(UNTRACKED_SAMPLE)
+        .filter_map(|e| e.path().ok().and_then(gitignore_entry_for))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
@@ -1,12 +1,25 @@
-use std::path::{Path, PathBuf};
+use std::{
+    iter,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
 use git2::Repository;
 
 use crate::{
-    gitinfo::{self, status::Status},
-    util::GitPathExt as _,
+    auth::AuthFailures,
+    disk_usage::DiskUsageCache,
+    forge::ForgeStatus,
+    gitinfo::{self, status::Status, symbol::Symbol},
+    scanner::IntegrityCheck,
+    throttle::FetchLimits,
+    util::{self, GitPathExt as _},
 };
 
+/// How long to wait for another process to finish its git operation on a repository before
+/// reporting it as busy.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// Holds information about a Git repository for status display.
 #[expect(
     clippy::struct_excessive_bools,
@@ -22,10 +35,24 @@ pub struct RepoInfo {
     pub ahead: usize,
     /// Number of commits behind upstream.
     pub behind: usize,
-    /// Total number of commits in the current branch.
-    pub commits: usize,
+    /// Total number of commits in the current branch, `None` if they were not counted, see
+    /// `--no-commit-count`.
+    pub commits: Option<usize>,
     /// Status of the repository.
     pub status: Status,
+    /// Number of conflicted files, see `gitinfo::get_conflict_count`.
+    #[serde(default)]
+    pub conflicts: usize,
+    /// How far the rebase, cherry-pick or revert in progress got, see
+    /// `gitinfo::get_progress`.
+    #[serde(default)]
+    pub progress: Option<OperationProgress>,
+    /// The lock files a crashed git process left behind, see `gitinfo::get_stale_locks`.
+    #[serde(default)]
+    pub stale_locks: Vec<PathBuf>,
+    /// The corruption found by `--check-integrity`, see `gitinfo::check_integrity`.
+    #[serde(default)]
+    pub integrity_errors: Vec<String>,
     /// True if there are unpushed commits.
     pub has_unpushed: bool,
     /// Remote URL (if available).
@@ -42,6 +69,328 @@ pub struct RepoInfo {
     pub repo_path: String,
     /// True if this is a Git worktree
     pub is_worktree: bool,
+    /// Identifies the repository independently of its path, see `gitinfo::get_repo_identity`.
+    #[serde(default)]
+    pub identity: Option<String>,
+    /// How many commits the default branch of `origin` is behind that of `upstream`, for
+    /// forks with both remotes, see `gitinfo::get_fork_behind`.
+    #[serde(default)]
+    pub fork_behind: Option<usize>,
+    /// Number of local branches whose upstream branch is gone from the remote, see
+    /// `gitinfo::get_gone_branches`.
+    #[serde(default)]
+    pub gone_branches: usize,
+    /// True if this is a shallow clone, whose commit count and ahead/behind counts only
+    /// cover the history that was fetched.
+    #[serde(default)]
+    pub is_shallow: bool,
+    /// True if this is a partial clone, which fetches objects only once they are needed.
+    #[serde(default)]
+    pub is_partial: bool,
+    /// The abbreviated id of the checked out commit, `None` without commits.
+    #[serde(default)]
+    pub head_sha: Option<String>,
+    /// The Git LFS objects of the checked out commit, `None` if the repository does not use
+    /// Git LFS.
+    #[serde(default)]
+    pub lfs: Option<LfsStatus>,
+    /// The `user.email` commits are made with, the repository's own if it sets one.
+    #[serde(default)]
+    pub user_email: Option<String>,
+    /// The open pull requests and CI status on the forge, only queried with `--github`.
+    #[serde(default)]
+    pub forge: Option<ForgeStatus>,
+    /// How long the steps of the scan took for this repository, only recorded with
+    /// `--timing`.
+    #[serde(default)]
+    pub timing: Option<Timing>,
+    /// The commit activity of the current branch, only gathered with `--activity`, and
+    /// `None` without commits.
+    #[serde(default)]
+    pub activity: Option<Activity>,
+    /// The state of the object store, only gathered with `--maintenance`.
+    #[serde(default)]
+    pub housekeeping: Option<Housekeeping>,
+    /// How much disk space the repository takes, only measured with `--disk-usage`.
+    #[serde(default)]
+    pub disk_usage: Option<DiskUsage>,
+    /// The signatures of the last commits, only checked with `--verify-signatures`.
+    #[serde(default)]
+    pub signatures: Option<Signatures>,
+    /// The files whose changes `git status` does not show, only counted with
+    /// `--hidden-files`.
+    #[serde(default)]
+    pub hidden_files: Option<HiddenFiles>,
+    /// Number of ignored files and directories, only counted with `--show-ignored`, see
+    /// `gitinfo::get_ignored_count`. They never make a repository dirty.
+    #[serde(default)]
+    pub ignored: Option<usize>,
+    /// The scanned directory the repository was found in, one of several when scanning
+    /// more than one.
+    #[serde(default)]
+    pub root: PathBuf,
+}
+
+/// Which Git LFS objects of a repository are there, see `gitinfo::get_lfs_status`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LfsStatus {
+    /// Number of files stored in Git LFS.
+    pub objects: usize,
+    /// Number of those whose content was never downloaded, e.g. because the clone ran
+    /// without Git LFS installed. They are checked out as pointer files.
+    pub missing: usize,
+}
+
+/// The commit activity of the current branch of a repository, see
+/// `gitinfo::get_activity`. Times are in seconds since the Unix epoch.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Activity {
+    /// When the oldest commit was made.
+    pub first_commit: i64,
+    /// When the newest commit was made.
+    pub last_commit: i64,
+    /// Number of commits made in the last 30 days.
+    pub recent_commits: usize,
+    /// Number of distinct author emails.
+    pub contributors: usize,
+}
+
+/// How far a rebase, cherry-pick or revert got, see `gitinfo::get_progress`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct OperationProgress {
+    /// The step being worked on, counting from 1.
+    pub step: usize,
+    /// Number of steps in total.
+    pub total: usize,
+    /// The branch being rebased, `None` for a detached HEAD or another operation.
+    pub branch: Option<String>,
+    /// The branch or, if none points at it, the commit a rebase goes onto.
+    pub onto: Option<String>,
+}
+
+/// The state of the object store of a repository, which `git gc` cleans up, see
+/// `gitinfo::get_housekeeping`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Housekeeping {
+    /// Number of objects stored in files of their own rather than in a pack.
+    pub loose_objects: usize,
+    /// Number of packfiles.
+    pub packs: usize,
+    /// Size of the git directory in bytes, shared with the other worktrees.
+    pub git_size: u64,
+}
+
+/// How much disk space a repository takes, in bytes, see `gitinfo::get_disk_usage`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// The files of the working directory, including ignored ones, 0 for a bare repository.
+    pub worktree: u64,
+    /// The git directory, shared with the other worktrees.
+    pub git: u64,
+}
+
+/// The signatures of the last commits of a repository, see `gitinfo::get_signatures`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Signatures {
+    /// Number of commits checked.
+    pub checked: usize,
+    /// Number of commits with a good signature.
+    pub good: usize,
+    /// Number of commits with a bad signature.
+    pub bad: usize,
+    /// Number of commits whose signature cannot be verified, e.g. as the key is missing,
+    /// expired or revoked.
+    pub unverified: usize,
+    /// Number of commits without a signature.
+    pub unsigned: usize,
+}
+
+impl Signatures {
+    /// Returns whether every checked commit has a good signature.
+    #[must_use]
+    pub const fn all_good(&self) -> bool {
+        self.good == self.checked
+    }
+}
+
+/// The files of a repository whose changes `git status` does not show, see
+/// `gitinfo::get_hidden_files`.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HiddenFiles {
+    /// Number of files marked with `git update-index --assume-unchanged`.
+    pub assume_unchanged: usize,
+    /// Number of files marked with `git update-index --skip-worktree`.
+    pub skip_worktree: usize,
+}
+
+impl HiddenFiles {
+    /// Returns the number of hidden files.
+    #[must_use]
+    pub const fn total(&self) -> usize {
+        self.assume_unchanged + self.skip_worktree
+    }
+}
+
+/// How long the steps of scanning a repository took, see `--timing`. In JSON, every step is
+/// in milliseconds.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Timing {
+    /// Opening the repository.
+    #[serde(with = "millis")]
+    pub open: Duration,
+    /// Fetching and fast-forwarding, zero without `--fetch` or `--ff`.
+    #[serde(with = "millis")]
+    pub fetch: Duration,
+    /// Walking the history to count the commits.
+    #[serde(with = "millis")]
+    pub revwalk: Duration,
+    /// Everything else: the status of the working directory, ahead/behind, stashes and the
+    /// other columns.
+    #[serde(with = "millis")]
+    pub status: Duration,
+}
+
+impl Timing {
+    /// Returns how long the repository took in total.
+    #[must_use]
+    pub fn total(&self) -> Duration {
+        self.open + self.fetch + self.revwalk + self.status
+    }
+}
+
+/// (De)serializes a `Duration` as fractional milliseconds.
+mod millis {
+    use std::time::Duration;
+
+    use serde::{Deserialize as _, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let millis = f64::deserialize(deserializer)?;
+        Duration::try_from_secs_f64(millis / 1000.0).map_err(serde::de::Error::custom)
+    }
+}
+
+/// How `RepoInfo::inspect` gathers the information about a repository.
+#[derive(Debug, Clone, Copy)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "The options mirror independent command line flags"
+)]
+pub struct InspectOptions<'a> {
+    /// Whether to include the remote URL in the info.
+    pub show_remote: bool,
+    /// Whether to run a fetch operation before gathering info.
+    pub fetch: bool,
+    /// Whether to fetch and fast-forward before gathering info.
+    pub merge: bool,
+    /// Whether fetching removes remote-tracking branches deleted on the remote.
+    pub prune: bool,
+    /// The remote to count ahead/behind against instead of the upstream branch, see
+    /// `gitinfo::get_ahead_behind_and_local_status`.
+    pub upstream_remote: Option<&'a str>,
+    /// Skips fetches from hosts whose authentication failed recently.
+    pub auth: &'a AuthFailures,
+    /// Limits how many fetches run at the same time.
+    pub limits: &'a FetchLimits,
+    /// Whether to walk the history to count the commits of the current branch.
+    pub count_commits: bool,
+    /// Whether to walk the history down to the root commit for `RepoInfo::identity`.
+    pub identify: bool,
+    /// How long opening the repository took, to record `RepoInfo::timing`. `None` records
+    /// no timing.
+    pub opened_in: Option<Duration>,
+    /// Whether to walk the history of the current branch for `RepoInfo::activity`.
+    pub activity: bool,
+    /// How to check the repository for corruption, `None` to not check it.
+    pub integrity: Option<IntegrityCheck>,
+    /// Whether to look at the object store for `RepoInfo::housekeeping`.
+    pub maintenance: bool,
+    /// Where to get `RepoInfo::disk_usage` from, `None` to not measure it.
+    pub disk_usage: Option<&'a DiskUsageCache>,
+    /// Whether to count the ignored files for `RepoInfo::ignored`.
+    pub show_ignored: bool,
+    /// Whether to count the hidden files for `RepoInfo::hidden_files`.
+    pub hidden_files: bool,
+    /// Whether to look for the Git LFS objects for `RepoInfo::lfs`.
+    pub lfs: bool,
+    /// Whether to compare the remotes of a fork for `RepoInfo::fork_behind`.
+    pub forks: bool,
+    /// Whether to look for the branches of `RepoInfo::gone_branches`.
+    pub gone_branches: bool,
+    /// Whether to look at the config for `RepoInfo::is_partial`.
+    pub clones: bool,
+    /// Whether to read the config for `RepoInfo::user_email`.
+    pub user_email: bool,
+    /// How many commits to check the signatures of for `RepoInfo::signatures`, `None` to
+    /// not check them.
+    pub verify_signatures: Option<usize>,
+}
+
+/// A repository that could not be opened or inspected.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct FailedRepo {
+    /// The directory name of the repository.
+    pub name: String,
+    /// Path to the repository directory.
+    pub path: PathBuf,
+    /// Why the repository could not be processed.
+    pub error: String,
+}
+
+/// The cheap subset of `RepoInfo` that `--list-only` shows: where a repository is, but
+/// nothing about its state.
+#[derive(serde::Serialize, serde::Deserialize, Clone)]
+pub struct RepoListing {
+    /// The repository name, as in `RepoInfo::name`.
+    pub name: String,
+    /// Path to the repository directory.
+    pub path: PathBuf,
+    /// relative path from the starting directory
+    pub repo_path: String,
+    /// Remote URL (if requested and available).
+    pub remote_url: Option<String>,
+}
+
+impl RepoListing {
+    /// Creates a new `RepoListing` instance.
+    /// # Arguments
+    /// * `repo` - The Git repository to describe.
+    /// * `name` - The directory name, used if the remote URL does not yield a name.
+    /// * `show_remote` - Whether to include the remote URL.
+    /// * `dir` - The scanned directory, for the relative path.
+    #[must_use]
+    pub fn new(repo: &Repository, name: &str, show_remote: bool, dir: &Path) -> Self {
+        let path = gitinfo::get_repo_path(repo);
+        Self {
+            name: gitinfo::get_repo_name(repo).unwrap_or_else(|| name.to_owned()),
+            repo_path: relative_repo_path(&path, dir),
+            path,
+            remote_url: show_remote.then(|| gitinfo::get_remote_url(repo)).flatten(),
+        }
+    }
+}
+
+/// Returns the path of a repository relative to the scanned directory, for display.
+/// # Arguments
+/// * `path` - The path of the repository.
+/// * `dir` - The scanned directory.
+fn relative_repo_path(path: &Path, dir: &Path) -> String {
+    let repo_path = util::normalize_path(path);
+    let root_path = util::normalize_path(dir);
+    let repo_path_relative = util::strip_path_prefix(&repo_path, &root_path).unwrap_or(&repo_path);
+    // The scanned directory is the repository itself when git-statuses is run from
+    // inside one, which leaves the relative path empty. Fall back to the directory
+    // name, so the column reads like it would for a repository one level down instead
+    // of suddenly showing an absolute path.
+    if repo_path_relative.as_os_str().is_empty() {
+        repo_path.dir_name()
+    } else {
+        repo_path_relative.display().to_string()
+    }
 }
 
 impl RepoInfo {
@@ -60,6 +409,10 @@ impl RepoInfo {
     ///
     /// Fetching and fast-forwarding are best-effort: a repository without a remote
     /// or without an upstream branch is still reported, with a warning logged.
+    ///
+    /// If another git process holds a lock on the repository for longer than
+    /// `BUSY_TIMEOUT`, it is neither fetched nor fast-forwarded and its status is
+    /// `Status::Busy` instead of a snapshot of a half-finished operation.
     pub fn new(
         repo: &mut Repository,
         name: &str,
@@ -68,87 +421,403 @@ impl RepoInfo {
         merge: bool,
         dir: &Path,
     ) -> anyhow::Result<Self> {
+        let options = InspectOptions {
+            show_remote,
+            fetch,
+            merge,
+            prune: false,
+            upstream_remote: None,
+            auth: &AuthFailures::default(),
+            limits: &FetchLimits::default(),
+            count_commits: true,
+            identify: true,
+            opened_in: None,
+            activity: false,
+            integrity: None,
+            maintenance: false,
+            disk_usage: None,
+            show_ignored: false,
+            hidden_files: false,
+            lfs: false,
+            forks: false,
+            gone_branches: false,
+            clones: false,
+            user_email: false,
+            verify_signatures: None,
+        };
+        Self::inspect(repo, name, dir, &options)
+    }
+
+    /// Creates a new `RepoInfo` like `new`, with the options `Scanner` has beyond those of
+    /// `new`.
+    pub(crate) fn inspect(
+        repo: &mut Repository,
+        name: &str,
+        dir: &Path,
+        options: &InspectOptions<'_>,
+    ) -> anyhow::Result<Self> {
+        let InspectOptions {
+            show_remote,
+            upstream_remote,
+            count_commits,
+            identify,
+            opened_in,
+            activity,
+            integrity,
+            maintenance,
+            disk_usage,
+            show_ignored,
+            hidden_files,
+            lfs,
+            forks,
+            gone_branches,
+            clones,
+            user_email,
+            verify_signatures,
+            ..
+        } = *options;
+        let started = Instant::now();
         let name = gitinfo::get_repo_name(repo).unwrap_or_else(|| name.to_owned());
 
         // Fetching and merging must happen before any state is gathered, otherwise the
         // reported ahead/behind counts, commit count and status describe the pre-merge
         // repository and contradict the fast-forward marker shown next to them.
-        if (fetch || merge)
-            && let Err(e) = gitinfo::fetch_origin(repo)
-        {
-            log::warn!("Failed to fetch for `{name}`: {e}");
+        let locks = gitinfo::wait_until_idle(repo, BUSY_TIMEOUT);
+        let busy = !locks.fresh.is_empty();
+        if busy {
+            log::warn!("`{name}` is locked by another git process, skipping it");
         }
-        let fast_forwarded = merge
-            && gitinfo::merge_ff(repo).unwrap_or_else(|e| {
-                log::warn!("Failed to fast-forward `{name}`: {e}");
-                false
-            });
+        let integrity_errors = Self::check_integrity(repo, &name, integrity);
+
+        let fetching = Instant::now();
+        let fast_forwarded = !busy && Self::update(repo, &name, options);
+        let fetched = fetching.elapsed();
 
         let branch = gitinfo::get_branch_name(repo);
-        let (ahead, behind, is_local_only) = gitinfo::get_ahead_behind_and_local_status(repo);
-        let commits = gitinfo::get_total_commits(repo)?;
-        let status = Status::new(repo);
-        let has_unpushed = ahead > 0;
-        let remote_url = if show_remote {
-            gitinfo::get_remote_url(repo)
-        } else {
-            None
-        };
+        let (ahead, behind, is_local_only) =
+            gitinfo::get_ahead_behind_and_local_status(repo, upstream_remote);
+        let walking = Instant::now();
+        let commits = Self::count_commits(repo, count_commits, !integrity_errors.is_empty())?;
+        let revwalk = walking.elapsed();
+        let stale_locks = locks.stale;
+        let (status, conflicts, progress) =
+            Self::state(repo, busy, &stale_locks, &integrity_errors);
         let path = gitinfo::get_repo_path(repo);
-        let stash_count = gitinfo::get_stash_count(repo);
-        let repo_path = path.canonicalize().unwrap_or_else(|_| path.clone());
-        let root_path = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
-        let repo_path_relative = repo_path.strip_prefix(&root_path).unwrap_or(&repo_path);
-        // The scanned directory is the repository itself when git-statuses is run from
-        // inside one, which leaves the relative path empty. Fall back to the directory
-        // name, so the column reads like it would for a repository one level down instead
-        // of suddenly showing an absolute path.
-        let repo_path = if repo_path_relative.as_os_str().is_empty() {
-            repo_path.dir_name()
-        } else {
-            repo_path_relative.display().to_string()
-        };
-        let is_worktree = repo.is_worktree();
+        let disk_usage = disk_usage.map(|cache| cache.get(&path, || gitinfo::get_disk_usage(repo)));
 
-        Ok(Self {
+        let mut info = Self {
             name,
             branch,
             ahead,
             behind,
             commits,
             status,
-            has_unpushed,
-            remote_url,
-            path,
-            stash_count,
+            conflicts,
+            progress,
+            stale_locks,
+            integrity_errors,
+            has_unpushed: ahead > 0,
+            remote_url: show_remote.then(|| gitinfo::get_remote_url(repo)).flatten(),
+            stash_count: gitinfo::get_stash_count(repo),
             is_local_only,
             fast_forwarded,
-            repo_path,
-            is_worktree,
-        })
+            repo_path: relative_repo_path(&path, dir),
+            path,
+            is_worktree: repo.is_worktree(),
+            identity: identify.then(|| gitinfo::get_repo_identity(repo)).flatten(),
+            fork_behind: forks.then(|| gitinfo::get_fork_behind(repo)).flatten(),
+            gone_branches: gone_branches
+                .then(|| gitinfo::get_gone_branches(repo))
+                .map_or(0, |gone| gone.len()),
+            is_shallow: repo.is_shallow(),
+            is_partial: clones && gitinfo::is_partial_clone(repo),
+            head_sha: gitinfo::get_head_sha(repo),
+            lfs: lfs.then(|| gitinfo::get_lfs_status(repo)).flatten(),
+            user_email: user_email.then(|| gitinfo::get_user_email(repo)).flatten(),
+            forge: None,
+            timing: None,
+            activity: activity.then(|| gitinfo::get_activity(repo)).flatten(),
+            housekeeping: maintenance.then(|| gitinfo::get_housekeeping(repo)),
+            disk_usage,
+            signatures: verify_signatures.and_then(|count| gitinfo::get_signatures(repo, count)),
+            hidden_files: hidden_files.then(|| gitinfo::get_hidden_files(repo)),
+            ignored: show_ignored.then(|| gitinfo::get_ignored_count(repo)),
+            root: dir.to_path_buf(),
+        };
+        info.timing = opened_in.map(|open| Timing {
+            open,
+            fetch: fetched,
+            revwalk,
+            status: started.elapsed().saturating_sub(fetched + revwalk),
+        });
+        Ok(info)
+    }
+
+    /// Determines the status of a repository, with the number of conflicted files and the
+    /// progress of the operation in progress. A busy repository is not looked at any
+    /// further, and a corrupt one or one with stale locks is reported as such whatever
+    /// else it looks like.
+    fn state(
+        repo: &Repository,
+        busy: bool,
+        stale_locks: &[PathBuf],
+        integrity_errors: &[String],
+    ) -> (Status, usize, Option<OperationProgress>) {
+        if busy {
+            return (Status::Busy, 0, None);
+        }
+        let conflicts = gitinfo::get_conflict_count(repo);
+        let progress = gitinfo::get_progress(repo);
+        let status = if !integrity_errors.is_empty() {
+            Status::Corrupt
+        } else if !stale_locks.is_empty() {
+            Status::StaleLock
+        } else {
+            Status::new(repo)
+        };
+        (status, conflicts, progress)
+    }
+
+    /// Fetches the repository and fast-forwards it, as far as `options` ask to, and warns
+    /// about what failed. Returns whether the repository was fast-forwarded.
+    fn update(repo: &Repository, name: &str, options: &InspectOptions<'_>) -> bool {
+        if (options.fetch || options.merge)
+            && let Err(e) = options.auth.fetch(repo, options.prune, options.limits)
+        {
+            log::warn!("Failed to fetch for `{name}`: {e}");
+        }
+        options.merge
+            && gitinfo::merge_ff(repo).unwrap_or_else(|e| {
+                log::warn!("Failed to fast-forward `{name}`: {e}");
+                false
+            })
+    }
+
+    /// Checks the repository for corruption with `check`, if given, and warns about what
+    /// was found.
+    fn check_integrity(
+        repo: &Repository,
+        name: &str,
+        check: Option<IntegrityCheck>,
+    ) -> Vec<String> {
+        let errors = check.map_or_else(Vec::new, |check| {
+            gitinfo::check_integrity(repo, check == IntegrityCheck::Fsck)
+        });
+        if !errors.is_empty() {
+            log::warn!("`{name}` is corrupt: {}", errors.join("; "));
+        }
+        errors
+    }
+
+    /// Counts the commits of the current branch if `count` is set.
+    ///
+    /// # Errors
+    /// Returns an error if the history cannot be walked, unless the repository is already
+    /// known to be `corrupt`: then the commits are just not counted, so that the repository
+    /// is reported as corrupt instead of as failed.
+    fn count_commits(
+        repo: &Repository,
+        count: bool,
+        corrupt: bool,
+    ) -> anyhow::Result<Option<usize>> {
+        match count.then(|| gitinfo::get_total_commits(repo)) {
+            Some(Err(_)) if corrupt => Ok(None),
+            commits => commits.transpose(),
+        }
+    }
+
+    /// Formats the commit count, `-` if the commits were not counted.
+    #[must_use]
+    pub fn format_commits(&self) -> String {
+        self.commits
+            .map_or_else(|| "-".to_owned(), |commits| commits.to_string())
     }
 
     /// Formats the local status showing ahead/behind counts or local-only indication.
     /// # Returns
-    /// A formatted string showing ahead/behind counts or local-only indication.
+    /// A formatted string showing ahead/behind counts or local-only indication, followed
+    /// by how far a fork is behind its upstream and how many branches lost their upstream,
+    /// if any.
+    #[must_use]
     pub fn format_local_status(&self) -> String {
+        let mut local = if self.is_local_only {
+            Symbol::LocalOnly.to_string()
+        } else {
+            format!(
+                "{}{} {}{}",
+                Symbol::Ahead,
+                self.ahead,
+                Symbol::Behind,
+                self.behind
+            )
+        };
+        if let Some(behind) = self.fork_behind.filter(|&behind| behind > 0) {
+            local = format!("{local} ({}{behind})", Symbol::ForkBehind);
+        }
+        if self.gone_branches > 0 {
+            local = format!("{local} ({} {})", self.gone_branches, Symbol::Gone);
+        }
+        local
+    }
+
+    /// Formats the local status like `format_local_status`, but only with the ahead/behind
+    /// counts, for narrow terminals.
+    /// # Returns
+    /// `↑1↓2`, leaving out the counts that are zero, `=` if both are, or `local` for a
+    /// local-only branch.
+    #[must_use]
+    pub fn format_local_status_short(&self) -> String {
+        let (up, down) = (Symbol::Ahead, Symbol::Behind);
         if self.is_local_only {
-            "local-only".to_owned()
+            return Symbol::LocalOnlyShort.to_string();
+        }
+        match (self.ahead, self.behind) {
+            (0, 0) => Symbol::InSync.to_string(),
+            (ahead, 0) => format!("{up}{ahead}"),
+            (0, behind) => format!("{down}{behind}"),
+            (ahead, behind) => format!("{up}{ahead}{down}{behind}"),
+        }
+    }
+
+    /// Describes what kind of clone the repository is.
+    /// # Returns
+    /// `shallow`, `partial`, both, or an empty string for a complete clone.
+    #[must_use]
+    pub fn format_clone_kind(&self) -> String {
+        [(self.is_shallow, "shallow"), (self.is_partial, "partial")]
+            .into_iter()
+            .filter_map(|(is, kind)| is.then_some(kind))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Describes the Git LFS objects of the repository.
+    /// # Returns
+    /// `-` without Git LFS, `ok` if all objects are there, and how many are missing otherwise.
+    #[must_use]
+    pub fn format_lfs(&self) -> String {
+        match self.lfs {
+            None => "-".to_owned(),
+            Some(LfsStatus { missing: 0, .. }) => "ok".to_owned(),
+            Some(LfsStatus { objects, missing }) => format!("{missing}/{objects} missing"),
+        }
+    }
+
+    /// Formats the size of the git directory for `--maintenance` or `--disk-usage`.
+    /// # Returns
+    /// The size, e.g. `1.5 GiB`, or `-` if it was not measured.
+    #[must_use]
+    pub fn format_git_size(&self) -> String {
+        self.disk_usage
+            .map(|usage| usage.git)
+            .or_else(|| self.housekeeping.map(|housekeeping| housekeeping.git_size))
+            .map_or_else(|| "-".to_owned(), util::format_size)
+    }
+
+    /// Formats the size of the working directory for `--disk-usage`.
+    /// # Returns
+    /// The size, e.g. `1.5 GiB`, or `-` if it was not measured.
+    #[must_use]
+    pub fn format_worktree_size(&self) -> String {
+        self.disk_usage
+            .map_or_else(|| "-".to_owned(), |usage| util::format_size(usage.worktree))
+    }
+
+    /// Formats the signatures of the last commits for `--verify-signatures`.
+    /// # Returns
+    /// `good` or what is wrong for a single commit, e.g. `unsigned`, otherwise how many
+    /// commits have a good signature and what is wrong with the others, e.g.
+    /// `8/10 good, 2 unsigned`. `-` if they were not checked.
+    #[must_use]
+    pub fn format_signatures(&self) -> String {
+        let Some(signatures) = self.signatures else {
+            return "-".to_owned();
+        };
+        let problems = [
+            (signatures.bad, "bad"),
+            (signatures.unverified, "unverified"),
+            (signatures.unsigned, "unsigned"),
+        ];
+        if signatures.checked == 1 {
+            return problems
+                .iter()
+                .find(|(count, _)| *count > 0)
+                .map_or("good", |(_, problem)| problem)
+                .to_owned();
+        }
+        let good = format!("{}/{} good", signatures.good, signatures.checked);
+        let problems = problems
+            .into_iter()
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, problem)| format!("{count} {problem}"));
+        iter::once(good)
+            .chain(problems)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Formats the files hidden from `git status` for `--hidden-files`.
+    /// # Returns
+    /// How many files are hidden and how, e.g. `2 assume-unchanged, 1 skip-worktree`, `0`
+    /// if none are, or `-` if they were not counted.
+    #[must_use]
+    pub fn format_hidden_files(&self) -> String {
+        let Some(hidden) = self.hidden_files else {
+            return "-".to_owned();
+        };
+        let kinds: Vec<String> = [
+            (hidden.assume_unchanged, "assume-unchanged"),
+            (hidden.skip_worktree, "skip-worktree"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, kind)| format!("{count} {kind}"))
+        .collect();
+        if kinds.is_empty() {
+            "0".to_owned()
         } else {
-            format!("↑{} ↓{}", self.ahead, self.behind)
+            kinds.join(", ")
         }
     }
 
-    /// Formats the status with stash information if stashes are present.
+    /// Formats the date of the first commit for `--activity`.
     /// # Returns
-    /// A formatted string showing status and stash count if present.
+    /// The date, e.g. `2021-03-04`, or `-` without commits or activity.
+    #[must_use]
+    pub fn format_first_commit(&self) -> String {
+        self.activity.map_or_else(
+            || "-".to_owned(),
+            |activity| util::format_date(activity.first_commit),
+        )
+    }
+
+    /// Formats the status with the progress of the operation in progress, the conflicted
+    /// files and stash information if present.
+    /// # Returns
+    /// A formatted string showing status, progress, conflict count and stash count if
+    /// present, e.g. `Rebase 3/7 (feature onto main) (2 conflicts)`.
+    #[must_use]
     pub fn format_status_with_stash_and_ff(&self) -> String {
         let mut status_str = self.status.to_string();
+        if let Some(progress) = &self.progress {
+            let target = match (&progress.branch, &progress.onto) {
+                (Some(branch), Some(onto)) => format!(" ({branch} onto {onto})"),
+                (Some(branch), None) => format!(" ({branch})"),
+                (None, Some(onto)) => format!(" (onto {onto})"),
+                (None, None) => String::new(),
+            };
+            status_str = format!("{status_str} {}/{}{target}", progress.step, progress.total);
+        }
+        match self.conflicts {
+            0 => {}
+            1 => status_str = format!("{status_str} (1 conflict)"),
+            conflicts => status_str = format!("{status_str} ({conflicts} conflicts)"),
+        }
         if self.stash_count > 0 {
-            status_str = format!("{status_str} ({}*)", self.stash_count);
+            status_str = format!("{status_str} ({}{})", self.stash_count, Symbol::Stashes);
         }
         if self.fast_forwarded {
-            status_str = format!("{status_str} ↑↑");
+            status_str = format!("{status_str} {}", Symbol::FastForwarded);
         }
         status_str
     }
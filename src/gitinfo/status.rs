@@ -2,17 +2,21 @@ use std::fmt::{self, Display, Formatter};
 
 use comfy_table::Cell;
 use git2::{Repository, RepositoryState, StatusOptions};
-use strum_macros::EnumIter;
+use strum_macros::{EnumCount, EnumIter};
 
-use crate::gitinfo;
+use crate::{gitinfo, theme::Theme};
 
 /// Represents the status of a Git repository.
-#[derive(Default, Clone, Debug, PartialEq, Eq, EnumIter, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Default, Clone, Debug, PartialEq, Eq, EnumIter, EnumCount, serde::Serialize, serde::Deserialize,
+)]
 pub enum Status {
     /// The repository is clean, with no changes or untracked files.
     Clean,
     /// The repository has changes or untracked files.
     Dirty(usize), // Number of untracked files
+    /// The repository itself is clean, but submodules have changes or untracked files.
+    SubmoduleDirty,
     /// The repository is in a merge state.
     Merge,
     /// The repository is in a revert state.
@@ -29,6 +33,13 @@ pub enum Status {
     Unpublished,
     /// The repository is in a detached HEAD state or has no upstream branch.
     Detached,
+    /// Another process is running a git operation on the repository.
+    Busy,
+    /// A git process left a lock file behind, which makes git commands fail, see
+    /// `gitinfo::get_stale_locks`.
+    StaleLock,
+    /// The repository is corrupt, see `gitinfo::check_integrity`.
+    Corrupt,
     /// The status of the repository is unknown or not recognized.
     #[default]
     Unknown,
@@ -42,6 +53,7 @@ impl Status {
     /// A `Status` enum indicating the state of the repository:
     /// * `Clean` - No changes, no untracked files.
     /// * `Dirty` - There are changes or untracked files.
+    #[must_use]
     pub fn new(repo: &Repository) -> Self {
         // Step 1: Handle explicit git states
         match repo.state() {
@@ -64,33 +76,59 @@ impl Status {
         let mut opts = StatusOptions::new();
         opts.include_untracked(true).include_ignored(false);
 
-        repo.statuses(Some(&mut opts))
-            .map_or(Self::Unknown, |statuses| {
-                if statuses
-                    .iter()
-                    .any(|e| !e.status().is_ignored() && e.status().intersects(gitinfo::CHANGED))
-                {
-                    // Dirty working directory – report how many changes
-                    Self::Dirty(gitinfo::get_changed_count(repo))
-                } else {
-                    // Clean working directory – check branch push state
-                    gitinfo::get_branch_push_status(repo)
-                }
-            })
+        let Ok(statuses) = repo.statuses(Some(&mut opts)) else {
+            return Self::Unknown;
+        };
+        let changed: Vec<_> = statuses
+            .iter()
+            .filter(|e| !e.status().is_ignored() && e.status().intersects(gitinfo::CHANGED))
+            .collect();
+        if changed.is_empty() {
+            // Clean working directory – check branch push state
+            return gitinfo::get_branch_push_status(repo);
+        }
+
+        // A submodule with changes of its own shows up as a modified entry of the parent,
+        // even though nothing the parent records changed. Only a submodule checked out at
+        // another commit is a change of the parent. Like `Dirty`, this takes precedence over
+        // the push state, as it is the change that is easier to lose.
+        let dirty_submodules = gitinfo::get_dirty_submodule_paths(repo);
+        if changed
+            .iter()
+            .all(|e| e.path().is_ok_and(|path| dirty_submodules.contains(path)))
+        {
+            return Self::SubmoduleDirty;
+        }
+
+        // Dirty working directory – report how many changes
+        Self::Dirty(gitinfo::get_changed_count(repo))
     }
 
     /// Get the color associated with the status.
     /// This is used for terminal output to visually distinguish different statuses.
+    #[must_use]
     pub const fn comfy_color(&self) -> comfy_table::Color {
         use comfy_table::Color;
         match self {
             Self::Clean => Color::Reset,
             Self::Dirty(_) | Self::Unpushed | Self::Unpublished => Color::Red,
+            Self::SubmoduleDirty => Color::DarkRed,
             Self::Merge => Color::Blue,
             Self::Revert => Color::Magenta,
             Self::Rebase => Color::Cyan,
             Self::Bisect => Color::Yellow,
             Self::CherryPick => Color::DarkYellow,
+            Self::Busy => Color::DarkGrey,
+            Self::StaleLock => Color::DarkMagenta,
+            Self::Corrupt =>
+            // Dark red color for corrupt repositories
+            {
+                Color::Rgb {
+                    r: 178,
+                    g: 34,
+                    b: 34,
+                }
+            }
             Self::Detached =>
             // Purple color for detached HEAD state
             {
@@ -112,16 +150,148 @@ impl Status {
         }
     }
 
+    /// Get the color of the status in the colorblind palette of `--theme`, from the
+    /// Okabe-Ito colors, which stay apart with red-green color blindness.
+    #[must_use]
+    pub const fn colorblind_color(&self) -> comfy_table::Color {
+        use comfy_table::Color;
+        const ORANGE: Color = Color::Rgb {
+            r: 230,
+            g: 159,
+            b: 0,
+        };
+        const VERMILLION: Color = Color::Rgb {
+            r: 213,
+            g: 94,
+            b: 0,
+        };
+        const SKY_BLUE: Color = Color::Rgb {
+            r: 86,
+            g: 180,
+            b: 233,
+        };
+        const BLUE: Color = Color::Rgb {
+            r: 0,
+            g: 114,
+            b: 178,
+        };
+        const BLUISH_GREEN: Color = Color::Rgb {
+            r: 0,
+            g: 158,
+            b: 115,
+        };
+        const YELLOW: Color = Color::Rgb {
+            r: 240,
+            g: 228,
+            b: 66,
+        };
+        const REDDISH_PURPLE: Color = Color::Rgb {
+            r: 204,
+            g: 121,
+            b: 167,
+        };
+        match self {
+            Self::Clean => Color::Reset,
+            Self::Dirty(_) | Self::Unpushed | Self::Unpublished | Self::SubmoduleDirty => ORANGE,
+            Self::Merge => BLUE,
+            Self::Revert | Self::Detached => REDDISH_PURPLE,
+            Self::Rebase => SKY_BLUE,
+            Self::Bisect => YELLOW,
+            Self::CherryPick => BLUISH_GREEN,
+            Self::Busy => Color::DarkGrey,
+            Self::StaleLock | Self::Corrupt => VERMILLION,
+            Self::Unknown => Color::White,
+        }
+    }
+
+    /// Returns the Graphviz fill color for the status, matching `comfy_color` as closely as
+    /// a light background allows.
+    #[must_use]
+    pub const fn dot_color(&self) -> &'static str {
+        match self {
+            Self::Clean => "palegreen",
+            Self::Dirty(_) | Self::Unpushed | Self::Unpublished => "lightcoral",
+            Self::SubmoduleDirty => "mistyrose",
+            Self::Merge => "lightblue",
+            Self::Revert => "plum",
+            Self::Rebase => "paleturquoise",
+            Self::Bisect => "khaki",
+            Self::CherryPick => "gold",
+            Self::Detached => "violet",
+            Self::Busy => "lightgrey",
+            Self::StaleLock => "sandybrown",
+            Self::Corrupt => "salmon",
+            Self::Unknown => "orange",
+        }
+    }
+
     /// Converts the status to a `Cell` for use in a table.
     /// This allows the status to be displayed with its associated color and attributes.
-    pub fn as_cell(&self) -> Cell {
+    #[must_use]
+    pub fn as_cell(&self, theme: &Theme) -> Cell {
         Cell::new(self.to_string())
-            .fg(self.comfy_color())
+            .fg(theme.color(self))
             .add_attribute(comfy_table::Attribute::Bold)
     }
 
+    /// Returns the name `--status-color` knows the status by, e.g. `cherry-pick`.
+    #[must_use]
+    pub const fn key(&self) -> &'static str {
+        match self {
+            Self::Clean => "clean",
+            Self::Dirty(_) => "dirty",
+            Self::SubmoduleDirty => "submodule-dirty",
+            Self::Merge => "merge",
+            Self::Revert => "revert",
+            Self::Rebase => "rebase",
+            Self::Bisect => "bisect",
+            Self::CherryPick => "cherry-pick",
+            Self::Unpushed => "unpushed",
+            Self::Unpublished => "unpublished",
+            Self::Detached => "detached",
+            Self::Busy => "busy",
+            Self::StaleLock => "stale-lock",
+            Self::Corrupt => "corrupt",
+            Self::Unknown => "unknown",
+        }
+    }
+
+    /// Returns the name of the status for a legend, where the number of changed files of
+    /// `Dirty` is an `N`.
+    #[must_use]
+    pub fn legend_name(&self) -> String {
+        if matches!(self, Self::Dirty(_)) {
+            "Dirty (N)".to_owned()
+        } else {
+            self.to_string()
+        }
+    }
+
+    /// Returns the number of changed files of `Dirty`, 0 for the other statuses.
+    #[must_use]
+    pub const fn changed_files(&self) -> usize {
+        match self {
+            Self::Dirty(count) => *count,
+            Self::Clean
+            | Self::SubmoduleDirty
+            | Self::Merge
+            | Self::Revert
+            | Self::Rebase
+            | Self::Bisect
+            | Self::CherryPick
+            | Self::Unpushed
+            | Self::Unpublished
+            | Self::Detached
+            | Self::Busy
+            | Self::StaleLock
+            | Self::Corrupt
+            | Self::Unknown => 0,
+        }
+    }
+
     /// Gets a description of the status.
     /// This provides a human-readable explanation of what the status means.
+    #[must_use]
     pub const fn description(&self) -> &str {
         match self {
             Self::Clean => "No changes, no unpushed commits.",
@@ -129,6 +299,7 @@ impl Status {
                 "The repository is in a detached HEAD state or has no upstream branch."
             }
             Self::Dirty(_) => "Working directory has changes.",
+            Self::SubmoduleDirty => "Only submodules have changes.",
             Self::Merge => "Merge in progress.",
             Self::Revert => "Revert in progress.",
             Self::Rebase => "Rebase in progress.",
@@ -136,6 +307,9 @@ impl Status {
             Self::CherryPick => "Cherry-pick in progress.",
             Self::Unpublished => "The branch is not published.",
             Self::Unpushed => "There are unpushed commits.",
+            Self::Busy => "Another git process is working on the repository.",
+            Self::StaleLock => "A crashed git process left a lock file behind.",
+            Self::Corrupt => "The repository is corrupt, see --check-integrity.",
             Self::Unknown => "Status is unknown or not recognized.",
         }
     }
@@ -147,6 +321,7 @@ impl Display for Status {
             Self::Clean => write!(f, "Clean"),
             Self::Detached => write!(f, "Detached"),
             Self::Dirty(count) => write!(f, "Dirty ({count})"),
+            Self::SubmoduleDirty => write!(f, "Clean (submodule dirty)"),
             Self::Merge => write!(f, "Merge"),
             Self::Revert => write!(f, "Revert"),
             Self::Rebase => write!(f, "Rebase"),
@@ -154,6 +329,9 @@ impl Display for Status {
             Self::CherryPick => write!(f, "Cherry Pick"),
             Self::Unpushed => write!(f, "Unpushed"),
             Self::Unpublished => write!(f, "Unpublished"),
+            Self::Busy => write!(f, "Busy"),
+            Self::StaleLock => write!(f, "Stale lock"),
+            Self::Corrupt => write!(f, "Corrupt"),
             Self::Unknown => write!(f, "Unknown"),
         }
     }
@@ -0,0 +1,97 @@
+use std::fmt::{self, Display, Formatter};
+
+use strum_macros::EnumIter;
+
+/// A symbol or notation of the repository table and `--tree`.
+///
+/// The formatting of `RepoInfo` writes the symbols through this enum and `printer::legend`
+/// lists all of them, so the legend cannot leave one out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum Symbol {
+    /// Commits ahead of the upstream branch.
+    Ahead,
+    /// Commits behind the upstream branch.
+    Behind,
+    /// A branch without upstream branch.
+    LocalOnly,
+    /// A branch without upstream branch in the shortened Local column.
+    LocalOnlyShort,
+    /// Neither ahead of nor behind the upstream branch in the shortened Local column.
+    InSync,
+    /// Commits the default branch of a fork is behind that of `upstream`.
+    ForkBehind,
+    /// Local branches whose upstream branch is gone.
+    Gone,
+    /// The number of stashes.
+    Stashes,
+    /// A repository that `--fetch` fast-forwarded.
+    FastForwarded,
+    /// A linked worktree.
+    Worktree,
+}
+
+impl Symbol {
+    /// Returns the symbol as it is written.
+    #[must_use]
+    pub const fn text(self) -> &'static str {
+        match self {
+            Self::Ahead => "↑",
+            Self::Behind => "↓",
+            Self::LocalOnly => "local-only",
+            Self::LocalOnlyShort => "local",
+            Self::InSync => "=",
+            Self::ForkBehind => "fork ↓",
+            Self::Gone => "gone",
+            Self::Stashes => "*",
+            Self::FastForwarded => "↑↑",
+            Self::Worktree => "⎇",
+        }
+    }
+
+    /// Returns an example of the symbol in the output.
+    #[must_use]
+    pub const fn example(self) -> &'static str {
+        match self {
+            Self::Ahead | Self::Behind => "↑2 ↓1",
+            Self::LocalOnly => "local-only",
+            Self::LocalOnlyShort => "local",
+            Self::InSync => "=",
+            Self::ForkBehind => "↑0 ↓0 (fork ↓4)",
+            Self::Gone => "↑0 ↓0 (2 gone)",
+            Self::Stashes => "Dirty (3) (2*)",
+            Self::FastForwarded => "Clean ↑↑",
+            Self::Worktree => "⎇ tool-feature",
+        }
+    }
+
+    /// Returns what the symbol means.
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Ahead => "Commits ahead of the upstream branch",
+            Self::Behind => "Commits behind the upstream branch",
+            Self::LocalOnly => "The branch has no upstream branch",
+            Self::LocalOnlyShort => "local-only, in the Local column of narrow terminals",
+            Self::InSync => {
+                "Neither ahead nor behind, in the Local column of narrow terminals, which \
+                 leaves out counts of zero"
+            }
+            Self::ForkBehind => {
+                "Commits the default branch of `origin` is behind that of `upstream`, for forks, \
+                 with --forks"
+            }
+            Self::Gone => {
+                "Local branches whose upstream branch is gone from the remote, with --gone"
+            }
+            Self::Stashes => "The count in brackets with an asterisk is the number of stashes",
+            Self::FastForwarded => "The repository was fast-forwarded by --ff",
+            Self::Worktree => "The repository is a linked Git worktree",
+        }
+    }
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text())
+    }
+}
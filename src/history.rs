@@ -0,0 +1,273 @@
+use std::{path::Path, time::SystemTime};
+
+use anyhow::Context as _;
+use rusqlite::{Connection, params};
+use strum::IntoEnumIterator as _;
+
+use crate::{
+    gitinfo::{repoinfo::RepoInfo, status::Status},
+    util::unix_seconds,
+};
+
+/// The tables `--history` writes to. Scans are never updated, only appended.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS scans (
+        id INTEGER PRIMARY KEY,
+        scanned_at INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS repositories (
+        scan_id INTEGER NOT NULL REFERENCES scans(id),
+        repo_path TEXT NOT NULL,
+        name TEXT NOT NULL,
+        branch TEXT NOT NULL,
+        status TEXT NOT NULL,
+        clean INTEGER NOT NULL,
+        ahead INTEGER NOT NULL,
+        behind INTEGER NOT NULL,
+        stashes INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS repositories_by_path ON repositories(repo_path, scan_id);
+    CREATE TABLE IF NOT EXISTS legend (
+        status TEXT PRIMARY KEY,
+        description TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS columns (
+        table_name TEXT NOT NULL,
+        column_name TEXT NOT NULL,
+        description TEXT NOT NULL,
+        PRIMARY KEY (table_name, column_name)
+    );
+";
+
+/// What the columns of the data tables mean, stored in the `columns` table so that the
+/// database explains itself to whoever it is handed to.
+const COLUMNS: &[(&str, &str, &str)] = &[
+    ("scans", "id", "Identifies the scan"),
+    (
+        "scans",
+        "scanned_at",
+        "When the scan ran, in seconds since the Unix epoch",
+    ),
+    ("repositories", "scan_id", "The scan the row belongs to"),
+    (
+        "repositories",
+        "repo_path",
+        "Path of the repository, relative to the scanned directory",
+    ),
+    ("repositories", "name", "Name of the repository"),
+    ("repositories", "branch", "The checked out branch"),
+    (
+        "repositories",
+        "status",
+        "Status of the repository, explained in the legend table",
+    ),
+    (
+        "repositories",
+        "clean",
+        "1 if the status is Clean, 0 otherwise",
+    ),
+    ("repositories", "ahead", "Commits ahead of upstream"),
+    ("repositories", "behind", "Commits behind upstream"),
+    ("repositories", "stashes", "Number of stashes"),
+    (
+        "legend",
+        "status",
+        "A status as it appears in repositories.status",
+    ),
+    ("legend", "description", "What the status means"),
+    ("columns", "table_name", "A table of this database"),
+    ("columns", "column_name", "A column of that table"),
+    ("columns", "description", "What the column holds"),
+];
+
+/// A SQLite database holding one row per repository and scan.
+pub struct History {
+    connection: Connection,
+}
+
+/// How a repository of the latest recorded scan developed over all recorded scans.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct HistoryRow {
+    /// The relative path of the repository.
+    pub repo_path: String,
+    /// The status in the latest scan.
+    pub status: String,
+    /// Number of scans the repository was recorded in.
+    pub scans: usize,
+    /// When the repository was first recorded, in seconds since the Unix epoch.
+    pub first_seen: u64,
+    /// When the repository stopped being clean, if it has not been clean since, in seconds
+    /// since the Unix epoch.
+    pub not_clean_since: Option<u64>,
+    /// Commits ahead of upstream in the first scan.
+    pub first_ahead: usize,
+    /// Commits ahead of upstream in the latest scan.
+    pub ahead: usize,
+    /// Commits behind upstream in the first scan.
+    pub first_behind: usize,
+    /// Commits behind upstream in the latest scan.
+    pub behind: usize,
+}
+
+impl History {
+    /// Opens the database at `path`, creating it and its tables if needed.
+    ///
+    /// The `legend` and `columns` tables are rewritten every time, so they describe the
+    /// statuses and columns of the version that last wrote to the database.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be opened or is not a history database.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let mut connection =
+            Connection::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        connection
+            .execute_batch(SCHEMA)
+            .with_context(|| format!("{} is not a history database", path.display()))?;
+        write_descriptions(&mut connection)
+            .with_context(|| format!("Failed to write the legend to {}", path.display()))?;
+        Ok(Self { connection })
+    }
+
+    /// Appends a scan.
+    /// # Arguments
+    /// * `repos` - The repositories scanned.
+    /// * `at` - When the scan happened.
+    ///
+    /// # Errors
+    /// Returns an error if the scan cannot be written.
+    pub fn record(&mut self, repos: &[RepoInfo], at: SystemTime) -> anyhow::Result<()> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "INSERT INTO scans (scanned_at) VALUES (?1)",
+            params![unix_seconds(at)],
+        )?;
+        let scan_id = transaction.last_insert_rowid();
+        {
+            let mut insert = transaction.prepare(
+                "INSERT INTO repositories
+                 (scan_id, repo_path, name, branch, status, clean, ahead, behind, stashes)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            for repo in repos {
+                insert.execute(params![
+                    scan_id,
+                    repo.repo_path,
+                    repo.name,
+                    repo.branch,
+                    repo.status.to_string(),
+                    repo.status == Status::Clean,
+                    repo.ahead,
+                    repo.behind,
+                    repo.stash_count,
+                ])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Sums up the recorded scans for every repository of the latest one. Repositories
+    /// that are gone by now are left out.
+    ///
+    /// # Returns
+    /// One row per repository, sorted by relative path.
+    ///
+    /// # Errors
+    /// Returns an error if the database cannot be read.
+    pub fn report(&self) -> anyhow::Result<Vec<HistoryRow>> {
+        let mut query = self.connection.prepare(
+            "SELECT r.repo_path, r.status, r.clean, r.ahead, r.behind, s.scanned_at,
+                    s.id = (SELECT MAX(id) FROM scans)
+             FROM repositories r JOIN scans s ON s.id = r.scan_id
+             ORDER BY r.repo_path, s.id",
+        )?;
+        let mut rows = query.query([])?;
+
+        let mut report: Vec<HistoryRow> = Vec::new();
+        let mut in_latest = false;
+        while let Some(row) = rows.next()? {
+            let repo_path: String = row.get(0)?;
+            let status: String = row.get(1)?;
+            let clean: bool = row.get(2)?;
+            let ahead: usize = row.get(3)?;
+            let behind: usize = row.get(4)?;
+            let scanned_at: u64 = row.get(5)?;
+
+            if report.last().is_none_or(|last| last.repo_path != repo_path) {
+                if !in_latest {
+                    report.pop();
+                }
+                report.push(HistoryRow {
+                    repo_path,
+                    status: status.clone(),
+                    scans: 0,
+                    first_seen: scanned_at,
+                    not_clean_since: None,
+                    first_ahead: ahead,
+                    ahead,
+                    first_behind: behind,
+                    behind,
+                });
+            }
+            if let Some(last) = report.last_mut() {
+                last.scans += 1;
+                last.status = status;
+                last.ahead = ahead;
+                last.behind = behind;
+                last.not_clean_since = if clean {
+                    None
+                } else {
+                    last.not_clean_since.or(Some(scanned_at))
+                };
+            }
+            in_latest = row.get(6)?;
+        }
+        if !in_latest {
+            report.pop();
+        }
+        Ok(report)
+    }
+}
+
+/// Fills the `legend` and `columns` tables.
+fn write_descriptions(connection: &mut Connection) -> rusqlite::Result<()> {
+    let transaction = connection.transaction()?;
+    transaction.execute_batch("DELETE FROM legend; DELETE FROM columns;")?;
+    {
+        let mut insert =
+            transaction.prepare("INSERT INTO legend (status, description) VALUES (?1, ?2)")?;
+        for status in Status::iter() {
+            insert.execute(params![status.legend_name(), status.description()])?;
+        }
+        let mut insert = transaction.prepare(
+            "INSERT INTO columns (table_name, column_name, description) VALUES (?1, ?2, ?3)",
+        )?;
+        for (table, column, description) in COLUMNS {
+            insert.execute(params![table, column, description])?;
+        }
+    }
+    transaction.commit()
+}
+
+/// Formats how long ago `since` was, to the two largest units, e.g. `3d 4h`.
+/// # Arguments
+/// * `since` - A point in time, in seconds since the Unix epoch.
+/// * `now` - The current time.
+#[must_use]
+pub fn format_age(since: u64, now: SystemTime) -> String {
+    let seconds = unix_seconds(now).saturating_sub(since);
+    let (days, hours, minutes) = (
+        seconds / 86_400,
+        seconds % 86_400 / 3600,
+        seconds % 3600 / 60,
+    );
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m")
+    } else {
+        "<1m".to_owned()
+    }
+}
@@ -0,0 +1,80 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use git2::Repository;
+
+use crate::gitinfo::repoinfo::RepoListing;
+
+/// Returns the directory git runs the hooks of a repository from: `core.hooksPath`, relative
+/// to the working directory if it is relative, or else `hooks` in the git directory.
+pub fn hooks_dir(repo: &Repository) -> PathBuf {
+    let configured = repo
+        .config()
+        .and_then(|config| config.get_path("core.hooksPath"))
+        .ok();
+    match configured {
+        Some(path) if path.is_absolute() => path,
+        Some(path) => repo.workdir().unwrap_or_else(|| repo.path()).join(path),
+        None => repo.commondir().join("hooks"),
+    }
+}
+
+/// Returns whether `hook` is installed in `dir`, i.e. there is a file git runs, unlike the
+/// `.sample` files `git init` leaves there.
+pub fn is_installed(dir: &Path, hook: &str) -> bool {
+    let Ok(metadata) = fs::metadata(dir.join(hook)) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+        // Git skips hooks that are not executable, with just a hint.
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.is_file()
+    }
+}
+
+/// Prints one line per repository telling which of `hooks` are installed (see
+/// `is_installed`) and which are missing.
+///
+/// Repositories that cannot be opened are skipped.
+/// # Arguments
+/// * `repos` - The repositories to check.
+/// * `hooks` - The names of the hooks every repository should have, e.g. `pre-commit`.
+/// * `out` - Where to write the lines to.
+///
+/// # Errors
+/// Returns an error if writing to `out` fails.
+pub fn audit(repos: &[RepoListing], hooks: &[String], out: &mut impl Write) -> anyhow::Result<()> {
+    let mut missing_repos = 0;
+    for listing in repos {
+        let Ok(repo) = Repository::open(&listing.path) else {
+            log::warn!("Failed to open `{}`, skipping it", listing.repo_path);
+            continue;
+        };
+        let dir = hooks_dir(&repo);
+        let (installed, missing): (Vec<_>, Vec<_>) = hooks
+            .iter()
+            .map(String::as_str)
+            .partition(|hook| is_installed(&dir, hook));
+        let mut parts = Vec::new();
+        if !installed.is_empty() {
+            parts.push(format!("{} installed", installed.join(", ")));
+        }
+        if !missing.is_empty() {
+            missing_repos += 1;
+            parts.push(format!("missing {}", missing.join(", ")));
+        }
+        writeln!(out, "{}: {}", listing.repo_path, parts.join("; "))?;
+    }
+    if missing_repos > 0 {
+        log::warn!("{missing_repos} repositories are missing hooks");
+    }
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use std::fmt::Write as _;
+
+use strum::IntoEnumIterator as _;
+
+use crate::{
+    gitinfo::{
+        repoinfo::{FailedRepo, RepoInfo},
+        status::Status,
+    },
+    printer::Summary,
+};
+
+/// Styles of the report. Status badges take their color from `Status::dot_color`, whose
+/// Graphviz color names are valid CSS colors as well.
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.4em; }
+.summary { display: flex; flex-wrap: wrap; gap: 1em; margin-bottom: 1.5em; }
+.summary div { border: 1px solid #ddd; border-radius: 6px; padding: .5em 1em; }
+.summary strong { display: block; font-size: 1.4em; }
+input[type=search] { width: 20em; padding: .4em; margin-bottom: 1em; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border-bottom: 1px solid #ddd; padding: .35em .6em; text-align: left; }
+th { cursor: pointer; user-select: none; background: #f6f6f6; position: sticky; top: 0; }
+th[aria-sort=ascending]::after { content: ' ▲'; }
+th[aria-sort=descending]::after { content: ' ▼'; }
+td.number { text-align: right; }
+.badge { border-radius: 4px; padding: .1em .5em; white-space: nowrap; }
+details { margin-top: 1.5em; }
+";
+
+/// Sorts the table when a header is clicked and hides the rows not matching the search box.
+/// Cells sort by their `data-sort` attribute if they have one, numerically if possible.
+const SCRIPT: &str = "
+const table = document.getElementById('repos');
+const body = table.tBodies[0];
+const key = (row, i) => row.cells[i].dataset.sort ?? row.cells[i].textContent;
+table.querySelectorAll('th').forEach((th, i) => th.addEventListener('click', () => {
+  const ascending = th.getAttribute('aria-sort') !== 'ascending';
+  table.querySelectorAll('th').forEach(h => h.removeAttribute('aria-sort'));
+  th.setAttribute('aria-sort', ascending ? 'ascending' : 'descending');
+  const rows = [...body.rows].sort((a, b) => {
+    const x = key(a, i), y = key(b, i);
+    const order = x !== '' && y !== '' && !isNaN(x) && !isNaN(y)
+      ? x - y : x.localeCompare(y, undefined, { numeric: true });
+    return ascending ? order : -order;
+  });
+  body.append(...rows);
+}));
+document.getElementById('search').addEventListener('input', e => {
+  const needle = e.target.value.toLowerCase();
+  for (const row of body.rows) {
+    row.hidden = !row.textContent.toLowerCase().includes(needle);
+  }
+});
+";
+
+/// Builds a self-contained HTML report of a scan: the summary, a table that can be sorted
+/// by clicking its headers and filtered with a search box, the failed repositories and the
+/// legend of the statuses.
+/// # Arguments
+/// * `repos` - List of repositories to list in the table.
+/// * `failed_repos` - List of repositories that failed to process.
+/// # Returns
+/// The HTML document, without external resources.
+pub fn report(repos: &[RepoInfo], failed_repos: &[FailedRepo]) -> String {
+    let summary = Summary::new(repos, failed_repos.len());
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>git-statuses</title>\n<style>{STYLE}</style>\n</head>\n<body>\n\
+         <h1>git-statuses</h1>\n<div class=\"summary\">\n"
+    );
+    for (label, count) in [
+        ("Repositories", summary.total),
+        ("Clean", summary.clean),
+        ("With changes", summary.dirty),
+        ("Submodules dirty", summary.submodule_dirty),
+        ("With unpushed", summary.unpushed),
+        ("With stashes", summary.with_stashes),
+        ("Forks behind", summary.forks_behind),
+        ("With gone upstreams", summary.with_gone_branches),
+        ("Local-only branches", summary.local_only),
+        ("Failed to process", summary.failed),
+    ] {
+        let _ = writeln!(html, "<div><strong>{count}</strong>{label}</div>");
+    }
+    html.push_str(
+        "</div>\n<input type=\"search\" id=\"search\" placeholder=\"Filter repositories\">\n\
+         <table id=\"repos\">\n<thead><tr><th>Repository</th><th>Branch</th><th>Status</th>\
+         <th>Ahead</th><th>Behind</th><th>Commits</th><th>Stashes</th><th>Remote</th>\
+         <th>Path</th></tr></thead>\n<tbody>\n",
+    );
+    for repo in repos {
+        let _ = writeln!(
+            html,
+            "<tr><td>{name}</td><td>{branch}</td>\
+             <td data-sort=\"{status}\"><span class=\"badge\" style=\"background: {color}\">{status}</span></td>\
+             <td class=\"number\">{ahead}</td><td class=\"number\">{behind}</td>\
+             <td class=\"number\">{commits}</td><td class=\"number\">{stashes}</td>\
+             <td>{remote}</td><td>{path}</td></tr>",
+            name = escape(&repo.repo_path),
+            branch = escape(&repo.branch),
+            status = escape(&repo.status.to_string()),
+            color = repo.status.dot_color(),
+            ahead = repo.ahead,
+            behind = repo.behind,
+            commits = repo.format_commits(),
+            stashes = repo.stash_count,
+            remote = escape(repo.remote_url.as_deref().unwrap_or("")),
+            path = escape(&repo.path.display().to_string()),
+        );
+    }
+    html.push_str("</tbody>\n</table>\n");
+
+    if !failed_repos.is_empty() {
+        html.push_str("<h2>Failed to process</h2>\n<ul>\n");
+        for repo in failed_repos {
+            let _ = writeln!(
+                html,
+                "<li>{}: {}</li>",
+                escape(&repo.name),
+                escape(&repo.error)
+            );
+        }
+        html.push_str("</ul>\n");
+    }
+
+    html.push_str("<details>\n<summary>Legend</summary>\n<table>\n");
+    for status in Status::iter() {
+        let _ = writeln!(
+            html,
+            "<tr><td><span class=\"badge\" style=\"background: {}\">{}</span></td><td>{}</td></tr>",
+            status.dot_color(),
+            escape(&status.legend_name()),
+            escape(status.description())
+        );
+    }
+    let _ = write!(
+        html,
+        "</table>\n<p>N is the number of changed files.</p>\n</details>\n\
+         <script>{SCRIPT}</script>\n</body>\n</html>\n"
+    );
+    html
+}
+
+/// Escapes text for use in HTML element content and quoted attribute values.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
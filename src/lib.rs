@@ -0,0 +1,699 @@
+//! Scans a directory for Git repositories and reports their status.
+//!
+//! The `git-statuses` binary is a thin wrapper around [`run`]. Other tools can embed the
+//! scanning itself through [`Scanner`], which does not depend on the command line, and
+//! print its results with the functions in [`render`]. [`prelude`] brings all of it into
+//! scope at once.
+
+#[cfg(feature = "history")]
+use std::time::SystemTime;
+use std::{
+    io::Write,
+    time::{Duration, Instant},
+};
+
+use clap::CommandFactory as _;
+use clap_complete::Shell;
+
+#[cfg(feature = "daemon")]
+use crate::cli::DaemonAction;
+#[cfg(feature = "history")]
+use crate::history::History;
+use crate::{
+    cli::Command, manifest::Manifest, report::Rollup, snapshot::Snapshot, watcher::GitWatcher,
+};
+pub use crate::{
+    cli::{Args, OutputFormat, TooManyFailures},
+    gitinfo::{
+        repoinfo::{FailedRepo, RepoInfo, RepoListing},
+        status::Status,
+    },
+    printer::Summary,
+    scanner::{
+        FailureBudget, FetchSelector, HostLimit, IntegrityCheck, ScanEvent, ScanResult, Scanner,
+    },
+    util::{initialize_logger, initialize_stderr_logger},
+};
+
+mod auth;
+mod bulk;
+mod cli;
+#[cfg(feature = "daemon")]
+mod daemon;
+mod diff;
+mod disk_usage;
+mod forge;
+mod gitinfo;
+#[cfg(feature = "history")]
+mod history;
+mod hooks;
+#[cfg(feature = "html")]
+mod html;
+mod lint;
+mod locks;
+mod manifest;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod pick;
+mod porcelain;
+mod printer;
+mod progress;
+mod prompt;
+mod prune;
+mod registry;
+mod remotes;
+pub mod render;
+mod report;
+mod scanner;
+#[cfg(feature = "daemon")]
+mod service;
+mod snapshot;
+#[cfg(feature = "template")]
+mod template;
+#[cfg(test)]
+mod tests;
+mod theme;
+mod throttle;
+mod util;
+mod watcher;
+mod webhook;
+mod workspace;
+
+/// The types and functions for embedding the scanner, for a glob import.
+///
+/// ```no_run
+/// use git_statuses::prelude::*;
+///
+/// let scan = Scanner::new("/path/to/projects").depth(2).scan();
+/// let dirty = scan.repos.iter().filter(|r| r.status != Status::Clean).count();
+/// println!("{dirty} of {} repositories need attention", scan.repos.len());
+/// print!("{}", render::plain(&scan.repos, false, false));
+/// ```
+///
+/// # Stability
+/// Everything re-exported here is covered by semantic versioning: while the crate is at
+/// 0.x, it only changes incompatibly in a new minor version, afterwards only in a new
+/// major version. New fields and enum variants are not considered breaking for the
+/// serialized forms, but are for code matching on them exhaustively. Items outside of the
+/// prelude and the crate root, and the command line parsing in [`Args`], may change in any
+/// release.
+pub mod prelude {
+    pub use crate::{
+        FailedRepo, FailureBudget, FetchSelector, OutputFormat, RepoInfo, RepoListing, ScanEvent,
+        ScanResult, Scanner, Status, Summary, render,
+    };
+}
+
+/// Runs the tool for the given arguments.
+///
+/// Split out of `main` so that it can be driven from tests without spawning a process.
+/// Repositories that cannot be read are collected into the failed list rather than
+/// aborting the scan.
+///
+/// # Arguments
+/// * `args` - The parsed CLI arguments.
+/// * `out` - Where to write generated shell completions, the JSON schema, the prompt line, the
+///   picked path and printed service files to.
+///
+/// # Errors
+/// Returns an error if the daemon cannot be started, queried or installed, and a
+/// `TooManyFailures` if more repositories failed than `--max-failures` allows.
+pub fn run(args: &Args, out: &mut impl Write) -> anyhow::Result<()> {
+    if args.is_informational() {
+        return print_informational(args, out);
+    }
+
+    match &args.command {
+        #[cfg(feature = "daemon")]
+        Some(Command::Daemon {
+            action: Some(DaemonAction::Request { method, repo }),
+            ..
+        }) => return daemon::request(&args.socket_path(), method, repo.as_deref(), out),
+        #[cfg(feature = "daemon")]
+        Some(Command::Daemon { .. }) if args.reads_stdin() => {
+            anyhow::bail!(
+                "stdin cannot be read by `daemon`, which has to rescan on its own, read the \
+                 paths from a file instead"
+            );
+        }
+        #[cfg(feature = "daemon")]
+        Some(Command::Daemon {
+            interval,
+            action: None,
+        }) => return daemon::serve(args, &args.socket_path(), Duration::from_secs(*interval)),
+        #[cfg(feature = "daemon")]
+        Some(Command::Daemon {
+            interval,
+            action: Some(DaemonAction::Install { print }),
+        }) => return service::install(args, *interval, *print, out),
+        Some(Command::CloneMissing { manifest }) => {
+            let [dir] = args.dirs.as_slice() else {
+                anyhow::bail!("`clone-missing` clones into a single directory");
+            };
+            return Manifest::read(manifest)?.clone_missing(dir, out);
+        }
+        Some(Command::Report { .. }) | None => {}
+    }
+
+    #[cfg(feature = "history")]
+    if let Some(path) = &args.history
+        && args.history_report
+    {
+        return history_report(args, path);
+    }
+
+    if let Some(result) = run_repository_check(args, out) {
+        return result;
+    }
+
+    if let Some(path) = &args.export_manifest {
+        let ScanResult { repos, failed } = args.scanner()?.remote(true).list();
+        printer::failed_summary(&failed);
+        return Manifest::new(&repos).write(path);
+    }
+
+    if args.remotes || args.convert_remotes.is_some() {
+        let (repos, failed_repos) = args.list_repositories()?;
+        printer::failed_summary(&failed_repos);
+        return match args.convert_remotes {
+            Some(conversion) => remotes::convert(&repos, conversion, args.apply, out),
+            None => remotes::audit(&repos, out),
+        };
+    }
+
+    if args.list_only {
+        return list_only(args);
+    }
+
+    if let Some(interval) = args.watch {
+        return watch(args, Duration::from_secs(interval));
+    }
+
+    #[cfg(feature = "metrics")]
+    if let Some(addr) = &args.serve_metrics {
+        return metrics::serve(args, addr, Duration::from_secs(args.metrics_interval));
+    }
+
+    if let Some(command) = &args.exec {
+        return exec(args, command, out);
+    }
+
+    if args.pick {
+        let (repos, failed_repos) = scan(args, None)?;
+        printer::failed_summary(&failed_repos);
+        return pick::pick(&args.filter_repos(&repos), &args.picker, out);
+    }
+
+    let (repos, failed_repos) = scan(args, None)?;
+    #[cfg(feature = "history")]
+    record_history(args, &repos)?;
+    if let Some(path) = &args.registry
+        && let Err(e) = registry::update(path, &repos)
+    {
+        log::warn!("Failed to update the registry: {e:#}");
+    }
+    if let Some(path) = &args.diff {
+        let previous = Snapshot::read(path)?;
+        printer::changes(&diff::diff(&previous.repositories, &repos), args.style());
+        printer::failed_summary(&failed_repos);
+    } else {
+        print_scan(args, &repos, &failed_repos)?;
+    }
+    finish_scan(args, &repos, &failed_repos)
+}
+
+/// Runs the check that goes through the repositories one by one instead of scanning
+/// them, `--prune-merged`, `--remove-stale-locks`, `--lint-commits` or `--hooks`, if one was
+/// asked for.
+/// # Returns
+/// The result of the check, or `None` if none was asked for.
+fn run_repository_check(args: &Args, out: &mut impl Write) -> Option<anyhow::Result<()>> {
+    if !args.prune_merged
+        && !args.remove_stale_locks
+        && args.lint_commits.is_none()
+        && args.hooks.is_none()
+    {
+        return None;
+    }
+    Some(args.list_repositories().and_then(|(repos, failed_repos)| {
+        printer::failed_summary(&failed_repos);
+        if args.prune_merged {
+            prune::prune_merged(&repos, args.apply, out)
+        } else if args.remove_stale_locks {
+            locks::remove_stale_locks(&repos, args.apply, out)
+        } else if let Some(policy) = &args.lint_commits {
+            lint::lint_commits(&repos, policy, out)
+        } else {
+            hooks::audit(&repos, args.hooks.as_deref().unwrap_or_default(), out)
+        }
+    }))
+}
+
+/// Does what is asked for once a scan has been printed: `--pull`, `--push`, `--gc`,
+/// `--snapshot`,
+/// `--emit-workspace` and `--report-webhook`.
+///
+/// # Errors
+/// Returns an error if the snapshot or the workspace cannot be written or the report
+/// cannot be posted.
+fn finish_scan(args: &Args, repos: &[RepoInfo], failed_repos: &[FailedRepo]) -> anyhow::Result<()> {
+    if args.pull {
+        printer::bulk_table(
+            &bulk::pull(&args.filter_repos(repos), args.dry_run),
+            args.style(),
+        );
+    }
+    if args.push {
+        printer::bulk_table(
+            &bulk::push(&args.filter_repos(repos), args.dry_run),
+            args.style(),
+        );
+    }
+    if args.gc {
+        printer::bulk_table(
+            &bulk::gc(&args.filter_repos(repos), args.dry_run),
+            args.style(),
+        );
+    }
+    // Written after `--diff` has read the file, so both may name the same one.
+    if let Some(path) = &args.snapshot {
+        Snapshot::write(path, repos, failed_repos)?;
+    }
+    if let Some(editor) = args.emit_workspace {
+        let file = args
+            .workspace_file
+            .clone()
+            .unwrap_or_else(|| editor.default_path(args.first_dir()));
+        workspace::write(editor, &args.filter_repos(repos), &file)?;
+        log::info!("Wrote the workspace to {}", file.display());
+    }
+    if let Some(url) = &args.report_webhook {
+        let payload = webhook::payload(
+            repos,
+            failed_repos.len(),
+            args.webhook_format,
+            args.webhook_repos,
+        );
+        webhook::post(url, &payload)?;
+    }
+    Ok(())
+}
+
+/// Prints the repositories found for `--list-only`, without computing their status.
+///
+/// # Errors
+/// Returns an error if the output format cannot list repositories.
+fn list_only(args: &Args) -> anyhow::Result<()> {
+    let format = args.output_format().require(
+        &[
+            OutputFormat::Table,
+            OutputFormat::Plain,
+            OutputFormat::Json,
+            OutputFormat::Ndjson,
+        ],
+        "--list-only",
+    )?;
+    let (repos, failed_repos) = args.list_repositories()?;
+    if format == OutputFormat::Json {
+        printer::json_output(&repos, &failed_repos, None);
+        return Ok(());
+    }
+    if format == OutputFormat::Ndjson {
+        printer::ndjson_output(&repos);
+    } else {
+        // The list has no decoration to drop in the first place.
+        printer::repository_list(&repos, args);
+    }
+    printer::failed_summary(&failed_repos);
+    Ok(())
+}
+
+/// Scans and runs `command` in every repository that passes the filters, then prints
+/// the outcome per repository.
+///
+/// # Errors
+/// Returns an error if the scan fails, or the commands cannot be started or their output
+/// cannot be written.
+fn exec(args: &Args, command: &str, out: &mut impl Write) -> anyhow::Result<()> {
+    let (repos, failed_repos) = scan(args, None)?;
+    printer::failed_summary(&failed_repos);
+    let results = bulk::exec(&args.filter_repos(&repos), command, args.jobs, out)?;
+    printer::bulk_table(&results, args.style());
+    Ok(())
+}
+
+/// Rescans every `interval` and prints the result, until the scan fails.
+///
+/// Between two scans, a repository whose HEAD, refs or index change is scanned again on
+/// its own, and the result is printed again right away.
+///
+/// With `--changes-only`, only the first result is printed in full, and every later scan
+/// just logs the repositories that changed since the scan before.
+///
+/// # Errors
+/// Returns the error of the first scan or print that fails.
+fn watch(args: &Args, interval: Duration) -> anyhow::Result<()> {
+    let mut watcher = GitWatcher::new();
+    let mut previous: Option<Vec<RepoInfo>> = None;
+    // Built once, as the repositories of `--stdin` can only be read once.
+    let scanner = args.scanner()?;
+    loop {
+        let (repos, failed) = scan(args, Some(&scanner))?;
+        let mut latest = ScanResult { repos, failed };
+        watcher.watch(&latest.repos);
+        let next_scan = Instant::now() + interval;
+        loop {
+            #[cfg(feature = "history")]
+            record_history(args, &latest.repos)?;
+            match &previous {
+                Some(previous) if args.changes_only => {
+                    for change in diff::diff(previous, &latest.repos) {
+                        log::info!("{change}");
+                    }
+                }
+                _ => print_scan(args, &latest.repos, &latest.failed)?,
+            }
+            previous = Some(latest.repos.clone());
+
+            let changed = watcher.wait(next_scan);
+            if changed.is_empty() {
+                break;
+            }
+            for path in &changed {
+                latest.replace(path, args.rescan(path));
+            }
+        }
+    }
+}
+
+/// Scans, or asks the daemon with `--from-daemon`, and enforces `--max-failures`. With
+/// `--stream`, every repository is printed as soon as it is processed.
+///
+/// # Arguments
+/// * `args` - The scan options.
+/// * `scanner` - The scanner of a scan that repeats, see `Args::scanner`, or `None` to
+///   build it from `args`.
+///
+/// # Errors
+/// Returns an error if `--stream` is given for an output that cannot be streamed, if the
+/// daemon cannot be queried, and a `TooManyFailures` if more repositories failed than
+/// `--max-failures` allows.
+fn scan(
+    args: &Args,
+    scanner: Option<&Scanner>,
+) -> anyhow::Result<(Vec<RepoInfo>, Vec<FailedRepo>)> {
+    if args.stream
+        && (!matches!(
+            args.output_format(),
+            OutputFormat::Plain | OutputFormat::Ndjson
+        ) || matches!(args.command, Some(Command::Report { .. })))
+    {
+        anyhow::bail!("--stream only applies to the scan with --output plain or ndjson");
+    }
+    let on_repo = |repo: &RepoInfo| {
+        if args.stream {
+            printer::stream_output(repo, args);
+        }
+    };
+    #[cfg(feature = "daemon")]
+    let (repos, failed_repos) = if args.from_daemon {
+        // The daemon answers with all repositories at once, there is nothing to wait for.
+        let (repos, failed_repos) = daemon::query(&args.socket_path())?;
+        repos.iter().for_each(on_repo);
+        (repos, failed_repos)
+    } else {
+        scan_repositories(args, scanner, on_repo)?
+    };
+    #[cfg(not(feature = "daemon"))]
+    let (repos, failed_repos) = scan_repositories(args, scanner, on_repo)?;
+    if let Some(budget) = args.max_failures {
+        let processed = repos.len() + failed_repos.len();
+        if budget.is_exceeded(failed_repos.len(), processed) {
+            printer::failed_summary(&failed_repos);
+            return Err(TooManyFailures {
+                failed: failed_repos.len(),
+                processed,
+            }
+            .into());
+        }
+    }
+    Ok((repos, failed_repos))
+}
+
+/// Scans with `scanner`, or with the scanner of `args` if there is none.
+///
+/// # Errors
+/// Returns an error if there is no scanner and the repositories to scan cannot be read.
+fn scan_repositories(
+    args: &Args,
+    scanner: Option<&Scanner>,
+    on_repo: impl Fn(&RepoInfo) + Sync,
+) -> anyhow::Result<(Vec<RepoInfo>, Vec<FailedRepo>)> {
+    match scanner {
+        Some(scanner) => Ok(args.scan_repositories(scanner, on_repo)),
+        None => args.find_repositories_with(on_repo),
+    }
+}
+
+/// Appends the scan to the `--history` database, if one is given.
+///
+/// # Errors
+/// Returns an error if the database cannot be opened or written.
+#[cfg(feature = "history")]
+fn record_history(args: &Args, repos: &[RepoInfo]) -> anyhow::Result<()> {
+    if let Some(path) = &args.history {
+        History::open(path)?.record(repos, SystemTime::now())?;
+    }
+    Ok(())
+}
+
+/// Prints the `--history-report` of the database at `path`.
+///
+/// # Errors
+/// Returns an error if the database cannot be read or the output format does not apply.
+#[cfg(feature = "history")]
+fn history_report(args: &Args, path: &std::path::Path) -> anyhow::Result<()> {
+    let format = args.output_format().require(
+        &[OutputFormat::Table, OutputFormat::Json],
+        "--history-report",
+    )?;
+    let rows = History::open(path)?.report()?;
+    if format == OutputFormat::Json {
+        printer::history_json_output(&rows);
+    } else {
+        printer::history_table(&rows, args.style());
+    }
+    Ok(())
+}
+
+/// Prints a scan result in the requested output format.
+///
+/// # Errors
+/// Returns an error if the output format does not apply to the result.
+fn print_scan(args: &Args, repos: &[RepoInfo], failed_repos: &[FailedRepo]) -> anyhow::Result<()> {
+    let layout = if args.tree {
+        Some("--tree")
+    } else {
+        args.group_by.map(|_| "--group-by")
+    };
+    if let Some(layout) = layout {
+        if matches!(args.command, Some(Command::Report { .. })) {
+            anyhow::bail!("`report` cannot be combined with {layout}");
+        }
+        args.output_format()
+            .require(&[OutputFormat::Table], layout)?;
+    }
+    if let Some(Command::Report { rollup }) = &args.command {
+        return print_report(args, *rollup, repos, failed_repos);
+    }
+
+    if args.duplicates {
+        return print_duplicates(args, repos, failed_repos);
+    }
+
+    let displayed = args.filter_repos(repos);
+
+    if let Some(version) = args.porcelain {
+        println!("{}", porcelain::value(version, &displayed, failed_repos));
+        return Ok(());
+    }
+
+    match args.output_format() {
+        OutputFormat::Table => {}
+        OutputFormat::Json => {
+            // Like the table's, the summary describes the whole scan.
+            let summary = args
+                .summary
+                .then(|| Summary::new(repos, failed_repos.len()));
+            printer::json_output(&displayed, failed_repos, summary.as_ref());
+            return Ok(());
+        }
+        OutputFormat::Plain => {
+            // With `--stream`, the repositories were printed during the scan.
+            if !args.stream {
+                printer::plain_output(&displayed, args);
+            }
+            printer::failed_summary(failed_repos);
+            return Ok(());
+        }
+        OutputFormat::Ndjson => {
+            if !args.stream {
+                printer::ndjson_output(&displayed);
+            }
+            printer::failed_summary(failed_repos);
+            return Ok(());
+        }
+        OutputFormat::Dot => {
+            printer::dot_output(&displayed);
+            printer::failed_summary(failed_repos);
+            return Ok(());
+        }
+        OutputFormat::Tmux => {
+            // The line counts the failed repositories, a list would break the status bar.
+            println!(
+                "{}",
+                args.style()
+                    .text(&printer::tmux_line(&displayed, failed_repos.len()))
+            );
+            return Ok(());
+        }
+        #[cfg(feature = "html")]
+        OutputFormat::Html => {
+            printer::html_output(&displayed, failed_repos);
+            return Ok(());
+        }
+        #[cfg(feature = "template")]
+        OutputFormat::Template => {
+            let template = args
+                .template
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("--output template needs a --template"))?;
+            printer::template_output(template, &displayed, failed_repos)?;
+            return Ok(());
+        }
+        #[cfg(feature = "svg")]
+        OutputFormat::Svg => {
+            printer::svg_output(&displayed)?;
+            printer::failed_summary(failed_repos);
+            return Ok(());
+        }
+    }
+
+    print_table(args, repos, &displayed, failed_repos);
+    Ok(())
+}
+
+/// Prints a scan result as a table, as a `--tree` or as `--group-by` sections, with the sections that follow it.
+/// # Arguments
+/// * `repos` - Every repository scanned.
+/// * `displayed` - The repositories left after filtering.
+/// * `failed_repos` - The repositories that could not be scanned.
+fn print_table(
+    args: &Args,
+    repos: &[RepoInfo],
+    displayed: &[RepoInfo],
+    failed_repos: &[FailedRepo],
+) {
+    if args.tree {
+        printer::tree(displayed, args);
+    } else if let Some(by) = args.group_by {
+        printer::grouped_tables(&report::group(displayed, by), args);
+    } else {
+        printer::repositories_table(displayed, args);
+    }
+    printer::failed_summary(failed_repos);
+    if args.gitignore_advice {
+        printer::gitignore_advice(displayed);
+    }
+    if args.summary {
+        // The summary describes the whole scan, not just the filtered selection.
+        printer::summary(repos, failed_repos.len());
+    }
+    if let Some(top) = args.timing {
+        printer::timing(repos, top);
+    }
+}
+
+/// Prints the `report` of a scan result.
+///
+/// # Errors
+/// Returns an error if the output format cannot show a report.
+fn print_report(
+    args: &Args,
+    rollup: Rollup,
+    repos: &[RepoInfo],
+    failed_repos: &[FailedRepo],
+) -> anyhow::Result<()> {
+    let format = args.output_format().require(
+        &[OutputFormat::Table, OutputFormat::Json, OutputFormat::Plain],
+        "`report`",
+    )?;
+    // Every repository counts towards its group, `--non-clean` would skew the numbers.
+    let rows = report::rollup(repos, rollup);
+    if format == OutputFormat::Json {
+        printer::rollup_json_output(&rows, failed_repos);
+        return Ok(());
+    }
+    if format == OutputFormat::Plain {
+        printer::rollup_plain_output(&rows, args.null);
+    } else {
+        printer::rollup_table(&rows, args.style());
+    }
+    printer::failed_summary(failed_repos);
+    Ok(())
+}
+
+/// Prints the `--duplicates` of a scan result.
+///
+/// # Errors
+/// Returns an error if the output format cannot show duplicates.
+fn print_duplicates(
+    args: &Args,
+    repos: &[RepoInfo],
+    failed_repos: &[FailedRepo],
+) -> anyhow::Result<()> {
+    let format = args
+        .output_format()
+        .require(&[OutputFormat::Table, OutputFormat::Json], "--duplicates")?;
+    // Like `report`, every repository counts: a filtered out copy is still a copy.
+    let groups = report::duplicates(repos);
+    if format == OutputFormat::Json {
+        printer::duplicates_json_output(&groups, failed_repos);
+    } else {
+        printer::duplicates_table(&groups, args.style());
+        printer::failed_summary(failed_repos);
+    }
+    Ok(())
+}
+
+/// Prints what is asked for without scanning: the shell completions, the legend, the JSON
+/// schema or the prompt line.
+///
+/// # Errors
+/// Returns an error if writing to `out` fails.
+fn print_informational(args: &Args, out: &mut impl Write) -> anyhow::Result<()> {
+    if let Some(shell) = args.completions {
+        completions(shell, out);
+    } else if args.legend {
+        printer::legend(args.style());
+    } else if let Some(version) = args.json_schema {
+        writeln!(out, "{:#}", porcelain::schema(version))?;
+    } else if args.prompt {
+        prompt::print(args.first_dir(), args.ascii, out)?;
+    }
+    Ok(())
+}
+
+/// Writes the shell completion script for `shell`.
+///
+/// # Arguments
+/// * `shell` - The shell to generate completions for.
+/// * `out` - Where to write the completion script to.
+fn completions(shell: Shell, out: &mut impl Write) {
+    clap_complete::generate(
+        shell,
+        &mut Args::command(),
+        env!("CARGO_PKG_NAME"),
+        &mut *out,
+    );
+}
@@ -0,0 +1,160 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io::Write,
+    str::FromStr,
+};
+
+use git2::Repository;
+use regex_lite::Regex;
+
+use crate::gitinfo::repoinfo::RepoListing;
+
+/// The types of the Conventional Commits preset of commitlint, which most server-side
+/// hooks checking for Conventional Commits accept.
+const CONVENTIONAL_TYPES: [&str; 11] = [
+    "build", "chore", "ci", "docs", "feat", "fix", "perf", "refactor", "revert", "style", "test",
+];
+
+/// What the subject of a commit has to look like, see `--lint-commits`.
+#[derive(Debug, Clone)]
+pub enum CommitPolicy {
+    /// A Conventional Commits subject, `type(scope)!: description`, with one of
+    /// `CONVENTIONAL_TYPES`.
+    Conventional,
+    /// A subject the regular expression matches.
+    Pattern(Regex),
+}
+
+impl CommitPolicy {
+    /// Checks the subject of a commit.
+    /// # Returns
+    /// Why the subject breaks the policy, or `None` if it does not.
+    pub fn check(&self, subject: &str) -> Option<String> {
+        match self {
+            Self::Conventional => {
+                let Some((prefix, description)) = subject.split_once(": ") else {
+                    return Some("no `type: description`".to_owned());
+                };
+                let prefix = prefix.strip_suffix('!').unwrap_or(prefix);
+                let kind = match prefix.split_once('(') {
+                    Some((kind, scope)) => {
+                        if !scope.strip_suffix(')').is_some_and(|scope| {
+                            !scope.is_empty() && !scope.contains(['(', ')', ' '])
+                        }) {
+                            return Some(format!("invalid scope `({scope}`"));
+                        }
+                        kind
+                    }
+                    None => prefix,
+                };
+                if !CONVENTIONAL_TYPES.contains(&kind) {
+                    Some(format!("unknown type `{kind}`"))
+                } else if description.trim().is_empty() {
+                    Some("no description".to_owned())
+                } else {
+                    None
+                }
+            }
+            Self::Pattern(regex) => {
+                (!regex.is_match(subject)).then(|| format!("does not match `{regex}`"))
+            }
+        }
+    }
+}
+
+impl FromStr for CommitPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "conventional" {
+            return Ok(Self::Conventional);
+        }
+        Regex::new(s)
+            .map(Self::Pattern)
+            .map_err(|e| format!("`{s}` is neither `conventional` nor a regular expression: {e}"))
+    }
+}
+
+impl Display for CommitPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Conventional => f.write_str("conventional"),
+            Self::Pattern(regex) => write!(f, "{regex}"),
+        }
+    }
+}
+
+/// Lists the commits of HEAD that are on no remote, oldest first, leaving out merge
+/// commits, whose message git writes.
+///
+/// A repository without remotes has nowhere to push to, so none of its commits are listed.
+/// # Returns
+/// The abbreviated id and the subject of every commit.
+pub fn unpushed_commits(repo: &Repository) -> Vec<(String, String)> {
+    if repo.remotes().map_or(true, |remotes| remotes.is_empty()) {
+        return Vec::new();
+    }
+    let revwalk = repo.revwalk().and_then(|mut revwalk| {
+        revwalk.push_head()?;
+        revwalk.hide_glob("refs/remotes/*")?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+        Ok(revwalk)
+    });
+    let Ok(revwalk) = revwalk else {
+        return Vec::new();
+    };
+    revwalk
+        .filter_map(|id| repo.find_commit(id.ok()?).ok())
+        .filter(|commit| commit.parent_count() <= 1)
+        .filter_map(|commit| {
+            let id = commit.as_object().short_id().ok()?;
+            let subject = commit
+                .summary()
+                .ok()
+                .flatten()
+                .unwrap_or_default()
+                .to_owned();
+            Some((id.as_str().ok()?.to_owned(), subject))
+        })
+        .collect()
+}
+
+/// Prints one line per unpushed commit (see `unpushed_commits`) whose subject breaks
+/// `policy`, with why, e.g. before pushing to a server whose hooks enforce it.
+///
+/// Repositories that cannot be opened are skipped.
+/// # Arguments
+/// * `repos` - The repositories to check.
+/// * `policy` - What the subjects have to look like.
+/// * `out` - Where to write the lines to.
+///
+/// # Errors
+/// Returns an error if writing to `out` fails.
+pub fn lint_commits(
+    repos: &[RepoListing],
+    policy: &CommitPolicy,
+    out: &mut impl Write,
+) -> anyhow::Result<()> {
+    let mut count = 0;
+    let mut rejected_repos = 0;
+    for listing in repos {
+        let Ok(repo) = Repository::open(&listing.path) else {
+            log::warn!("Failed to open `{}`, skipping it", listing.repo_path);
+            continue;
+        };
+        let before = count;
+        for (id, subject) in unpushed_commits(&repo) {
+            if let Some(reason) = policy.check(&subject) {
+                count += 1;
+                writeln!(out, "{}: {id} {subject} ({reason})", listing.repo_path)?;
+            }
+        }
+        if count > before {
+            rejected_repos += 1;
+        }
+    }
+    if count > 0 {
+        log::warn!("{count} unpushed commits in {rejected_repos} repositories break `{policy}`");
+    }
+    Ok(())
+}
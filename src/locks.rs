@@ -0,0 +1,80 @@
+use std::{fs, io::Write, path::Path, time::SystemTime};
+
+use git2::Repository;
+
+use crate::{
+    gitinfo::{self, repoinfo::RepoListing},
+    util,
+};
+
+/// Lists the stale lock files of every repository (see `gitinfo::get_stale_locks`), and
+/// removes them if `apply` is set.
+///
+/// Repositories that cannot be opened are skipped, and a lock file that cannot be removed
+/// is reported without stopping the others.
+/// # Arguments
+/// * `repos` - The repositories to look at.
+/// * `apply` - Remove the lock files instead of only listing them.
+/// * `out` - Where to write one line per lock file to.
+///
+/// # Errors
+/// Returns an error if writing to `out` fails.
+pub fn remove_stale_locks(
+    repos: &[RepoListing],
+    apply: bool,
+    out: &mut impl Write,
+) -> anyhow::Result<()> {
+    let mut count = 0;
+    for listing in repos {
+        let Ok(repo) = Repository::open(&listing.path) else {
+            log::warn!("Failed to open `{}`, skipping it", listing.repo_path);
+            continue;
+        };
+        for lock in gitinfo::get_stale_locks(&repo) {
+            count += 1;
+            let line = format!(
+                "{}: {} ({} old)",
+                listing.repo_path,
+                display_path(&lock, &listing.path),
+                age(&lock)
+            );
+            if !apply {
+                writeln!(out, "{line}")?;
+                continue;
+            }
+            match fs::remove_file(&lock) {
+                Ok(()) => writeln!(out, "{line} removed")?,
+                Err(e) => log::warn!("Failed to remove {line}: {e}"),
+            }
+        }
+    }
+    if count > 0 && !apply {
+        log::info!("{count} lock files can be removed, run again with --apply to remove them");
+    }
+    Ok(())
+}
+
+/// Shows a lock file relative to its repository, or by its full path if it is elsewhere,
+/// e.g. in the common git directory of a linked worktree.
+fn display_path(lock: &Path, repo: &Path) -> String {
+    util::strip_path_prefix(lock, repo)
+        .unwrap_or(lock)
+        .display()
+        .to_string()
+}
+
+/// Formats how long ago a lock file was last modified, in the largest whole unit.
+fn age(lock: &Path) -> String {
+    let Some(age) = fs::metadata(lock)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+    else {
+        return "?".to_owned();
+    };
+    match age.as_secs() / 60 {
+        minutes @ 0..60 => format!("{minutes}m"),
+        minutes @ 60..2880 => format!("{}h", minutes / 60),
+        minutes => format!("{}d", minutes / 1440),
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Component, Path},
+    process::Command,
+};
+
+use anyhow::Context as _;
+
+use crate::gitinfo::repoinfo::RepoListing;
+
+/// One repository of a manifest.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// The repository name, as in `RepoInfo::name`.
+    pub name: String,
+    /// The path of the repository, relative to the scanned directory.
+    pub path: String,
+    /// Where to clone the repository from, `None` for a repository without a remote.
+    pub remote_url: Option<String>,
+}
+
+/// The repositories of a directory, as `--export-manifest` writes them and `clone-missing`
+/// reads them.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    /// The repositories, sorted by path.
+    pub repositories: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    /// Builds the manifest of a scan.
+    /// # Arguments
+    /// * `repos` - The repositories found, with their remote URL.
+    #[must_use]
+    pub fn new(repos: &[RepoListing]) -> Self {
+        let mut repositories: Vec<ManifestEntry> = repos
+            .iter()
+            .map(|repo| ManifestEntry {
+                name: repo.name.clone(),
+                path: repo.repo_path.clone(),
+                remote_url: repo.remote_url.clone(),
+            })
+            .collect();
+        repositories.sort_by(|a, b| a.path.cmp(&b.path));
+        Self { repositories }
+    }
+
+    /// Reads a manifest written by `write`.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not a manifest.
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("{} is not a manifest", path.display()))
+    }
+
+    /// Writes the manifest to `path` as JSON.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written.
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Clones the repositories of the manifest that are missing from `dir`, each to its path
+    /// relative to `dir`, with the git executable.
+    ///
+    /// Paths that already exist are left alone, whatever they hold. Repositories without a
+    /// remote, and paths that would end up outside of `dir`, are skipped with a warning, and
+    /// a failed clone is reported without stopping the others.
+    /// # Arguments
+    /// * `dir` - The directory to clone into.
+    /// * `out` - Where to write one line per cloned repository to.
+    ///
+    /// # Errors
+    /// Returns an error if writing to `out` fails.
+    pub fn clone_missing(&self, dir: &Path, out: &mut impl Write) -> anyhow::Result<()> {
+        let mut cloned = 0;
+        for entry in &self.repositories {
+            let relative = Path::new(&entry.path);
+            if !relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+            {
+                log::warn!(
+                    "Skipping `{}`, which is not below the directory",
+                    entry.path
+                );
+                continue;
+            }
+            let target = dir.join(relative);
+            if target.exists() {
+                continue;
+            }
+            let Some(url) = &entry.remote_url else {
+                log::warn!("Skipping `{}`, which has no remote to clone", entry.path);
+                continue;
+            };
+            let output = Command::new("git")
+                .arg("clone")
+                .arg("--quiet")
+                // A URL starting with `-` must not pass for an option.
+                .arg("--")
+                .arg(url)
+                .arg(&target)
+                .env("GIT_TERMINAL_PROMPT", "0")
+                .output()
+                .context("Failed to run git")?;
+            if output.status.success() {
+                cloned += 1;
+                writeln!(out, "{}: cloned from {url}", entry.path)?;
+            } else {
+                log::warn!(
+                    "Failed to clone `{}` from {url}: {}",
+                    entry.path,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+        }
+        log::info!(
+            "Cloned {cloned} of {} repositories",
+            self.repositories.len()
+        );
+        Ok(())
+    }
+}
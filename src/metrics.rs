@@ -0,0 +1,155 @@
+use std::{
+    fmt::Write as _,
+    io::{BufRead as _, BufReader, Write as _},
+    net::{TcpListener, TcpStream},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+use parking_lot::RwLock;
+
+use crate::{cli::Args, gitinfo::repoinfo::RepoInfo, printer::Summary, scanner::Scanner};
+
+/// How long a client may take to send its request before it is given up on.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Runs the metrics exporter: rescans every `interval` and answers `GET /metrics` on `addr`
+/// with the gauges of the latest scan in the Prometheus text format.
+///
+/// Like the daemon, the first scan completes before the address is bound, and requests
+/// are answered from memory without waiting for a scan in progress. The repositories of
+/// `--stdin`, `--paths-from` or `--registered` are read once, before the first scan.
+///
+/// # Arguments
+/// * `args` - The scan options, used for every rescan.
+/// * `addr` - The address to listen on, e.g. `127.0.0.1:9898`.
+/// * `interval` - The time between the end of one scan and the start of the next.
+///
+/// # Errors
+/// Returns an error if the address cannot be bound. Does not return otherwise.
+pub fn serve(args: &Args, addr: &str, interval: Duration) -> anyhow::Result<()> {
+    // Built once, as the repositories of `--stdin` can only be read once.
+    let scanner = args.scanner()?;
+    let latest = RwLock::new(scan_metrics(args, &scanner));
+    let listener = TcpListener::bind(addr).with_context(|| format!("Failed to bind {addr}"))?;
+    log::info!("Serving metrics on http://{addr}/metrics");
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            loop {
+                thread::sleep(interval);
+                let metrics = scan_metrics(args, &scanner);
+                *latest.write() = metrics;
+                log::debug!("Rescanned {}", args.dirs_display());
+            }
+        });
+
+        for stream in listener.incoming() {
+            let result = stream.and_then(|stream| answer(stream, &latest.read().clone()));
+            if let Err(e) = result {
+                log::warn!("Failed to answer a client: {e}");
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Scans with the given options and scanner and renders the gauges of the result.
+fn scan_metrics(args: &Args, scanner: &Scanner) -> String {
+    let started = Instant::now();
+    let (repos, failed_repos) = args.scan_repositories(scanner, |_| {});
+    render(&repos, failed_repos.len(), started.elapsed())
+}
+
+/// Reads the request of a client and writes the response.
+fn answer(mut stream: TcpStream, metrics: &str) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    stream.write_all(response(&request_line, metrics).as_bytes())
+}
+
+/// Builds the HTTP response to a request.
+/// # Arguments
+/// * `request_line` - The first line of the request, e.g. `GET /metrics HTTP/1.1`.
+/// * `metrics` - The rendered gauges.
+/// # Returns
+/// The gauges for `GET /metrics`, a 404 for any other path and a 405 for any other method.
+#[must_use]
+pub fn response(request_line: &str, metrics: &str) -> String {
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => (
+            "200 OK",
+            "text/plain; version=0.0.4; charset=utf-8",
+            metrics,
+        ),
+        (Some("GET"), _) => ("404 Not Found", "text/plain", "Not found, try /metrics\n"),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "Only GET is supported\n",
+        ),
+    };
+    format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Renders the gauges of a scan in the Prometheus text format. Every name starts with
+/// `git_statuses_`.
+/// # Arguments
+/// * `repos` - The repositories scanned.
+/// * `failed` - Number of repositories that failed to process.
+/// * `duration` - How long the scan took.
+/// # Returns
+/// One `HELP`, `TYPE` and sample line per gauge.
+#[must_use]
+pub fn render(repos: &[RepoInfo], failed: usize, duration: Duration) -> String {
+    let summary = Summary::new(repos, failed);
+    let gauges = [
+        (
+            "repos_total",
+            "Number of repositories scanned.",
+            summary.total.to_string(),
+        ),
+        (
+            "repos_dirty",
+            "Number of repositories with changes in their working directory.",
+            summary.dirty.to_string(),
+        ),
+        (
+            "repos_behind",
+            "Number of repositories behind their upstream.",
+            repos.iter().filter(|r| r.behind > 0).count().to_string(),
+        ),
+        (
+            "repos_failed",
+            "Number of repositories that could not be scanned.",
+            failed.to_string(),
+        ),
+        (
+            "commits_unpushed_total",
+            "Number of commits ahead of the upstream, across all repositories.",
+            summary.ahead.to_string(),
+        ),
+        (
+            "scan_duration_seconds",
+            "How long the latest scan took.",
+            format!("{:.3}", duration.as_secs_f64()),
+        ),
+    ];
+    let mut text = String::new();
+    for (name, help, value) in gauges {
+        let _ = write!(
+            text,
+            "# HELP git_statuses_{name} {help}\n# TYPE git_statuses_{name} gauge\n\
+             git_statuses_{name} {value}\n"
+        );
+    }
+    text
+}
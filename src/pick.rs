@@ -0,0 +1,59 @@
+use std::{fmt::Write as _, io::Write, process::Stdio};
+
+use anyhow::Context as _;
+
+use crate::{bulk, gitinfo::repoinfo::RepoInfo};
+
+/// Lets the user pick one of the repositories with `picker`, e.g. `fzf`, and prints the
+/// path of the picked one.
+///
+/// The picker reads the `candidates` on stdin and writes the picked line to stdout. It
+/// draws on the terminal itself, so nothing but the path is written to `out`.
+/// # Arguments
+/// * `repos` - The repositories to pick from.
+/// * `picker` - The shell command to pick with.
+/// * `out` - Where to write the absolute path of the picked repository to.
+/// # Errors
+/// Returns an error if there is nothing to pick from, the picker cannot be run, or
+/// nothing was picked, e.g. because the picker was cancelled.
+pub fn pick(repos: &[RepoInfo], picker: &str, out: &mut impl Write) -> anyhow::Result<()> {
+    if repos.is_empty() {
+        anyhow::bail!("There is no repository to pick from");
+    }
+    let mut child = bulk::shell(picker)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run `{picker}`"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A picker may quit before it has read every line, which is no error.
+        let _ = stdin.write_all(candidates(repos).as_bytes());
+    }
+    let output = child
+        .wait_with_output()
+        .with_context(|| format!("Failed to run `{picker}`"))?;
+    let Some(repo) = picked_repo(repos, &String::from_utf8_lossy(&output.stdout)) else {
+        anyhow::bail!("No repository was picked");
+    };
+    writeln!(out, "{}", repo.path.display())?;
+    Ok(())
+}
+
+/// Builds the lines to pick from: the relative path, the branch and the status of every
+/// repository, separated by tabs.
+pub fn candidates(repos: &[RepoInfo]) -> String {
+    repos.iter().fold(String::new(), |mut lines, repo| {
+        let _ = writeln!(
+            lines,
+            "{}\t{}\t{}",
+            repo.repo_path, repo.branch, repo.status
+        );
+        lines
+    })
+}
+
+/// Finds the repository of the first line a picker wrote, by its relative path.
+pub fn picked_repo<'a>(repos: &'a [RepoInfo], picked: &str) -> Option<&'a RepoInfo> {
+    let repo_path = picked.lines().next()?.split('\t').next()?;
+    repos.iter().find(|repo| repo.repo_path == repo_path)
+}
@@ -0,0 +1,223 @@
+use clap::ValueEnum;
+use serde_json::{Value, json};
+
+use crate::gitinfo::{
+    repoinfo::{FailedRepo, RepoInfo},
+    status::Status,
+};
+
+/// The versions of the `--porcelain` format.
+///
+/// A version never changes once released: fields are neither renamed, removed nor added,
+/// and a status keeps its name. Anything else needs a new version.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PorcelainVersion {
+    /// The first version, described by `--json-schema`
+    V1,
+}
+
+/// A repository in version 1 of the porcelain format.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RepoV1 {
+    /// The directory name of the repository.
+    pub name: String,
+    /// The absolute path of the repository.
+    pub path: String,
+    /// The path relative to the scanned directory.
+    pub relative_path: String,
+    /// The checked out branch.
+    pub branch: String,
+    /// The abbreviated id of the checked out commit, `None` without commits.
+    pub head: Option<String>,
+    /// The state of the repository, see `status_v1`.
+    pub status: &'static str,
+    /// Number of changed files, counted for the `dirty` status only.
+    pub changed_files: usize,
+    /// Number of commits ahead of the upstream.
+    pub ahead: usize,
+    /// Number of commits behind the upstream.
+    pub behind: usize,
+    /// Whether the branch has an upstream.
+    pub has_upstream: bool,
+    /// Number of commits of the branch, `None` if they were not counted.
+    pub commits: Option<usize>,
+    /// Number of stashes.
+    pub stashes: usize,
+    /// The remote URL, only read with `--remote`.
+    pub remote_url: Option<String>,
+    /// Whether this is a linked worktree.
+    pub worktree: bool,
+    /// Whether this is a shallow clone.
+    pub shallow: bool,
+}
+
+impl From<&RepoInfo> for RepoV1 {
+    fn from(repo: &RepoInfo) -> Self {
+        Self {
+            name: repo.name.clone(),
+            path: repo.path.display().to_string(),
+            relative_path: repo.repo_path.clone(),
+            branch: repo.branch.clone(),
+            head: repo.head_sha.clone(),
+            status: status_v1(&repo.status),
+            changed_files: repo.status.changed_files(),
+            ahead: repo.ahead,
+            behind: repo.behind,
+            has_upstream: !repo.is_local_only,
+            commits: repo.commits,
+            stashes: repo.stash_count,
+            remote_url: repo.remote_url.clone(),
+            worktree: repo.is_worktree,
+            shallow: repo.is_shallow,
+        }
+    }
+}
+
+/// A repository that could not be scanned, in version 1 of the porcelain format.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct FailedV1 {
+    /// The directory name of the repository.
+    pub name: String,
+    /// The path of the repository.
+    pub path: String,
+    /// Why the repository could not be scanned.
+    pub error: String,
+}
+
+impl From<&FailedRepo> for FailedV1 {
+    fn from(failed: &FailedRepo) -> Self {
+        Self {
+            name: failed.name.clone(),
+            path: failed.path.display().to_string(),
+            error: failed.error.clone(),
+        }
+    }
+}
+
+/// The names of the statuses in version 1 of the porcelain format.
+const STATUSES_V1: [&str; 13] = [
+    "clean",
+    "detached",
+    "dirty",
+    "submodule-dirty",
+    "merge",
+    "revert",
+    "rebase",
+    "bisect",
+    "cherry-pick",
+    "unpushed",
+    "unpublished",
+    "busy",
+    "unknown",
+];
+
+/// Returns the name of a status in version 1 of the porcelain format, one of `STATUSES_V1`.
+pub const fn status_v1(status: &Status) -> &'static str {
+    match status {
+        Status::Clean => "clean",
+        Status::Detached => "detached",
+        Status::Dirty(_) => "dirty",
+        Status::SubmoduleDirty => "submodule-dirty",
+        Status::Merge => "merge",
+        Status::Revert => "revert",
+        Status::Rebase => "rebase",
+        Status::Bisect => "bisect",
+        Status::CherryPick => "cherry-pick",
+        Status::Unpushed => "unpushed",
+        Status::Unpublished => "unpublished",
+        // Version 1 knows no stale locks. Like a busy repository, one cannot be changed.
+        Status::Busy | Status::StaleLock => "busy",
+        // Version 1 knows no corrupt repositories, whose state cannot be told.
+        Status::Corrupt | Status::Unknown => "unknown",
+    }
+}
+
+/// Builds the `--porcelain` output of a scan.
+/// # Arguments
+/// * `version` - The version of the format.
+/// * `repos` - The repositories to output.
+/// * `failed_repos` - The repositories that could not be scanned.
+/// # Returns
+/// The JSON object, with the `version` next to the `repositories` and the `failed`.
+pub fn value(version: PorcelainVersion, repos: &[RepoInfo], failed_repos: &[FailedRepo]) -> Value {
+    match version {
+        PorcelainVersion::V1 => json!({
+            "version": 1,
+            "repositories": repos.iter().map(RepoV1::from).collect::<Vec<_>>(),
+            "failed": failed_repos.iter().map(FailedV1::from).collect::<Vec<_>>(),
+        }),
+    }
+}
+
+/// Builds the JSON schema of a version of the `--porcelain` output, for `--json-schema`.
+pub fn schema(version: PorcelainVersion) -> Value {
+    match version {
+        PorcelainVersion::V1 => json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "git-statuses --porcelain=v1",
+            "type": "object",
+            "required": ["version", "repositories", "failed"],
+            "additionalProperties": false,
+            "properties": {
+                "version": { "const": 1 },
+                "repositories": { "type": "array", "items": { "$ref": "#/$defs/repository" } },
+                "failed": { "type": "array", "items": { "$ref": "#/$defs/failed" } },
+            },
+            "$defs": {
+                "repository": {
+                    "type": "object",
+                    "required": [
+                        "name", "path", "relative_path", "branch", "head", "status",
+                        "changed_files", "ahead", "behind", "has_upstream", "commits",
+                        "stashes", "remote_url", "worktree", "shallow",
+                    ],
+                    "additionalProperties": false,
+                    "properties": {
+                        "name": { "type": "string" },
+                        "path": { "type": "string", "description": "Absolute path" },
+                        "relative_path": {
+                            "type": "string",
+                            "description": "Path relative to the scanned directory",
+                        },
+                        "branch": { "type": "string" },
+                        "head": {
+                            "type": ["string", "null"],
+                            "description": "Abbreviated id of the checked out commit",
+                        },
+                        "status": { "enum": STATUSES_V1 },
+                        "changed_files": {
+                            "type": "integer",
+                            "minimum": 0,
+                            "description": "Counted for the dirty status only",
+                        },
+                        "ahead": { "type": "integer", "minimum": 0 },
+                        "behind": { "type": "integer", "minimum": 0 },
+                        "has_upstream": { "type": "boolean" },
+                        "commits": {
+                            "type": ["integer", "null"],
+                            "minimum": 0,
+                            "description": "null with --no-commit-count",
+                        },
+                        "stashes": { "type": "integer", "minimum": 0 },
+                        "remote_url": {
+                            "type": ["string", "null"],
+                            "description": "Only read with --remote",
+                        },
+                        "worktree": { "type": "boolean" },
+                        "shallow": { "type": "boolean" },
+                    },
+                },
+                "failed": {
+                    "type": "object",
+                    "required": ["name", "path", "error"],
+                    "additionalProperties": false,
+                    "properties": {
+                        "name": { "type": "string" },
+                        "path": { "type": "string" },
+                        "error": { "type": "string" },
+                    },
+                },
+            },
+        }),
+    }
+}
@@ -1,15 +1,151 @@
-use comfy_table::{Attribute, Cell, ContentArrangement, Table, presets};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write as _,
+    path::Path,
+    slice,
+};
+
+use comfy_table::{Attribute, Cell, Color, ContentArrangement, Table, presets};
+use git2::Repository;
 use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
 
+#[cfg(feature = "history")]
+use crate::history::{self, HistoryRow};
+#[cfg(feature = "html")]
+use crate::html;
 use crate::{
-    cli::Args,
-    gitinfo::{repoinfo::RepoInfo, status::Status},
+    bulk::{BulkResult, Outcome},
+    cli::{Args, OutputFormat},
+    diff::RepoChange,
+    forge,
+    gitinfo::{
+        self,
+        repoinfo::{FailedRepo, RepoInfo, RepoListing, Timing},
+        status::Status,
+        symbol::Symbol,
+    },
+    report::{self, DuplicateGroup, RepoGroup, Rollup, RollupRow},
+    theme::Theme,
+    util,
 };
 
+/// How the output draws its tables and symbols, see `Args::style`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    /// Whether tables leave out the lines between their rows.
+    pub condensed: bool,
+    /// Whether to draw with ASCII characters only, see `util::to_ascii`.
+    pub ascii: bool,
+    /// The colors of the statuses.
+    pub theme: Theme,
+}
+
+impl Style {
+    /// Creates a table drawn in this style, whose cells wrap to fit the terminal.
+    #[must_use]
+    pub fn table(self) -> Table {
+        let preset = match (self.ascii, self.condensed) {
+            (false, false) => presets::UTF8_FULL,
+            (false, true) => presets::UTF8_FULL_CONDENSED,
+            (true, false) => presets::ASCII_FULL,
+            (true, true) => presets::ASCII_FULL_CONDENSED,
+        };
+        let mut table = Table::new();
+        table
+            .load_preset(preset)
+            .set_content_arrangement(ContentArrangement::Dynamic);
+        table
+    }
+
+    /// Returns `text` with its symbols replaced by ASCII if this style asks for it.
+    #[must_use]
+    pub fn text(self, text: &str) -> String {
+        if self.ascii {
+            util::to_ascii(text)
+        } else {
+            text.to_owned()
+        }
+    }
+}
+
+/// Below this terminal width the table hides the Commits column and shortens paths.
+const NARROW_WIDTH: u16 = 100;
+
+/// Below this terminal width the table also shortens the Local column.
+const VERY_NARROW_WIDTH: u16 = 80;
+
+/// Paths are never shortened to fewer characters than this.
+const MIN_PATH_WIDTH: usize = 16;
+
+/// How `repositories_table` fits the columns into the width of the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableLayout {
+    /// Whether to show the Commits column.
+    pub commits: bool,
+    /// Whether to shorten the Local column, see `RepoInfo::format_local_status_short`.
+    pub short_local: bool,
+    /// How many characters of a path to show at most, `None` for all of them.
+    pub max_path: Option<usize>,
+}
+
+impl TableLayout {
+    /// Every column in full.
+    pub const FULL: Self = Self {
+        commits: true,
+        short_local: false,
+        max_path: None,
+    };
+
+    /// Chooses the layout for a terminal of the given width.
+    /// # Arguments
+    /// * `width` - The width of the terminal in characters, `None` if the output is no
+    ///   terminal, which gets every column in full.
+    /// * `wide` - Whether `--wide` asks for every column in full anyway.
+    #[must_use]
+    pub fn new(width: Option<u16>, wide: bool) -> Self {
+        match width {
+            Some(width) if !wide && width < NARROW_WIDTH => Self {
+                commits: false,
+                short_local: width < VERY_NARROW_WIDTH,
+                max_path: Some((usize::from(width) / 4).max(MIN_PATH_WIDTH)),
+            },
+            _ => Self::FULL,
+        }
+    }
+
+    /// Formats the Directory column of a repository, marking a linked worktree with `⎇`.
+    fn directory(&self, repo: &RepoInfo) -> String {
+        let path = self.path(&repo.repo_path);
+        if repo.is_worktree {
+            format!("{} {path}", Symbol::Worktree)
+        } else {
+            path
+        }
+    }
+
+    /// Formats the Local column of a repository, shortened if `short_local` is set.
+    fn local_status(&self, repo: &RepoInfo) -> String {
+        if self.short_local {
+            repo.format_local_status_short()
+        } else {
+            repo.format_local_status()
+        }
+    }
+
+    /// Shortens `path` to `max_path` characters, keeping both of its ends.
+    fn path(&self, path: &str) -> String {
+        self.max_path
+            .map_or_else(|| path.to_owned(), |max| util::truncate_middle(path, max))
+    }
+}
+
 /// Prints the repository status information as a table or list, depending on CLI options.
 ///
 /// Expects the repositories to already be sorted and filtered (see
-/// `Args::find_repositories` and `Args::filter_repos`).
+/// `Args::find_repositories` and `Args::filter_repos`). On a narrow terminal, columns are
+/// hidden or shortened as `TableLayout::new` chooses, unless `--wide` is given.
 ///
 /// # Arguments
 /// * `repos` - List of repositories to display.
@@ -20,132 +156,1028 @@ pub fn repositories_table(repos: &[RepoInfo], args: &Args) {
         return;
     }
 
-    let mut table = Table::new();
-    let preset = if args.condensed {
-        presets::UTF8_FULL_CONDENSED
+    let mut table = args.style().table();
+    let layout = TableLayout::new(table.width(), args.wide);
+    // With `--wide`, long lines are left to the terminal rather than wrapped within cells.
+    if args.wide {
+        table.set_content_arrangement(ContentArrangement::Disabled);
+    }
+
+    table.set_header(table_header(args, layout));
+    for repo in repos {
+        table.add_row(table_row(repo, args, layout));
+    }
+    println!("{table}");
+}
+
+/// A column of the table of `repositories_table`.
+///
+/// The header, the rows and `legend` are all built from this enum, so they list the same
+/// columns in the same order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum Column {
+    Directory,
+    Branch,
+    Local,
+    Commits,
+    Status,
+    Remote,
+    Clone,
+    Lfs,
+    Since,
+    RecentCommits,
+    Authors,
+    Loose,
+    Packs,
+    Worktree,
+    GitDir,
+    Ignored,
+    Hidden,
+    Signed,
+    Email,
+    Pulls,
+    Ci,
+    Path,
+}
+
+impl Column {
+    /// Returns the title of the column in the header.
+    #[must_use]
+    pub const fn title(self) -> &'static str {
+        match self {
+            Self::Directory => "Directory",
+            Self::Branch => "Branch",
+            Self::Local => "Local",
+            Self::Commits => "Commits",
+            Self::Status => "Status",
+            Self::Remote => "Remote",
+            Self::Clone => "Clone",
+            Self::Lfs => "LFS",
+            Self::Since => "Since",
+            Self::RecentCommits => "30d",
+            Self::Authors => "Authors",
+            Self::Loose => "Loose",
+            Self::Packs => "Packs",
+            Self::Worktree => "Worktree",
+            Self::GitDir => ".git",
+            Self::Ignored => "Ignored",
+            Self::Hidden => "Hidden",
+            Self::Signed => "Signed",
+            Self::Email => "Email",
+            Self::Pulls => "PRs",
+            Self::Ci => "CI",
+            Self::Path => "Path",
+        }
+    }
+
+    /// Returns the options that show the column, `None` if it is always shown.
+    #[must_use]
+    pub const fn option(self) -> Option<&'static str> {
+        match self {
+            Self::Directory | Self::Branch | Self::Local | Self::Status => None,
+            Self::Commits => Some("unless --no-commit-count"),
+            Self::Remote => Some("--remote"),
+            Self::Clone => Some("--clones"),
+            Self::Lfs => Some("--lfs"),
+            Self::Since | Self::RecentCommits | Self::Authors => Some("--activity"),
+            Self::Loose | Self::Packs => Some("--maintenance"),
+            Self::Worktree => Some("--disk-usage"),
+            Self::GitDir => Some("--maintenance, --disk-usage"),
+            Self::Ignored => Some("--show-ignored"),
+            Self::Hidden => Some("--hidden-files"),
+            Self::Signed => Some("--verify-signatures"),
+            Self::Email => Some("--identity"),
+            Self::Pulls | Self::Ci => Some("--github, --forge"),
+            Self::Path => Some("--path"),
+        }
+    }
+
+    /// Returns what the column shows.
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Directory => {
+                "The repository, relative to the scanned directory, in the color of its status"
+            }
+            Self::Branch => "The checked out branch",
+            Self::Local => "How the branch compares to its upstream branch, see the symbols",
+            Self::Commits => "The number of commits of HEAD, left out on narrow terminals",
+            Self::Status => "The status with its counts, see the statuses and symbols",
+            Self::Remote => "The URL of the remote",
+            Self::Clone => {
+                "Whether the repository is a shallow clone, whose counts only cover the \
+                 fetched history, or a partial clone, which lacks objects until they are needed"
+            }
+            Self::Lfs => "Whether the Git LFS files were downloaded",
+            Self::Since => "The date of the first commit",
+            Self::RecentCommits => "The commits of the last 30 days",
+            Self::Authors => "The number of authors",
+            Self::Loose => "The number of loose objects",
+            Self::Packs => "The number of packfiles",
+            Self::Worktree => "The size of the working directory",
+            Self::GitDir => "The size of the git directory",
+            Self::Ignored => "The number of ignored files and directories",
+            Self::Hidden => "The files marked assume-unchanged or skip-worktree",
+            Self::Signed => "Whether the last commits are signed with a good signature",
+            Self::Email => "The user.email of the repository, red if it does not match",
+            Self::Pulls => "The number of open pull requests",
+            Self::Ci => "The CI status of the default branch",
+            Self::Path => "The absolute path of the repository",
+        }
+    }
+
+    /// Returns the columns the CLI options ask for and `layout` leaves, in table order.
+    #[must_use]
+    pub fn selected(args: &Args, layout: TableLayout) -> Vec<Self> {
+        let forges = !args.forges().is_empty();
+        Self::iter()
+            .filter(|column| match column {
+                Self::Directory | Self::Branch | Self::Local | Self::Status => true,
+                Self::Commits => !args.no_commit_count && layout.commits,
+                Self::Remote => args.remote,
+                Self::Clone => args.clones,
+                Self::Lfs => args.lfs,
+                Self::Since | Self::RecentCommits | Self::Authors => args.activity,
+                Self::Loose | Self::Packs => args.maintenance,
+                Self::Worktree => args.disk_usage,
+                Self::GitDir => args.maintenance || args.disk_usage,
+                Self::Ignored => args.show_ignored,
+                Self::Hidden => args.hidden_files,
+                Self::Signed => args.verify_signatures.is_some(),
+                Self::Email => args.identity.is_some(),
+                Self::Pulls | Self::Ci => forges,
+                Self::Path => args.path,
+            })
+            .collect()
+    }
+}
+
+/// Builds the header of the table of `repositories_table`, with the columns the CLI
+/// options ask for and `layout` leaves.
+fn table_header(args: &Args, layout: TableLayout) -> Vec<Cell> {
+    Column::selected(args, layout)
+        .into_iter()
+        .map(|column| Cell::new(column.title()).add_attribute(Attribute::Bold))
+        .collect()
+}
+
+/// Builds the row of a repository in the table of `repositories_table`, with the columns
+/// the CLI options ask for and `layout` leaves.
+fn table_row(repo: &RepoInfo, args: &Args, layout: TableLayout) -> Vec<Cell> {
+    let style = args.style();
+    let status_color = style.theme.color(&repo.status);
+    Column::selected(args, layout)
+        .into_iter()
+        .map(|column| match column {
+            Column::Directory => Cell::new(style.text(&layout.directory(repo))).fg(status_color),
+            Column::Branch => Cell::new(&repo.branch),
+            Column::Local => Cell::new(style.text(&layout.local_status(repo))),
+            Column::Commits => Cell::new(repo.format_commits()),
+            Column::Status => {
+                Cell::new(style.text(&repo.format_status_with_stash_and_ff())).fg(status_color)
+            }
+            Column::Remote => Cell::new(repo.remote_url.as_deref().unwrap_or("-")),
+            Column::Clone => Cell::new(repo.format_clone_kind()),
+            Column::Lfs => {
+                let missing = repo.lfs.is_some_and(|lfs| lfs.missing > 0);
+                Cell::new(repo.format_lfs()).fg(if missing { Color::Red } else { Color::Reset })
+            }
+            Column::Since => Cell::new(repo.format_first_commit()),
+            Column::RecentCommits => Cell::new(
+                repo.activity
+                    .map_or_else(|| "-".to_owned(), |a| a.recent_commits.to_string()),
+            ),
+            Column::Authors => Cell::new(
+                repo.activity
+                    .map_or_else(|| "-".to_owned(), |a| a.contributors.to_string()),
+            ),
+            Column::Loose => Cell::new(
+                repo.housekeeping
+                    .map_or_else(|| "-".to_owned(), |h| h.loose_objects.to_string()),
+            ),
+            Column::Packs => Cell::new(
+                repo.housekeeping
+                    .map_or_else(|| "-".to_owned(), |h| h.packs.to_string()),
+            ),
+            Column::Worktree => Cell::new(repo.format_worktree_size()),
+            Column::GitDir => Cell::new(repo.format_git_size()),
+            Column::Ignored => Cell::new(
+                repo.ignored
+                    .map_or_else(|| "-".to_owned(), |ignored| ignored.to_string()),
+            ),
+            Column::Hidden => {
+                let hidden = repo.hidden_files.is_some_and(|hidden| hidden.total() > 0);
+                Cell::new(repo.format_hidden_files()).fg(if hidden {
+                    Color::Yellow
+                } else {
+                    Color::Reset
+                })
+            }
+            Column::Signed => {
+                let color = match repo.signatures {
+                    Some(signatures) if signatures.bad > 0 => Color::Red,
+                    Some(signatures) if !signatures.all_good() => Color::Yellow,
+                    Some(_) => Color::Green,
+                    None => Color::Reset,
+                };
+                Cell::new(repo.format_signatures()).fg(color)
+            }
+            Column::Email => {
+                let email = repo.user_email.as_deref();
+                let expected = args.identity.as_ref().is_some_and(|pattern| {
+                    email.is_some_and(|email| util::wildcard_match(pattern, email))
+                });
+                Cell::new(email.unwrap_or("-")).fg(if expected { Color::Reset } else { Color::Red })
+            }
+            Column::Pulls => Cell::new(
+                repo.forge
+                    .as_ref()
+                    .map_or_else(|| "-".to_owned(), |f| f.open_pulls.to_string()),
+            ),
+            Column::Ci => {
+                let ci = repo.forge.as_ref().and_then(|f| f.ci);
+                Cell::new(ci.map_or_else(|| "-".to_owned(), |ci| ci.to_string()))
+                    .fg(ci.map_or(Color::Reset, forge::CiStatus::comfy_color))
+            }
+            Column::Path => Cell::new(style.text(&layout.path(&repo.path.display().to_string()))),
+        })
+        .collect()
+}
+
+/// Prints the repositories found by `--list-only`, one per line.
+///
+/// Each line holds the relative directory, followed by the path and the remote URL
+/// (`-` if there is none) if requested, separated by tabs.
+/// # Arguments
+/// * `repos` - List of repositories to print.
+/// * `args` - CLI arguments selecting the extra columns and the line terminator.
+pub fn repository_list(repos: &[RepoListing], args: &Args) {
+    for repo in repos {
+        let mut line = repo.repo_path.clone();
+        if args.path {
+            line = format!("{line}\t{}", repo.path.display());
+        }
+        if args.remote {
+            line = format!("{line}\t{}", repo.remote_url.as_deref().unwrap_or("-"));
+        }
+        print!("{line}{}", line_end(args.null));
+    }
+}
+
+/// Prints one table per `--group-by` section, each under a header and followed by its
+/// subtotals.
+/// # Arguments
+/// * `groups` - The sections, see `report::group`.
+/// * `args` - CLI arguments controlling the tables.
+pub fn grouped_tables(groups: &[RepoGroup], args: &Args) {
+    if groups.is_empty() {
+        log::info!("No repositories found.");
+        return;
+    }
+    for group in groups {
+        println!("\n{}", group.name);
+        repositories_table(&group.repos, args);
+        println!("{}", group_subtotal(group));
+    }
+}
+
+/// Describes a `--group-by` section in one line.
+/// # Arguments
+/// * `group` - The section.
+/// # Returns
+/// E.g. `3 repositories: 1 dirty, 2 behind, 0 ahead`.
+pub fn group_subtotal(group: &RepoGroup) -> String {
+    let count = group.repos.len();
+    let noun = if count == 1 {
+        "repository"
     } else {
-        presets::UTF8_FULL
+        "repositories"
     };
-    table
-        .load_preset(preset)
-        .set_content_arrangement(ContentArrangement::Dynamic);
+    format!(
+        "{count} {noun}: {} dirty, {} behind, {} ahead",
+        group.dirty, group.behind, group.ahead
+    )
+}
 
-    let mut header = vec![
-        Cell::new("Directory").add_attribute(Attribute::Bold),
-        Cell::new("Branch").add_attribute(Attribute::Bold),
-        Cell::new("Local").add_attribute(Attribute::Bold),
-        Cell::new("Commits").add_attribute(Attribute::Bold),
-        Cell::new("Status").add_attribute(Attribute::Bold),
-    ];
-    if args.remote {
-        header.push(Cell::new("Remote").add_attribute(Attribute::Bold));
+/// Prints the repositories nested under the directories they live in, see `--tree`.
+///
+/// Expects the repositories to already be sorted and filtered.
+/// # Arguments
+/// * `repos` - List of repositories to display.
+/// * `args` - CLI arguments, the scanned directories are the root of the tree.
+pub fn tree(repos: &[RepoInfo], args: &Args) {
+    if repos.is_empty() {
+        log::info!("No repositories found.");
+        return;
     }
-    if args.path {
-        header.push(Cell::new("Path").add_attribute(Attribute::Bold));
+    println!("{}", args.dirs_display());
+    for line in tree_lines(repos) {
+        println!("{}", args.style().text(&line));
     }
-    table.set_header(header);
+}
 
+/// A directory of the `--tree`, with the repository that lives in it, if any.
+#[derive(Default)]
+struct TreeNode<'a> {
+    repo: Option<&'a RepoInfo>,
+    children: BTreeMap<String, Self>,
+}
+
+/// Builds the lines `tree` prints below the scanned directory.
+///
+/// Directories that hold no repository and a single subdirectory are folded into
+/// their child, so a deep scan does not spend a line on every level.
+/// # Arguments
+/// * `repos` - List of repositories to display, relative to the scanned directory.
+/// # Returns
+/// One line per repository or directory, drawn like the `tree` command.
+pub fn tree_lines(repos: &[RepoInfo]) -> Vec<String> {
+    let mut root = TreeNode::default();
     for repo in repos {
-        let display_path = if repo.is_worktree {
-            format!("⎇ {}", repo.repo_path)
+        let node = Path::new(&repo.repo_path)
+            .components()
+            .fold(&mut root, |node, component| {
+                node.children
+                    .entry(component.as_os_str().to_string_lossy().into_owned())
+                    .or_default()
+            });
+        node.repo = Some(repo);
+    }
+    let mut lines = Vec::new();
+    tree_children(&root, "", &mut lines);
+    lines
+}
+
+/// Appends the lines of the children of `node` to `lines`.
+fn tree_children(node: &TreeNode<'_>, prefix: &str, lines: &mut Vec<String>) {
+    let count = node.children.len();
+    for (index, (name, child)) in node.children.iter().enumerate() {
+        let last = index + 1 == count;
+        let mut name = name.clone();
+        let mut child = child;
+        while child.repo.is_none()
+            && child.children.len() == 1
+            && let Some((next_name, next)) = child.children.first_key_value()
+        {
+            name = format!("{name}/{next_name}");
+            child = next;
+        }
+        let label = child.repo.map_or_else(
+            || format!("{name}/"),
+            |repo| {
+                let marker = if repo.is_worktree {
+                    format!("{} ", Symbol::Worktree)
+                } else {
+                    String::new()
+                };
+                format!(
+                    "{marker}{name}  [{}] {} {}",
+                    repo.branch,
+                    repo.format_local_status(),
+                    repo.format_status_with_stash_and_ff()
+                )
+            },
+        );
+        let (branch, indent) = if last {
+            ("└── ", "    ")
         } else {
-            repo.repo_path.clone()
+            ("├── ", "│   ")
         };
-        let name_cell = Cell::new(&display_path).fg(repo.status.comfy_color());
-
-        let mut row = vec![
-            name_cell,
-            Cell::new(&repo.branch),
-            Cell::new(repo.format_local_status()),
-            Cell::new(repo.commits),
-            Cell::new(repo.format_status_with_stash_and_ff()).fg(repo.status.comfy_color()),
-        ];
-        if args.remote {
-            row.push(Cell::new(repo.remote_url.as_deref().unwrap_or("-")));
-        }
-        if args.path {
-            row.push(Cell::new(repo.path.display()));
+        lines.push(format!("{prefix}{branch}{label}"));
+        tree_children(child, &format!("{prefix}{indent}"), lines);
+    }
+}
+
+/// Prints the repositories for `--output plain`, see `plain_lines`.
+/// # Arguments
+/// * `repos` - List of repositories to print.
+/// * `args` - CLI arguments selecting the extra columns and the line terminator.
+pub fn plain_output(repos: &[RepoInfo], args: &Args) {
+    for line in plain_lines(repos, args.path, args.remote) {
+        print!("{line}{}", line_end(args.null));
+    }
+}
+
+/// Prints the repositories for `--output ndjson`, one JSON object per line.
+/// # Arguments
+/// * `repos` - List of repositories to print.
+pub fn ndjson_output(repos: &[impl serde::Serialize]) {
+    for repo in repos {
+        println!("{}", serde_json::json!(repo));
+    }
+}
+
+/// Prints a single repository for `--stream` as soon as it is processed, if it passes the
+/// filters. Formats other than `plain` and `ndjson` are not streamed.
+/// # Arguments
+/// * `repo` - The repository just processed.
+/// * `args` - CLI arguments selecting the format and the filters.
+pub fn stream_output(repo: &RepoInfo, args: &Args) {
+    let displayed = args.filter_repos(slice::from_ref(repo));
+    match args.output_format() {
+        OutputFormat::Plain => plain_output(&displayed, args),
+        OutputFormat::Ndjson => ndjson_output(&displayed),
+        OutputFormat::Table | OutputFormat::Json | OutputFormat::Dot | OutputFormat::Tmux => {}
+        #[cfg(feature = "html")]
+        OutputFormat::Html => {}
+        #[cfg(feature = "template")]
+        OutputFormat::Template => {}
+        #[cfg(feature = "svg")]
+        OutputFormat::Svg => {}
+    }
+}
+
+/// Formats one line per repository with tab-separated fields: the relative directory,
+/// branch, status, ahead, behind, commits and stashes, followed by the path and the remote
+/// URL (`-` if there is none) if requested.
+/// # Arguments
+/// * `repos` - List of repositories to format.
+/// * `path` - Whether to add the path.
+/// * `remote` - Whether to add the remote URL.
+/// # Returns
+/// The lines, without terminators.
+pub fn plain_lines(repos: &[RepoInfo], path: bool, remote: bool) -> Vec<String> {
+    repos
+        .iter()
+        .map(|repo| {
+            let mut fields = vec![
+                repo.repo_path.clone(),
+                repo.branch.clone(),
+                repo.status.to_string(),
+                repo.ahead.to_string(),
+                repo.behind.to_string(),
+                repo.format_commits(),
+                repo.stash_count.to_string(),
+            ];
+            if path {
+                fields.push(repo.path.display().to_string());
+            }
+            if remote {
+                fields.push(repo.remote_url.clone().unwrap_or_else(|| "-".to_owned()));
+            }
+            fields.join("\t")
+        })
+        .collect()
+}
+
+/// Returns what ends a line of plain output: a NUL with `--null`, a newline otherwise.
+const fn line_end(null: bool) -> char {
+    if null { '\0' } else { '\n' }
+}
+
+/// Prints what a bulk operation like `--pull` did to every repository, followed by how
+/// many were done, skipped or failed.
+/// # Arguments
+/// * `results` - The outcome per repository.
+/// * `style` - How to draw the table, which is always condensed.
+pub fn bulk_table(results: &[BulkResult], style: Style) {
+    if results.is_empty() {
+        log::info!("Nothing to do.");
+        return;
+    }
+
+    let mut table = Style {
+        condensed: true,
+        ..style
+    }
+    .table();
+    table.set_header(
+        ["Repository", "Result"].map(|header| Cell::new(header).add_attribute(Attribute::Bold)),
+    );
+    for result in results {
+        table.add_row(vec![
+            Cell::new(&result.repo_path),
+            Cell::new(style.text(&result.outcome.to_string())).fg(result.outcome.comfy_color()),
+        ]);
+    }
+    println!("{table}");
+
+    let mut counts = [0; 4];
+    for result in results {
+        counts[match result.outcome {
+            Outcome::Done(_) => 0,
+            Outcome::WouldDo(_) => 1,
+            Outcome::Skipped(_) => 2,
+            Outcome::Failed(_) => 3,
+        }] += 1;
+    }
+    let summary: Vec<String> = counts
+        .iter()
+        .zip(["done", "to do", "skipped", "failed"])
+        .filter(|&(&count, _)| count > 0)
+        .map(|(count, what)| format!("{count} {what}"))
+        .collect();
+    println!("{}", summary.join(", "));
+}
+
+/// Prints the rows of a `report --rollup` as a table.
+/// # Arguments
+/// * `rows` - The groups to print.
+/// * `style` - How to draw the table.
+pub fn rollup_table(rows: &[RollupRow], style: Style) {
+    if rows.is_empty() {
+        log::info!("No repositories found.");
+        return;
+    }
+
+    let mut table = style.table();
+    table.set_header(
+        [
+            "Group",
+            "Repos",
+            "Clean",
+            "Unpushed commits",
+            "Worst offender",
+        ]
+        .map(|header| Cell::new(header).add_attribute(Attribute::Bold)),
+    );
+    for row in rows {
+        table.add_row(vec![
+            Cell::new(&row.group),
+            Cell::new(row.repos),
+            Cell::new(format!("{} ({}%)", row.clean, row.clean_percent)),
+            Cell::new(row.unpushed_commits),
+            Cell::new(row.worst_offender.as_deref().unwrap_or("-")),
+        ]);
+    }
+    println!("{table}");
+}
+
+/// Prints the repositories that are checkouts of the same remote as a table, one row per
+/// checkout, with those that hold nothing the remote does not have marked as disposable.
+/// # Arguments
+/// * `groups` - The checkouts, grouped by remote.
+/// * `style` - How to draw the table.
+pub fn duplicates_table(groups: &[DuplicateGroup], style: Style) {
+    if groups.is_empty() {
+        log::info!("No remote is checked out more than once.");
+        return;
+    }
+
+    let mut table = style.table();
+    table.set_header(
+        [
+            "Remote",
+            "Directory",
+            "Branch",
+            "Local",
+            "Status",
+            "Disposable",
+        ]
+        .map(|header| Cell::new(header).add_attribute(Attribute::Bold)),
+    );
+    for group in groups {
+        for (index, checkout) in group.checkouts.iter().enumerate() {
+            let remote = if index == 0 {
+                group.remote.as_str()
+            } else {
+                ""
+            };
+            let (disposable, color) = if checkout.disposable {
+                ("yes", Color::Green)
+            } else {
+                ("no", Color::Reset)
+            };
+            table.add_row(vec![
+                Cell::new(remote),
+                Cell::new(&checkout.repo_path).fg(style.theme.color(&checkout.status)),
+                Cell::new(&checkout.branch),
+                Cell::new(style.text(&format!(
+                    "{}{} {}{}",
+                    Symbol::Ahead,
+                    checkout.ahead,
+                    Symbol::Behind,
+                    checkout.behind
+                ))),
+                Cell::new(&checkout.status).fg(style.theme.color(&checkout.status)),
+                Cell::new(disposable).fg(color),
+            ]);
         }
-        table.add_row(row);
     }
     println!("{table}");
 }
 
-/// Prints a legend explaining the color codes and statuses used in the output.
+/// Prints the repositories that are checkouts of the same remote as a JSON object.
 /// # Arguments
-/// * `condensed` - If true, uses a condensed format for the legend.
-pub fn legend(condensed: bool) {
-    let mut table = Table::new();
-    let preset = if condensed {
-        presets::UTF8_FULL_CONDENSED
-    } else {
-        presets::UTF8_FULL
+/// * `groups` - The checkouts, grouped by remote.
+/// * `failed_repos` - List of repositories that failed to process.
+pub fn duplicates_json_output(groups: &[DuplicateGroup], failed_repos: &[FailedRepo]) {
+    println!(
+        "{}",
+        serde_json::json!({
+            "duplicates": groups,
+            "failed": failed_repos
+        })
+    );
+}
+
+/// Prints the rows of a `report --rollup` one per line, with the group, repositories,
+/// clean repositories, clean percentage, unpushed commits and worst offender (`-` if none)
+/// separated by tabs.
+/// # Arguments
+/// * `rows` - The groups to print.
+/// * `null` - End lines with a NUL instead of a newline.
+pub fn rollup_plain_output(rows: &[RollupRow], null: bool) {
+    for row in rows {
+        print!(
+            "{}\t{}\t{}\t{}\t{}\t{}{}",
+            row.group,
+            row.repos,
+            row.clean,
+            row.clean_percent,
+            row.unpushed_commits,
+            row.worst_offender.as_deref().unwrap_or("-"),
+            line_end(null)
+        );
+    }
+}
+
+/// Prints the rows of a `report --rollup` in JSON format.
+/// # Arguments
+/// * `rows` - The groups to print.
+/// * `failed_repos` - List of repositories that failed to process.
+pub fn rollup_json_output(rows: &[RollupRow], failed_repos: &[FailedRepo]) {
+    println!(
+        "{}",
+        serde_json::json!({
+            "groups": rows,
+            "failed": failed_repos
+        })
+    );
+}
+
+/// Prints the `--history-report` as a table.
+/// # Arguments
+/// * `rows` - The repositories to print.
+/// * `style` - How to draw the table.
+#[cfg(feature = "history")]
+pub fn history_table(rows: &[HistoryRow], style: Style) {
+    if rows.is_empty() {
+        log::info!("No scans recorded.");
+        return;
+    }
+
+    let now = std::time::SystemTime::now();
+    let trend = |first: usize, latest: usize| {
+        if first == latest {
+            latest.to_string()
+        } else {
+            style.text(&format!("{first} → {latest}"))
+        }
     };
-    table
-        .load_preset(preset)
-        .set_content_arrangement(ContentArrangement::Dynamic);
-    table.set_header(vec![
-        Cell::new("Status").add_attribute(Attribute::Bold),
-        Cell::new("Description").add_attribute(Attribute::Bold),
-    ]);
+    let mut table = style.table();
+    table.set_header(
+        [
+            "Repository",
+            "Status",
+            "Not clean for",
+            "Ahead",
+            "Behind",
+            "Tracked for",
+        ]
+        .map(|header| Cell::new(header).add_attribute(Attribute::Bold)),
+    );
+    for row in rows {
+        table.add_row(vec![
+            Cell::new(&row.repo_path),
+            Cell::new(&row.status),
+            Cell::new(
+                row.not_clean_since
+                    .map_or_else(|| "-".to_owned(), |since| history::format_age(since, now)),
+            ),
+            Cell::new(trend(row.first_ahead, row.ahead)),
+            Cell::new(trend(row.first_behind, row.behind)),
+            Cell::new(format!(
+                "{} ({} scans)",
+                history::format_age(row.first_seen, now),
+                row.scans
+            )),
+        ]);
+    }
+    println!("{table}");
+}
+
+/// Prints the `--history-report` in JSON format.
+/// # Arguments
+/// * `rows` - The repositories to print.
+#[cfg(feature = "history")]
+pub fn history_json_output(rows: &[HistoryRow]) {
+    println!("{}", serde_json::json!({ "repositories": rows }));
+}
+
+/// Prints the changes found by `--diff`, one repository per line.
+/// # Arguments
+/// * `changes` - The changes to print.
+/// * `style` - How to draw the arrows between the old and the new values.
+pub fn changes(changes: &[RepoChange], style: Style) {
+    if changes.is_empty() {
+        log::info!("No changes.");
+    }
+    for change in changes {
+        println!("{}", style.text(&change.to_string()));
+    }
+}
+
+/// Prints a legend explaining the statuses and their colors, the columns of the table and
+/// the symbols used in the output, from `Status`, `Column` and `Symbol`.
+/// # Arguments
+/// * `style` - How to draw the legend.
+pub fn legend(style: Style) {
+    let bold = |title| Cell::new(title).add_attribute(Attribute::Bold);
+
+    let mut statuses = style.table();
+    statuses.set_header(vec![bold("Status"), bold("Description")]);
     Status::iter().for_each(|status| {
-        table.add_row(vec![status.as_cell(), Cell::new(status.description())]);
+        statuses.add_row(vec![
+            status.as_cell(&style.theme),
+            Cell::new(status.description()),
+        ]);
     });
-    println!("{table}");
+    println!("{statuses}");
     println!("The counts in brackets indicate the number of changed files.");
-    println!("The counts in brackets with an asterisk (*) indicate the number of stashes.");
-    println!("↑↑ indicates that the repository was fast-forwarded");
-    println!("⎇ indicates a Git worktree");
+
+    let mut columns = style.table();
+    columns.set_header(vec![
+        bold("Column"),
+        bold("Shown with"),
+        bold("Description"),
+    ]);
+    for column in Column::iter() {
+        columns.add_row(vec![
+            Cell::new(column.title()),
+            Cell::new(column.option().unwrap_or("always")),
+            Cell::new(column.description()),
+        ]);
+    }
+    println!("{columns}");
+
+    let mut symbols = style.table();
+    symbols.set_header(vec![bold("Symbol"), bold("Example"), bold("Description")]);
+    for symbol in Symbol::iter() {
+        symbols.add_row(vec![
+            Cell::new(style.text(symbol.text())),
+            Cell::new(style.text(symbol.example())),
+            Cell::new(symbol.description()),
+        ]);
+    }
+    println!("{symbols}");
+}
+
+/// The counts `--summary` prints.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    /// Number of repositories.
+    pub total: usize,
+    /// Number of clean repositories.
+    pub clean: usize,
+    /// Number of repositories with changes in their working directory.
+    pub dirty: usize,
+    /// Number of otherwise clean repositories with changes in their submodules.
+    pub submodule_dirty: usize,
+    /// Number of repositories with unpushed commits.
+    pub unpushed: usize,
+    /// Number of repositories with stashes.
+    pub with_stashes: usize,
+    /// Number of forks whose `origin` is behind their `upstream`.
+    pub forks_behind: usize,
+    /// Number of repositories with branches whose upstream is gone.
+    pub with_gone_branches: usize,
+    /// Number of repositories whose branch has no upstream.
+    pub local_only: usize,
+    /// Number of repositories that were fast-forwarded.
+    pub fast_forwarded: usize,
+    /// Number of repositories that failed to process.
+    pub failed: usize,
+    /// Number of repositories whose checked commits are not all signed with a good
+    /// signature, `None` if no signatures were checked.
+    pub unsigned: Option<usize>,
+    /// Number of commits ahead of upstream, across all repositories.
+    pub ahead: usize,
+    /// Number of commits behind upstream, across all repositories.
+    pub behind: usize,
+    /// Number of repositories per status (see `Status::legend_name`), leaving out the
+    /// statuses no repository has.
+    pub statuses: BTreeMap<String, usize>,
+    /// The repositories grouped by the host and owner of their remote, see `report::rollup`.
+    pub groups: Vec<RollupRow>,
 }
 
-/// Prints a summary of the repository scan (total, clean, dirty, unpushed).
+impl Summary {
+    /// Counts the repositories of a scan.
+    /// # Arguments
+    /// * `repos` - List of repositories to summarize.
+    /// * `failed` - Number of repositories that failed to process.
+    #[must_use]
+    pub fn new(repos: &[RepoInfo], failed: usize) -> Self {
+        let count =
+            |predicate: fn(&RepoInfo) -> bool| repos.iter().filter(|r| predicate(r)).count();
+        Self {
+            total: repos.len(),
+            clean: count(|r| r.status == Status::Clean),
+            dirty: count(|r| matches!(r.status, Status::Dirty(_))),
+            submodule_dirty: count(|r| r.status == Status::SubmoduleDirty),
+            unpushed: count(|r| r.has_unpushed),
+            with_stashes: count(|r| r.stash_count > 0),
+            forks_behind: count(|r| r.fork_behind.is_some_and(|behind| behind > 0)),
+            with_gone_branches: count(|r| r.gone_branches > 0),
+            local_only: count(|r| r.is_local_only),
+            fast_forwarded: count(|r| r.fast_forwarded),
+            failed,
+            unsigned: repos.iter().any(|r| r.signatures.is_some()).then(|| {
+                count(|r| {
+                    r.signatures
+                        .is_some_and(|signatures| !signatures.all_good())
+                })
+            }),
+            ahead: repos.iter().map(|r| r.ahead).sum(),
+            behind: repos.iter().map(|r| r.behind).sum(),
+            statuses: repos.iter().fold(BTreeMap::new(), |mut statuses, r| {
+                *statuses.entry(r.status.legend_name()).or_default() += 1;
+                statuses
+            }),
+            groups: report::rollup(repos, Rollup::Org),
+        }
+    }
+}
+
+/// Prints a summary of the repository scan: the counts, the ahead/behind totals and the
+/// breakdowns per status and per remote owner.
 ///
 /// # Arguments
 /// * `repos` - List of repositories to summarize.
 /// * `failed` - Number of repositories that failed to process.
 pub fn summary(repos: &[RepoInfo], failed: usize) {
-    let total = repos.len();
-    let clean = repos.iter().filter(|r| r.status == Status::Clean).count();
-    let dirty = repos
-        .iter()
-        .filter(|r| matches!(r.status, Status::Dirty(_)))
-        .count();
-    let unpushed = repos.iter().filter(|r| r.has_unpushed).count();
-    let with_stashes = repos.iter().filter(|r| r.stash_count > 0).count();
-    let local_only = repos.iter().filter(|r| r.is_local_only).count();
-    let fast_forwarded = repos.iter().filter(|r| r.fast_forwarded).count();
+    let summary = Summary::new(repos, failed);
     println!("\nSummary:");
-    println!("  Total repositories:   {total}");
-    println!("  Clean:                {clean}");
-    println!("  With changes:         {dirty}");
-    println!("  With unpushed:        {unpushed}");
-    println!("  With stashes:         {with_stashes}");
-    println!("  Local-only branches:  {local_only}");
-    println!("  Fast-forwarded:       {fast_forwarded}");
+    println!("  Total repositories:   {}", summary.total);
+    println!("  Clean:                {}", summary.clean);
+    println!("  With changes:         {}", summary.dirty);
+    if summary.submodule_dirty > 0 {
+        println!("  Submodules dirty:     {}", summary.submodule_dirty);
+    }
+    println!("  With unpushed:        {}", summary.unpushed);
+    println!("  With stashes:         {}", summary.with_stashes);
+    if summary.forks_behind > 0 {
+        println!("  Forks behind:         {}", summary.forks_behind);
+    }
+    if summary.with_gone_branches > 0 {
+        println!("  With gone upstreams:  {}", summary.with_gone_branches);
+    }
+    println!("  Local-only branches:  {}", summary.local_only);
+    println!("  Fast-forwarded:       {}", summary.fast_forwarded);
+    println!("  Commits ahead:        {}", summary.ahead);
+    println!("  Commits behind:       {}", summary.behind);
+    if let Some(unsigned) = summary.unsigned {
+        println!("  Not validly signed:   {unsigned}");
+    }
     if failed > 0 {
         println!("  Failed to process:    {failed}");
     }
+    if !summary.statuses.is_empty() {
+        println!("  By status:");
+        for (status, count) in &summary.statuses {
+            println!("    {status}: {count}");
+        }
+    }
+    if !summary.groups.is_empty() {
+        println!("  By owner:");
+        for group in &summary.groups {
+            println!(
+                "    {}: {} ({} clean)",
+                group.group, group.repos, group.clean
+            );
+        }
+    }
+}
+
+/// Prints the repositories that took longest to scan, with how long each step took.
+///
+/// Nothing is printed if no timing was recorded, see `--timing`.
+/// # Arguments
+/// * `repos` - List of repositories scanned.
+/// * `top` - How many repositories to print.
+pub fn timing(repos: &[RepoInfo], top: usize) {
+    let lines = timing_lines(repos, top);
+    if lines.is_empty() {
+        return;
+    }
+    println!("\nSlowest repositories:");
+    for line in lines {
+        println!("  {line}");
+    }
+}
+
+/// Builds the lines `timing` prints, slowest repository first.
+/// # Arguments
+/// * `repos` - List of repositories scanned.
+/// * `top` - How many repositories to list.
+/// # Returns
+/// One line per repository with a recorded timing, at most `top`.
+pub fn timing_lines(repos: &[RepoInfo], top: usize) -> Vec<String> {
+    let mut timed: Vec<(&RepoInfo, Timing)> = repos
+        .iter()
+        .filter_map(|repo| Some((repo, repo.timing?)))
+        .collect();
+    timed.sort_by_key(|(_, timing)| Reverse(timing.total()));
+    timed
+        .into_iter()
+        .take(top)
+        .map(|(repo, timing)| {
+            format!(
+                "{}: {:.1?} (open {:.1?}, fetch {:.1?}, revwalk {:.1?}, status {:.1?})",
+                repo.repo_path,
+                timing.total(),
+                timing.open,
+                timing.fetch,
+                timing.revwalk,
+                timing.status
+            )
+        })
+        .collect()
+}
+
+/// Builds the `--output tmux` line, e.g. `3 dirty · 2 unpushed`, with every count colored
+/// with a tmux style tag. Counts of zero are left out, and a scan with nothing to report
+/// reads like `12 clean`.
+/// # Arguments
+/// * `repos` - The repositories to count.
+/// * `failed` - Number of repositories that could not be scanned.
+/// # Returns
+/// The line, without a trailing newline.
+pub fn tmux_line(repos: &[RepoInfo], failed: usize) -> String {
+    let dirty = repos
+        .iter()
+        .filter(|r| matches!(r.status, Status::Dirty(_) | Status::SubmoduleDirty))
+        .count();
+    let unpushed = repos.iter().filter(|r| r.ahead > 0).count();
+    let behind = repos.iter().filter(|r| r.behind > 0).count();
+    let parts: Vec<String> = [
+        (dirty, "dirty", "red"),
+        (unpushed, "unpushed", "yellow"),
+        (behind, "behind", "blue"),
+        (failed, "failed", "magenta"),
+    ]
+    .into_iter()
+    .filter(|&(count, ..)| count > 0)
+    .map(|(count, label, color)| format!("#[fg={color}]{count} {label}#[default]"))
+    .collect();
+    if parts.is_empty() {
+        format!("#[fg=green]{} clean#[default]", repos.len())
+    } else {
+        parts.join(" · ")
+    }
 }
 
 /// Prints a summary of failed repositories that could not be processed.
 /// # Arguments
-/// * `failed_repos` - List of repository names that failed to process.
-pub fn failed_summary(failed_repos: &[String]) {
+/// * `failed_repos` - List of repositories that failed to process.
+pub fn failed_summary(failed_repos: &[FailedRepo]) {
     if !failed_repos.is_empty() {
         log::warn!("Failed to process the following repositories:");
         for repo in failed_repos {
-            log::warn!(" - {repo}");
+            log::warn!(" - {}: {}", repo.name, repo.error);
         }
     }
 }
 
+/// Prints the `.gitignore` entries suggested for untracked artifacts, one line per repository.
+///
+/// Repositories without suggestions are left out; nothing is printed if none have any.
+/// # Arguments
+/// * `repos` - List of repositories to analyze.
+pub fn gitignore_advice(repos: &[RepoInfo]) {
+    let advice = repos
+        .iter()
+        .filter_map(|repo| {
+            let git_repo = Repository::open(&repo.path)
+                .inspect_err(|e| {
+                    log::debug!("Failed to reopen {}: {e}", repo.path.display());
+                })
+                .ok()?;
+            let entries = gitinfo::suggest_gitignore_entries(&git_repo);
+            (!entries.is_empty()).then_some((&repo.repo_path, entries))
+        })
+        .collect::<Vec<_>>();
+
+    if advice.is_empty() {
+        return;
+    }
+    println!("\nSuggested .gitignore entries:");
+    for (repo_path, entries) in advice {
+        println!("  {repo_path}: {}", entries.join(", "));
+    }
+}
+
 /// Builds the JSON representation of a scan result.
 /// # Arguments
 /// * `repos` - List of repositories to output.
-/// * `failed_repos` - List of repository names that failed to process.
+/// * `failed_repos` - List of repositories that failed to process.
 /// # Returns
 /// The JSON value that `json_output` prints.
-pub fn json_value(repos: &[RepoInfo], failed_repos: &[String]) -> serde_json::Value {
+pub fn json_value(
+    repos: &[impl serde::Serialize],
+    failed_repos: &[FailedRepo],
+) -> serde_json::Value {
     serde_json::json!({
         "repositories": repos,
         "failed": failed_repos
@@ -155,7 +1187,177 @@ pub fn json_value(repos: &[RepoInfo], failed_repos: &[String]) -> serde_json::Va
 /// Prints the repository information in JSON format.
 /// # Arguments
 /// * `repos` - List of repositories to output.
-/// * `failed_repos` - List of repository names that failed to process.
-pub fn json_output(repos: &[RepoInfo], failed_repos: &[String]) {
-    println!("{}", json_value(repos, failed_repos));
+/// * `failed_repos` - List of repositories that failed to process.
+/// * `summary` - The summary to add as a `summary` field, for `--summary`.
+pub fn json_output(
+    repos: &[impl serde::Serialize],
+    failed_repos: &[FailedRepo],
+    summary: Option<&Summary>,
+) {
+    let mut value = json_value(repos, failed_repos);
+    if let Some(summary) = summary {
+        value["summary"] = serde_json::json!(summary);
+    }
+    println!("{value}");
+}
+
+/// Builds a Graphviz graph of the repositories, grouped by the host and owner of their
+/// remote, with every repository filled in the color of its status.
+///
+/// Repositories without a remote, or with a local one, hang off a `local` node.
+/// # Arguments
+/// * `repos` - List of repositories to draw.
+/// # Returns
+/// The graph in the dot language.
+pub fn dot_graph(repos: &[RepoInfo]) -> String {
+    let mut groups = BTreeSet::new();
+    let mut body = String::new();
+    for repo in repos {
+        let group = match repo
+            .remote_url
+            .as_deref()
+            .and_then(gitinfo::remote_host_and_owner)
+        {
+            Some((host, owner)) if owner.is_empty() => {
+                groups.insert((host.clone(), None));
+                host
+            }
+            Some((host, owner)) => {
+                let group = format!("{host}/{owner}");
+                groups.insert((group.clone(), Some((host, owner))));
+                group
+            }
+            None => {
+                groups.insert(("local".to_owned(), None));
+                "local".to_owned()
+            }
+        };
+        let label = format!(
+            "{}: {}",
+            repo.repo_path,
+            repo.format_status_with_stash_and_ff()
+        );
+        let _ = writeln!(
+            body,
+            "    {} [label={}, fillcolor={}];",
+            dot_id(&repo.path.display().to_string()),
+            dot_id(&label),
+            repo.status.dot_color()
+        );
+        let _ = writeln!(
+            body,
+            "    {} -> {};",
+            dot_id(&group),
+            dot_id(&repo.path.display().to_string())
+        );
+    }
+
+    let mut graph = String::from(
+        "digraph \"git-statuses\" {\n    rankdir=LR;\n    node [shape=box, style=filled];\n",
+    );
+    let mut hosts = BTreeSet::new();
+    for (group, owner) in &groups {
+        if let Some((host, owner)) = owner {
+            hosts.insert(host);
+            let _ = writeln!(
+                graph,
+                "    {} [label={}, fillcolor=white];",
+                dot_id(group),
+                dot_id(owner)
+            );
+            let _ = writeln!(graph, "    {} -> {};", dot_id(host), dot_id(group));
+        } else {
+            hosts.insert(group);
+        }
+    }
+    for host in hosts {
+        let _ = writeln!(graph, "    {} [fillcolor=lightgrey];", dot_id(host));
+    }
+    graph.push_str(&body);
+    graph.push_str("}\n");
+    graph
+}
+
+/// Quotes a string as a dot identifier.
+fn dot_id(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Prints the repositories as a Graphviz graph, see `dot_graph`.
+/// # Arguments
+/// * `repos` - List of repositories to draw.
+pub fn dot_output(repos: &[RepoInfo]) {
+    print!("{}", dot_graph(repos));
+}
+
+/// Prints the repositories as a self-contained HTML report, see `html::report`.
+/// # Arguments
+/// * `repos` - List of repositories to list.
+/// * `failed_repos` - List of repositories that failed to process.
+#[cfg(feature = "html")]
+pub fn html_output(repos: &[RepoInfo], failed_repos: &[FailedRepo]) {
+    print!("{}", html::report(repos, failed_repos));
+}
+
+/// Prints the repositories rendered through a template, see `template::render`.
+/// # Arguments
+/// * `path` - The template file.
+/// * `repos` - List of repositories to render.
+/// * `failed_repos` - List of repositories that failed to process.
+///
+/// # Errors
+/// Returns an error if the template cannot be read or rendered.
+#[cfg(feature = "template")]
+pub fn template_output(
+    path: &std::path::Path,
+    repos: &[RepoInfo],
+    failed_repos: &[FailedRepo],
+) -> anyhow::Result<()> {
+    use anyhow::Context as _;
+
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let name = path.file_name().map_or_else(
+        || path.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    let rendered = crate::template::render(&name, &source, repos, failed_repos)
+        .with_context(|| format!("Failed to render {}", path.display()))?;
+    print!("{rendered}");
+    Ok(())
+}
+
+/// Lays out the graph of `dot_graph` and prints it as SVG.
+/// # Arguments
+/// * `repos` - List of repositories to draw.
+///
+/// # Errors
+/// Returns an error if the graph cannot be laid out.
+#[cfg(feature = "svg")]
+pub fn svg_output(repos: &[RepoInfo]) -> anyhow::Result<()> {
+    println!("{}", svg_graph(repos)?);
+    Ok(())
+}
+
+/// Lays out the graph of `dot_graph` as SVG.
+/// # Arguments
+/// * `repos` - List of repositories to draw.
+/// # Returns
+/// The SVG document.
+///
+/// # Errors
+/// Returns an error if the graph cannot be laid out.
+#[cfg(feature = "svg")]
+pub fn svg_graph(repos: &[RepoInfo]) -> anyhow::Result<String> {
+    use layout::{backends::svg::SVGWriter, gv};
+
+    let dot = dot_graph(repos);
+    let graph = gv::DotParser::new(&dot)
+        .process()
+        .map_err(|e| anyhow::anyhow!("Failed to lay out the graph: {e}"))?;
+    let mut builder = gv::GraphBuilder::new();
+    builder.visit_graph(&graph);
+    let mut svg = SVGWriter::new();
+    builder.get().do_it(false, false, false, &mut svg);
+    Ok(svg.finalize())
 }
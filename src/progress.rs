@@ -0,0 +1,76 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, RecvTimeoutError},
+    },
+    thread,
+    time::Duration,
+};
+
+/// Counts the repositories a running scan has processed, for periodic progress lines.
+#[derive(Default)]
+pub struct Progress {
+    processed: AtomicUsize,
+    failed: AtomicUsize,
+}
+
+impl Progress {
+    /// Records that one more repository has been processed.
+    /// # Arguments
+    /// * `failed` - Whether the repository failed to process.
+    pub fn processed(&self, failed: bool) {
+        self.processed.fetch_add(1, Ordering::Relaxed);
+        if failed {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the number of repositories that failed to process so far.
+    pub fn failed(&self) -> usize {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// Formats the current state as a single line, e.g. `fetched 40/200 repos, 3 failures`.
+    /// # Arguments
+    /// * `verb` - What is being done to the repositories, in the past tense.
+    /// * `total` - The number of repositories the scan will process.
+    pub fn line(&self, verb: &str, total: usize) -> String {
+        format!(
+            "{verb} {}/{total} repos, {} failures",
+            self.processed.load(Ordering::Relaxed),
+            self.failed.load(Ordering::Relaxed)
+        )
+    }
+
+    /// Runs `work`, printing a progress line to stderr every `interval` until it returns.
+    ///
+    /// The lines come from a separate thread, so they keep coming while a single slow
+    /// repository holds up every worker.
+    /// # Arguments
+    /// * `verb` - What is being done to the repositories, in the past tense.
+    /// * `total` - The number of repositories `work` processes.
+    /// * `interval` - The time between two progress lines.
+    /// * `work` - The scan, which reports to `self` through `processed`.
+    /// # Returns
+    /// What `work` returns.
+    pub fn report_while<R>(
+        &self,
+        verb: &str,
+        total: usize,
+        interval: Duration,
+        work: impl FnOnce() -> R,
+    ) -> R {
+        let (done, finished) = mpsc::channel::<()>();
+        thread::scope(|s| {
+            s.spawn(move || {
+                while finished.recv_timeout(interval) == Err(RecvTimeoutError::Timeout) {
+                    eprintln!("{}", self.line(verb, total));
+                }
+            });
+            let result = work();
+            // Wakes the reporter up, so the scan does not wait out the rest of an interval.
+            drop(done);
+            result
+        })
+    }
+}
@@ -0,0 +1,49 @@
+use std::{fmt::Write as _, io::Write, path::Path};
+
+use git2::Repository;
+
+use crate::{gitinfo, util};
+
+/// Prints the `--prompt` line of the repository `dir` is in, and nothing if it is in none.
+///
+/// Only what is cheap to find out is looked at: the commits are not counted, nothing is
+/// fetched and the other repositories below `dir` are not scanned.
+/// # Arguments
+/// * `dir` - A directory in the repository, usually the current one.
+/// * `ascii` - Whether to replace the symbols with ASCII, see `util::to_ascii`.
+/// * `out` - Where to write the line to.
+/// # Errors
+/// Returns an error if the line cannot be written.
+pub fn print(dir: &Path, ascii: bool, out: &mut impl Write) -> anyhow::Result<()> {
+    let Ok(mut repo) = Repository::discover(dir) else {
+        return Ok(());
+    };
+    let line = line(&mut repo);
+    writeln!(out, "{}", if ascii { util::to_ascii(&line) } else { line })?;
+    Ok(())
+}
+
+/// Builds the `--prompt` line of a repository, e.g. `main ↑2 ↓0 ✚3 ⚑1`.
+///
+/// The ahead and behind counts are left out for a branch without upstream, the changed
+/// files (`✚`) and the stashes (`⚑`) when there are none.
+/// # Arguments
+/// * `repo` - The repository to describe.
+/// # Returns
+/// The line, without a trailing newline.
+pub fn line(repo: &mut Repository) -> String {
+    let mut line = gitinfo::get_branch_name(repo);
+    let (ahead, behind, is_local_only) = gitinfo::get_ahead_behind_and_local_status(repo, None);
+    if !is_local_only {
+        let _ = write!(line, " ↑{ahead} ↓{behind}");
+    }
+    let changed = gitinfo::get_changed_count(repo);
+    if changed > 0 {
+        let _ = write!(line, " ✚{changed}");
+    }
+    let stashes = gitinfo::get_stash_count(repo);
+    if stashes > 0 {
+        let _ = write!(line, " ⚑{stashes}");
+    }
+    line
+}
@@ -0,0 +1,155 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io::Write,
+};
+
+use git2::{BranchType, Repository};
+
+use crate::gitinfo::{self, repoinfo::RepoListing};
+
+/// Why `--prune-merged` deletes a branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PruneReason {
+    /// The branch is merged into the default branch, named here.
+    Merged(String),
+    /// The remote branch it tracked was deleted, see `gitinfo::get_gone_branches`, and HEAD
+    /// has all of its commits.
+    Gone,
+    /// The remote branch it tracked was deleted, but neither HEAD nor the default branch
+    /// has all of its commits. The branch is listed, but kept, like `git branch -d` does.
+    Unmerged,
+}
+
+impl PruneReason {
+    /// Checks whether the branch is kept rather than deleted.
+    #[must_use]
+    pub const fn is_kept(&self) -> bool {
+        matches!(self, Self::Unmerged)
+    }
+}
+
+impl Display for PruneReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Merged(branch) => write!(f, "merged into {branch}"),
+            Self::Gone => f.write_str("upstream gone"),
+            Self::Unmerged => f.write_str("upstream gone, unmerged"),
+        }
+    }
+}
+
+/// A local branch that is no longer needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrunableBranch {
+    /// The name of the branch.
+    pub name: String,
+    /// Why it is no longer needed.
+    pub reason: PruneReason,
+}
+
+/// Lists the local branches that are merged into the default branch (see
+/// `gitinfo::get_default_branch`) or whose upstream is gone, the latter as
+/// `PruneReason::Unmerged` unless HEAD has all of their commits. The checked out branch and
+/// the default branch itself are never listed.
+/// # Arguments
+/// * `repo` - The Git repository to check.
+/// # Returns
+/// The branches, sorted by name.
+pub fn prunable_branches(repo: &Repository) -> Vec<PrunableBranch> {
+    let default = gitinfo::get_default_branch(repo);
+    let head = repo.head().ok();
+    let head_tip = head.as_ref().and_then(git2::Reference::target);
+    let head = head
+        .filter(git2::Reference::is_branch)
+        .and_then(|head| head.shorthand().ok().map(ToOwned::to_owned));
+    let gone = gitinfo::get_gone_branches(repo);
+    let Ok(branches) = repo.branches(Some(BranchType::Local)) else {
+        return Vec::new();
+    };
+
+    let mut prunable: Vec<PrunableBranch> = branches
+        .filter_map(Result::ok)
+        .filter_map(|(branch, _)| {
+            let name = branch.name().ok().flatten()?.to_owned();
+            if head.as_ref() == Some(&name)
+                || default
+                    .as_ref()
+                    .is_some_and(|(default, _)| *default == name)
+            {
+                return None;
+            }
+            let tip = branch.get().target()?;
+            let reason = match &default {
+                Some((default, default_tip))
+                    if tip == *default_tip
+                        || repo.graph_descendant_of(*default_tip, tip).unwrap_or(false) =>
+                {
+                    PruneReason::Merged(default.clone())
+                }
+                _ if gone.contains(&name) => {
+                    let merged = head_tip.is_some_and(|head_tip| {
+                        tip == head_tip || repo.graph_descendant_of(head_tip, tip).unwrap_or(false)
+                    });
+                    if merged {
+                        PruneReason::Gone
+                    } else {
+                        PruneReason::Unmerged
+                    }
+                }
+                _ => return None,
+            };
+            Some(PrunableBranch { name, reason })
+        })
+        .collect();
+    prunable.sort_by(|a, b| a.name.cmp(&b.name));
+    prunable
+}
+
+/// Lists the prunable branches of every repository, and deletes them if `apply` is set.
+/// Those of `PruneReason::Unmerged` are listed as kept, and never deleted.
+///
+/// Repositories that cannot be opened are skipped, and a branch that cannot be deleted is
+/// reported without stopping the others.
+/// # Arguments
+/// * `repos` - The repositories to prune.
+/// * `apply` - Delete the branches instead of only listing them.
+/// * `out` - Where to write one line per branch to.
+///
+/// # Errors
+/// Returns an error if writing to `out` fails.
+pub fn prune_merged(
+    repos: &[RepoListing],
+    apply: bool,
+    out: &mut impl Write,
+) -> anyhow::Result<()> {
+    let mut count = 0;
+    for listing in repos {
+        let Ok(repo) = Repository::open(&listing.path) else {
+            log::warn!("Failed to open `{}`, skipping it", listing.repo_path);
+            continue;
+        };
+        for branch in prunable_branches(&repo) {
+            let line = format!("{}: {} ({})", listing.repo_path, branch.name, branch.reason);
+            if branch.reason.is_kept() {
+                writeln!(out, "{line} kept")?;
+                continue;
+            }
+            count += 1;
+            if !apply {
+                writeln!(out, "{line}")?;
+                continue;
+            }
+            match repo
+                .find_branch(&branch.name, BranchType::Local)
+                .and_then(|mut b| b.delete())
+            {
+                Ok(()) => writeln!(out, "{line} deleted")?,
+                Err(e) => log::warn!("Failed to delete {line}: {}", e.message()),
+            }
+        }
+    }
+    if count > 0 && !apply {
+        log::info!("{count} branches can be deleted, run again with --apply to delete them");
+    }
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use std::{
+    collections::BTreeSet,
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+
+use crate::gitinfo::repoinfo::RepoInfo;
+
+/// Returns where the registry is kept by default: `git-statuses/registry.json` in
+/// `$XDG_DATA_HOME`, or in `~/.local/share` if that is not set.
+#[must_use]
+pub fn default_path() -> Option<PathBuf> {
+    env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .map(|data| data.join("git-statuses").join("registry.json"))
+}
+
+/// Reads the repositories of the registry at `path`. A registry that does not exist yet
+/// is empty.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or is not a registry.
+pub fn load(path: &Path) -> anyhow::Result<BTreeSet<PathBuf>> {
+    let json = match fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(BTreeSet::new()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    serde_json::from_str(&json).with_context(|| format!("{} is not a registry", path.display()))
+}
+
+/// Adds the repositories of a scan to the registry at `path` and drops the ones that no
+/// longer exist. Repositories that are merely outside of the scanned directory are kept.
+///
+/// # Errors
+/// Returns an error if the registry cannot be read or written.
+pub fn update(path: &Path, repos: &[RepoInfo]) -> anyhow::Result<()> {
+    let mut registered = load(path)?;
+    let before = registered.clone();
+    registered.retain(|repo| repo.exists());
+    registered.extend(repos.iter().map(|repo| repo.path.clone()));
+    if registered == before {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&registered)? + "\n")
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
@@ -0,0 +1,255 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io::Write,
+    str::FromStr,
+};
+
+use git2::Repository;
+
+use crate::gitinfo::repoinfo::RepoListing;
+
+/// How a remote is reached, judged by its URL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    /// `ssh://host/path` or the scp-like `user@host:path`.
+    Ssh,
+    /// `https://host/path`.
+    Https,
+    /// `http://host/path`.
+    Http,
+    /// `git://host/path`.
+    Git,
+    /// A local path or `file://` URL.
+    File,
+}
+
+impl Protocol {
+    /// Classifies a remote URL.
+    /// # Arguments
+    /// * `url` - The URL of the remote.
+    /// # Returns
+    /// The protocol, or `None` for schemes git-statuses does not know, e.g. `ftp://`.
+    pub fn of(url: &str) -> Option<Self> {
+        if let Some((scheme, _)) = url.split_once("://") {
+            return match scheme.to_ascii_lowercase().as_str() {
+                "ssh" | "git+ssh" | "ssh+git" => Some(Self::Ssh),
+                "https" => Some(Self::Https),
+                "http" => Some(Self::Http),
+                "git" => Some(Self::Git),
+                "file" => Some(Self::File),
+                _ => None,
+            };
+        }
+        // Git treats `host:path` as scp-like unless a slash comes before the colon.
+        match url.split_once(':') {
+            Some((host, _)) if !host.is_empty() && !host.contains('/') && host.len() > 1 => {
+                Some(Self::Ssh)
+            }
+            _ => Some(Self::File),
+        }
+    }
+
+    /// Splits a URL of this protocol into its host and path, dropping the user, port and
+    /// scheme. `None` for local paths, which have no host to move to another protocol.
+    fn host_and_path(url: &str) -> Option<(&str, &str)> {
+        let (authority, path) = match url.split_once("://") {
+            Some((_, rest)) => rest.split_once('/')?,
+            None => url.split_once(':')?,
+        };
+        let host = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        let host = host.split_once(':').map_or(host, |(host, _)| host);
+        let path = path.trim_start_matches('/');
+        (!host.is_empty() && !path.is_empty()).then_some((host, path))
+    }
+}
+
+impl Display for Protocol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Ssh => "ssh",
+            Self::Https => "https",
+            Self::Http => "http",
+            Self::Git => "git",
+            Self::File => "file",
+        })
+    }
+}
+
+impl FromStr for Protocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ssh" => Ok(Self::Ssh),
+            "https" => Ok(Self::Https),
+            "http" => Ok(Self::Http),
+            "git" => Ok(Self::Git),
+            "file" => Ok(Self::File),
+            _ => Err(format!(
+                "`{s}` is not a protocol, use `ssh`, `https`, `http`, `git` or `file`"
+            )),
+        }
+    }
+}
+
+/// Which remotes `--convert-remotes` rewrites, given as `FROM:TO` (or `FROM→TO`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conversion {
+    /// The protocol of the remotes to rewrite.
+    pub from: Protocol,
+    /// The protocol to rewrite them to.
+    pub to: Protocol,
+}
+
+impl Conversion {
+    /// Rewrites a URL if it uses the protocol converted from.
+    /// # Arguments
+    /// * `url` - The URL of a remote.
+    /// # Returns
+    /// The URL for the protocol converted to, or `None` if the URL is left alone.
+    pub fn convert(self, url: &str) -> Option<String> {
+        if Protocol::of(url) != Some(self.from) {
+            return None;
+        }
+        let (host, path) = Protocol::host_and_path(url)?;
+        match self.to {
+            Protocol::Ssh => Some(format!("git@{host}:{path}")),
+            Protocol::Https | Protocol::Http | Protocol::Git => {
+                Some(format!("{}://{host}/{path}", self.to))
+            }
+            Protocol::File => None,
+        }
+    }
+}
+
+impl FromStr for Conversion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((from, to)) = s.split_once(':').or_else(|| s.split_once('→')) else {
+            return Err(format!("`{s}` is not a conversion, use e.g. `ssh:https`"));
+        };
+        let conversion = Self {
+            from: from.parse()?,
+            to: to.parse()?,
+        };
+        if conversion.to == Protocol::File {
+            return Err("remotes cannot be converted to local paths".to_owned());
+        }
+        if conversion.from == conversion.to {
+            return Err(format!("`{s}` converts `{from}` to itself"));
+        }
+        Ok(conversion)
+    }
+}
+
+impl Display for Conversion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.from, self.to)
+    }
+}
+
+/// The remotes of a repository with their fetch URLs, sorted by name.
+fn remotes(repo: &Repository) -> Vec<(String, String)> {
+    let mut remotes: Vec<(String, String)> = repo
+        .remotes()
+        .map(|names| {
+            names
+                .iter()
+                .flatten()
+                .flatten()
+                .filter_map(|name| {
+                    let remote = repo.find_remote(name).ok()?;
+                    let url = remote.url().ok()?.to_owned();
+                    Some((name.to_owned(), url))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    remotes.sort();
+    remotes
+}
+
+/// Prints one line per remote of every repository with its protocol and URL.
+///
+/// Repositories that cannot be opened are skipped.
+/// # Arguments
+/// * `repos` - The repositories to audit.
+/// * `out` - Where to write the lines to.
+///
+/// # Errors
+/// Returns an error if writing to `out` fails.
+pub fn audit(repos: &[RepoListing], out: &mut impl Write) -> anyhow::Result<()> {
+    for listing in repos {
+        let Ok(repo) = Repository::open(&listing.path) else {
+            log::warn!("Failed to open `{}`, skipping it", listing.repo_path);
+            continue;
+        };
+        for (name, url) in remotes(&repo) {
+            let protocol =
+                Protocol::of(&url).map_or_else(|| "unknown".to_owned(), |p| p.to_string());
+            writeln!(out, "{}: {name} {protocol} {url}", listing.repo_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Lists the remotes of every repository that `conversion` would rewrite, and rewrites
+/// their fetch URL and push URL if `apply` is set.
+///
+/// Repositories that cannot be opened are skipped, and a remote that cannot be rewritten
+/// is reported without stopping the others.
+/// # Arguments
+/// * `repos` - The repositories to convert.
+/// * `conversion` - Which remotes to rewrite to what.
+/// * `apply` - Rewrite the remotes instead of only listing them.
+/// * `out` - Where to write one line per remote to.
+///
+/// # Errors
+/// Returns an error if writing to `out` fails.
+pub fn convert(
+    repos: &[RepoListing],
+    conversion: Conversion,
+    apply: bool,
+    out: &mut impl Write,
+) -> anyhow::Result<()> {
+    let mut count = 0;
+    for listing in repos {
+        let Ok(repo) = Repository::open(&listing.path) else {
+            log::warn!("Failed to open `{}`, skipping it", listing.repo_path);
+            continue;
+        };
+        for (name, url) in remotes(&repo) {
+            let Some(converted) = conversion.convert(&url) else {
+                continue;
+            };
+            count += 1;
+            let line = format!("{}: {name} {url} → {converted}", listing.repo_path);
+            if !apply {
+                writeln!(out, "{line}")?;
+                continue;
+            }
+            let push_url = repo
+                .find_remote(&name)
+                .ok()
+                .and_then(|remote| remote.pushurl().ok().flatten().map(ToOwned::to_owned));
+            let result = repo.remote_set_url(&name, &converted).and_then(|()| {
+                push_url
+                    .and_then(|push_url| conversion.convert(&push_url))
+                    .map_or(Ok(()), |push_url| {
+                        repo.remote_set_pushurl(&name, Some(&push_url))
+                    })
+            });
+            match result {
+                Ok(()) => writeln!(out, "{line} rewritten")?,
+                Err(e) => log::warn!("Failed to rewrite {line}: {}", e.message()),
+            }
+        }
+    }
+    if count > 0 && !apply {
+        log::info!("{count} remotes can be rewritten, run again with --apply to rewrite them");
+    }
+    Ok(())
+}
@@ -0,0 +1,70 @@
+//! Renders scan results to strings, in the formats the command line prints them in.
+
+use crate::{
+    gitinfo::repoinfo::{FailedRepo, RepoInfo},
+    printer,
+};
+
+/// Renders a scan result as the JSON object `--output json` prints: `repositories` and
+/// `failed`.
+#[must_use]
+pub fn json(repos: &[RepoInfo], failed_repos: &[FailedRepo]) -> String {
+    printer::json_value(repos, failed_repos).to_string()
+}
+
+/// Renders the lines of `--output plain`, each ended by a newline.
+/// # Arguments
+/// * `repos` - List of repositories to render.
+/// * `path` - Whether to add the path of every repository.
+/// * `remote` - Whether to add the remote URL of every repository.
+#[must_use]
+pub fn plain(repos: &[RepoInfo], path: bool, remote: bool) -> String {
+    printer::plain_lines(repos, path, remote)
+        .into_iter()
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/// Renders the Graphviz graph of `--output dot`. Repositories are grouped by the host and
+/// owner of their `remote_url`, so it should be filled in.
+#[must_use]
+pub fn dot(repos: &[RepoInfo]) -> String {
+    printer::dot_graph(repos)
+}
+
+/// Renders the graph of [`dot`] as SVG, like `--output svg`.
+///
+/// # Errors
+/// Returns an error if the graph cannot be laid out.
+#[cfg(feature = "svg")]
+pub fn svg(repos: &[RepoInfo]) -> anyhow::Result<String> {
+    printer::svg_graph(repos)
+}
+
+/// Renders the self-contained HTML report of `--output html`.
+#[cfg(feature = "html")]
+#[must_use]
+pub fn html(repos: &[RepoInfo], failed_repos: &[FailedRepo]) -> String {
+    crate::html::report(repos, failed_repos)
+}
+
+/// Renders a scan through a Jinja template, like `--output template`. The template sees
+/// `repositories`, `failed` and `summary`.
+/// # Arguments
+/// * `name` - The name of the template. Names ending in `.html`, `.xml` or `.json` turn on
+///   escaping for that format.
+/// * `source` - The template.
+/// * `repos` - List of repositories to render.
+/// * `failed_repos` - List of repositories that failed to process.
+///
+/// # Errors
+/// Returns an error if the template has a syntax error or fails to render.
+#[cfg(feature = "template")]
+pub fn template(
+    name: &str,
+    source: &str,
+    repos: &[RepoInfo],
+    failed_repos: &[FailedRepo],
+) -> anyhow::Result<String> {
+    crate::template::render(name, source, repos, failed_repos)
+}
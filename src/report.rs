@@ -0,0 +1,222 @@
+use std::{collections::BTreeMap, path::Path};
+
+use clap::ValueEnum;
+
+use crate::gitinfo::{self, repoinfo::RepoInfo, status::Status};
+
+/// What `report --rollup` groups repositories by.
+#[derive(ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Rollup {
+    /// The host and owner (user, organization or group) of the remote
+    #[default]
+    Org,
+    /// The host of the remote
+    Host,
+}
+
+/// One group of repositories in a rollup report.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RollupRow {
+    /// The group, e.g. `github.com/bircni`, or `local` for repositories without a remote.
+    pub group: String,
+    /// Number of repositories in the group.
+    pub repos: usize,
+    /// Number of clean repositories in the group.
+    pub clean: usize,
+    /// Percentage of clean repositories, rounded.
+    pub clean_percent: usize,
+    /// Number of unpushed commits across the group.
+    pub unpushed_commits: usize,
+    /// The relative path of the repository most in need of attention, if any is not clean.
+    pub worst_offender: Option<String>,
+}
+
+/// Groups the repositories and sums up every group.
+///
+/// The worst offender of a group is the repository that is not clean with the most
+/// unpushed commits plus changed files.
+/// # Arguments
+/// * `repos` - The repositories to group. Their remote URL decides their group.
+/// * `by` - What to group by.
+/// # Returns
+/// One row per group, sorted by group name.
+pub fn rollup(repos: &[RepoInfo], by: Rollup) -> Vec<RollupRow> {
+    let mut groups: BTreeMap<String, Vec<&RepoInfo>> = BTreeMap::new();
+    for repo in repos {
+        groups.entry(group_of(repo, by)).or_default().push(repo);
+    }
+
+    groups
+        .into_iter()
+        .map(|(group, members)| {
+            let clean = members.iter().filter(|r| r.status == Status::Clean).count();
+            let worst_offender = members
+                .iter()
+                .filter(|r| r.status != Status::Clean)
+                .max_by_key(|r| r.ahead + r.status.changed_files())
+                .map(|r| r.repo_path.clone());
+            RollupRow {
+                repos: members.len(),
+                clean,
+                clean_percent: (clean * 100 + members.len() / 2) / members.len(),
+                unpushed_commits: members.iter().map(|r| r.ahead).sum(),
+                worst_offender,
+                group,
+            }
+        })
+        .collect()
+}
+
+/// Returns the group a repository is counted in.
+fn group_of(repo: &RepoInfo, by: Rollup) -> String {
+    let Some((host, owner)) = repo
+        .remote_url
+        .as_deref()
+        .and_then(gitinfo::remote_host_and_owner)
+    else {
+        return "local".to_owned();
+    };
+    match by {
+        Rollup::Org if !owner.is_empty() => format!("{host}/{owner}"),
+        Rollup::Org | Rollup::Host => host,
+    }
+}
+
+/// A checkout among the duplicates of a remote.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateCheckout {
+    /// The relative path of the repository.
+    pub repo_path: String,
+    /// The current branch.
+    pub branch: String,
+    /// The status of the repository.
+    pub status: Status,
+    /// Number of commits ahead of the upstream.
+    pub ahead: usize,
+    /// Number of commits behind the upstream.
+    pub behind: usize,
+    /// Number of stashes.
+    pub stash_count: usize,
+    /// True if the checkout has nothing that is not on the remote: it is clean, its branch
+    /// is pushed and it has no stashes, so deleting it loses nothing.
+    pub disposable: bool,
+}
+
+/// The repositories that are checkouts of the same remote.
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// The remote, as `host/owner/name` so that the SSH and HTTPS URL of a project match.
+    pub remote: String,
+    /// The checkouts, sorted by relative path.
+    pub checkouts: Vec<DuplicateCheckout>,
+}
+
+/// Groups the repositories that share a remote, for `--duplicates`.
+/// # Arguments
+/// * `repos` - The repositories, with their remote URL.
+/// # Returns
+/// One group per remote with more than one checkout, sorted by remote. Repositories without
+/// a remote are left out.
+pub fn duplicates(repos: &[RepoInfo]) -> Vec<DuplicateGroup> {
+    let mut groups: BTreeMap<String, Vec<&RepoInfo>> = BTreeMap::new();
+    for repo in repos {
+        if let Some(remote) = repo.remote_url.as_deref().and_then(remote_key) {
+            groups.entry(remote).or_default().push(repo);
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(remote, mut members)| {
+            members.sort_by_key(|r| r.repo_path.to_lowercase());
+            let checkouts = members
+                .into_iter()
+                .map(|r| DuplicateCheckout {
+                    repo_path: r.repo_path.clone(),
+                    branch: r.branch.clone(),
+                    status: r.status.clone(),
+                    ahead: r.ahead,
+                    behind: r.behind,
+                    stash_count: r.stash_count,
+                    disposable: r.status == Status::Clean
+                        && r.ahead == 0
+                        && r.stash_count == 0
+                        && !r.is_local_only,
+                })
+                .collect();
+            DuplicateGroup { remote, checkouts }
+        })
+        .collect()
+}
+
+/// Returns the `host/owner/name` of a remote URL, or the URL itself for a local path.
+fn remote_key(url: &str) -> Option<String> {
+    let Some((host, owner)) = gitinfo::remote_host_and_owner(url) else {
+        return Some(url.trim_end_matches('/').to_owned());
+    };
+    let name = gitinfo::repo_name_from_url(url)?;
+    Some(if owner.is_empty() {
+        format!("{}/{name}", host.to_lowercase())
+    } else {
+        format!("{}/{owner}/{name}", host.to_lowercase())
+    })
+}
+
+/// What `--group-by` splits the table into.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// The directory the repository lives in, relative to the scanned directory
+    ParentDir,
+    /// The scanned directory the repository was found in
+    Root,
+}
+
+/// The repositories of one `--group-by` section, with its subtotals.
+#[derive(Clone)]
+pub struct RepoGroup {
+    /// The group, e.g. the parent directory, `.` for the scanned directory itself.
+    pub name: String,
+    /// The repositories of the group, in the order they were given.
+    pub repos: Vec<RepoInfo>,
+    /// Number of repositories with uncommitted changes, in themselves or a submodule.
+    pub dirty: usize,
+    /// Number of repositories behind their upstream.
+    pub behind: usize,
+    /// Number of repositories ahead of their upstream.
+    pub ahead: usize,
+}
+
+/// Splits the repositories into the sections of `--group-by`.
+/// # Arguments
+/// * `repos` - The repositories to group, already sorted and filtered.
+/// * `by` - What to group them by.
+/// # Returns
+/// One group per distinct value, sorted by name. Every group keeps the order of `repos`.
+pub fn group(repos: &[RepoInfo], by: GroupBy) -> Vec<RepoGroup> {
+    let mut groups: BTreeMap<String, Vec<RepoInfo>> = BTreeMap::new();
+    for repo in repos {
+        let name = match by {
+            GroupBy::ParentDir => Path::new(&repo.repo_path)
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .map_or_else(|| ".".to_owned(), |parent| parent.display().to_string()),
+            GroupBy::Root => repo.root.display().to_string(),
+        };
+        groups.entry(name).or_default().push(repo.clone());
+    }
+
+    groups
+        .into_iter()
+        .map(|(name, repos)| RepoGroup {
+            dirty: repos
+                .iter()
+                .filter(|r| matches!(r.status, Status::Dirty(_) | Status::SubmoduleDirty))
+                .count(),
+            behind: repos.iter().filter(|r| r.behind > 0).count(),
+            ahead: repos.iter().filter(|r| r.ahead > 0).count(),
+            name,
+            repos,
+        })
+        .collect()
+}
@@ -0,0 +1,947 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsStr,
+    fmt::{self, Display, Formatter},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use parking_lot::Mutex;
+use rayon::iter::{IntoParallelRefIterator as _, ParallelIterator as _};
+use walkdir::WalkDir;
+
+use crate::{
+    auth::AuthFailures,
+    disk_usage::DiskUsageCache,
+    gitinfo::{
+        self,
+        repoinfo::{FailedRepo, InspectOptions, RepoInfo, RepoListing},
+    },
+    progress::Progress,
+    throttle::FetchLimits,
+    util::{self, GitPathExt as _, wildcard_match},
+};
+
+/// Finds the Git repositories below a directory and gathers their status.
+///
+/// This is the scanning logic of the CLI without any of its argument handling:
+///
+/// ```no_run
+/// use git_statuses::Scanner;
+///
+/// let result = Scanner::new("/path/to/checkouts")
+///     .depth(-1)
+///     .fetch(false)
+///     .exclude(["node_modules", "vendor"])
+///     .scan();
+/// for repo in &result.repos {
+///     println!("{}: {}", repo.repo_path, repo.status);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "The builder mirrors independent command line flags"
+)]
+pub struct Scanner {
+    dirs: Vec<PathBuf>,
+    depth: i32,
+    subdir: Option<String>,
+    exclude: Vec<String>,
+    repositories: Option<Vec<PathBuf>>,
+    remote: bool,
+    fetch: bool,
+    fetch_selector: FetchSelector,
+    fast_forward: bool,
+    prune: bool,
+    upstream_remote: Option<String>,
+    progress_interval: Option<Duration>,
+    max_failures: Option<FailureBudget>,
+    auth_failures: Option<PathBuf>,
+    retry_auth: bool,
+    fetch_jobs: Option<usize>,
+    host_limits: Vec<HostLimit>,
+    timing: bool,
+    count_commits: bool,
+    identify: bool,
+    activity: bool,
+    integrity: Option<IntegrityCheck>,
+    maintenance: bool,
+    disk_usage: bool,
+    disk_usage_cache: Option<PathBuf>,
+    show_ignored: bool,
+    hidden_files: bool,
+    lfs: bool,
+    forks: bool,
+    gone_branches: bool,
+    clones: bool,
+    user_email: bool,
+    verify_signatures: Option<usize>,
+    dedupe: bool,
+}
+
+/// The outcome of a scan.
+#[derive(Debug, Clone)]
+pub struct ScanResult<T = RepoInfo> {
+    /// The repositories found, sorted by their relative path.
+    pub repos: Vec<T>,
+    /// The repositories that could not be opened or inspected, sorted by name.
+    pub failed: Vec<FailedRepo>,
+}
+
+impl ScanResult {
+    /// Replaces what is known about the repository at `path` with the result of
+    /// scanning it again, keeping both lists sorted.
+    pub fn replace(&mut self, path: &Path, result: Self) {
+        let key = util::path_key(path);
+        self.repos.retain(|r| util::path_key(&r.path) != key);
+        self.failed.retain(|f| util::path_key(&f.path) != key);
+        self.repos.extend(result.repos);
+        self.repos.sort_by_key(|r| r.repo_path.to_lowercase());
+        self.failed.extend(result.failed);
+        self.failed.sort_by_key(|f| f.name.to_lowercase());
+    }
+}
+
+/// What happens during a scan, as reported to the callback of `Scanner::scan_with_events`.
+///
+/// For every scan, all `RepoFound` events come first, followed by one `RepoProcessed` or
+/// `RepoFailed` per repository (in no particular order) and a final `ScanFinished`.
+/// Repositories left out because the failure budget was exceeded get neither.
+#[derive(Debug, Clone)]
+pub enum ScanEvent<T = RepoInfo> {
+    /// A repository was found and is going to be processed.
+    RepoFound {
+        /// The path of the repository.
+        path: PathBuf,
+    },
+    /// A repository was processed.
+    RepoProcessed(T),
+    /// A repository could not be opened or processed.
+    RepoFailed(FailedRepo),
+    /// The scan is done.
+    ScanFinished {
+        /// Number of repositories processed.
+        repos: usize,
+        /// Number of repositories that failed.
+        failed: usize,
+    },
+}
+
+/// How many repositories may fail to process before a scan is given up, see `--max-failures`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureBudget {
+    /// An absolute number of repositories.
+    Count(usize),
+    /// A percentage of the repositories found.
+    Percent(u8),
+}
+
+impl Display for FailureBudget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Count(count) => write!(f, "{count}"),
+            Self::Percent(percent) => write!(f, "{percent}%"),
+        }
+    }
+}
+
+impl FailureBudget {
+    /// Returns the number of failures allowed in a scan of `total` repositories.
+    #[must_use]
+    pub fn limit(self, total: usize) -> usize {
+        match self {
+            Self::Count(count) => count,
+            Self::Percent(percent) => total * usize::from(percent) / 100,
+        }
+    }
+
+    /// Checks whether `failed` out of `total` repositories exceed the budget.
+    #[must_use]
+    pub fn is_exceeded(self, failed: usize, total: usize) -> bool {
+        failed > self.limit(total)
+    }
+}
+
+impl FromStr for FailureBudget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.strip_suffix('%').map_or_else(
+            || {
+                s.trim()
+                    .parse()
+                    .map(Self::Count)
+                    .map_err(|_| format!("`{s}` is neither a count nor a percentage"))
+            },
+            |percent| match percent.trim().parse::<u8>() {
+                Ok(percent) if percent <= 100 => Ok(Self::Percent(percent)),
+                _ => Err(format!("`{s}` is not a percentage between 0% and 100%")),
+            },
+        )
+    }
+}
+
+/// How thoroughly a scan checks every repository for corruption, see `--check-integrity`.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntegrityCheck {
+    /// Whether HEAD, the refs and the index can be read, and the objects they point to exist
+    Refs,
+    /// Also run `git fsck --no-full`, which checks the history and the loose objects
+    Fsck,
+}
+
+impl Display for IntegrityCheck {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Refs => "refs",
+            Self::Fsck => "fsck",
+        })
+    }
+}
+
+/// Which repositories a fetching scan updates, see `--fetch`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FetchSelector {
+    /// Every repository.
+    #[default]
+    All,
+    /// The repositories behind their upstream as of their last fetch, e.g. to update
+    /// them before pulling.
+    Behind,
+    /// The repositories whose remote, written as `host/owner/name`, matches the pattern,
+    /// in which `*` stands for anything.
+    Remote(String),
+}
+
+impl FetchSelector {
+    /// Checks whether `repo` is to be fetched.
+    #[must_use]
+    pub fn matches(&self, repo: &git2::Repository) -> bool {
+        match self {
+            Self::All => true,
+            Self::Behind => gitinfo::get_ahead_behind_and_local_status(repo, None).1 > 0,
+            Self::Remote(pattern) => gitinfo::get_remote_url(repo)
+                .and_then(|url| {
+                    let (host, owner) = gitinfo::remote_host_and_owner(&url)?;
+                    let name = gitinfo::repo_name_from_url(&url)?;
+                    Some(if owner.is_empty() {
+                        format!("{host}/{name}")
+                    } else {
+                        format!("{host}/{owner}/{name}")
+                    })
+                })
+                .is_some_and(|remote| wildcard_match(pattern, &remote)),
+        }
+    }
+}
+
+impl FromStr for FetchSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "all" => Ok(Self::All),
+            None if s == "behind" => Ok(Self::Behind),
+            Some(("remote", pattern)) if !pattern.is_empty() => {
+                Ok(Self::Remote(pattern.to_owned()))
+            }
+            Some(("label", _)) => Err(
+                "repositories have no labels to select by, use `behind` or `remote:PATTERN`"
+                    .to_owned(),
+            ),
+            _ => Err(format!(
+                "`{s}` is not a selector, use `all`, `behind` or `remote:PATTERN`"
+            )),
+        }
+    }
+}
+
+impl Display for FetchSelector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All => f.write_str("all"),
+            Self::Behind => f.write_str("behind"),
+            Self::Remote(pattern) => write!(f, "remote:{pattern}"),
+        }
+    }
+}
+
+/// How many fetches from the hosts matching a pattern may run at the same time, given to
+/// `--fetch-host-limit` as `HOST=N`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostLimit {
+    /// The host, in lowercase, e.g. `github.com`. `*` stands for any run of characters, as
+    /// in `*.acme.com`.
+    pub host: String,
+    /// How many fetches may run at the same time, at least 1.
+    pub limit: usize,
+}
+
+impl FromStr for HostLimit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((host, limit)) = s.rsplit_once('=') else {
+            return Err(format!(
+                "`{s}` is not a host limit, use e.g. `github.com=4`"
+            ));
+        };
+        match limit.trim().parse() {
+            Ok(limit) if limit > 0 && !host.is_empty() => Ok(Self {
+                host: host.to_lowercase(),
+                limit,
+            }),
+            _ => Err(format!(
+                "`{s}` is not a host limit, use a host and a number of at least 1, e.g. `github.com=4`"
+            )),
+        }
+    }
+}
+
+impl Display for HostLimit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.host, self.limit)
+    }
+}
+
+impl Scanner {
+    /// Creates a scanner for `dir` that looks at its direct subdirectories only and
+    /// neither fetches nor reads remote URLs.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dirs: vec![dir.into()],
+            depth: 1,
+            subdir: None,
+            exclude: Vec::new(),
+            repositories: None,
+            remote: false,
+            fetch: false,
+            fetch_selector: FetchSelector::All,
+            fast_forward: false,
+            prune: false,
+            upstream_remote: None,
+            progress_interval: None,
+            max_failures: None,
+            auth_failures: None,
+            retry_auth: false,
+            fetch_jobs: None,
+            host_limits: Vec::new(),
+            timing: false,
+            count_commits: true,
+            identify: true,
+            activity: false,
+            integrity: None,
+            maintenance: false,
+            disk_usage: false,
+            disk_usage_cache: None,
+            show_ignored: false,
+            hidden_files: false,
+            lfs: false,
+            forks: false,
+            gone_branches: false,
+            clones: false,
+            user_email: false,
+            verify_signatures: None,
+            dedupe: true,
+        }
+    }
+
+    /// Also scans `dir`, next to the directory given to `new`. With more than one
+    /// directory, the relative path of every repository starts with the name of the
+    /// directory it was found in, or its full path where two of them share a name.
+    #[must_use]
+    pub fn root(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dirs.push(dir.into());
+        self
+    }
+
+    /// Sets how deep to look for repositories. 1 only looks at `dir` and its direct
+    /// subdirectories, a negative value has no limit.
+    #[must_use]
+    pub const fn depth(mut self, depth: i32) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Also looks for a repository in `subdir` of every directory, for layouts like
+    /// `repo-name/checkout`.
+    #[must_use]
+    pub fn subdir(mut self, subdir: impl Into<String>) -> Self {
+        self.subdir = Some(subdir.into());
+        self
+    }
+
+    /// Skips directories with any of the given names, and everything below them.
+    #[must_use]
+    pub fn exclude(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.exclude.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Scans exactly the given repositories instead of walking the directory, which is then
+    /// only used for the relative paths. Depth, subdir and excludes do not apply. A
+    /// repository below none of the directories is shown relative to the first one.
+    #[must_use]
+    pub fn repositories(mut self, paths: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        self.repositories = Some(paths.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets whether to read the remote URL of every repository.
+    #[must_use]
+    pub const fn remote(mut self, remote: bool) -> Self {
+        self.remote = remote;
+        self
+    }
+
+    /// Sets whether to fetch every repository before gathering its status.
+    #[must_use]
+    pub const fn fetch(mut self, fetch: bool) -> Self {
+        self.fetch = fetch;
+        self
+    }
+
+    /// Restricts fetching and fast-forwarding to the repositories `selector` matches. The
+    /// others are scanned as they are.
+    #[must_use]
+    pub fn fetch_only(mut self, selector: FetchSelector) -> Self {
+        self.fetch_selector = selector;
+        self
+    }
+
+    /// Sets whether to fetch and fast-forward every repository before gathering its status.
+    #[must_use]
+    pub const fn fast_forward(mut self, fast_forward: bool) -> Self {
+        self.fast_forward = fast_forward;
+        self
+    }
+
+    /// Sets whether fetching removes remote-tracking branches that were deleted on the
+    /// remote, like `git fetch --prune`.
+    #[must_use]
+    pub const fn prune(mut self, prune: bool) -> Self {
+        self.prune = prune;
+        self
+    }
+
+    /// Counts ahead/behind against the branch of the same name on `remote`, where it
+    /// exists, instead of the upstream branch.
+    #[must_use]
+    pub fn upstream_remote(mut self, remote: impl Into<String>) -> Self {
+        self.upstream_remote = Some(remote.into());
+        self
+    }
+
+    /// Prints a progress line to stderr every `interval` while the scan runs.
+    #[must_use]
+    pub const fn progress_interval(mut self, interval: Duration) -> Self {
+        self.progress_interval = Some(interval);
+        self
+    }
+
+    /// Stops opening further repositories once more of them failed than `budget` allows.
+    #[must_use]
+    pub const fn max_failures(mut self, budget: FailureBudget) -> Self {
+        self.max_failures = Some(budget);
+        self
+    }
+
+    /// Remembers the hosts whose authentication failed while fetching in `path`, and skips
+    /// fetching from them for a day, in this and later scans. Without it, failures are only
+    /// remembered for the current scan.
+    #[must_use]
+    pub fn auth_failures(mut self, path: impl Into<PathBuf>) -> Self {
+        self.auth_failures = Some(path.into());
+        self
+    }
+
+    /// Sets whether to fetch from hosts whose authentication failed recently anyway.
+    #[must_use]
+    pub const fn retry_auth(mut self, retry: bool) -> Self {
+        self.retry_auth = retry;
+        self
+    }
+
+    /// Lets at most `jobs` fetches run at the same time, whatever the number of threads
+    /// the repositories are scanned on.
+    #[must_use]
+    pub const fn fetch_jobs(mut self, jobs: usize) -> Self {
+        self.fetch_jobs = Some(jobs);
+        self
+    }
+
+    /// Lets at most `limit.limit` fetches from the hosts matching `limit.host` run at the
+    /// same time. When several limits match a host, the first one applies.
+    #[must_use]
+    pub fn fetch_host_limit(mut self, limit: HostLimit) -> Self {
+        self.host_limits.push(limit);
+        self
+    }
+
+    /// Sets whether to walk the history of every repository to count the commits of its
+    /// current branch, which is the slowest part of scanning large repositories. Without
+    /// it, `RepoInfo::commits` is `None`.
+    #[must_use]
+    pub const fn count_commits(mut self, count: bool) -> Self {
+        self.count_commits = count;
+        self
+    }
+
+    /// Sets whether to walk the history of every repository down to its root commit for
+    /// `RepoInfo::identity`, which recognizes a moved repository when diffing two scans.
+    /// Without it, `RepoInfo::identity` is `None`.
+    #[must_use]
+    pub const fn identify(mut self, identify: bool) -> Self {
+        self.identify = identify;
+        self
+    }
+
+    /// Sets whether to walk the history of every repository for its commit activity: the
+    /// dates of its first and last commits, its commits of the last 30 days and its number
+    /// of authors, in `RepoInfo::activity`.
+    #[must_use]
+    pub const fn activity(mut self, activity: bool) -> Self {
+        self.activity = activity;
+        self
+    }
+
+    /// Sets how to check every repository for corruption, see `IntegrityCheck`. A corrupt
+    /// repository has the status `Status::Corrupt`, with the problems found in
+    /// `RepoInfo::integrity_errors`. `None`, the default, does not check.
+    #[must_use]
+    pub const fn check_integrity(mut self, check: Option<IntegrityCheck>) -> Self {
+        self.integrity = check;
+        self
+    }
+
+    /// Sets whether to look at the object store of every repository: its loose objects, its
+    /// packfiles and the size of its git directory, in `RepoInfo::housekeeping`.
+    #[must_use]
+    pub const fn maintenance(mut self, maintenance: bool) -> Self {
+        self.maintenance = maintenance;
+        self
+    }
+
+    /// Sets whether to measure how much disk space the working directory and the git
+    /// directory of every repository take, in `RepoInfo::disk_usage`. This reads the
+    /// metadata of every file, including ignored ones.
+    #[must_use]
+    pub const fn disk_usage(mut self, disk_usage: bool) -> Self {
+        self.disk_usage = disk_usage;
+        self
+    }
+
+    /// Remembers the disk usages measured in `path`, and reuses them for an hour in later
+    /// scans. Without it, every scan with `disk_usage` measures every repository.
+    #[must_use]
+    pub fn disk_usage_cache(mut self, path: impl Into<PathBuf>) -> Self {
+        self.disk_usage_cache = Some(path.into());
+        self
+    }
+
+    /// Sets whether to count the ignored files and directories of every repository, in
+    /// `RepoInfo::ignored`.
+    #[must_use]
+    pub const fn show_ignored(mut self, show_ignored: bool) -> Self {
+        self.show_ignored = show_ignored;
+        self
+    }
+
+    /// Sets whether to count the files of every repository whose changes `git status` does
+    /// not show, as they are marked assume-unchanged or skip-worktree, in
+    /// `RepoInfo::hidden_files`.
+    #[must_use]
+    pub const fn hidden_files(mut self, hidden_files: bool) -> Self {
+        self.hidden_files = hidden_files;
+        self
+    }
+
+    /// Sets whether to look for the Git LFS objects of every repository, and which of them
+    /// are only pointers, in `RepoInfo::lfs`.
+    #[must_use]
+    pub const fn lfs(mut self, lfs: bool) -> Self {
+        self.lfs = lfs;
+        self
+    }
+
+    /// Sets whether to count how many commits the default branch of `origin` is behind that
+    /// of `upstream` in every repository with both remotes, in `RepoInfo::fork_behind`.
+    #[must_use]
+    pub const fn forks(mut self, forks: bool) -> Self {
+        self.forks = forks;
+        self
+    }
+
+    /// Sets whether to count the local branches of every repository whose upstream branch
+    /// is gone from the remote, in `RepoInfo::gone_branches`.
+    #[must_use]
+    pub const fn gone_branches(mut self, gone_branches: bool) -> Self {
+        self.gone_branches = gone_branches;
+        self
+    }
+
+    /// Sets whether to find out which repositories are partial clones, in
+    /// `RepoInfo::is_partial`. Shallow clones are always recognized.
+    #[must_use]
+    pub const fn clones(mut self, clones: bool) -> Self {
+        self.clones = clones;
+        self
+    }
+
+    /// Sets whether to read the `user.email` every repository commits with, in
+    /// `RepoInfo::user_email`.
+    #[must_use]
+    pub const fn user_email(mut self, user_email: bool) -> Self {
+        self.user_email = user_email;
+        self
+    }
+
+    /// Sets how many commits of every repository, starting at HEAD, to check the
+    /// signatures of in `RepoInfo::signatures`, `None` to not check them.
+    #[must_use]
+    pub const fn verify_signatures(mut self, commits: Option<usize>) -> Self {
+        self.verify_signatures = commits;
+        self
+    }
+
+    /// Sets whether a repository reached through several paths, e.g. through a symlink or
+    /// a bind mount, is scanned once, under the shortest of them (the default). Without it,
+    /// it is scanned once per path, and each is shown under the path it was found at.
+    #[must_use]
+    pub const fn dedupe(mut self, dedupe: bool) -> Self {
+        self.dedupe = dedupe;
+        self
+    }
+
+    /// Sets whether to record how long the steps of the scan take for every repository, in
+    /// `RepoInfo::timing`.
+    #[must_use]
+    pub const fn timing(mut self, timing: bool) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// Finds the repositories and collects their status information.
+    ///
+    /// The repositories are collected in parallel and sorted afterwards, so every
+    /// consumer sees the same, reproducible order.
+    #[must_use]
+    pub fn scan(&self) -> ScanResult {
+        self.scan_with_events(|_| {})
+    }
+
+    /// Scans like `scan`, and reports the progress of the scan to `on_event` while it runs.
+    ///
+    /// `on_event` is called from the worker threads, so it has to be cheap or hand the
+    /// events off, e.g. to an `mpsc::Sender`.
+    pub fn scan_with_events(&self, on_event: impl Fn(ScanEvent) + Sync) -> ScanResult {
+        let fetching = self.fetch || self.fast_forward;
+        let auth = AuthFailures::load(
+            self.auth_failures.as_deref().filter(|_| fetching),
+            self.retry_auth,
+        );
+        let limits = FetchLimits::new(self.fetch_jobs, self.host_limits.clone());
+        let disk_usage = self
+            .disk_usage
+            .then(|| DiskUsageCache::load(self.disk_usage_cache.as_deref()));
+        let mut result = self.scan_with(on_event, |git_repo, found, root, repo_name, opened_in| {
+            let selected = fetching && self.fetch_selector.matches(git_repo);
+            let options = InspectOptions {
+                show_remote: self.remote,
+                fetch: self.fetch && selected,
+                merge: self.fast_forward && selected,
+                prune: self.prune,
+                upstream_remote: self.upstream_remote.as_deref(),
+                auth: &auth,
+                limits: &limits,
+                count_commits: self.count_commits,
+                identify: self.identify,
+                opened_in: self.timing.then_some(opened_in),
+                activity: self.activity,
+                integrity: self.integrity,
+                maintenance: self.maintenance,
+                disk_usage: disk_usage.as_ref(),
+                show_ignored: self.show_ignored,
+                hidden_files: self.hidden_files,
+                lfs: self.lfs,
+                forks: self.forks,
+                gone_branches: self.gone_branches,
+                clones: self.clones,
+                user_email: self.user_email,
+                verify_signatures: self.verify_signatures,
+            };
+            let mut info = RepoInfo::inspect(git_repo, repo_name, root, &options)?;
+            if let Some(repo_path) = self.alias_path(found, root) {
+                info.repo_path = repo_path;
+            }
+            info.repo_path = self.qualify(&info.repo_path, found, root);
+            Ok(info)
+        });
+        auth.finish();
+        if let Some(cache) = disk_usage {
+            cache.finish();
+        }
+        result.repos.sort_by_key(|r| r.repo_path.to_lowercase());
+        result
+    }
+
+    /// Finds the repositories like `scan`, but only opens them instead of computing their
+    /// status. This is what `--list-only` shows.
+    #[must_use]
+    pub fn list(&self) -> ScanResult<RepoListing> {
+        let mut result = self.scan_with(
+            |_| {},
+            |git_repo, found, root, repo_name, _| {
+                let mut listing = RepoListing::new(git_repo, repo_name, self.remote, root);
+                if let Some(repo_path) = self.alias_path(found, root) {
+                    listing.repo_path = repo_path;
+                }
+                listing.repo_path = self.qualify(&listing.repo_path, found, root);
+                Ok(listing)
+            },
+        );
+        result.repos.sort_by_key(|r| r.repo_path.to_lowercase());
+        result
+    }
+
+    /// Walks the directory and runs `inspect` on every Git repository found, in parallel.
+    ///
+    /// # Arguments
+    /// * `on_event` - Is told about every step of the scan, see `ScanEvent`.
+    /// * `inspect` - Gathers the information about a single repository, given the opened
+    ///   repository, the path it was found at, the scanned directory it was found in, the
+    ///   name of the directory it was found in and how long opening it took.
+    ///
+    /// # Returns
+    /// The successfully inspected repositories, in no particular order, and the
+    /// repositories that could not be opened or inspected, sorted by name.
+    fn scan_with<T: Clone + Send>(
+        &self,
+        on_event: impl Fn(ScanEvent<T>) + Sync,
+        inspect: impl Fn(&mut git2::Repository, &Path, &Path, &str, Duration) -> anyhow::Result<T>
+        + Sync,
+    ) -> ScanResult<T> {
+        let candidates = self.repository_dirs();
+        for (path, ..) in &candidates {
+            on_event(ScanEvent::RepoFound { path: path.clone() });
+        }
+
+        // The workers only borrow these, so they can be taken apart again once all of
+        // them are done.
+        let repos: Mutex<Vec<T>> = Mutex::new(Vec::new());
+        let failed_repos: Mutex<Vec<FailedRepo>> = Mutex::new(Vec::new());
+        let progress = Progress::default();
+        let max_failures = self.max_failures.map(|b| b.limit(candidates.len()));
+
+        let work = || {
+            candidates
+                .par_iter()
+                .for_each(|(path_buf, repo_name, root)| {
+                    // Once the failure budget is spent the run is going to be given up, so
+                    // there is no point in opening (let alone fetching) any more repositories.
+                    if max_failures.is_some_and(|limit| progress.failed() > limit) {
+                        return;
+                    }
+                    let opening = Instant::now();
+                    let inspected = match git2::Repository::open(path_buf.as_path()) {
+                        Ok(mut git_repo) => {
+                            inspect(&mut git_repo, path_buf, root, repo_name, opening.elapsed())
+                                .map_err(|e| (repo_name.clone(), format!("{e:#}")))
+                        }
+                        Err(e) => {
+                            log::debug!(
+                                "Failed to open repository at {}: {}",
+                                path_buf.display(),
+                                e
+                            );
+                            Err((path_buf.dir_name(), e.message().to_owned()))
+                        }
+                    };
+                    progress.processed(inspected.is_err());
+                    match inspected {
+                        Ok(repo) => {
+                            on_event(ScanEvent::RepoProcessed(repo.clone()));
+                            repos.lock().push(repo);
+                        }
+                        Err((name, error)) => {
+                            let failed = FailedRepo {
+                                name,
+                                path: path_buf.clone(),
+                                error,
+                            };
+                            on_event(ScanEvent::RepoFailed(failed.clone()));
+                            failed_repos.lock().push(failed);
+                        }
+                    }
+                });
+        };
+        match self.progress_interval {
+            Some(interval) => progress.report_while("fetched", candidates.len(), interval, work),
+            None => work(),
+        }
+
+        let repos = repos.into_inner();
+        let mut failed = failed_repos.into_inner();
+        failed.sort_by_key(|r| r.name.to_lowercase());
+        on_event(ScanEvent::ScanFinished {
+            repos: repos.len(),
+            failed: failed.len(),
+        });
+        ScanResult { repos, failed }
+    }
+
+    /// Finds the repositories to scan: the given ones, or those below the directories.
+    ///
+    /// # Returns
+    /// The path of every repository to open, with the name of the directory it was found
+    /// in and the scanned directory it was found below. With a subdir, the former is the
+    /// parent of the repository.
+    fn repository_dirs(&self) -> Vec<(PathBuf, String, PathBuf)> {
+        let candidates = self.repositories.as_ref().map_or_else(
+            || {
+                self.dirs
+                    .iter()
+                    .flat_map(|dir| {
+                        self.walk(dir)
+                            .into_iter()
+                            .map(|(path, name)| (path, name, dir.clone()))
+                    })
+                    .collect()
+            },
+            |paths| {
+                paths
+                    .iter()
+                    .map(|path| (path.clone(), path.dir_name(), self.root_of(path).clone()))
+                    .collect()
+            },
+        );
+
+        if !self.dedupe {
+            return candidates;
+        }
+        // A repository reached twice, through a symlink, a `..` or (on file systems that
+        // ignore case) a differently cased path, is listed once, under the shortest path
+        // (the one found first of equally long ones), whatever order the walk found them in.
+        let mut shortest: HashMap<String, usize> = HashMap::new();
+        for (index, (path, ..)) in candidates.iter().enumerate() {
+            shortest
+                .entry(util::path_key(path))
+                .and_modify(|kept| {
+                    if path.as_os_str().len() < candidates[*kept].0.as_os_str().len() {
+                        *kept = index;
+                    }
+                })
+                .or_insert(index);
+        }
+        let kept: HashSet<usize> = shortest.into_values().collect();
+        candidates
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, candidate)| kept.contains(&index).then_some(candidate))
+            .collect()
+    }
+
+    /// Returns the scanned directory a given repository counts as found in: the first one
+    /// it is below, or the first one if it is below none of them.
+    fn root_of(&self, path: &Path) -> &PathBuf {
+        let path = util::normalize_path(path);
+        self.dirs
+            .iter()
+            .find(|dir| util::strip_path_prefix(&path, &util::normalize_path(dir)).is_some())
+            .unwrap_or(&self.dirs[0])
+    }
+
+    /// Returns the relative path to show a repository under when it is not deduplicated:
+    /// the one it was found at, which for a symlink is not the one it resolves to. `None`
+    /// when deduplicating, or if the path is not below the directory.
+    fn alias_path(&self, found: &Path, root: &Path) -> Option<String> {
+        if self.dedupe {
+            return None;
+        }
+        let relative = found.strip_prefix(root).ok()?;
+        (!relative.as_os_str().is_empty()).then(|| relative.display().to_string())
+    }
+
+    /// Prefixes the path of a repository relative to `root` with the name of `root`, when
+    /// scanning more than one directory, so that the repositories of each can be told
+    /// apart. Where two scanned directories share a name, the full path is used instead.
+    fn qualify(&self, repo_path: &str, found: &Path, root: &Path) -> String {
+        if self.dirs.len() < 2 {
+            return repo_path.to_owned();
+        }
+        let name_of = |dir: &Path| util::normalize_path(dir).dir_name();
+        let name = name_of(root);
+        let label = if self
+            .dirs
+            .iter()
+            .filter(|dir| name_of(dir) == name)
+            .nth(1)
+            .is_some()
+        {
+            root.display().to_string()
+        } else {
+            name
+        };
+        // A scanned directory that is a repository itself is shown as just its label.
+        if util::path_key(found) == util::path_key(root) {
+            label
+        } else {
+            format!("{label}/{repo_path}")
+        }
+    }
+
+    /// Walks a directory (recursively if requested) for Git repositories, see
+    /// `repository_dirs`.
+    #[expect(
+        clippy::cast_sign_loss,
+        reason = "We check i32 to be non-negative, so casting to usize is safe"
+    )]
+    fn walk(&self, dir: &Path) -> Vec<(PathBuf, String)> {
+        let walker = {
+            let mut walk = WalkDir::new(dir).min_depth(0).follow_links(false);
+
+            // Any negative depth means "no limit"; `-1` is just the documented spelling.
+            // A depth of 0 would find nothing at all, so it is treated like 1.
+            if self.depth >= 0 {
+                walk = walk.max_depth(self.depth.max(1) as usize);
+            }
+
+            // Never descend into a repository's own git directory. Nothing inside it is a
+            // repository the user asked about - it holds git's bookkeeping, including the
+            // `worktrees/<name>` metadata directories - and on a deep scan it is a lot of
+            // entries to walk and stat for nothing.
+            walk.into_iter()
+                .filter_entry(|e| {
+                    e.depth() == 0
+                        || (e.file_name() != OsStr::new(".git")
+                            && !self
+                                .exclude
+                                .iter()
+                                .any(|name| e.file_name() == OsStr::new(name)))
+                })
+                .filter_map(Result::ok)
+                .collect::<Vec<_>>()
+        };
+
+        walker
+            .par_iter()
+            .filter_map(|entry| {
+                let orig_path = entry.path();
+                let path_buf = if orig_path.is_git_directory() || orig_path.is_git_worktree() {
+                    orig_path.to_path_buf()
+                } else {
+                    // Without a subdir, a path that is not a git directory is skipped, and
+                    // so is one whose subdir does not exist.
+                    let subdir_path = orig_path.join(self.subdir.as_ref()?);
+                    if !subdir_path.is_git_directory() && !subdir_path.is_git_worktree() {
+                        return None;
+                    }
+                    subdir_path
+                };
+                Some((path_buf, orig_path.dir_name()))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    env, fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+
+use crate::cli::Args;
+
+/// The launchd label, also used as the file name of the agent.
+const LAUNCHD_LABEL: &str = "io.github.bircni.git-statuses";
+
+/// The file name of the systemd user unit.
+const SYSTEMD_UNIT: &str = "git-statuses.service";
+
+/// Writes a user-level service file that runs `git-statuses daemon` with the scan options
+/// of `args`: a launchd agent on macOS and a systemd user unit everywhere else.
+///
+/// # Arguments
+/// * `args` - The scan options the daemon should use.
+/// * `interval` - Seconds between two scans of the daemon.
+/// * `print` - Write the service file to `out` instead of installing it.
+/// * `out` - Where to print the service file to.
+///
+/// # Errors
+/// Returns an error on platforms without a supported service manager, if the scanned
+/// directory does not exist, or if the file cannot be written.
+pub fn install(
+    args: &Args,
+    interval: u64,
+    print: bool,
+    out: &mut impl Write,
+) -> anyhow::Result<()> {
+    if cfg!(not(unix)) {
+        anyhow::bail!("The daemon is only supported on unix platforms");
+    }
+
+    let command = daemon_command(args, interval)?;
+    let launchd = cfg!(target_os = "macos");
+    let contents = if launchd {
+        launchd_agent(&command)
+    } else {
+        systemd_unit(&command)
+    };
+
+    if print {
+        write!(out, "{contents}")?;
+        return Ok(());
+    }
+
+    let path = if launchd {
+        home_dir()?
+            .join("Library/LaunchAgents")
+            .join(format!("{LAUNCHD_LABEL}.plist"))
+    } else {
+        systemd_user_dir()?.join(SYSTEMD_UNIT)
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    fs::write(&path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+
+    log::info!("Wrote {}", path.display());
+    if launchd {
+        log::info!("Start it with `launchctl load -w {}`", path.display());
+    } else {
+        log::info!("Start it with `systemctl --user enable --now {SYSTEMD_UNIT}`");
+    }
+    Ok(())
+}
+
+/// Builds the command line the service runs.
+///
+/// Only the options that influence the scan are carried over, see `Args::scan_arguments`.
+/// The directories are made absolute and the socket is fixed, because a service neither
+/// starts in the current directory nor necessarily sees the same environment.
+///
+/// # Errors
+/// Returns an error if the executable or a scanned directory cannot be resolved.
+fn daemon_command(args: &Args, interval: u64) -> anyhow::Result<Vec<String>> {
+    let exe = env::current_exe().context("Failed to locate the git-statuses executable")?;
+    let mut command = vec![exe.display().to_string()];
+    for dir in &args.dirs {
+        let dir = dir
+            .canonicalize()
+            .with_context(|| format!("Failed to resolve {}", dir.display()))?;
+        command.push(dir.display().to_string());
+    }
+    command.extend(args.scan_arguments());
+    command.push(format!("--socket={}", args.socket_path().display()));
+    command.push("daemon".to_owned());
+    command.push(format!("--interval={interval}"));
+    Ok(command)
+}
+
+/// Renders a systemd user unit running `command`.
+pub fn systemd_unit(command: &[String]) -> String {
+    let exec_start = command
+        .iter()
+        .map(|arg| systemd_quote(arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        "[Unit]\n\
+         Description=git-statuses daemon\n\
+         \n\
+         [Service]\n\
+         ExecStart={exec_start}\n\
+         Restart=on-failure\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n"
+    )
+}
+
+/// Renders a launchd agent running `command`.
+pub fn launchd_agent(command: &[String]) -> String {
+    let arguments = command
+        .iter()
+        .map(|arg| format!("        <string>{}</string>", xml_escape(arg)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n\
+         \x20   <key>Label</key>\n\
+         \x20   <string>{LAUNCHD_LABEL}</string>\n\
+         \x20   <key>ProgramArguments</key>\n\
+         \x20   <array>\n\
+         {arguments}\n\
+         \x20   </array>\n\
+         \x20   <key>RunAtLoad</key>\n\
+         \x20   <true/>\n\
+         \x20   <key>KeepAlive</key>\n\
+         \x20   <true/>\n\
+         </dict>\n\
+         </plist>\n"
+    )
+}
+
+/// Quotes an argument for a systemd `ExecStart=` line.
+///
+/// `%` starts a specifier in unit files, so it has to be doubled even inside quotes.
+fn systemd_quote(arg: &str) -> String {
+    let escaped = arg.replace('%', "%%");
+    if escaped
+        .chars()
+        .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '\\' | ';'))
+    {
+        format!("\"{}\"", escaped.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        escaped
+    }
+}
+
+/// Escapes the characters that are not allowed verbatim in XML text.
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Returns the user's home directory.
+fn home_dir() -> anyhow::Result<PathBuf> {
+    env::var_os("HOME")
+        .map(PathBuf::from)
+        .context("HOME is not set")
+}
+
+/// Returns the directory systemd loads user units from.
+fn systemd_user_dir() -> anyhow::Result<PathBuf> {
+    let config = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => home_dir()?.join(".config"),
+    };
+    Ok(Path::new(&config).join("systemd/user"))
+}
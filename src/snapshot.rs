@@ -0,0 +1,54 @@
+use std::{fs, path::Path};
+
+use anyhow::Context as _;
+
+use crate::{
+    gitinfo::repoinfo::{FailedRepo, RepoInfo},
+    printer,
+};
+
+/// A scan result as `--json` prints it, which is also what `--snapshot` stores and the
+/// daemon hands out.
+#[derive(serde::Deserialize)]
+pub struct Snapshot {
+    /// The repositories scanned.
+    pub repositories: Vec<RepoInfo>,
+    /// The repositories that could not be processed.
+    #[cfg_attr(
+        not(feature = "daemon"),
+        expect(
+            dead_code,
+            reason = "Only `--from-daemon` reports the failed repositories of a snapshot"
+        )
+    )]
+    pub failed: Vec<FailedRepo>,
+}
+
+impl Snapshot {
+    /// Parses a snapshot from its JSON representation.
+    ///
+    /// # Errors
+    /// Returns an error if `json` is not a scan result.
+    pub fn parse(json: &str) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Reads a snapshot written by `write`.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or is not a snapshot.
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&json).with_context(|| format!("{} is not a snapshot", path.display()))
+    }
+
+    /// Writes a scan result to `path`, for a later `--diff`.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written.
+    pub fn write(path: &Path, repos: &[RepoInfo], failed: &[FailedRepo]) -> anyhow::Result<()> {
+        fs::write(path, printer::json_value(repos, failed).to_string())
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
@@ -0,0 +1,39 @@
+use minijinja::{Environment, context};
+
+use crate::{
+    gitinfo::repoinfo::{FailedRepo, RepoInfo},
+    printer::Summary,
+};
+
+/// Renders a scan through a Jinja template.
+///
+/// The template sees `repositories` (every field of `RepoInfo`, as in the JSON output),
+/// `failed` (the repositories that could not be processed) and `summary` (the counts of
+/// `--summary`). Templates whose name ends in `.html`, `.xml` or `.json` have their
+/// values escaped for that format.
+/// # Arguments
+/// * `name` - The name of the template, used for escaping and in error messages.
+/// * `source` - The template.
+/// * `repos` - List of repositories to render.
+/// * `failed_repos` - List of repositories that failed to process.
+/// # Returns
+/// The rendered template.
+///
+/// # Errors
+/// Returns an error if the template has a syntax error or fails to render.
+pub fn render(
+    name: &str,
+    source: &str,
+    repos: &[RepoInfo],
+    failed_repos: &[FailedRepo],
+) -> anyhow::Result<String> {
+    let mut env = Environment::new();
+    env.set_keep_trailing_newline(true);
+    env.add_template(name, source)?;
+    let rendered = env.get_template(name)?.render(context! {
+        repositories => repos,
+        failed => failed_repos,
+        summary => Summary::new(repos, failed_repos.len()),
+    })?;
+    Ok(rendered)
+}
@@ -0,0 +1,86 @@
+use std::{collections::BTreeMap, fs, time::SystemTime};
+
+use git2::Repository;
+
+use crate::{auth::AuthFailures, throttle::FetchLimits, util::unix_seconds};
+
+fn repo_with_remote(url: &str) -> (tempfile::TempDir, Repository) {
+    let tmp = tempfile::tempdir().unwrap();
+    let repo = Repository::init(tmp.path()).unwrap();
+    repo.remote("origin", url).unwrap();
+    (tmp, repo)
+}
+
+#[test]
+fn test_recently_failed_host_is_skipped() {
+    let cache = tempfile::tempdir().unwrap();
+    let path = cache.path().join("auth-failures.json");
+    let now = unix_seconds(SystemTime::now());
+    fs::write(&path, format!(r#"{{"git.example.invalid": {now}}}"#)).unwrap();
+    let (_tmp, repo) = repo_with_remote("https://git.example.invalid/acme/tool.git");
+
+    let auth = AuthFailures::load(Some(&path), false);
+    assert!(
+        auth.fetch(&repo, false, &FetchLimits::default()).is_ok(),
+        "a skipped fetch is no error"
+    );
+    assert!(
+        auth.fetch(&repo, false, &FetchLimits::default()).is_ok(),
+        "a skipped fetch is no error"
+    );
+    assert_eq!(
+        auth.skipped(),
+        BTreeMap::from([("git.example.invalid".to_owned(), 2)]),
+        "both fetches should be skipped"
+    );
+}
+
+/// Nothing listens on port 1, so a fetch from there fails at once, without authenticating.
+const UNREACHABLE: &str = "https://127.0.0.1:1/acme/tool.git";
+
+#[test]
+fn test_old_failure_is_retried() {
+    let cache = tempfile::tempdir().unwrap();
+    let path = cache.path().join("auth-failures.json");
+    fs::write(&path, r#"{"127.0.0.1": 0}"#).unwrap();
+    let (_tmp, repo) = repo_with_remote(UNREACHABLE);
+
+    let auth = AuthFailures::load(Some(&path), false);
+    assert!(
+        auth.fetch(&repo, false, &FetchLimits::default()).is_err(),
+        "the fetch should be attempted"
+    );
+    assert!(auth.skipped().is_empty(), "nothing should be skipped");
+}
+
+#[test]
+fn test_retry_fetches_recently_failed_host() {
+    let cache = tempfile::tempdir().unwrap();
+    let path = cache.path().join("auth-failures.json");
+    let now = unix_seconds(SystemTime::now());
+    fs::write(&path, format!(r#"{{"127.0.0.1": {now}}}"#)).unwrap();
+    let (_tmp, repo) = repo_with_remote(UNREACHABLE);
+
+    let auth = AuthFailures::load(Some(&path), true);
+    assert!(
+        auth.fetch(&repo, false, &FetchLimits::default()).is_err(),
+        "the fetch should be attempted"
+    );
+    assert!(auth.skipped().is_empty(), "nothing should be skipped");
+}
+
+#[test]
+fn test_corrupt_cache_is_ignored() {
+    let cache = tempfile::tempdir().unwrap();
+    let path = cache.path().join("auth-failures.json");
+    fs::write(&path, "not json").unwrap();
+
+    let auth = AuthFailures::load(Some(&path), false);
+    assert!(auth.skipped().is_empty(), "nothing should be skipped");
+    auth.finish();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "not json",
+        "an unchanged cache should not be rewritten"
+    );
+}
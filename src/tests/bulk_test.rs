@@ -0,0 +1,329 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use git2::Repository;
+use tempfile::TempDir;
+
+use crate::{
+    bulk::{self, BulkResult, Outcome},
+    gitinfo::{self, repoinfo::RepoInfo, status::Status},
+};
+
+fn git(dir: &Path, args: &[&str]) {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn commit(dir: &Path, file: &str) {
+    fs::write(dir.join(file), file).unwrap();
+    git(dir, &["add", file]);
+    git(
+        dir,
+        &[
+            "-c",
+            "user.name=Test",
+            "-c",
+            "user.email=test@example.com",
+            "commit",
+            "-q",
+            "-m",
+            file,
+        ],
+    );
+}
+
+fn repo(path: &Path, status: Status, ahead: usize, behind: usize) -> RepoInfo {
+    RepoInfo {
+        name: "tool".to_owned(),
+        branch: "main".to_owned(),
+        ahead,
+        behind,
+        commits: Some(1),
+        status,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
+        has_unpushed: ahead > 0,
+        remote_url: None,
+        path: path.to_path_buf(),
+        stash_count: 0,
+        is_local_only: false,
+        fast_forwarded: false,
+        repo_path: "tool".to_owned(),
+        is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
+    }
+}
+
+#[test]
+fn test_pull_fast_forwards_repositories_behind() {
+    let temp = TempDir::new().unwrap();
+    let origin = temp.path().join("origin");
+    fs::create_dir_all(&origin).unwrap();
+    git(&origin, &["init", "-q", "-b", "main"]);
+    commit(&origin, "one");
+    git(temp.path(), &["clone", "-q", "origin", "clone"]);
+    let clone = temp.path().join("clone");
+    commit(&origin, "two");
+    git(&clone, &["fetch", "-q"]);
+    let repos = [
+        repo(&clone, Status::Clean, 0, 1),
+        repo(&temp.path().join("up-to-date"), Status::Clean, 0, 0),
+    ];
+
+    assert_eq!(
+        bulk::pull(&repos, true),
+        [BulkResult {
+            repo_path: "tool".to_owned(),
+            outcome: Outcome::WouldDo("fast-forward 1 commits".to_owned()),
+        }],
+        "only repositories behind are pulled"
+    );
+    let head = |path: &Path| {
+        Repository::open(path)
+            .unwrap()
+            .head()
+            .unwrap()
+            .target()
+            .unwrap()
+    };
+    assert_ne!(head(&clone), head(&origin), "a dry run must not pull");
+
+    assert_eq!(
+        bulk::pull(&repos, false)[0].outcome,
+        Outcome::Done("fast-forwarded 1 commits".to_owned())
+    );
+    assert_eq!(head(&clone), head(&origin));
+}
+
+#[test]
+fn test_pull_skips_dirty_and_diverged_repositories() {
+    let missing = Path::new("/nonexistent/tool");
+    let repos = [
+        repo(missing, Status::Dirty(2), 0, 3),
+        repo(missing, Status::Unpushed, 1, 3),
+    ];
+    let outcomes: Vec<String> = bulk::pull(&repos, false)
+        .iter()
+        .map(|result| result.outcome.to_string())
+        .collect();
+    assert_eq!(
+        outcomes,
+        [
+            "skipped: the status is Dirty (2)",
+            "skipped: diverged, set pull.rebase or pull.ff to say how to pull",
+        ]
+    );
+}
+
+#[test]
+fn test_push_pushes_repositories_ahead() {
+    let temp = TempDir::new().unwrap();
+    let origin = temp.path().join("origin.git");
+    fs::create_dir_all(&origin).unwrap();
+    git(&origin, &["init", "-q", "--bare", "-b", "main"]);
+    git(temp.path(), &["clone", "-q", "origin.git", "clone"]);
+    let clone = temp.path().join("clone");
+    git(&clone, &["checkout", "-q", "-b", "main"]);
+    commit(&clone, "one");
+    git(&clone, &["push", "-q", "-u", "origin", "main"]);
+    commit(&clone, "two");
+    let repos = [
+        repo(&clone, Status::Unpushed, 1, 0),
+        repo(&temp.path().join("up-to-date"), Status::Clean, 0, 0),
+    ];
+
+    assert_eq!(
+        bulk::push(&repos, true),
+        [BulkResult {
+            repo_path: "tool".to_owned(),
+            outcome: Outcome::WouldDo("push 1 commits to origin/main".to_owned()),
+        }],
+        "only repositories ahead are pushed"
+    );
+    let main = |path: &Path| {
+        Repository::open(path)
+            .unwrap()
+            .refname_to_id("refs/heads/main")
+            .unwrap()
+    };
+    assert_ne!(main(&clone), main(&origin), "a dry run must not push");
+
+    assert_eq!(
+        bulk::push(&repos, false)[0].outcome,
+        Outcome::Done("pushed 1 commits to origin/main".to_owned())
+    );
+    assert_eq!(main(&clone), main(&origin));
+}
+
+#[test]
+fn test_bulk_skips_branches_tracking_a_local_branch() {
+    let temp = TempDir::new().unwrap();
+    git(temp.path(), &["init", "-q", "-b", "main"]);
+    commit(temp.path(), "one");
+    git(
+        temp.path(),
+        &["checkout", "-q", "-b", "topic", "--track", "main"],
+    );
+    commit(temp.path(), "two");
+    let mut ahead = repo(temp.path(), Status::Unpushed, 1, 0);
+    ahead.branch = "topic".to_owned();
+    let mut behind = repo(temp.path(), Status::Clean, 0, 1);
+    behind.branch = "topic".to_owned();
+
+    let skipped = Outcome::Skipped("the upstream is a local branch".to_owned());
+    assert_eq!(bulk::push(&[ahead], false)[0].outcome, skipped);
+    assert_eq!(bulk::pull(&[behind], false)[0].outcome, skipped);
+}
+
+#[test]
+fn test_push_skips_dirty_and_diverged_repositories() {
+    let missing = Path::new("/nonexistent/tool");
+    let repos = [
+        repo(missing, Status::Dirty(1), 2, 0),
+        repo(missing, Status::Unpushed, 2, 1),
+        repo(missing, Status::Unpushed, 2, 0),
+    ];
+    let outcomes: Vec<String> = bulk::push(&repos, false)
+        .iter()
+        .map(|result| result.outcome.to_string())
+        .collect();
+    assert_eq!(
+        outcomes,
+        [
+            "skipped: the status is Dirty (1)",
+            "skipped: diverged, pull the 1 new commits first",
+            "skipped: no upstream is configured",
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+#[expect(
+    clippy::literal_string_with_formatting_args,
+    reason = "the placeholders of --exec"
+)]
+fn test_exec_runs_the_command_in_every_repository() {
+    let temp = TempDir::new().unwrap();
+    let mut repos = Vec::new();
+    for (dir, name) in [("one", "it's"), ("two", "two")] {
+        let path = temp.path().join(dir);
+        fs::create_dir_all(&path).unwrap();
+        let mut info = repo(&path, Status::Clean, 0, 0);
+        info.name = name.to_owned();
+        info.repo_path = dir.to_owned();
+        repos.push(info);
+    }
+    let mut out = Vec::new();
+    let results = bulk::exec(
+        &repos,
+        "echo {name} {branch} $(basename {path}); test -f missing",
+        1,
+        &mut out,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "==> one <==\nit's main one\n==> two <==\ntwo main two\n"
+    );
+    assert_eq!(
+        results
+            .iter()
+            .map(|result| result.outcome.to_string())
+            .collect::<Vec<_>>(),
+        ["failed: exit code 1", "failed: exit code 1"]
+    );
+
+    let results = bulk::exec(&repos, "true", 0, &mut Vec::new()).unwrap();
+    assert_eq!(results[1].outcome, Outcome::Done("exit code 0".to_owned()));
+}
+
+#[cfg(unix)]
+#[test]
+#[expect(
+    clippy::literal_string_with_formatting_args,
+    reason = "the placeholders of --exec"
+)]
+fn test_exec_does_not_substitute_placeholders_in_values() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join("{branch}");
+    fs::create_dir_all(&path).unwrap();
+    let mut info = repo(&path, Status::Clean, 0, 0);
+    info.name = "{branch}$(echo injected)".to_owned();
+    info.repo_path = "{branch}".to_owned();
+    let mut out = Vec::new();
+    bulk::exec(
+        &[info],
+        "echo {name} {branch} $(basename {path})",
+        1,
+        &mut out,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "==> {branch} <==\n{branch}$(echo injected) main {branch}\n"
+    );
+}
+
+#[test]
+fn test_gc_packs_loose_objects() {
+    let temp = TempDir::new().unwrap();
+    git(temp.path(), &["init", "-q", "-b", "main"]);
+    commit(temp.path(), "one");
+    let repos = [
+        RepoInfo {
+            housekeeping: Some(gitinfo::get_housekeeping(
+                &Repository::open(temp.path()).unwrap(),
+            )),
+            ..repo(temp.path(), Status::Unpublished, 0, 0)
+        },
+        repo(temp.path(), Status::Busy, 0, 0),
+    ];
+
+    let results = bulk::gc(&repos, true);
+    assert!(
+        matches!(&results[0].outcome, Outcome::WouldDo(what) if what.starts_with("pack 3 loose objects and 0 packs")),
+        "{results:?}"
+    );
+    assert_eq!(
+        results[1].outcome,
+        Outcome::Skipped("the status is Busy".to_owned())
+    );
+
+    let results = bulk::gc(&repos[..1], false);
+    assert!(
+        matches!(&results[0].outcome, Outcome::Done(what) if what.starts_with("packed, ")),
+        "{results:?}"
+    );
+    let after = gitinfo::get_housekeeping(&Repository::open(temp.path()).unwrap());
+    assert_eq!((after.loose_objects, after.packs), (0, 1));
+}
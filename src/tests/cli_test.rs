@@ -4,8 +4,13 @@ use std::str;
 
 use clap::ColorChoice;
 use clap::Parser;
+use tempfile::TempDir;
 
-use crate::cli::Args;
+#[cfg(feature = "daemon")]
+use crate::cli::DaemonAction;
+use crate::cli::{Args, Command, OutputFormat, repository_paths};
+use crate::report::Rollup;
+use crate::scanner::{FailureBudget, FetchSelector};
 
 /// From <https://github.com/EmbarkStudios/cargo-deny/blob/f6e40d8eff6a507977b20588c842c53bc0bfd427/src/cargo-deny/main.rs#L369>
 /// Snapshot tests for the CLI commands
@@ -49,6 +54,18 @@ fn snapshot_test_cli_command(app: clap::Command, cmd_name: &str) {
 }
 
 #[test]
+#[cfg_attr(
+    any(
+        feature = "daemon",
+        feature = "fs-events",
+        feature = "html",
+        feature = "metrics",
+        feature = "svg",
+        feature = "history",
+        feature = "template"
+    ),
+    ignore = "the help lists the flags of every enabled feature, the snapshot is the one without features"
+)]
 fn test_cli_snapshot() {
     use clap::CommandFactory as _;
 
@@ -65,23 +82,25 @@ fn test_cli_snapshot() {
 #[test]
 fn test_cli_default_args() {
     let args = Args::parse_from(["git-statuses"]);
-    assert_eq!(args.dir, Path::new("."));
+    assert_eq!(args.dirs, [Path::new(".")]);
     assert_eq!(args.depth, 1);
     assert!(!args.remote);
     assert!(!args.condensed);
     assert!(!args.summary);
-    assert!(!args.fetch);
+    assert!(args.fetch.is_none());
     assert!(!args.legend);
     assert!(args.subdir.is_none());
     assert!(args.completions.is_none());
     assert!(!args.path);
     assert!(!args.non_clean);
+    assert!(!args.gitignore_advice);
+    assert_eq!(args.progress_interval, 10);
 }
 
 #[test]
 fn test_cli_directory_argument() {
     let args = Args::parse_from(["git-statuses", "/path/to/repos"]);
-    assert_eq!(args.dir, Path::new("/path/to/repos"));
+    assert_eq!(args.dirs, [Path::new("/path/to/repos")]);
 }
 
 #[test]
@@ -104,14 +123,16 @@ fn test_cli_boolean_flags() {
         "--legend",
         "--path",
         "--non-clean",
+        "--gitignore-advice",
     ]);
     assert!(args.remote);
     assert!(args.condensed);
     assert!(args.summary);
-    assert!(args.fetch);
+    assert_eq!(args.fetch, Some(FetchSelector::All));
     assert!(args.legend);
     assert!(args.path);
     assert!(args.non_clean);
+    assert!(args.gitignore_advice);
 }
 
 #[test]
@@ -119,7 +140,7 @@ fn test_cli_short_flags() {
     let args = Args::parse_from(["git-statuses", "-r", "-c", "-f", "-l", "-p"]);
     assert!(args.remote);
     assert!(args.condensed);
-    assert!(args.fetch);
+    assert_eq!(args.fetch, Some(FetchSelector::All));
     assert!(args.legend);
     assert!(args.path);
 }
@@ -144,13 +165,13 @@ fn test_cli_complex_combinations() {
         "--condensed",
     ]);
 
-    assert_eq!(args.dir, Path::new("/home/user/projects"));
+    assert_eq!(args.dirs, [Path::new("/home/user/projects")]);
     assert_eq!(args.depth, 3);
     assert!(args.remote);
     assert!(args.summary);
     assert_eq!(args.subdir, Some("checkout".to_owned()));
     assert!(args.condensed);
-    assert!(!args.fetch); // not specified
+    assert!(args.fetch.is_none()); // not specified
     assert!(!args.legend); // not specified
 }
 
@@ -188,13 +209,514 @@ fn test_cli_filter_combination() {
 fn test_cli_path_variations() {
     // Test relative path
     let args = Args::parse_from(["git-statuses", "."]);
-    assert_eq!(args.dir, Path::new("."));
+    assert_eq!(args.dirs, [Path::new(".")]);
 
     // Test path with tilde (will be treated literally by clap)
     let args = Args::parse_from(["git-statuses", "~/projects"]);
-    assert_eq!(args.dir, Path::new("~/projects"));
+    assert_eq!(args.dirs, [Path::new("~/projects")]);
 
     // Test absolute path
     let args = Args::parse_from(["git-statuses", "/absolute/path"]);
-    assert_eq!(args.dir, Path::new("/absolute/path"));
+    assert_eq!(args.dirs, [Path::new("/absolute/path")]);
+}
+
+#[cfg(feature = "daemon")]
+#[test]
+fn test_cli_daemon_subcommand() {
+    let args = Args::parse_from(["git-statuses", "--depth", "3", "daemon", "--interval", "60"]);
+    assert_eq!(args.depth, 3);
+    assert!(matches!(
+        args.command,
+        Some(Command::Daemon {
+            interval: 60,
+            action: None
+        })
+    ));
+
+    let args = Args::parse_from(["git-statuses", "daemon"]);
+    assert!(matches!(
+        args.command,
+        Some(Command::Daemon {
+            interval: 300,
+            action: None
+        })
+    ));
+    assert_eq!(args.dirs, [Path::new(".")]);
+
+    let args = Args::parse_from(["git-statuses", "/some/dir", "daemon", "--socket", "/tmp/s"]);
+    assert_eq!(args.dirs, [Path::new("/some/dir")]);
+    assert_eq!(args.socket_path(), Path::new("/tmp/s"));
+}
+
+#[cfg(feature = "daemon")]
+#[test]
+fn test_cli_daemon_install_subcommand() {
+    for argv in [
+        [
+            "git-statuses",
+            "daemon",
+            "--interval",
+            "60",
+            "install",
+            "--print",
+        ],
+        [
+            "git-statuses",
+            "daemon",
+            "install",
+            "--interval",
+            "60",
+            "--print",
+        ],
+    ] {
+        let args = Args::parse_from(argv);
+        assert!(matches!(
+            args.command,
+            Some(Command::Daemon {
+                interval: 60,
+                action: Some(DaemonAction::Install { print: true })
+            })
+        ));
+    }
+}
+
+#[test]
+fn test_cli_list_only_conflicts_with_status_options() {
+    let args = Args::parse_from(["git-statuses", "--list-only", "--remote", "--path"]);
+    assert!(args.list_only);
+
+    for option in ["--non-clean", "--fetch", "--ff", "--from-daemon"] {
+        assert!(
+            Args::try_parse_from(["git-statuses", "--list-only", option]).is_err(),
+            "--list-only must not be combined with {option}"
+        );
+    }
+}
+
+#[test]
+fn test_cli_progress_interval_argument() {
+    let args = Args::parse_from(["git-statuses", "--fetch", "--progress-interval", "30"]);
+    assert_eq!(args.progress_interval, 30);
+
+    let args = Args::parse_from(["git-statuses", "--progress-interval=0"]);
+    assert_eq!(args.progress_interval, 0);
+}
+
+#[test]
+fn test_cli_max_failures_argument() {
+    let args = Args::parse_from(["git-statuses", "--max-failures", "5"]);
+    assert_eq!(args.max_failures, Some(FailureBudget::Count(5)));
+
+    let args = Args::parse_from(["git-statuses", "--max-failures=10%"]);
+    assert_eq!(args.max_failures, Some(FailureBudget::Percent(10)));
+
+    for budget in ["101%", "many", "-1"] {
+        assert!(
+            Args::try_parse_from(["git-statuses", "--max-failures", budget]).is_err(),
+            "{budget} must be rejected as a failure budget"
+        );
+    }
+}
+
+#[test]
+fn test_failure_budget_limit() {
+    assert_eq!(FailureBudget::Count(2).limit(100), 2);
+    assert_eq!(FailureBudget::Percent(10).limit(25), 2);
+    assert!(!FailureBudget::Percent(10).is_exceeded(2, 25));
+    assert!(FailureBudget::Percent(10).is_exceeded(3, 25));
+    assert!(FailureBudget::Count(0).is_exceeded(1, 1));
+}
+
+#[test]
+fn test_cli_output_argument() {
+    let args = Args::parse_from(["git-statuses"]);
+    assert_eq!(args.output_format(), OutputFormat::Table);
+
+    let args = Args::parse_from(["git-statuses", "--output", "dot"]);
+    assert_eq!(args.output_format(), OutputFormat::Dot);
+
+    let args = Args::parse_from(["git-statuses", "--json"]);
+    assert_eq!(args.output_format(), OutputFormat::Json);
+
+    assert!(
+        Args::try_parse_from(["git-statuses", "--json", "--output", "dot"]).is_err(),
+        "--json is short for --output json and must not be combined with it"
+    );
+
+    let args = Args::parse_from(["git-statuses", "--tmux"]);
+    assert_eq!(args.output_format(), OutputFormat::Tmux);
+    assert!(
+        Args::try_parse_from(["git-statuses", "--tmux", "--json"]).is_err(),
+        "--tmux is short for --output tmux"
+    );
+}
+
+#[test]
+fn test_cli_clone_missing_subcommand() {
+    let args = Args::parse_from(["git-statuses", "/new/home", "clone-missing", "repos.json"]);
+    assert_eq!(args.dirs, [Path::new("/new/home")]);
+    assert!(matches!(
+        args.command,
+        Some(Command::CloneMissing { manifest }) if manifest == Path::new("repos.json")
+    ));
+}
+
+#[test]
+fn test_cli_report_subcommand() {
+    let args = Args::parse_from(["git-statuses", "report"]);
+    assert!(matches!(
+        args.command,
+        Some(Command::Report {
+            rollup: Rollup::Org
+        })
+    ));
+
+    let args = Args::parse_from(["git-statuses", "-d", "2", "report", "--rollup", "host"]);
+    assert_eq!(args.depth, 2);
+    assert!(matches!(
+        args.command,
+        Some(Command::Report {
+            rollup: Rollup::Host
+        })
+    ));
+}
+
+#[test]
+fn test_repository_paths_from_stdin() {
+    let input = "repo-a\n\n/abs/repo-b/.git\nnested/repo-c\t/ignored/path\t-\n  spaced  \n";
+    let paths = repository_paths(input.as_bytes(), Path::new("/scan"));
+    assert_eq!(
+        paths,
+        [
+            Path::new("/scan/repo-a"),
+            Path::new("/abs/repo-b"),
+            Path::new("/scan/nested/repo-c"),
+            Path::new("/scan/spaced"),
+        ]
+    );
+}
+
+#[test]
+fn test_repository_paths_separated_by_nul() {
+    let input = "repo-a\0 with\ttab \0\0/abs/repo-b/.git\0";
+    let paths = repository_paths(input.as_bytes(), Path::new("/scan"));
+    assert_eq!(
+        paths,
+        [
+            Path::new("/scan/repo-a"),
+            Path::new("/scan/ with\ttab "),
+            Path::new("/abs/repo-b"),
+        ]
+    );
+    assert!(
+        Args::try_parse_from(["git-statuses", "--paths-from", "-", "--stdin"]).is_err(),
+        "there is only one list of repositories"
+    );
+}
+
+#[test]
+fn test_cli_watch_arguments() {
+    let args = Args::parse_from(["git-statuses", "--watch", "30", "--changes-only"]);
+    assert_eq!(args.watch, Some(30));
+    assert!(args.changes_only);
+
+    assert!(
+        Args::try_parse_from(["git-statuses", "--changes-only"]).is_err(),
+        "--changes-only only makes sense with --watch"
+    );
+}
+
+#[test]
+fn test_cli_snapshot_and_diff_arguments() {
+    let args = Args::parse_from([
+        "git-statuses",
+        "--diff",
+        "state.json",
+        "--snapshot",
+        "state.json",
+    ]);
+    assert_eq!(args.diff.as_deref(), Some(Path::new("state.json")));
+    assert_eq!(args.snapshot.as_deref(), Some(Path::new("state.json")));
+
+    assert!(
+        Args::try_parse_from(["git-statuses", "--diff", "state.json", "--json"]).is_err(),
+        "--diff prints changes, not a scan result"
+    );
+}
+
+#[cfg(feature = "html")]
+#[test]
+fn test_output_format_display_matches_the_flag_value() {
+    assert_eq!(OutputFormat::Html.to_string(), "html");
+    let args = Args::try_parse_from(["git-statuses", "--output", "html"]).unwrap();
+    assert_eq!(args.output_format(), OutputFormat::Html);
+}
+
+#[cfg(feature = "template")]
+#[test]
+fn test_cli_template_output_needs_a_template() {
+    assert!(
+        Args::try_parse_from(["git-statuses", "--output", "template"]).is_err(),
+        "--output template without --template"
+    );
+    let args = Args::parse_from([
+        "git-statuses",
+        "--output",
+        "template",
+        "--template",
+        "report.j2",
+    ]);
+    assert_eq!(args.output_format(), OutputFormat::Template);
+    assert_eq!(args.template.as_deref(), Some(Path::new("report.j2")));
+}
+
+#[test]
+fn test_cli_plain_output_with_null() {
+    let args = Args::parse_from(["git-statuses", "-o", "plain", "-0"]);
+    assert_eq!(args.output_format(), OutputFormat::Plain);
+    assert!(args.null, "-0 is short for --null");
+}
+
+#[test]
+fn test_cli_apply_needs_a_change() {
+    assert!(
+        Args::try_parse_from(["git-statuses", "--apply"]).is_err(),
+        "nothing to apply"
+    );
+    assert!(
+        Args::try_parse_from(["git-statuses", "--prune-merged", "--apply"]).is_ok(),
+        "`--apply` applies `--prune-merged`"
+    );
+    let args = Args::parse_from(["git-statuses", "--convert-remotes=ssh:https", "--apply"]);
+    assert_eq!(
+        args.convert_remotes.map(|c| c.to_string()).as_deref(),
+        Some("ssh:https")
+    );
+    assert!(
+        Args::try_parse_from([
+            "git-statuses",
+            "--prune-merged",
+            "--convert-remotes=ssh:https"
+        ])
+        .is_err(),
+        "one change at a time"
+    );
+}
+
+#[test]
+fn test_cli_fetch_selector() {
+    let args = Args::parse_from(["git-statuses", "--fetch=behind"]);
+    assert_eq!(args.fetch, Some(FetchSelector::Behind));
+
+    let args = Args::parse_from(["git-statuses", "--fetch=remote:github.com/acme/*"]);
+    assert_eq!(
+        args.fetch,
+        Some(FetchSelector::Remote("github.com/acme/*".to_owned()))
+    );
+
+    // Without `=` the next argument is the directory, not a selector.
+    let args = Args::parse_from(["git-statuses", "--fetch", "behind"]);
+    assert_eq!(args.fetch, Some(FetchSelector::All));
+    assert_eq!(args.dirs, [Path::new("behind")]);
+
+    for selector in ["label:work", "remote:", "ahead"] {
+        assert!(
+            Args::try_parse_from(["git-statuses", &format!("--fetch={selector}")]).is_err(),
+            "`{selector}` should be rejected"
+        );
+    }
+}
+
+#[test]
+fn test_cli_is_informational() {
+    assert!(Args::parse_from(["git-statuses", "--legend"]).is_informational());
+    assert!(Args::parse_from(["git-statuses", "--completions", "bash"]).is_informational());
+    assert!(!Args::parse_from(["git-statuses", "-f"]).is_informational());
+}
+
+#[test]
+fn test_cli_push_needs_confirmation() {
+    assert!(
+        Args::try_parse_from(["git-statuses", "--push"]).is_err(),
+        "`--push` needs `--yes` or `--dry-run`"
+    );
+    assert!(
+        Args::try_parse_from(["git-statuses", "--push", "--yes"]).is_ok(),
+        "`--yes` confirms `--push`"
+    );
+    assert!(
+        Args::try_parse_from(["git-statuses", "--push", "--dry-run"]).is_ok(),
+        "`--dry-run` only lists what `--push` would do"
+    );
+    assert!(
+        Args::try_parse_from(["git-statuses", "--yes"]).is_err(),
+        "nothing to confirm"
+    );
+    assert!(
+        Args::try_parse_from(["git-statuses", "--pull", "--dry-run"]).is_ok(),
+        "`--pull` needs no confirmation"
+    );
+}
+
+#[test]
+fn test_cli_gc_needs_confirmation() {
+    assert!(
+        Args::try_parse_from(["git-statuses", "--gc"]).is_err(),
+        "`--gc` needs `--yes` or `--dry-run`"
+    );
+    assert!(
+        Args::try_parse_from(["git-statuses", "--gc", "--yes"]).is_ok(),
+        "`--yes` confirms `--gc`"
+    );
+    assert!(
+        Args::try_parse_from(["git-statuses", "--gc", "--dry-run"]).is_ok(),
+        "`--dry-run` only lists what `--gc` would collect"
+    );
+    assert!(
+        Args::try_parse_from(["git-statuses", "--pull", "--yes"]).is_err(),
+        "`--pull` has nothing to confirm"
+    );
+}
+
+#[test]
+fn test_cli_identifies_repositories_only_for_diffing() {
+    let identifies = |args: &[&str]| {
+        let args = Args::parse_from(std::iter::once("git-statuses").chain(args.iter().copied()));
+        format!("{:?}", args.scanner().unwrap()).contains("identify: true")
+    };
+    assert!(!identifies(&[]));
+    assert!(!identifies(&["--json"]), "JSON leaves the identity out");
+    assert!(identifies(&["--snapshot=scan.json"]));
+    assert!(identifies(&["--diff=scan.json"]));
+}
+
+#[test]
+fn test_cli_upstream_remote_conflicts_with_pull_and_push() {
+    assert!(
+        Args::try_parse_from(["git-statuses", "--upstream-remote=upstream", "--fetch"]).is_ok(),
+        "a scan counts against the remote"
+    );
+    for bulk in [&["--pull"][..], &["--push", "--yes"]] {
+        let args = ["git-statuses", "--upstream-remote=upstream"]
+            .iter()
+            .chain(bulk);
+        assert!(
+            Args::try_parse_from(args).is_err(),
+            "{bulk:?} goes by the configured upstream branch"
+        );
+    }
+}
+
+#[test]
+fn test_cli_catches_a_fetch_selector_taken_as_directory() {
+    let temp = TempDir::new().unwrap();
+    let dir = temp.path().to_str().unwrap();
+    for selector in ["behind", "remote:github.com/acme/*"] {
+        let args = Args::parse_from(["git-statuses", dir, "--fetch", selector]);
+        let error = args.scanner().unwrap_err().to_string();
+        assert!(
+            error.contains(&format!("did you mean --fetch={selector}?")),
+            "{error}"
+        );
+    }
+    let args = Args::parse_from(["git-statuses", "--fetch=behind", dir]);
+    assert!(args.scanner().is_ok(), "the selector follows an `=`");
+}
+
+#[test]
+fn test_cli_accepts_several_directories() {
+    let args = Args::parse_from(["git-statuses", "/home/user/work", "/home/user/oss"]);
+    assert_eq!(
+        args.dirs,
+        [Path::new("/home/user/work"), Path::new("/home/user/oss")]
+    );
+    assert_eq!(args.dirs_display(), "/home/user/work /home/user/oss");
+
+    let args = Args::parse_from(["git-statuses", "report"]);
+    assert!(matches!(args.command, Some(Command::Report { .. })));
+    assert_eq!(args.dirs, [Path::new(".")]);
+}
+
+#[test]
+fn test_cli_hooks_argument() {
+    let args = Args::parse_from(["git-statuses", "--hooks"]);
+    assert_eq!(args.hooks, Some(vec!["pre-commit".to_owned()]));
+
+    let args = Args::parse_from(["git-statuses", "--hooks=pre-commit,commit-msg"]);
+    assert_eq!(
+        args.hooks,
+        Some(vec!["pre-commit".to_owned(), "commit-msg".to_owned()])
+    );
+    assert!(
+        Args::try_parse_from(["git-statuses", "--hooks", "--remotes"]).is_err(),
+        "`--hooks` replaces the scan like `--remotes`"
+    );
+}
+
+/// Parses the options `Args::scan_arguments` returns, as a service started with them would.
+fn reparse(args: &Args) -> Args {
+    Args::parse_from(std::iter::once("git-statuses".to_owned()).chain(args.scan_arguments()))
+}
+
+#[test]
+fn test_scan_arguments_round_trip_every_scan_option() {
+    let args = Args::parse_from([
+        "git-statuses",
+        "--depth=3",
+        "--subdir=checkout",
+        "--remote",
+        "--fetch=behind",
+        "--ff",
+        "--prune",
+        "--upstream-remote=upstream",
+        "--retry-auth",
+        "--timing=5",
+        "--no-commit-count",
+        "--activity",
+        "--check-integrity=fsck",
+        "--maintenance",
+        "--disk-usage",
+        "--show-ignored",
+        "--hidden-files",
+        "--lfs",
+        "--forks",
+        "--gone",
+        "--clones",
+        "--identity=*@acme.com",
+        "--verify-signatures=3",
+        "--no-dedupe",
+        "--max-failures=10%",
+        "--fetch-jobs=2",
+        "--fetch-host-limit=github.com=4",
+        "--fetch-host-limit=*.acme.com=1",
+        "--progress-interval=30",
+        "--github",
+        "--forge=git.acme.com=gitea",
+    ]);
+    let again = reparse(&args);
+    assert_eq!(
+        format!("{:?}", again.scanner().unwrap()),
+        format!("{:?}", args.scanner().unwrap()),
+        "the scan differs with {:?}",
+        args.scan_arguments()
+    );
+    assert_eq!(again.forges(), args.forges());
+    assert_eq!(again.scan_arguments(), args.scan_arguments());
+}
+
+#[test]
+fn test_scan_arguments_carry_the_repository_sources() {
+    let args = Args::parse_from(["git-statuses", "--registered", "--registry=registry.json"]);
+    let again = reparse(&args);
+    assert!(again.registered);
+    let registry = again.registry.unwrap();
+    assert!(registry.is_absolute(), "{}", registry.display());
+    assert!(registry.ends_with("registry.json"));
+
+    let args = Args::parse_from(["git-statuses", "--paths-from=repos.txt"]);
+    let paths_from = reparse(&args).paths_from.unwrap();
+    assert!(paths_from.is_absolute(), "{}", paths_from.display());
+
+    let args = Args::parse_from(["git-statuses", "--from-git-maintenance"]);
+    assert!(reparse(&args).from_git_maintenance);
 }
@@ -0,0 +1,179 @@
+use std::{fs, path::Path, thread, time::Duration};
+
+use git2::Repository;
+use parking_lot::RwLock;
+use serde_json::{Value, json};
+use tempfile::TempDir;
+
+use crate::{
+    cli::Args,
+    daemon,
+    gitinfo::{
+        repoinfo::{FailedRepo, RepoInfo},
+        status::Status,
+    },
+};
+
+fn create_repo(parent: &Path, name: &str) {
+    let repo_path = parent.join(name);
+    fs::create_dir_all(&repo_path).unwrap();
+    Repository::init(&repo_path).unwrap();
+}
+
+/// Polls the daemon until it answers, as it only binds the socket after its first scan.
+fn query_when_ready(socket: &Path) -> (Vec<RepoInfo>, Vec<FailedRepo>) {
+    for _ in 0..100 {
+        if let Ok(result) = daemon::query(socket) {
+            return result;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    daemon::query(socket).unwrap()
+}
+
+#[test]
+fn test_daemon_serves_the_latest_scan() {
+    let scan = TempDir::new().unwrap();
+    create_repo(scan.path(), "first");
+    let sockets = TempDir::new().unwrap();
+    let socket = sockets.path().join("daemon.sock");
+
+    let args = Args {
+        dirs: vec![scan.path().to_path_buf()],
+        depth: 1,
+        ..Default::default()
+    };
+    let serve_socket = socket.clone();
+    thread::spawn(move || daemon::serve(&args, &serve_socket, Duration::from_millis(50)));
+
+    let (repos, failed) = query_when_ready(&socket);
+    assert_eq!(repos.len(), 1);
+    assert_eq!(repos[0].repo_path, "first");
+    assert!(failed.is_empty());
+
+    // A repository created after the daemon started shows up after a rescan.
+    create_repo(scan.path(), "second");
+    let mut found = false;
+    for _ in 0..100 {
+        if daemon::query(&socket).unwrap().0.len() == 2 {
+            found = true;
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    assert!(found, "the daemon must pick up new repositories on rescan");
+}
+
+#[test]
+fn test_daemon_refuses_to_start_twice() {
+    let scan = TempDir::new().unwrap();
+    let sockets = TempDir::new().unwrap();
+    let socket = sockets.path().join("daemon.sock");
+
+    let args = Args {
+        dirs: vec![scan.path().to_path_buf()],
+        ..Default::default()
+    };
+    let serve_socket = socket.clone();
+    thread::spawn(move || daemon::serve(&args, &serve_socket, Duration::from_mins(1)));
+    query_when_ready(&socket);
+
+    let second = Args {
+        dirs: vec![scan.path().to_path_buf()],
+        ..Default::default()
+    };
+    assert!(
+        daemon::serve(&second, &socket, Duration::from_mins(1)).is_err(),
+        "a second daemon must not steal the socket of a running one"
+    );
+}
+
+#[test]
+fn test_daemon_replaces_a_stale_socket_file() {
+    let scan = TempDir::new().unwrap();
+    let sockets = TempDir::new().unwrap();
+    let socket = sockets.path().join("daemon.sock");
+    fs::write(&socket, "").unwrap();
+
+    let args = Args {
+        dirs: vec![scan.path().to_path_buf()],
+        ..Default::default()
+    };
+    let serve_socket = socket.clone();
+    thread::spawn(move || daemon::serve(&args, &serve_socket, Duration::from_mins(1)));
+
+    let (repos, failed) = query_when_ready(&socket);
+    assert!(repos.is_empty());
+    assert!(failed.is_empty());
+}
+
+#[test]
+fn test_query_without_daemon_fails() {
+    let sockets = TempDir::new().unwrap();
+    assert!(daemon::query(&sockets.path().join("missing.sock")).is_err());
+}
+
+/// Starts a daemon scanning `dir`, and waits until it answers.
+fn start_daemon(dir: &Path, socket: &Path) {
+    let args = Args {
+        dirs: vec![dir.to_path_buf()],
+        depth: 1,
+        ..Default::default()
+    };
+    let serve_socket = socket.to_path_buf();
+    thread::spawn(move || daemon::serve(&args, &serve_socket, Duration::from_mins(1)));
+    query_when_ready(socket);
+}
+
+#[test]
+fn test_daemon_answers_list_and_summary_requests() {
+    let scan = TempDir::new().unwrap();
+    create_repo(scan.path(), "first");
+    create_repo(scan.path(), "second");
+    let sockets = TempDir::new().unwrap();
+    let socket = sockets.path().join("daemon.sock");
+    start_daemon(scan.path(), &socket);
+
+    let list = daemon::call(&socket, "list", Value::Null).unwrap();
+    assert_eq!(list["repositories"].as_array().unwrap().len(), 2);
+    assert_eq!(list["repositories"][0]["repo_path"], "first");
+
+    let summary = daemon::call(&socket, "summary", Value::Null).unwrap();
+    assert_eq!(summary["total"], 2);
+}
+
+#[test]
+fn test_daemon_refreshes_a_single_repository() {
+    let scan = TempDir::new().unwrap();
+    create_repo(scan.path(), "first");
+    let sockets = TempDir::new().unwrap();
+    let socket = sockets.path().join("daemon.sock");
+    start_daemon(scan.path(), &socket);
+
+    fs::write(scan.path().join("first").join("new.txt"), "new").unwrap();
+    let refreshed = daemon::call(&socket, "refresh", json!({ "repo": "first" })).unwrap();
+    let refreshed: RepoInfo = serde_json::from_value(refreshed).unwrap();
+    assert_eq!(refreshed.status, Status::Dirty(1));
+
+    let (repos, _) = daemon::query(&socket).unwrap();
+    assert_eq!(repos.len(), 1);
+    assert_eq!(repos[0].status, Status::Dirty(1));
+
+    let error = daemon::call(&socket, "refresh", json!({ "repo": "missing" })).unwrap_err();
+    assert!(error.to_string().contains("missing"));
+}
+
+#[test]
+fn test_daemon_rejects_unknown_methods() {
+    let scan = TempDir::new().unwrap();
+    let sockets = TempDir::new().unwrap();
+    let socket = sockets.path().join("daemon.sock");
+    start_daemon(scan.path(), &socket);
+
+    let error = daemon::call(&socket, "shutdown", Value::Null).unwrap_err();
+    assert!(error.to_string().contains("Method not found"));
+
+    let latest = RwLock::new(daemon::Latest::scan(&Args::default()).unwrap());
+    let response = daemon::respond("not json", &Args::default(), &latest);
+    assert_eq!(response["error"]["code"], -32700);
+}
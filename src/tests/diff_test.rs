@@ -0,0 +1,111 @@
+use std::path::PathBuf;
+
+use crate::{
+    diff::{ChangeKind, FieldChange, RepoChange, diff},
+    gitinfo::{repoinfo::RepoInfo, status::Status},
+};
+
+fn repo(repo_path: &str, identity: &str) -> RepoInfo {
+    RepoInfo {
+        name: repo_path.to_owned(),
+        branch: "main".to_owned(),
+        ahead: 0,
+        behind: 0,
+        commits: Some(1),
+        status: Status::Clean,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
+        has_unpushed: false,
+        remote_url: None,
+        path: PathBuf::from("/path/to").join(repo_path),
+        stash_count: 0,
+        is_local_only: false,
+        fast_forwarded: false,
+        repo_path: repo_path.to_owned(),
+        is_worktree: false,
+        identity: Some(identity.to_owned()),
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
+    }
+}
+
+#[test]
+fn test_diff_of_identical_scans_is_empty() {
+    let scan = [repo("a", "1"), repo("b", "2")];
+    assert_eq!(diff(&scan, &scan), []);
+}
+
+#[test]
+fn test_diff_reports_changed_fields() {
+    let old = [repo("a", "1")];
+    let mut new = repo("a", "1");
+    new.status = Status::Dirty(2);
+    new.ahead = 1;
+    new.stash_count = 3;
+
+    let changes = diff(&old, &[new]);
+    assert_eq!(
+        changes,
+        [RepoChange {
+            repo_path: "a".to_owned(),
+            kind: ChangeKind::Changed(vec![
+                FieldChange {
+                    field: "status",
+                    old: "Clean".to_owned(),
+                    new: "Dirty (2)".to_owned(),
+                },
+                FieldChange {
+                    field: "ahead",
+                    old: "0".to_owned(),
+                    new: "1".to_owned(),
+                },
+                FieldChange {
+                    field: "stashes",
+                    old: "0".to_owned(),
+                    new: "3".to_owned(),
+                },
+            ]),
+        }]
+    );
+    assert_eq!(
+        changes[0].to_string(),
+        "a: status Clean → Dirty (2), ahead 0 → 1, stashes 0 → 3"
+    );
+}
+
+#[test]
+fn test_diff_reports_added_removed_and_moved_repositories() {
+    let old = [repo("gone", "1"), repo("old-name", "2")];
+    let mut moved = repo("new-name", "2");
+    moved.branch = "feature".to_owned();
+    let new = [repo("fresh", "3"), moved];
+
+    let lines = diff(&old, &new)
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        [
+            "fresh: added",
+            "gone: removed",
+            "new-name: moved from old-name, branch main → feature",
+        ]
+    );
+}
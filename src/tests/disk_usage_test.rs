@@ -0,0 +1,46 @@
+use std::{cell::Cell, fs, path::Path};
+
+use tempfile::TempDir;
+
+use crate::{disk_usage::DiskUsageCache, gitinfo::repoinfo::DiskUsage};
+
+const USAGE: DiskUsage = DiskUsage {
+    worktree: 1000,
+    git: 200,
+};
+
+#[test]
+fn test_disk_usage_cache_measures_once() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("cache").join("disk-usage.json");
+    let measured = Cell::new(0);
+    let measure = || {
+        measured.set(measured.get() + 1);
+        USAGE
+    };
+
+    let cache = DiskUsageCache::load(Some(&file));
+    assert_eq!(cache.get(Path::new("/src/tool"), measure), USAGE);
+    assert_eq!(cache.get(Path::new("/src/tool"), measure), USAGE);
+    assert_eq!(measured.get(), 1, "the second scan reuses the first");
+    cache.finish();
+
+    let cache = DiskUsageCache::load(Some(&file));
+    assert_eq!(cache.get(Path::new("/src/tool"), measure), USAGE);
+    assert_eq!(measured.get(), 1, "the size is kept between runs");
+}
+
+#[test]
+fn test_disk_usage_cache_measures_expired_entries_again() {
+    let temp = TempDir::new().unwrap();
+    let file = temp.path().join("disk-usage.json");
+    let key = crate::util::path_key(Path::new("/src/tool"));
+    fs::write(
+        &file,
+        serde_json::json!({ key: { "measured_at": 0, "worktree": 1, "git": 1 } }).to_string(),
+    )
+    .unwrap();
+
+    let cache = DiskUsageCache::load(Some(&file));
+    assert_eq!(cache.get(Path::new("/src/tool"), || USAGE), USAGE);
+}
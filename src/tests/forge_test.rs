@@ -0,0 +1,122 @@
+use serde_json::json;
+
+use crate::forge::{CiStatus, Forge, ForgeKind};
+
+#[test]
+fn test_forge_project() {
+    let github = Forge::github();
+    for (url, expected) in [
+        ("git@github.com:acme/tool.git", Some("acme/tool")),
+        ("https://github.com/acme/tool", Some("acme/tool")),
+        ("ssh://git@GitHub.com/acme/tool.git", Some("acme/tool")),
+        ("https://github.com/acme/tools/tool.git", None),
+        ("https://gitlab.com/acme/tool.git", None),
+        ("https://github.com/tool.git", None),
+        ("/srv/git/tool.git", None),
+    ] {
+        assert_eq!(github.project(url).as_deref(), expected, "{url}");
+    }
+
+    let gitlab: Forge = "gitlab.acme.com=gitlab".parse().unwrap();
+    assert_eq!(
+        gitlab
+            .project("git@gitlab.acme.com:acme/tools/tool.git")
+            .as_deref(),
+        Some("acme/tools/tool"),
+        "GitLab nests groups"
+    );
+}
+
+#[test]
+fn test_forge_from_str() {
+    let gitlab: Forge = "gitlab.acme.com=gitlab".parse().unwrap();
+    assert_eq!(
+        gitlab,
+        Forge {
+            host: "gitlab.acme.com".to_owned(),
+            kind: ForgeKind::GitLab,
+            api: "https://gitlab.acme.com/api/v4".to_owned(),
+        }
+    );
+    assert_eq!(
+        gitlab.token_variable(),
+        "GIT_STATUSES_TOKEN_GITLAB_ACME_COM"
+    );
+
+    let gitea: Forge = "git.acme.com=forgejo:http://git.acme.com:3000/api/v1"
+        .parse()
+        .unwrap();
+    assert_eq!(gitea.kind, ForgeKind::Gitea);
+    assert_eq!(gitea.api, "http://git.acme.com:3000/api/v1");
+
+    let enterprise: Forge = "github.acme.com=github".parse().unwrap();
+    assert_eq!(enterprise.api, "https://github.acme.com/api/v3");
+
+    for forge in ["gitlab.acme.com", "=gitlab", "git.acme.com=bitbucket"] {
+        assert!(
+            forge.parse::<Forge>().is_err(),
+            "`{forge}` should be rejected"
+        );
+    }
+}
+
+#[test]
+fn test_ci_status_from_check_runs() {
+    let runs = |conclusions: &[Option<&str>]| {
+        json!({
+            "total_count": conclusions.len(),
+            "check_runs": conclusions
+                .iter()
+                .map(|conclusion| json!({ "conclusion": conclusion }))
+                .collect::<Vec<_>>(),
+        })
+    };
+
+    assert_eq!(CiStatus::from_check_runs(&runs(&[])), None);
+    assert_eq!(
+        CiStatus::from_check_runs(&runs(&[Some("success"), Some("skipped")])),
+        Some(CiStatus::Success)
+    );
+    assert_eq!(
+        CiStatus::from_check_runs(&runs(&[Some("success"), None])),
+        Some(CiStatus::Pending),
+        "a run without a conclusion is still running"
+    );
+    assert_eq!(
+        CiStatus::from_check_runs(&runs(&[None, Some("timed_out"), Some("success")])),
+        Some(CiStatus::Failure),
+        "a failure outweighs everything else"
+    );
+    assert_eq!(
+        CiStatus::from_check_runs(&json!({ "message": "Not Found" })),
+        None
+    );
+}
+
+#[test]
+fn test_ci_status_from_pipelines_and_combined_status() {
+    assert_eq!(CiStatus::from_pipelines(&json!([])), None);
+    assert_eq!(
+        CiStatus::from_pipelines(&json!([{ "status": "failed" }, { "status": "success" }])),
+        Some(CiStatus::Failure),
+        "only the latest pipeline counts"
+    );
+    assert_eq!(
+        CiStatus::from_pipelines(&json!([{ "status": "running" }])),
+        Some(CiStatus::Pending)
+    );
+
+    assert_eq!(
+        CiStatus::from_combined_status(&json!({ "state": "pending", "total_count": 0 })),
+        None,
+        "a commit without statuses is reported as pending by Gitea"
+    );
+    assert_eq!(
+        CiStatus::from_combined_status(&json!({ "state": "success", "total_count": 2 })),
+        Some(CiStatus::Success)
+    );
+    assert_eq!(
+        CiStatus::from_combined_status(&json!({ "state": "error", "total_count": 1 })),
+        Some(CiStatus::Failure)
+    );
+}
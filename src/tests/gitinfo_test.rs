@@ -1,12 +1,20 @@
 use std::{
     fs,
     path::{Path, PathBuf},
+    time::{Duration, SystemTime},
 };
 
 use comfy_table::Color;
 use git2::Repository;
 
-use crate::gitinfo::{self, repoinfo::RepoInfo, status::Status};
+use crate::{
+    gitinfo::{
+        self,
+        repoinfo::{HiddenFiles, LfsStatus, RepoInfo, Signatures},
+        status::Status,
+    },
+    theme::Theme,
+};
 
 fn init_temp_repo() -> (tempfile::TempDir, Repository) {
     let tmp_dir = tempfile::tempdir().unwrap();
@@ -54,7 +62,7 @@ fn test_get_repo_status_clean_dirty() {
 #[test]
 fn test_get_ahead_behind_no_upstream() {
     let (_tmp, repo) = init_temp_repo();
-    let (ahead, behind, is_local_only) = gitinfo::get_ahead_behind_and_local_status(&repo);
+    let (ahead, behind, is_local_only) = gitinfo::get_ahead_behind_and_local_status(&repo, None);
     assert_eq!((ahead, behind, is_local_only), (0, 0, true));
 }
 
@@ -153,12 +161,32 @@ fn test_get_branch_name_no_head() {
     assert_eq!(branch, "nonexistent-branch (no commits)");
 }
 
+#[test]
+fn test_get_branch_name_detached_shows_commit_and_tag() {
+    let (tmp, repo) = init_temp_repo();
+    commit_initial(&tmp, &repo);
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let sha = gitinfo::get_head_sha(&repo).unwrap();
+    assert!(
+        sha.len() >= 7 && head.id().to_string().starts_with(&sha),
+        "`{sha}` should abbreviate {}",
+        head.id()
+    );
+
+    repo.set_head_detached(head.id()).unwrap();
+    assert_eq!(gitinfo::get_branch_name(&repo), sha);
+
+    repo.tag_lightweight("v1.0.0", head.as_object(), false)
+        .unwrap();
+    assert_eq!(gitinfo::get_branch_name(&repo), format!("v1.0.0 ({sha})"));
+}
+
 #[test]
 fn test_get_ahead_behind_error_cases() {
     let (_tmp, repo) = init_temp_repo();
     // Simulate a repository with an invalid HEAD by setting it to a non-existent branch
     repo.set_head("refs/heads/nonexistent-branch").unwrap();
-    let (ahead, behind, is_local_only) = gitinfo::get_ahead_behind_and_local_status(&repo);
+    let (ahead, behind, is_local_only) = gitinfo::get_ahead_behind_and_local_status(&repo, None);
     assert_eq!((ahead, behind, is_local_only), (0, 0, true));
 }
 
@@ -167,7 +195,7 @@ fn test_fetch_origin_failure() {
     let (_tmp, repo) = init_temp_repo();
     // Simulate a fetch failure by pointing to a non-existent remote
     repo.remote("origin", "https://invalid-url").unwrap();
-    let result = gitinfo::fetch_origin(&repo);
+    let result = gitinfo::fetch_origin(&repo, false);
     assert!(result.is_err());
 }
 
@@ -236,7 +264,7 @@ fn test_status_descriptions() {
 #[test]
 fn test_as_cell_contains_expected_text_and_color() {
     let status = Status::Dirty(5);
-    let cell = status.as_cell();
+    let cell = status.as_cell(&Theme::default());
     assert!(cell.content().contains("Dirty (5)"));
 }
 
@@ -250,10 +278,269 @@ fn test_get_stash_count_empty() {
 #[test]
 fn test_get_ahead_behind_and_local_status_no_upstream() {
     let (_tmp, repo) = init_temp_repo();
-    let (ahead, behind, is_local_only) = gitinfo::get_ahead_behind_and_local_status(&repo);
+    let (ahead, behind, is_local_only) = gitinfo::get_ahead_behind_and_local_status(&repo, None);
     assert_eq!((ahead, behind, is_local_only), (0, 0, true));
 }
 
+#[test]
+fn test_get_ahead_behind_against_named_remote() {
+    let (tmp, repo) = init_temp_repo();
+    commit_initial(&tmp, &repo);
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    let branch = gitinfo::get_branch_name(&repo);
+    repo.reference(
+        &format!("refs/remotes/upstream/{branch}"),
+        head.id(),
+        false,
+        "test",
+    )
+    .unwrap();
+    let sig = repo.signature().unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &sig,
+        &sig,
+        "second",
+        &head.tree().unwrap(),
+        &[&head],
+    )
+    .unwrap();
+
+    assert_eq!(
+        gitinfo::get_ahead_behind_and_local_status(&repo, Some("upstream")),
+        (1, 0, false),
+        "the branch should be compared with upstream/{branch}"
+    );
+    assert_eq!(
+        gitinfo::get_ahead_behind_and_local_status(&repo, Some("missing")),
+        (0, 0, true),
+        "without the remote branch it should fall back to the (missing) upstream"
+    );
+    assert_eq!(
+        gitinfo::get_ahead_behind_and_local_status(&repo, None),
+        (0, 0, true),
+        "without a remote the configured upstream counts"
+    );
+}
+
+#[test]
+fn test_fetch_origin_prune_removes_deleted_remote_branches() {
+    let (origin_tmp, origin) = init_temp_repo();
+    commit_initial(&origin_tmp, &origin);
+    let head = origin.head().unwrap().peel_to_commit().unwrap();
+    origin.branch("feature", &head, false).unwrap();
+    let (_tmp, repo) = init_temp_repo();
+    repo.remote("origin", origin_tmp.path().to_str().unwrap())
+        .unwrap();
+    gitinfo::fetch_origin(&repo, false).unwrap();
+    let tracking = "refs/remotes/origin/feature";
+    assert!(repo.find_reference(tracking).is_ok(), "feature is fetched");
+
+    origin
+        .find_branch("feature", git2::BranchType::Local)
+        .unwrap()
+        .delete()
+        .unwrap();
+    gitinfo::fetch_origin(&repo, false).unwrap();
+    assert!(
+        repo.find_reference(tracking).is_ok(),
+        "a plain fetch keeps the remote-tracking branch"
+    );
+    gitinfo::fetch_origin(&repo, true).unwrap();
+    assert!(
+        repo.find_reference(tracking).is_err(),
+        "a pruning fetch removes it"
+    );
+}
+
+#[test]
+fn test_repo_info_marks_shallow_and_partial_clones() {
+    let (origin_tmp, origin) = init_temp_repo();
+    commit_initial(&origin_tmp, &origin);
+    let clone_tmp = tempfile::tempdir().unwrap();
+    let url = format!("file://{}", origin_tmp.path().display());
+    git(
+        clone_tmp.path(),
+        &["clone", "--quiet", "--depth=1", &url, "clone"],
+    );
+    let clone_path = clone_tmp.path().join("clone");
+    let mut clone = Repository::open(&clone_path).unwrap();
+
+    let info = RepoInfo::new(&mut clone, "clone", false, false, false, clone_tmp.path()).unwrap();
+    assert!(info.is_shallow, "a --depth clone is shallow");
+    assert!(!info.is_partial, "nothing was filtered");
+
+    clone
+        .config()
+        .unwrap()
+        .set_bool("remote.origin.promisor", true)
+        .unwrap();
+    assert!(
+        gitinfo::is_partial_clone(&clone),
+        "a promisor remote makes a partial clone"
+    );
+}
+
+#[test]
+fn test_get_lfs_status() {
+    let (tmp, repo) = init_temp_repo();
+    commit_initial(&tmp, &repo);
+    assert_eq!(gitinfo::get_lfs_status(&repo), None, "LFS is not used");
+
+    let oid = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+    fs::write(
+        tmp.path().join(".gitattributes"),
+        "*.bin filter=lfs diff=lfs merge=lfs -text\n",
+    )
+    .unwrap();
+    fs::write(
+        tmp.path().join("model.bin"),
+        format!("version https://git-lfs.github.com/spec/v1\noid sha256:{oid}\nsize 12345\n"),
+    )
+    .unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(".gitattributes")).unwrap();
+    index.add_path(Path::new("model.bin")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+    let sig = repo.signature().unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "add model", &tree, &[&parent])
+        .unwrap();
+    assert_eq!(
+        gitinfo::get_lfs_status(&repo),
+        Some(LfsStatus {
+            objects: 1,
+            missing: 1
+        }),
+        "the object was never downloaded"
+    );
+
+    let stored = repo
+        .path()
+        .join("lfs/objects")
+        .join(&oid[..2])
+        .join(&oid[2..4]);
+    fs::create_dir_all(&stored).unwrap();
+    fs::write(stored.join(oid), "model data").unwrap();
+    assert_eq!(
+        gitinfo::get_lfs_status(&repo),
+        Some(LfsStatus {
+            objects: 1,
+            missing: 0
+        }),
+        "the object is in the store"
+    );
+}
+
+#[test]
+fn test_get_lfs_status_ignores_malformed_pointers() {
+    let (tmp, repo) = init_temp_repo();
+    commit_initial(&tmp, &repo);
+    fs::write(
+        tmp.path().join(".gitattributes"),
+        "*.bin filter=lfs diff=lfs merge=lfs -text
+",
+    )
+    .unwrap();
+    for (file, oid) in [("short.bin", "4d7a"), ("multibyte.bin", "aéb€cdef")] {
+        fs::write(
+            tmp.path().join(file),
+            format!("version https://git-lfs.github.com/spec/v1\noid sha256:{oid}\nsize 1\n"),
+        )
+        .unwrap();
+    }
+    let mut index = repo.index().unwrap();
+    for file in [".gitattributes", "short.bin", "multibyte.bin"] {
+        index.add_path(Path::new(file)).unwrap();
+    }
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+    let sig = repo.signature().unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "add pointers", &tree, &[&parent])
+        .unwrap();
+    assert_eq!(
+        gitinfo::get_lfs_status(&repo),
+        Some(LfsStatus::default()),
+        "a pointer without a valid oid is no LFS object"
+    );
+}
+
+#[test]
+fn test_get_gone_branches() {
+    let (tmp, repo) = init_temp_repo();
+    commit_initial(&tmp, &repo);
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.remote("origin", "https://example.com/acme/tool.git")
+        .unwrap();
+    repo.branch("merged", &head, false).unwrap();
+    repo.branch("local", &head, false).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("branch.merged.remote", "origin").unwrap();
+    config
+        .set_str("branch.merged.merge", "refs/heads/merged")
+        .unwrap();
+
+    assert_eq!(
+        gitinfo::get_gone_branches(&repo),
+        ["merged"],
+        "the remote branch of `merged` does not exist"
+    );
+
+    repo.reference("refs/remotes/origin/merged", head.id(), false, "test")
+        .unwrap();
+    assert!(
+        gitinfo::get_gone_branches(&repo).is_empty(),
+        "the remote branch exists again"
+    );
+}
+
+#[test]
+fn test_get_fork_behind() {
+    let (tmp, repo) = init_temp_repo();
+    commit_initial(&tmp, &repo);
+    let first = repo.head().unwrap().peel_to_commit().unwrap();
+    let sig = repo.signature().unwrap();
+    let second = repo
+        .commit(
+            None,
+            &sig,
+            &sig,
+            "second",
+            &first.tree().unwrap(),
+            &[&first],
+        )
+        .unwrap();
+    repo.remote("origin", "https://example.com/me/tool.git")
+        .unwrap();
+    repo.reference("refs/remotes/origin/main", first.id(), false, "test")
+        .unwrap();
+    assert_eq!(
+        gitinfo::get_fork_behind(&repo),
+        None,
+        "without an upstream remote it is no fork"
+    );
+
+    repo.remote("upstream", "https://example.com/acme/tool.git")
+        .unwrap();
+    repo.reference("refs/remotes/upstream/main", second, false, "test")
+        .unwrap();
+    assert_eq!(
+        gitinfo::get_fork_behind(&repo),
+        Some(1),
+        "origin/main lacks the second commit"
+    );
+
+    repo.reference("refs/remotes/origin/main", second, true, "test")
+        .unwrap();
+    assert_eq!(
+        gitinfo::get_fork_behind(&repo),
+        Some(0),
+        "the fork is in sync"
+    );
+}
+
 #[test]
 fn test_repo_info_includes_stash_and_local_status() {
     let (_tmp, mut repo) = init_temp_repo();
@@ -296,6 +583,101 @@ fn test_status_new_with_merge_state() {
     assert_eq!(status, Status::Merge);
 }
 
+#[test]
+fn test_get_conflict_count_of_stopped_merge() {
+    let (tmp, repo) = init_temp_repo();
+    commit_initial(&tmp, &repo);
+    assert_eq!(gitinfo::get_conflict_count(&repo), 0);
+
+    let dir = tmp.path();
+    fs::write(dir.join("other.txt"), "base").unwrap();
+    git(dir, &["add", "other.txt"]);
+    git(dir, &["commit", "-m", "base"]);
+    git(dir, &["checkout", "-b", "topic"]);
+    fs::write(dir.join("file.txt"), "topic").unwrap();
+    fs::write(dir.join("other.txt"), "topic").unwrap();
+    git(dir, &["commit", "-am", "topic"]);
+    git(dir, &["checkout", "-"]);
+    fs::write(dir.join("file.txt"), "main").unwrap();
+    fs::write(dir.join("other.txt"), "main").unwrap();
+    git(dir, &["commit", "-am", "main"]);
+    let merge = std::process::Command::new("git")
+        .args(["merge", "topic"])
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(!merge.status.success(), "the merge stops on the conflicts");
+
+    assert_eq!(Status::new(&repo), Status::Merge);
+    assert_eq!(gitinfo::get_conflict_count(&repo), 2);
+}
+
+/// Makes a `topic` branch of three commits, the second of which conflicts with the last
+/// commit of the branch that is checked out afterwards.
+fn diverge_with_conflict(dir: &Path) {
+    git(dir, &["checkout", "-b", "topic"]);
+    for (file, message) in [
+        ("one.txt", "one"),
+        ("file.txt", "two"),
+        ("three.txt", "three"),
+    ] {
+        fs::write(dir.join(file), message).unwrap();
+        git(dir, &["add", file]);
+        git(dir, &["commit", "-m", message]);
+    }
+    git(dir, &["checkout", "-"]);
+    fs::write(dir.join("file.txt"), "main").unwrap();
+    git(dir, &["commit", "-am", "main"]);
+}
+
+/// Runs a git command that is expected to stop on a conflict.
+fn git_conflicting(dir: &Path, args: &[&str]) {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        !output.status.success(),
+        "git {args:?} stops on the conflict"
+    );
+}
+
+#[test]
+fn test_get_progress_of_stopped_rebase() {
+    let (tmp, repo) = init_temp_repo();
+    commit_initial(&tmp, &repo);
+    assert_eq!(gitinfo::get_progress(&repo), None);
+    let main = gitinfo::get_branch_name(&repo);
+
+    diverge_with_conflict(tmp.path());
+    git(tmp.path(), &["checkout", "topic"]);
+    git_conflicting(tmp.path(), &["rebase", "--merge", &main]);
+
+    let progress = gitinfo::get_progress(&repo).unwrap();
+    assert_eq!((progress.step, progress.total), (2, 3));
+    assert_eq!(progress.branch.as_deref(), Some("topic"));
+    assert_eq!(
+        progress.onto,
+        Some(main),
+        "the commit is named by its branch"
+    );
+}
+
+#[test]
+fn test_get_progress_of_stopped_cherry_pick_sequence() {
+    let (tmp, repo) = init_temp_repo();
+    commit_initial(&tmp, &repo);
+
+    diverge_with_conflict(tmp.path());
+    git_conflicting(tmp.path(), &["cherry-pick", "topic~3..topic"]);
+
+    let progress = gitinfo::get_progress(&repo).unwrap();
+    assert_eq!((progress.step, progress.total), (2, 3));
+    assert_eq!(progress.branch, None);
+    assert_eq!(progress.onto, None);
+}
+
 #[test]
 fn test_get_changed_count_multiple_types() {
     let (tmp, repo) = init_temp_repo();
@@ -591,6 +973,29 @@ fn test_repo_name_from_url_local_path() {
     );
 }
 
+#[test]
+fn test_remote_host_and_owner() {
+    let parsed = |url| gitinfo::remote_host_and_owner(url);
+    let expected = |host: &str, owner: &str| Some((host.to_owned(), owner.to_owned()));
+
+    assert_eq!(
+        parsed("https://github.com/bircni/git-statuses.git"),
+        expected("github.com", "bircni")
+    );
+    assert_eq!(
+        parsed("git@github.com:bircni/git-statuses.git"),
+        expected("github.com", "bircni")
+    );
+    assert_eq!(
+        parsed("ssh://git@gitlab.example.com:2222/group/sub/repo.git"),
+        expected("gitlab.example.com", "group/sub")
+    );
+    assert_eq!(parsed("git@host:repo.git"), expected("host", ""));
+    assert_eq!(parsed("/home/user/projects/repo"), None);
+    assert_eq!(parsed("C:\\projects\\repo.git"), None);
+    assert_eq!(parsed("file:///srv/git/repo.git"), None);
+}
+
 #[test]
 fn test_repo_name_from_url_without_a_name() {
     assert_eq!(gitinfo::repo_name_from_url(""), None);
@@ -751,6 +1156,64 @@ fn commit_initial(tmp: &tempfile::TempDir, repo: &Repository) {
         .unwrap();
 }
 
+/// Runs git in `dir`, for what git2 has no API for.
+fn git(dir: &Path, args: &[&str]) {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_status_of_changes_in_submodules_only() {
+    let (lib_tmp, lib) = init_temp_repo();
+    commit_initial(&lib_tmp, &lib);
+    let (tmp, repo) = init_temp_repo();
+    commit_initial(&tmp, &repo);
+    let lib_url = lib_tmp.path().to_str().unwrap();
+    git(
+        tmp.path(),
+        &[
+            "-c",
+            "protocol.file.allow=always",
+            "submodule",
+            "add",
+            lib_url,
+            "lib",
+        ],
+    );
+    git(tmp.path(), &["commit", "-m", "add lib"]);
+    assert_eq!(
+        Status::new(&repo),
+        Status::Unpublished,
+        "a fresh submodule should be clean"
+    );
+
+    fs::write(tmp.path().join("lib/scratch.txt"), "notes").unwrap();
+    assert_eq!(
+        Status::new(&repo),
+        Status::SubmoduleDirty,
+        "an untracked file in the submodule is no change of the parent"
+    );
+    assert_eq!(
+        Status::SubmoduleDirty.to_string(),
+        "Clean (submodule dirty)"
+    );
+
+    fs::write(tmp.path().join("file.txt"), "changed").unwrap();
+    assert_eq!(
+        Status::new(&repo),
+        Status::Dirty(2),
+        "a change of the parent makes it dirty"
+    );
+}
+
 /// git records an in-progress operation with marker files inside the git directory, and
 /// each one maps to a distinct status. These are the states a user most wants to be warned
 /// about, so every arm must map to the status it claims.
@@ -869,3 +1332,409 @@ fn test_get_repo_path_for_bare_repository_without_git_suffix() {
     );
     assert_eq!(info.repo_path, "plain-bare");
 }
+
+/// Untracked artifacts map to their `.gitignore` entries; ordinary untracked files do not.
+#[test]
+fn test_suggest_gitignore_entries_for_untracked_artifacts() {
+    let (tmp, repo) = init_temp_repo();
+    fs::create_dir_all(tmp.path().join("target/debug")).unwrap();
+    fs::write(tmp.path().join("target/debug/app"), "binary").unwrap();
+    fs::create_dir_all(tmp.path().join("web/node_modules/left-pad")).unwrap();
+    fs::write(tmp.path().join("web/node_modules/left-pad/index.js"), "").unwrap();
+    fs::write(tmp.path().join(".DS_Store"), "").unwrap();
+    fs::write(tmp.path().join("module.pyc"), "").unwrap();
+    fs::write(tmp.path().join("notes.txt"), "not an artifact").unwrap();
+
+    // `web/` is untracked as a whole, so `node_modules` below it is not looked at.
+    assert_eq!(
+        gitinfo::suggest_gitignore_entries(&repo),
+        vec!["*.pyc", ".DS_Store", "target/"]
+    );
+}
+
+#[test]
+fn test_suggest_gitignore_entries_ignores_already_ignored_artifacts() {
+    let (tmp, repo) = init_temp_repo();
+    fs::write(tmp.path().join(".gitignore"), "target/\n").unwrap();
+    fs::create_dir_all(tmp.path().join("target")).unwrap();
+    fs::write(tmp.path().join("target/out"), "binary").unwrap();
+
+    assert!(gitinfo::suggest_gitignore_entries(&repo).is_empty());
+}
+
+/// The identity must not depend on where a checkout lives: a clone somewhere else is the
+/// same repository.
+#[test]
+fn test_repo_identity_is_the_root_commit() {
+    let (tmp, repo) = init_temp_repo();
+    commit_initial(&tmp, &repo);
+    let root = repo.head().unwrap().target().unwrap();
+    fs::write(tmp.path().join("file.txt"), "changed").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("file.txt")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = repo.signature().unwrap();
+    let parent = repo.find_commit(root).unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "second", &tree, &[&parent])
+        .unwrap();
+
+    let clone_dir = tempfile::tempdir().unwrap();
+    let clone = Repository::clone(tmp.path().to_str().unwrap(), clone_dir.path()).unwrap();
+
+    assert_eq!(gitinfo::get_repo_identity(&repo), Some(root.to_string()));
+    assert_eq!(gitinfo::get_repo_identity(&clone), Some(root.to_string()));
+}
+
+#[test]
+fn test_repo_identity_without_commits_falls_back_to_the_remote() {
+    let (_tmp, repo) = init_temp_repo();
+    assert_eq!(gitinfo::get_repo_identity(&repo), None);
+
+    repo.remote("origin", "https://example.com/user/repo.git/")
+        .unwrap();
+    assert_eq!(
+        gitinfo::get_repo_identity(&repo).as_deref(),
+        Some("https://example.com/user/repo")
+    );
+}
+
+#[test]
+fn test_get_locks_while_a_lock_is_held() {
+    let (_tmp, repo) = init_temp_repo();
+    assert!(
+        gitinfo::get_locks(&repo).fresh.is_empty(),
+        "a fresh repository is idle"
+    );
+
+    let index_lock = repo.path().join("index.lock");
+    fs::write(&index_lock, "").unwrap();
+    assert!(
+        !gitinfo::get_locks(&repo).fresh.is_empty(),
+        "index.lock marks the repository busy"
+    );
+    assert_eq!(
+        gitinfo::wait_until_idle(&repo, Duration::ZERO).fresh,
+        std::slice::from_ref(&index_lock),
+        "the lock is still held"
+    );
+    fs::remove_file(&index_lock).unwrap();
+    assert!(
+        gitinfo::wait_until_idle(&repo, Duration::ZERO)
+            .fresh
+            .is_empty(),
+        "the lock is gone"
+    );
+
+    let ref_lock = repo.path().join("refs/remotes/origin/main.lock");
+    fs::create_dir_all(ref_lock.parent().unwrap()).unwrap();
+    fs::write(&ref_lock, "").unwrap();
+    assert!(
+        !gitinfo::get_locks(&repo).fresh.is_empty(),
+        "a ref lock marks the repository busy"
+    );
+}
+
+#[test]
+fn test_get_locks_tells_stale_locks_apart() {
+    let (_tmp, repo) = init_temp_repo();
+    let lock = fs::File::create(repo.path().join("index.lock")).unwrap();
+    lock.set_modified(SystemTime::now() - Duration::from_hours(1))
+        .unwrap();
+    assert!(
+        gitinfo::get_locks(&repo).fresh.is_empty(),
+        "an hour old lock is left over"
+    );
+    let locks = gitinfo::wait_until_idle(&repo, Duration::from_mins(1));
+    assert!(locks.fresh.is_empty(), "nothing to wait for");
+    assert_eq!(locks.stale, [repo.path().join("index.lock")]);
+}
+
+#[test]
+fn test_get_ignored_count() {
+    let (tmp, mut repo) = init_temp_repo();
+    fs::write(tmp.path().join(".gitignore"), "target/\n*.log\n").unwrap();
+    git(tmp.path(), &["add", ".gitignore"]);
+    git(tmp.path(), &["commit", "-m", "ignore"]);
+    assert_eq!(gitinfo::get_ignored_count(&repo), 0);
+
+    fs::create_dir_all(tmp.path().join("target/debug")).unwrap();
+    fs::write(tmp.path().join("target/debug/tool"), "").unwrap();
+    fs::write(tmp.path().join("target/.rustc_info.json"), "").unwrap();
+    fs::write(tmp.path().join("build.log"), "").unwrap();
+    assert_eq!(
+        gitinfo::get_ignored_count(&repo),
+        2,
+        "an ignored directory counts once"
+    );
+
+    let info = RepoInfo::new(&mut repo, "tmp", false, false, false, tmp.path()).unwrap();
+    assert_eq!(info.ignored, None, "only counted when asked for");
+    assert_eq!(
+        info.status,
+        Status::Unpublished,
+        "ignored files are no changes"
+    );
+}
+
+#[test]
+fn test_get_signatures() {
+    let (tmp, mut repo) = init_temp_repo();
+    assert_eq!(
+        gitinfo::get_signatures(&repo, 1),
+        None,
+        "no commits to check"
+    );
+
+    let key = tmp.path().join(".git/signing-key");
+    let output = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-C", "test", "-f"])
+        .arg(&key)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "ssh-keygen failed: {output:?}");
+    let public_key = fs::read_to_string(key.with_extension("pub")).unwrap();
+    let allowed_signers = tmp.path().join(".git/allowed-signers");
+    fs::write(&allowed_signers, format!("test@example.com {public_key}")).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("gpg.format", "ssh").unwrap();
+    config
+        .set_str("user.signingkey", key.to_str().unwrap())
+        .unwrap();
+    config
+        .set_str(
+            "gpg.ssh.allowedSignersFile",
+            allowed_signers.to_str().unwrap(),
+        )
+        .unwrap();
+    fs::write(tmp.path().join("file.txt"), "signed").unwrap();
+    git(tmp.path(), &["add", "file.txt"]);
+    git(tmp.path(), &["commit", "-S", "-m", "signed"]);
+    fs::write(tmp.path().join("file.txt"), "unsigned").unwrap();
+    git(tmp.path(), &["commit", "--no-gpg-sign", "-am", "unsigned"]);
+
+    let head = gitinfo::get_signatures(&repo, 1).unwrap();
+    assert_eq!((head.checked, head.unsigned), (1, 1));
+    let signatures = gitinfo::get_signatures(&repo, 5).unwrap();
+    assert_eq!(
+        signatures,
+        Signatures {
+            checked: 2,
+            good: 1,
+            bad: 0,
+            unverified: 0,
+            unsigned: 1,
+        },
+        "only the existing commits are checked"
+    );
+
+    let mut info = RepoInfo::new(&mut repo, "tmp", false, false, false, tmp.path()).unwrap();
+    assert_eq!(info.signatures, None, "only checked when asked for");
+    assert_eq!(info.format_signatures(), "-");
+    info.signatures = Some(head);
+    assert_eq!(info.format_signatures(), "unsigned");
+    info.signatures = Some(signatures);
+    assert_eq!(info.format_signatures(), "1/2 good, 1 unsigned");
+}
+
+#[test]
+fn test_get_hidden_files() {
+    let (tmp, mut repo) = init_temp_repo();
+    commit_initial(&tmp, &repo);
+    fs::write(tmp.path().join("config.toml"), "token = \"\"").unwrap();
+    git(tmp.path(), &["add", "config.toml"]);
+    git(tmp.path(), &["commit", "-m", "config"]);
+    assert_eq!(gitinfo::get_hidden_files(&repo), HiddenFiles::default());
+
+    git(
+        tmp.path(),
+        &["update-index", "--assume-unchanged", "file.txt"],
+    );
+    git(
+        tmp.path(),
+        &["update-index", "--skip-worktree", "config.toml"],
+    );
+    fs::write(tmp.path().join("file.txt"), "changed").unwrap();
+    fs::write(tmp.path().join("config.toml"), "token = \"secret\"").unwrap();
+    assert_eq!(
+        gitinfo::get_hidden_files(&repo),
+        HiddenFiles {
+            assume_unchanged: 1,
+            skip_worktree: 1,
+        }
+    );
+    let mut info = RepoInfo::new(&mut repo, "tmp", false, false, false, tmp.path()).unwrap();
+    assert_eq!(info.status, Status::Unpublished, "the changes are hidden");
+    assert_eq!(info.hidden_files, None, "only counted when asked for");
+    info.hidden_files = Some(gitinfo::get_hidden_files(&repo));
+    assert_eq!(
+        info.format_hidden_files(),
+        "1 assume-unchanged, 1 skip-worktree"
+    );
+
+    repo.config()
+        .unwrap()
+        .set_bool("core.sparseCheckout", true)
+        .unwrap();
+    assert_eq!(
+        gitinfo::get_hidden_files(&repo).skip_worktree,
+        0,
+        "sparse checkout skips files on purpose"
+    );
+}
+
+#[test]
+fn test_get_disk_usage() {
+    let (tmp, repo) = init_temp_repo();
+    commit_initial(&tmp, &repo);
+    fs::create_dir_all(tmp.path().join("target")).unwrap();
+    fs::write(tmp.path().join("target/build.bin"), vec![0; 1000]).unwrap();
+
+    let usage = gitinfo::get_disk_usage(&repo);
+    assert_eq!(
+        usage.worktree,
+        "content".len() as u64 + 1000,
+        "the files outside of .git count"
+    );
+    assert!(usage.git > 0);
+}
+
+#[test]
+fn test_check_integrity() {
+    let (tmp, repo) = init_temp_repo();
+    assert!(
+        gitinfo::check_integrity(&repo, true).is_empty(),
+        "a repository without commits is intact"
+    );
+    commit_initial(&tmp, &repo);
+    assert!(gitinfo::check_integrity(&repo, true).is_empty());
+
+    let blob = repo.blob(b"lost").unwrap().to_string();
+    git(tmp.path(), &["update-ref", "refs/tags/lost", &blob]);
+    let (dir, file) = blob.split_at(2);
+    fs::remove_file(repo.path().join("objects").join(dir).join(file)).unwrap();
+
+    let errors = gitinfo::check_integrity(&repo, false);
+    assert_eq!(errors, [format!("refs/tags/lost: missing object {blob}")]);
+    let errors = gitinfo::check_integrity(&repo, true);
+    assert!(
+        errors.len() > 1 && errors[1..].iter().all(|e| e.starts_with("fsck: ")),
+        "{errors:?}"
+    );
+}
+
+#[test]
+fn test_get_stale_locks() {
+    let (tmp, mut repo) = init_temp_repo();
+    commit_initial(&tmp, &repo);
+    assert!(gitinfo::get_stale_locks(&repo).is_empty());
+
+    let index_lock = repo.path().join("index.lock");
+    fs::write(&index_lock, "").unwrap();
+    assert!(
+        gitinfo::get_stale_locks(&repo).is_empty(),
+        "a fresh lock belongs to a running git process"
+    );
+    let stale = SystemTime::now() - Duration::from_hours(1);
+    fs::File::options()
+        .write(true)
+        .open(&index_lock)
+        .unwrap()
+        .set_modified(stale)
+        .unwrap();
+    let ref_lock = repo.path().join("refs/heads/main.lock");
+    fs::File::create(&ref_lock)
+        .unwrap()
+        .set_modified(stale)
+        .unwrap();
+
+    let mut locks = gitinfo::get_stale_locks(&repo);
+    locks.sort();
+    assert_eq!(locks, [index_lock, ref_lock]);
+    let info = RepoInfo::new(&mut repo, "tmp", false, false, false, tmp.path()).unwrap();
+    assert_eq!(info.status, Status::StaleLock);
+    assert_eq!(info.stale_locks.len(), 2);
+}
+
+#[test]
+fn test_repo_info_of_a_busy_repository() {
+    let (tmp, mut repo) = init_temp_repo();
+    fs::write(repo.path().join("index.lock"), "").unwrap();
+    let info = RepoInfo::new(&mut repo, "tmp", false, true, true, tmp.path()).unwrap();
+    assert_eq!(info.status, Status::Busy);
+    assert!(!info.fast_forwarded, "busy repositories are left alone");
+}
+
+#[test]
+fn test_get_user_email() {
+    let (_tmp, repo) = init_temp_repo();
+    assert_eq!(
+        gitinfo::get_user_email(&repo).as_deref(),
+        Some("test@example.com"),
+        "the repository's own email wins"
+    );
+}
+
+#[test]
+fn test_get_activity() {
+    let (tmp, repo) = init_temp_repo();
+    assert_eq!(
+        gitinfo::get_activity(&repo),
+        None,
+        "no commits, no activity"
+    );
+
+    commit_initial(&tmp, &repo);
+    let tree = repo.head().unwrap().peel_to_tree().unwrap();
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+    let old = git2::Signature::new(
+        "Old Timer",
+        "old@example.com",
+        &git2::Time::new(951_782_400, 0),
+    )
+    .unwrap();
+    let old_commit = repo
+        .commit(None, &old, &old, "ancient", &tree, &[])
+        .unwrap();
+    let old_commit = repo.find_commit(old_commit).unwrap();
+    let sig = repo.signature().unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &sig,
+        &sig,
+        "merge",
+        &tree,
+        &[&parent, &old_commit],
+    )
+    .unwrap();
+
+    let activity = gitinfo::get_activity(&repo).unwrap();
+    assert_eq!(activity.first_commit, 951_782_400);
+    assert!(
+        activity.last_commit > activity.first_commit,
+        "the merge is newer"
+    );
+    assert_eq!(
+        activity.recent_commits, 2,
+        "only the old commit is not recent"
+    );
+    assert_eq!(activity.contributors, 2);
+}
+
+#[test]
+fn test_maintenance_repositories() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut config = git2::Config::open(&tmp.path().join("gitconfig")).unwrap();
+    assert!(gitinfo::maintenance_repositories(&config).is_empty());
+
+    for repo in ["/home/user/src/tool", "/home/user/src/site"] {
+        config.set_multivar("maintenance.repo", "^$", repo).unwrap();
+    }
+    assert_eq!(
+        gitinfo::maintenance_repositories(&config),
+        [
+            PathBuf::from("/home/user/src/tool"),
+            PathBuf::from("/home/user/src/site")
+        ]
+    );
+}
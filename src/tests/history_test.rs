@@ -0,0 +1,173 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    gitinfo::{repoinfo::RepoInfo, status::Status},
+    history::{self, History, HistoryRow},
+};
+
+fn repo(repo_path: &str, status: Status, ahead: usize) -> RepoInfo {
+    RepoInfo {
+        name: repo_path.to_owned(),
+        branch: "main".to_owned(),
+        ahead,
+        behind: 0,
+        commits: Some(1),
+        status,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
+        has_unpushed: ahead > 0,
+        remote_url: None,
+        path: PathBuf::from("/path/to").join(repo_path),
+        stash_count: 0,
+        is_local_only: false,
+        fast_forwarded: false,
+        repo_path: repo_path.to_owned(),
+        is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
+    }
+}
+
+fn at(seconds: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+#[test]
+fn test_history_report() {
+    let temp = tempfile::tempdir().unwrap();
+    let db = temp.path().join("history.db");
+
+    let mut history = History::open(&db).unwrap();
+    assert_eq!(history.report().unwrap(), []);
+    history
+        .record(
+            &[
+                repo("a", Status::Dirty(1), 0),
+                repo("b", Status::Clean, 0),
+                repo("gone", Status::Clean, 0),
+            ],
+            at(100),
+        )
+        .unwrap();
+    history
+        .record(
+            &[repo("a", Status::Clean, 1), repo("b", Status::Unpushed, 2)],
+            at(200),
+        )
+        .unwrap();
+    drop(history);
+
+    // Reopening keeps what was recorded.
+    let mut history = History::open(&db).unwrap();
+    history
+        .record(
+            &[
+                repo("a", Status::Dirty(3), 1),
+                repo("b", Status::Unpushed, 5),
+            ],
+            at(300),
+        )
+        .unwrap();
+
+    assert_eq!(
+        history.report().unwrap(),
+        [
+            HistoryRow {
+                repo_path: "a".to_owned(),
+                status: "Dirty (3)".to_owned(),
+                scans: 3,
+                first_seen: 100,
+                not_clean_since: Some(300),
+                first_ahead: 0,
+                ahead: 1,
+                first_behind: 0,
+                behind: 0,
+            },
+            HistoryRow {
+                repo_path: "b".to_owned(),
+                status: "Unpushed".to_owned(),
+                scans: 3,
+                first_seen: 100,
+                not_clean_since: Some(200),
+                first_ahead: 0,
+                ahead: 5,
+                first_behind: 0,
+                behind: 0,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_history_open_rejects_other_files() {
+    let temp = tempfile::tempdir().unwrap();
+    let path = temp.path().join("not-a-db");
+    std::fs::write(&path, "just some text, definitely not SQLite").unwrap();
+    assert!(History::open(&path).is_err(), "a text file is no database");
+}
+
+#[test]
+fn test_format_age() {
+    assert_eq!(history::format_age(100, at(130)), "<1m");
+    assert_eq!(history::format_age(0, at(7 * 60)), "7m");
+    assert_eq!(history::format_age(0, at(5 * 3600 + 12 * 60)), "5h 12m");
+    assert_eq!(
+        history::format_age(0, at(3 * 86_400 + 4 * 3600 + 59)),
+        "3d 4h"
+    );
+    assert_eq!(history::format_age(500, at(100)), "<1m");
+}
+
+#[test]
+fn test_history_describes_itself() {
+    let temp = tempfile::tempdir().unwrap();
+    let db = temp.path().join("history.db");
+    drop(History::open(&db).unwrap());
+    // Opening again must not trip over the descriptions already there.
+    drop(History::open(&db).unwrap());
+
+    let connection = rusqlite::Connection::open(&db).unwrap();
+    let description: String = connection
+        .query_row(
+            "SELECT description FROM legend WHERE status = 'Dirty (N)'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(description, Status::Dirty(0).description());
+
+    let undescribed: Vec<(String, String)> = connection
+        .prepare(
+            "SELECT m.name, p.name FROM sqlite_master m JOIN pragma_table_info(m.name) p
+             WHERE m.type = 'table'
+             AND NOT EXISTS (
+                 SELECT 1 FROM columns c WHERE c.table_name = m.name AND c.column_name = p.name
+             )",
+        )
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(undescribed, []);
+}
@@ -0,0 +1,82 @@
+use std::fs;
+
+use git2::Repository;
+use tempfile::TempDir;
+
+use crate::{gitinfo::repoinfo::RepoListing, hooks};
+
+/// Writes an executable hook to `dir`.
+fn install(dir: &std::path::Path, hook: &str) {
+    fs::create_dir_all(dir).unwrap();
+    let path = dir.join(hook);
+    fs::write(&path, "#!/bin/sh\nexit 0\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt as _;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+}
+
+#[test]
+fn test_hooks_dir_follows_core_hooks_path() {
+    let temp = TempDir::new().unwrap();
+    let repo = Repository::init(temp.path()).unwrap();
+    assert_eq!(hooks::hooks_dir(&repo), repo.path().join("hooks"));
+
+    repo.config()
+        .unwrap()
+        .set_str("core.hooksPath", ".githooks")
+        .unwrap();
+    assert_eq!(
+        hooks::hooks_dir(&repo),
+        repo.workdir().unwrap().join(".githooks"),
+        "a relative path is relative to the working directory"
+    );
+}
+
+#[test]
+fn test_is_installed_ignores_samples() {
+    let temp = TempDir::new().unwrap();
+    fs::write(temp.path().join("pre-commit.sample"), "").unwrap();
+    assert!(!hooks::is_installed(temp.path(), "pre-commit"));
+
+    install(temp.path(), "pre-commit");
+    assert!(hooks::is_installed(temp.path(), "pre-commit"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_is_installed_requires_executable() {
+    let temp = TempDir::new().unwrap();
+    fs::write(temp.path().join("pre-commit"), "#!/bin/sh\n").unwrap();
+    assert!(
+        !hooks::is_installed(temp.path(), "pre-commit"),
+        "git does not run a hook that is not executable"
+    );
+}
+
+#[test]
+fn test_audit_lists_installed_and_missing_hooks() {
+    let temp = TempDir::new().unwrap();
+    let repo = Repository::init(temp.path()).unwrap();
+    install(&repo.path().join("hooks"), "pre-commit");
+    let repos = [RepoListing::new(&repo, "tool", false, temp.path())];
+    let expected = ["pre-commit".to_owned(), "commit-msg".to_owned()];
+
+    let mut out = Vec::new();
+    hooks::audit(&repos, &expected, &mut out).unwrap();
+    let listed = String::from_utf8(out).unwrap();
+    assert!(
+        listed.ends_with(": pre-commit installed; missing commit-msg\n"),
+        "{listed}"
+    );
+
+    install(&repo.path().join("hooks"), "commit-msg");
+    let mut out = Vec::new();
+    hooks::audit(&repos, &expected, &mut out).unwrap();
+    let listed = String::from_utf8(out).unwrap();
+    assert!(
+        listed.ends_with(": pre-commit, commit-msg installed\n"),
+        "{listed}"
+    );
+}
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use crate::{
+    gitinfo::{
+        repoinfo::{FailedRepo, RepoInfo},
+        status::Status,
+    },
+    html,
+};
+
+fn repo(name: &str, status: Status) -> RepoInfo {
+    RepoInfo {
+        name: name.to_owned(),
+        branch: "main".to_owned(),
+        ahead: 0,
+        behind: 0,
+        commits: Some(1),
+        status,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
+        has_unpushed: false,
+        remote_url: None,
+        path: PathBuf::from("/path/to").join(name),
+        stash_count: 0,
+        is_local_only: false,
+        fast_forwarded: false,
+        repo_path: name.to_owned(),
+        is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
+    }
+}
+
+#[test]
+fn test_html_report() {
+    let mut dirty = repo("<script>alert(1)</script>", Status::Dirty(2));
+    dirty.ahead = 3;
+    let failed = FailedRepo {
+        name: "broken".to_owned(),
+        path: PathBuf::from("/path/to/broken"),
+        error: "not a \"repository\"".to_owned(),
+    };
+
+    let report = html::report(&[repo("clean", Status::Clean), dirty], &[failed]);
+
+    assert!(report.starts_with("<!DOCTYPE html>"), "not a document");
+    assert!(
+        report.contains("<div><strong>2</strong>Repositories</div>")
+            && report.contains("<div><strong>1</strong>Clean</div>")
+            && report.contains("<div><strong>1</strong>Failed to process</div>"),
+        "the summary must come with the report: {report}"
+    );
+    assert!(
+        report.contains("<td>&lt;script&gt;alert(1)&lt;/script&gt;</td>"),
+        "names must be escaped: {report}"
+    );
+    assert!(
+        !report.contains("<script>alert"),
+        "nothing may be injected: {report}"
+    );
+    assert!(
+        report.contains("<span class=\"badge\" style=\"background: lightcoral\">Dirty (2)</span>"),
+        "statuses are colored badges: {report}"
+    );
+    assert!(
+        report.contains("<td class=\"number\">3</td>"),
+        "the ahead count is listed: {report}"
+    );
+    assert!(
+        report.contains("<li>broken: not a &quot;repository&quot;</li>"),
+        "failed repositories are listed: {report}"
+    );
+    assert!(
+        report.contains(Status::Busy.description()),
+        "the legend is included: {report}"
+    );
+}
+
+#[test]
+fn test_html_report_without_failures_has_no_failure_section() {
+    let report = html::report(&[repo("clean", Status::Clean)], &[]);
+    assert!(
+        !report.contains("<h2>Failed to process</h2>"),
+        "no failures to list: {report}"
+    );
+}
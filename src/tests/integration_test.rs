@@ -4,7 +4,7 @@ use std::path::Path;
 use git2::Repository;
 use tempfile::TempDir;
 
-use crate::cli::Args;
+use crate::{cli::Args, scanner::FetchSelector};
 
 /// Helper to create a git repository with initial commit
 fn create_git_repo_with_commit(path: &Path, repo_name: &str) -> Repository {
@@ -62,17 +62,17 @@ fn test_integration_single_clean_repository() {
     let _repo = create_git_repo_with_commit(temp_dir.path(), "test-repo");
 
     let args = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 1,
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     assert_eq!(repos.len(), 1);
     assert_eq!(failed.len(), 0);
     assert_eq!(repos[0].name, "test-repo");
-    assert!(repos[0].commits > 0);
+    assert!(repos[0].commits > Some(0));
     assert!(!repos[0].branch.is_empty());
 }
 
@@ -84,12 +84,12 @@ fn test_integration_multiple_repositories() {
     let _repo3 = create_dirty_repo(temp_dir.path(), "repo3");
 
     let args = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 1,
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     assert_eq!(repos.len(), 3);
     assert_eq!(failed.len(), 0);
@@ -122,21 +122,21 @@ fn test_integration_nested_repositories_with_depth() {
 
     // Test depth 1 - should only find root repo
     let args_depth1 = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 1,
         ..Default::default()
     };
-    let (repos_depth1, _) = args_depth1.find_repositories();
+    let (repos_depth1, _) = args_depth1.find_repositories().unwrap();
     assert_eq!(repos_depth1.len(), 1);
     assert_eq!(repos_depth1[0].name, "root-repo");
 
     // Test depth 3 - should find all repos
     let args_depth3 = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 3,
         ..Default::default()
     };
-    let (repos_depth3, _) = args_depth3.find_repositories();
+    let (repos_depth3, _) = args_depth3.find_repositories().unwrap();
     assert_eq!(repos_depth3.len(), 3);
 
     let repo_names: Vec<&str> = repos_depth3.iter().map(|r| r.name.as_str()).collect();
@@ -158,13 +158,13 @@ fn test_integration_subdir_functionality() {
 
     // Test with subdir option
     let args = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 3,
         subdir: Some("checkout".to_owned()),
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     assert_eq!(repos.len(), 1);
     assert_eq!(failed.len(), 0);
@@ -190,12 +190,12 @@ fn test_integration_find_repositories_returns_sorted_results() {
     }
 
     let args = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 1,
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     let paths: Vec<&str> = repos.iter().map(|r| r.repo_path.as_str()).collect();
     assert_eq!(
@@ -204,14 +204,14 @@ fn test_integration_find_repositories_returns_sorted_results() {
         "repositories must be sorted case-insensitively"
     );
     assert_eq!(
-        failed,
+        failed.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(),
         ["echo-broken", "zeta-broken"],
         "failed repositories must be sorted"
     );
 
     // The order must not depend on the parallel scheduling of a particular run.
     for _ in 0..5 {
-        let (again, failed_again) = args.find_repositories();
+        let (again, failed_again) = args.find_repositories().unwrap();
         assert_eq!(
             again.iter().map(|r| &r.repo_path).collect::<Vec<_>>(),
             repos.iter().map(|r| &r.repo_path).collect::<Vec<_>>(),
@@ -239,12 +239,12 @@ fn test_integration_mixed_git_and_non_git_directories() {
     fs::create_dir_all(&empty_dir).unwrap();
 
     let args = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 1,
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     assert_eq!(repos.len(), 1);
     assert_eq!(failed.len(), 0);
@@ -276,12 +276,12 @@ fn test_integration_repository_with_stashes() {
     .unwrap();
 
     let args = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 1,
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     assert_eq!(repos.len(), 1);
     assert_eq!(failed.len(), 0);
@@ -298,13 +298,13 @@ fn test_integration_repository_with_remote() {
         .unwrap();
 
     let args = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 1,
         remote: true,
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     assert_eq!(repos.len(), 1);
     assert_eq!(failed.len(), 0);
@@ -332,12 +332,12 @@ fn test_integration_repository_fast_forward() {
 
     // Test that the clone was NOT fast-forwarded
     let args = Args {
-        dir: local_temp_dir.path().to_path_buf(),
+        dirs: vec![local_temp_dir.path().to_path_buf()],
         fast_forward: true,
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     assert_eq!(repos.len(), 1);
     assert_eq!(failed.len(), 0);
@@ -372,13 +372,14 @@ fn test_integration_repository_fast_forward() {
 
     // Test that the clone was fast-forwarded, and that the reported state describes the
     // repository *after* the merge rather than before it.
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     assert_eq!(repos.len(), 1);
     assert_eq!(failed.len(), 0);
     assert!(repos[0].fast_forwarded);
     assert_eq!(
-        repos[0].commits, 2,
+        repos[0].commits,
+        Some(2),
         "commit count must be gathered after the fast-forward"
     );
     assert_eq!(
@@ -387,11 +388,11 @@ fn test_integration_repository_fast_forward() {
     );
 
     // Test that the clone is now up to date and doesn't need fast-forward
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     assert_eq!(repos.len(), 1);
     assert_eq!(failed.len(), 0);
-    assert_eq!(repos[0].commits, 2);
+    assert_eq!(repos[0].commits, Some(2));
     assert_eq!(repos[0].behind, 0);
     assert!(!repos[0].fast_forwarded);
 }
@@ -404,19 +405,15 @@ fn test_integration_fetch_on_repo_without_remote_is_not_a_failure() {
     let _repo = create_git_repo_with_commit(temp_dir.path(), "local-only");
 
     let args = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 1,
-        fetch: true,
+        fetch: Some(FetchSelector::All),
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
-    assert_eq!(
-        failed,
-        Vec::<String>::new(),
-        "repo must not be reported as failed"
-    );
+    assert_eq!(failed, Vec::new(), "repo must not be reported as failed");
     assert_eq!(repos.len(), 1, "repo must still be listed");
     assert_eq!(repos[0].name, "local-only");
     assert!(!repos[0].fast_forwarded);
@@ -430,20 +427,16 @@ fn test_integration_fast_forward_without_upstream_is_not_a_failure() {
     let _repo = create_git_repo_with_commit(temp_dir.path(), "no-upstream");
 
     let args = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 1,
-        fetch: true,
+        fetch: Some(FetchSelector::All),
         fast_forward: true,
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
-    assert_eq!(
-        failed,
-        Vec::<String>::new(),
-        "repo must not be reported as failed"
-    );
+    assert_eq!(failed, Vec::new(), "repo must not be reported as failed");
     assert_eq!(repos.len(), 1, "repo must still be listed");
     assert!(!repos[0].fast_forwarded);
     assert!(repos[0].is_local_only);
@@ -509,12 +502,12 @@ fn test_integration_worktree_detection() {
 
     // Now scan the temp directory for repositories
     let args = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 2,
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     // We should find exactly 2 repositories: main repo and worktree
     assert_eq!(failed.len(), 0, "Failed repos: {failed:?}");
@@ -619,12 +612,12 @@ fn test_integration_worktree_with_changes() {
 
     // Scan repositories
     let args = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 2,
         ..Default::default()
     };
 
-    let (repos, _failed) = args.find_repositories();
+    let (repos, _failed) = args.find_repositories().unwrap();
 
     // Find worktree
     let worktree = repos.iter().find(|r| r.is_worktree).unwrap();
@@ -648,11 +641,11 @@ fn test_integration_scanning_a_repository_directly_shows_its_name() {
     let repo_dir = temp_dir.path().join("my-repo");
 
     let scanned_directly = Args {
-        dir: repo_dir.clone(),
+        dirs: vec![repo_dir.clone()],
         depth: 1,
         ..Default::default()
     };
-    let (repos, failed) = scanned_directly.find_repositories();
+    let (repos, failed) = scanned_directly.find_repositories().unwrap();
 
     assert_eq!(failed.len(), 0);
     assert_eq!(repos.len(), 1);
@@ -663,11 +656,11 @@ fn test_integration_scanning_a_repository_directly_shows_its_name() {
 
     // Scanning the parent must produce the same label for the same repository.
     let scanned_from_parent = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 1,
         ..Default::default()
     };
-    let (from_parent, _) = scanned_from_parent.find_repositories();
+    let (from_parent, _) = scanned_from_parent.find_repositories().unwrap();
     assert_eq!(from_parent[0].repo_path, repos[0].repo_path);
 
     // The absolute location is still available in the dedicated path field (`--path`).
@@ -695,11 +688,11 @@ fn test_integration_depth_edge_values() {
 
     let scan = |depth: i32| {
         let args = Args {
-            dir: temp_dir.path().to_path_buf(),
+            dirs: vec![temp_dir.path().to_path_buf()],
             depth,
             ..Default::default()
         };
-        let (repos, _) = args.find_repositories();
+        let (repos, _) = args.find_repositories().unwrap();
         repos.len()
     };
 
@@ -749,13 +742,13 @@ fn test_integration_unlimited_depth_ignores_git_internals() {
 
     // An unlimited scan walks everything below the root, git directories included.
     let args = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: -1,
         ..Default::default()
     };
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
-    assert_eq!(failed, Vec::<String>::new());
+    assert_eq!(failed, Vec::new());
     assert_eq!(
         repos.len(),
         2,
@@ -785,11 +778,11 @@ fn test_integration_subdir_finds_repo_below_the_scanned_level() {
 
     // Depth 1 only reaches `project`, so without --subdir there is nothing to find.
     let without_subdir = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 1,
         ..Default::default()
     };
-    let (repos, failed) = without_subdir.find_repositories();
+    let (repos, failed) = without_subdir.find_repositories().unwrap();
     assert_eq!(
         repos.len(),
         0,
@@ -799,24 +792,24 @@ fn test_integration_subdir_finds_repo_below_the_scanned_level() {
 
     // With --subdir, `project/checkout` is inspected and found.
     let with_subdir = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 1,
         subdir: Some("checkout".to_owned()),
         ..Default::default()
     };
-    let (repos, failed) = with_subdir.find_repositories();
+    let (repos, failed) = with_subdir.find_repositories().unwrap();
     assert_eq!(failed.len(), 0);
     assert_eq!(repos.len(), 1, "--subdir must find the nested checkout");
     assert_eq!(repos[0].repo_path, "project/checkout");
 
     // A subdir that does not exist anywhere must simply yield nothing.
     let missing_subdir = Args {
-        dir: temp_dir.path().to_path_buf(),
+        dirs: vec![temp_dir.path().to_path_buf()],
         depth: 1,
         subdir: Some("does-not-exist".to_owned()),
         ..Default::default()
     };
-    let (repos, failed) = missing_subdir.find_repositories();
+    let (repos, failed) = missing_subdir.find_repositories().unwrap();
     assert_eq!(repos.len(), 0);
     assert_eq!(failed.len(), 0);
 }
@@ -839,13 +832,13 @@ fn test_integration_clean_and_unpushed_against_a_remote() {
     drop(config);
 
     let args = Args {
-        dir: local_dir.path().to_path_buf(),
+        dirs: vec![local_dir.path().to_path_buf()],
         depth: 1,
         ..Default::default()
     };
 
     // A fresh clone tracks its remote and matches it exactly.
-    let (repos, _) = args.find_repositories();
+    let (repos, _) = args.find_repositories().unwrap();
     assert_eq!(repos.len(), 1);
     assert_eq!(repos[0].status, crate::gitinfo::status::Status::Clean);
     assert!(!repos[0].has_unpushed);
@@ -864,7 +857,7 @@ fn test_integration_clean_and_unpushed_against_a_remote() {
         .commit(Some("HEAD"), &sig, &sig, "local commit", &tree, &[&parent])
         .unwrap();
 
-    let (repos, _) = args.find_repositories();
+    let (repos, _) = args.find_repositories().unwrap();
     assert_eq!(repos.len(), 1);
     assert_eq!(
         repos[0].status,
@@ -875,3 +868,83 @@ fn test_integration_clean_and_unpushed_against_a_remote() {
     assert_eq!(repos[0].ahead, 1);
     assert_eq!(repos[0].behind, 0);
 }
+
+/// `--list-only` finds the same repositories as a full scan, in the same order, without
+/// computing any status.
+#[test]
+fn test_integration_list_repositories_matches_the_full_scan() {
+    let temp_dir = TempDir::new().unwrap();
+    for name in ["beta", "Alpha", "gamma"] {
+        create_git_repo_with_commit(temp_dir.path(), name);
+    }
+    let with_remote = create_git_repo_with_commit(temp_dir.path(), "delta");
+    with_remote
+        .remote("origin", "https://github.com/example/delta-upstream.git")
+        .unwrap();
+    let broken = temp_dir.path().join("broken");
+    fs::create_dir_all(&broken).unwrap();
+    fs::write(broken.join(".git"), "not a git directory").unwrap();
+
+    let args = Args {
+        dirs: vec![temp_dir.path().to_path_buf()],
+        depth: 1,
+        remote: true,
+        list_only: true,
+        ..Default::default()
+    };
+
+    let (listed, listed_failed) = args.list_repositories().unwrap();
+    let (scanned, scanned_failed) = args.find_repositories().unwrap();
+
+    assert_eq!(
+        listed.iter().map(|r| &r.repo_path).collect::<Vec<_>>(),
+        scanned.iter().map(|r| &r.repo_path).collect::<Vec<_>>(),
+    );
+    assert_eq!(listed_failed, scanned_failed);
+    assert_eq!(listed_failed[0].name, "broken");
+
+    let delta = listed.iter().find(|r| r.repo_path == "delta").unwrap();
+    assert_eq!(
+        delta.name, "delta-upstream",
+        "the name comes from the remote"
+    );
+    assert_eq!(
+        delta.remote_url.as_deref(),
+        Some("https://github.com/example/delta-upstream.git")
+    );
+    assert!(
+        listed
+            .iter()
+            .filter(|r| r.repo_path != "delta")
+            .all(|r| r.remote_url.is_none())
+    );
+}
+
+/// A repository scanned again on its own keeps the columns of the full scan, e.g. its
+/// remote URL.
+#[test]
+fn test_integration_rescan_matches_the_full_scan() {
+    let temp_dir = TempDir::new().unwrap();
+    let repo = create_git_repo_with_commit(temp_dir.path(), "tool");
+    repo.remote("origin", "https://github.com/example/tool.git")
+        .unwrap();
+
+    let args = Args {
+        dirs: vec![temp_dir.path().to_path_buf()],
+        depth: 1,
+        remote: true,
+        ..Default::default()
+    };
+    let (repos, _) = args.find_repositories().unwrap();
+    assert_eq!(
+        repos[0].remote_url.as_deref(),
+        Some("https://github.com/example/tool.git")
+    );
+
+    let rescanned = args.rescan(&repos[0].path);
+    assert!(rescanned.failed.is_empty());
+    assert_eq!(
+        serde_json::to_value(&rescanned.repos).unwrap(),
+        serde_json::to_value(&repos).unwrap()
+    );
+}
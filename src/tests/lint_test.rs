@@ -0,0 +1,88 @@
+use git2::{Oid, Repository, Signature};
+use tempfile::TempDir;
+
+use crate::{
+    gitinfo::repoinfo::RepoListing,
+    lint::{self, CommitPolicy},
+};
+
+/// Commits an empty tree on top of HEAD with the given message.
+fn commit(repo: &Repository, message: &str) -> Oid {
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    let tree = repo
+        .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+        .unwrap();
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let parents: Vec<_> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+        .unwrap()
+}
+
+#[test]
+fn test_conventional_policy() {
+    let policy: CommitPolicy = "conventional".parse().unwrap();
+    for subject in [
+        "feat: add --lint-commits",
+        "fix(scanner): skip bare repositories",
+        "refactor!: drop the old output",
+        "chore(deps)!: bump git2",
+    ] {
+        assert_eq!(policy.check(subject), None, "{subject}");
+    }
+    for (subject, reason) in [
+        ("Add --lint-commits", "no `type: description`"),
+        ("feature: add --lint-commits", "unknown type `feature`"),
+        ("Fix: typo", "unknown type `Fix`"),
+        ("fix(): typo", "invalid scope `()`"),
+        ("fix(scanner: typo", "invalid scope `(scanner`"),
+        ("fix:  ", "no description"),
+    ] {
+        assert_eq!(policy.check(subject).as_deref(), Some(reason), "{subject}");
+    }
+}
+
+#[test]
+fn test_pattern_policy() {
+    let policy: CommitPolicy = r"^[A-Z]+-\d+ ".parse().unwrap();
+    assert_eq!(policy.check("GS-42 Add --lint-commits"), None);
+    assert_eq!(
+        policy.check("Add --lint-commits").as_deref(),
+        Some(r"does not match `^[A-Z]+-\d+ `")
+    );
+    let error = "(unclosed".parse::<CommitPolicy>().unwrap_err();
+    assert!(error.contains("neither `conventional` nor"), "{error}");
+}
+
+#[test]
+fn test_lint_commits_checks_only_unpushed_commits() {
+    let temp = TempDir::new().unwrap();
+    let repo = Repository::init(temp.path()).unwrap();
+    let pushed = commit(&repo, "not checked, already pushed");
+    commit(&repo, "feat: add the first half");
+    commit(&repo, "wip");
+    let repos = [RepoListing::new(&repo, "tool", false, temp.path())];
+    let policy = CommitPolicy::Conventional;
+
+    let mut out = Vec::new();
+    lint::lint_commits(&repos, &policy, &mut out).unwrap();
+    assert!(out.is_empty(), "without a remote there is nothing to push");
+
+    repo.remote("origin", "https://example.com/tool.git")
+        .unwrap();
+    repo.reference("refs/remotes/origin/main", pushed, false, "fetched")
+        .unwrap();
+    let unpushed: Vec<_> = lint::unpushed_commits(&repo)
+        .into_iter()
+        .map(|(_, subject)| subject)
+        .collect();
+    assert_eq!(unpushed, ["feat: add the first half", "wip"]);
+
+    let mut out = Vec::new();
+    lint::lint_commits(&repos, &policy, &mut out).unwrap();
+    let listed = String::from_utf8(out).unwrap();
+    assert_eq!(listed.lines().count(), 1, "{listed}");
+    assert!(
+        listed.ends_with(" wip (no `type: description`)\n"),
+        "{listed}"
+    );
+}
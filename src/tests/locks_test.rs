@@ -0,0 +1,37 @@
+use std::{
+    fs,
+    time::{Duration, SystemTime},
+};
+
+use git2::Repository;
+use tempfile::TempDir;
+
+use crate::{gitinfo::repoinfo::RepoListing, locks};
+
+#[test]
+fn test_remove_stale_locks_only_removes_with_apply() {
+    let temp = TempDir::new().unwrap();
+    let repo = Repository::init(temp.path()).unwrap();
+    let stale = repo.path().join("index.lock");
+    fs::File::create(&stale)
+        .unwrap()
+        .set_modified(SystemTime::now() - Duration::from_hours(3))
+        .unwrap();
+    let fresh = repo.path().join("config.lock");
+    fs::write(&fresh, "").unwrap();
+    let repos = [RepoListing::new(&repo, "tool", false, temp.path())];
+
+    let mut out = Vec::new();
+    locks::remove_stale_locks(&repos, false, &mut out).unwrap();
+    let listed = String::from_utf8(out).unwrap();
+    assert_eq!(listed.lines().count(), 1, "{listed}");
+    assert!(listed.contains("index.lock (3h old)"), "{listed}");
+    assert!(stale.exists(), "a dry run must not remove anything");
+
+    let mut out = Vec::new();
+    locks::remove_stale_locks(&repos, true, &mut out).unwrap();
+    let removed = String::from_utf8(out).unwrap();
+    assert!(removed.ends_with("removed\n"), "{removed}");
+    assert!(!stale.exists(), "the stale lock should be removed");
+    assert!(fresh.exists(), "a lock in use must be kept");
+}
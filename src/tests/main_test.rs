@@ -1,15 +1,22 @@
-use std::{fs, io, path::Path, path::PathBuf};
+use std::{fs, io, num::NonZeroUsize, path::Path, path::PathBuf};
 
 use clap::Parser;
 use clap_complete::Shell;
 use git2::Repository;
+use parking_lot::Mutex;
 use tempfile::TempDir;
 
 use crate::{
-    cli::Args,
+    cli::{Args, Command, OutputFormat, TooManyFailures},
     completions,
-    gitinfo::{repoinfo::RepoInfo, status::Status},
+    gitinfo::{
+        repoinfo::{LfsStatus, OperationProgress, RepoInfo},
+        status::Status,
+    },
+    report::Rollup,
     run,
+    scanner::FailureBudget,
+    snapshot::Snapshot,
 };
 
 fn repo_info_with_status(status: Status, stash_count: usize, fast_forwarded: bool) -> RepoInfo {
@@ -18,8 +25,12 @@ fn repo_info_with_status(status: Status, stash_count: usize, fast_forwarded: boo
         branch: "main".to_owned(),
         ahead: 3,
         behind: 1,
-        commits: 42,
+        commits: Some(42),
         status,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
         has_unpushed: true,
         remote_url: Some("https://example.com/repo.git".to_owned()),
         path: PathBuf::from("/tmp/repo"),
@@ -28,6 +39,23 @@ fn repo_info_with_status(status: Status, stash_count: usize, fast_forwarded: boo
         fast_forwarded,
         repo_path: "repo".to_owned(),
         is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
     }
 }
 
@@ -44,6 +72,56 @@ fn test_repo_info_format_local_status_with_upstream_counts() {
     assert_eq!(repo.format_local_status(), "↑3 ↓1");
 }
 
+#[test]
+fn test_repo_info_format_local_status_of_fork_behind_upstream() {
+    let mut repo = repo_info_with_status(Status::Clean, 0, false);
+    repo.fork_behind = Some(0);
+    assert_eq!(repo.format_local_status(), "↑3 ↓1");
+    repo.fork_behind = Some(5);
+    assert_eq!(repo.format_local_status(), "↑3 ↓1 (fork ↓5)");
+    repo.gone_branches = 2;
+    assert_eq!(repo.format_local_status(), "↑3 ↓1 (fork ↓5) (2 gone)");
+}
+
+#[test]
+fn test_repo_info_format_local_status_short() {
+    let mut repo = repo_info_with_status(Status::Clean, 0, false);
+    repo.fork_behind = Some(5);
+    assert_eq!(repo.format_local_status_short(), "↑3↓1");
+    repo.behind = 0;
+    assert_eq!(repo.format_local_status_short(), "↑3");
+    repo.ahead = 0;
+    assert_eq!(repo.format_local_status_short(), "=");
+    repo.is_local_only = true;
+    assert_eq!(repo.format_local_status_short(), "local");
+}
+
+#[test]
+fn test_repo_info_format_clone_kind() {
+    let mut repo = repo_info_with_status(Status::Clean, 0, false);
+    assert_eq!(repo.format_clone_kind(), "");
+    repo.is_shallow = true;
+    assert_eq!(repo.format_clone_kind(), "shallow");
+    repo.is_partial = true;
+    assert_eq!(repo.format_clone_kind(), "shallow, partial");
+}
+
+#[test]
+fn test_repo_info_format_lfs() {
+    let mut repo = repo_info_with_status(Status::Clean, 0, false);
+    assert_eq!(repo.format_lfs(), "-");
+    repo.lfs = Some(LfsStatus {
+        objects: 4,
+        missing: 0,
+    });
+    assert_eq!(repo.format_lfs(), "ok");
+    repo.lfs = Some(LfsStatus {
+        objects: 4,
+        missing: 3,
+    });
+    assert_eq!(repo.format_lfs(), "3/4 missing");
+}
+
 #[test]
 fn test_repo_info_format_status_with_stash_only() {
     let repo = repo_info_with_status(Status::Dirty(2), 4, false);
@@ -62,6 +140,45 @@ fn test_repo_info_format_status_with_stash_and_fast_forward() {
     assert_eq!(repo.format_status_with_stash_and_ff(), "Unpushed (2*) ↑↑");
 }
 
+#[test]
+fn test_repo_info_format_status_with_conflicts() {
+    let mut repo = repo_info_with_status(Status::Merge, 0, false);
+    repo.conflicts = 4;
+    assert_eq!(
+        repo.format_status_with_stash_and_ff(),
+        "Merge (4 conflicts)"
+    );
+    repo.conflicts = 1;
+    repo.stash_count = 2;
+    assert_eq!(
+        repo.format_status_with_stash_and_ff(),
+        "Merge (1 conflict) (2*)"
+    );
+}
+
+#[test]
+fn test_repo_info_format_status_with_progress() {
+    let mut repo = repo_info_with_status(Status::Rebase, 0, false);
+    repo.progress = Some(OperationProgress {
+        step: 3,
+        total: 7,
+        branch: Some("feature".to_owned()),
+        onto: Some("main".to_owned()),
+    });
+    repo.conflicts = 2;
+    assert_eq!(
+        repo.format_status_with_stash_and_ff(),
+        "Rebase 3/7 (feature onto main) (2 conflicts)"
+    );
+    repo.progress = Some(OperationProgress {
+        step: 1,
+        total: 2,
+        ..OperationProgress::default()
+    });
+    repo.conflicts = 0;
+    assert_eq!(repo.format_status_with_stash_and_ff(), "Rebase 1/2");
+}
+
 #[test]
 fn test_args_parse_json_fast_forward_and_subdir() {
     let args = Args::parse_from([
@@ -117,63 +234,331 @@ fn scan_dir() -> TempDir {
 fn test_run_prints_table() {
     let temp = scan_dir();
     let args = Args {
-        dir: temp.path().to_path_buf(),
+        dirs: vec![temp.path().to_path_buf()],
         depth: 1,
         ..Default::default()
     };
-    run(&args, &mut io::sink());
+    run(&args, &mut io::sink()).unwrap();
 }
 
 #[test]
 fn test_run_with_all_display_options() {
     let temp = scan_dir();
     let args = Args {
-        dir: temp.path().to_path_buf(),
+        dirs: vec![temp.path().to_path_buf()],
         depth: 1,
         remote: true,
         path: true,
         condensed: true,
         summary: true,
+        activity: true,
+        maintenance: true,
+        show_ignored: true,
+        hidden_files: true,
+        verify_signatures: NonZeroUsize::new(3),
         ..Default::default()
     };
-    run(&args, &mut io::sink());
+    run(&args, &mut io::sink()).unwrap();
 }
 
 #[test]
 fn test_run_with_non_clean_filter() {
     let temp = scan_dir();
     let args = Args {
-        dir: temp.path().to_path_buf(),
+        dirs: vec![temp.path().to_path_buf()],
         depth: 1,
         non_clean: true,
         summary: true,
         ..Default::default()
     };
-    run(&args, &mut io::sink());
+    run(&args, &mut io::sink()).unwrap();
+}
+
+#[test]
+fn test_run_with_gitignore_advice() {
+    let temp = scan_dir();
+    fs::create_dir_all(temp.path().join("dirty-repo/node_modules")).unwrap();
+    fs::write(temp.path().join("dirty-repo/node_modules/dep.js"), "").unwrap();
+    let args = Args {
+        dirs: vec![temp.path().to_path_buf()],
+        depth: 1,
+        gitignore_advice: true,
+        ..Default::default()
+    };
+    run(&args, &mut io::sink()).unwrap();
+}
+
+#[test]
+fn test_run_list_only() {
+    let temp = scan_dir();
+    for json in [false, true] {
+        let args = Args {
+            dirs: vec![temp.path().to_path_buf()],
+            depth: 1,
+            list_only: true,
+            path: true,
+            remote: true,
+            json,
+            ..Default::default()
+        };
+        run(&args, &mut io::sink()).unwrap();
+    }
 }
 
 #[test]
 fn test_run_json() {
     let temp = scan_dir();
     let args = Args {
-        dir: temp.path().to_path_buf(),
+        dirs: vec![temp.path().to_path_buf()],
         depth: 1,
         json: true,
         ..Default::default()
     };
-    run(&args, &mut io::sink());
+    run(&args, &mut io::sink()).unwrap();
+}
+
+#[test]
+fn test_run_plain_output() {
+    let temp = scan_dir();
+    let args = Args {
+        dirs: vec![temp.path().to_path_buf()],
+        depth: 1,
+        output: OutputFormat::Plain,
+        null: true,
+        ..Default::default()
+    };
+    run(&args, &mut io::sink()).unwrap();
+    run(
+        &Args {
+            list_only: true,
+            ..args
+        },
+        &mut io::sink(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_run_streams_plain_and_ndjson_output() {
+    let temp = scan_dir();
+    let args = Args {
+        dirs: vec![temp.path().to_path_buf()],
+        depth: 1,
+        output: OutputFormat::Ndjson,
+        ..Default::default()
+    };
+    run(&args, &mut io::sink()).unwrap();
+
+    let streamed = Mutex::new(Vec::new());
+    let (repos, _) = args
+        .find_repositories_with(|repo| {
+            streamed.lock().push(repo.repo_path.clone());
+        })
+        .unwrap();
+    let mut streamed = streamed.into_inner();
+    streamed.sort();
+    assert_eq!(
+        streamed,
+        repos
+            .iter()
+            .map(|r| r.repo_path.clone())
+            .collect::<Vec<_>>(),
+        "every repository is passed on while scanning"
+    );
+
+    for output in [OutputFormat::Plain, OutputFormat::Ndjson] {
+        let args = Args {
+            dirs: vec![temp.path().to_path_buf()],
+            depth: 1,
+            output,
+            stream: true,
+            ..Default::default()
+        };
+        run(&args, &mut io::sink()).unwrap();
+    }
+    let table = Args {
+        dirs: vec![temp.path().to_path_buf()],
+        stream: true,
+        ..Default::default()
+    };
+    assert!(
+        run(&table, &mut io::sink()).is_err(),
+        "a table cannot be streamed"
+    );
+}
+
+#[test]
+fn test_run_dot_output() {
+    let temp = scan_dir();
+    let args = Args {
+        dirs: vec![temp.path().to_path_buf()],
+        depth: 1,
+        output: OutputFormat::Dot,
+        ..Default::default()
+    };
+    run(&args, &mut io::sink()).unwrap();
+
+    let args = Args {
+        list_only: true,
+        ..args
+    };
+    assert!(
+        run(&args, &mut io::sink()).is_err(),
+        "--list-only has no status to draw a graph with"
+    );
+}
+
+#[cfg(feature = "html")]
+#[test]
+fn test_run_html_output() {
+    let temp = scan_dir();
+    let args = Args {
+        dirs: vec![temp.path().to_path_buf()],
+        depth: 1,
+        output: OutputFormat::Html,
+        ..Default::default()
+    };
+    run(&args, &mut io::sink()).unwrap();
+
+    let args = Args {
+        command: Some(Command::Report {
+            rollup: Rollup::Org,
+        }),
+        ..args
+    };
+    let error = run(&args, &mut io::sink()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "`report` cannot be combined with --output html"
+    );
+}
+
+#[test]
+fn test_run_report() {
+    let temp = scan_dir();
+    let args = Args {
+        dirs: vec![temp.path().to_path_buf()],
+        depth: 1,
+        command: Some(Command::Report {
+            rollup: Rollup::Org,
+        }),
+        ..Default::default()
+    };
+    run(&args, &mut io::sink()).unwrap();
+
+    let args = Args { json: true, ..args };
+    run(&args, &mut io::sink()).unwrap();
+}
+
+#[test]
+fn test_run_snapshot_and_diff() {
+    let temp = scan_dir();
+    let state = TempDir::new().unwrap();
+    let snapshot = state.path().join("snapshot.json");
+    let args = Args {
+        dirs: vec![temp.path().to_path_buf()],
+        depth: 1,
+        snapshot: Some(snapshot.clone()),
+        ..Default::default()
+    };
+    run(&args, &mut io::sink()).unwrap();
+    let saved = Snapshot::read(&snapshot).unwrap();
+    assert_eq!(saved.repositories.len(), 2);
+
+    // Diffing against the file and replacing it in the same run.
+    fs::write(temp.path().join("clean-repo/new.txt"), "change").unwrap();
+    let args = Args {
+        diff: Some(snapshot.clone()),
+        ..args
+    };
+    run(&args, &mut io::sink()).unwrap();
+    let saved = Snapshot::read(&snapshot).unwrap();
+    assert_eq!(saved.repositories[0].status, Status::Dirty(1));
+
+    let args = Args {
+        diff: Some(state.path().join("missing.json")),
+        snapshot: None,
+        ..args
+    };
+    assert!(
+        run(&args, &mut io::sink()).is_err(),
+        "diffing against a missing snapshot must fail"
+    );
+}
+
+#[test]
+fn test_run_registers_and_rescans_repositories() {
+    let temp = scan_dir();
+    let state = TempDir::new().unwrap();
+    let registry = state.path().join("data/registry.json");
+    let args = Args {
+        dirs: vec![temp.path().to_path_buf()],
+        depth: 1,
+        registry: Some(registry.clone()),
+        ..Default::default()
+    };
+    run(&args, &mut io::sink()).unwrap();
+    let registered = crate::registry::load(&registry).unwrap();
+    assert_eq!(registered.len(), 2, "{registered:?}");
+
+    // Without walking, the registered repositories are found from anywhere.
+    fs::remove_dir_all(temp.path().join("dirty-repo")).unwrap();
+    let snapshot = state.path().join("snapshot.json");
+    let args = Args {
+        dirs: vec![state.path().to_path_buf()],
+        registered: true,
+        snapshot: Some(snapshot.clone()),
+        ..args
+    };
+    run(&args, &mut io::sink()).unwrap();
+    let saved = Snapshot::read(&snapshot).unwrap();
+    assert_eq!(saved.repositories.len(), 1);
+    assert_eq!(saved.repositories[0].name, "clean-repo");
+    assert_eq!(
+        crate::registry::load(&registry).unwrap().len(),
+        1,
+        "the deleted repository should be dropped from the registry"
+    );
+}
+
+#[test]
+fn test_scan_reads_the_paths_from_a_file() {
+    let temp = scan_dir();
+    let state = TempDir::new().unwrap();
+    let list = state.path().join("paths");
+    fs::write(&list, "clean-repo\0").unwrap();
+    let args = Args {
+        dirs: vec![temp.path().to_path_buf()],
+        depth: 1,
+        paths_from: Some(list),
+        ..Default::default()
+    };
+    let result = args.scanner().unwrap().scan();
+    let names: Vec<_> = result.repos.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(names, ["clean-repo"]);
+
+    let missing = state.path().join("missing");
+    let args = Args {
+        paths_from: Some(missing.clone()),
+        ..args
+    };
+    let error = format!("{:#}", args.scanner().unwrap_err());
+    assert!(
+        error.contains(&missing.display().to_string()),
+        "a list that cannot be read fails the scan: {error}"
+    );
 }
 
 #[test]
 fn test_run_on_directory_without_repositories() {
     let temp = TempDir::new().unwrap();
     let args = Args {
-        dir: temp.path().to_path_buf(),
+        dirs: vec![temp.path().to_path_buf()],
         depth: 1,
         summary: true,
         ..Default::default()
     };
-    run(&args, &mut io::sink());
+    run(&args, &mut io::sink()).unwrap();
 }
 
 /// A directory whose `.git` git cannot open is reported as failed, not as a hard error.
@@ -185,12 +570,50 @@ fn test_run_reports_failed_repositories() {
     fs::write(broken.join(".git"), "not a git directory").unwrap();
 
     let args = Args {
-        dir: temp.path().to_path_buf(),
+        dirs: vec![temp.path().to_path_buf()],
         depth: 1,
         summary: true,
         ..Default::default()
     };
-    run(&args, &mut io::sink());
+    run(&args, &mut io::sink()).unwrap();
+}
+
+#[test]
+fn test_run_gives_up_above_max_failures() {
+    let temp = TempDir::new().unwrap();
+    create_repo(temp.path(), "good-repo", false);
+    for name in ["broken-a", "broken-b"] {
+        let broken = temp.path().join(name);
+        fs::create_dir_all(&broken).unwrap();
+        fs::write(broken.join(".git"), "not a git directory").unwrap();
+    }
+
+    let args = Args {
+        dirs: vec![temp.path().to_path_buf()],
+        depth: 1,
+        max_failures: Some(FailureBudget::Count(1)),
+        ..Default::default()
+    };
+    let err = run(&args, &mut io::sink()).unwrap_err();
+    assert!(err.is::<TooManyFailures>());
+
+    let args = Args {
+        max_failures: Some(FailureBudget::Percent(100)),
+        ..args
+    };
+    run(&args, &mut io::sink()).unwrap();
+}
+
+#[cfg(feature = "daemon")]
+#[test]
+fn test_run_from_daemon_without_daemon_fails() {
+    let temp = TempDir::new().unwrap();
+    let args = Args {
+        from_daemon: true,
+        socket: Some(temp.path().join("missing.sock")),
+        ..Default::default()
+    };
+    assert!(run(&args, &mut io::sink()).is_err());
 }
 
 #[test]
@@ -201,7 +624,7 @@ fn test_run_legend() {
             condensed,
             ..Default::default()
         };
-        run(&args, &mut io::sink());
+        run(&args, &mut io::sink()).unwrap();
     }
 }
 
@@ -210,7 +633,7 @@ fn test_run_legend() {
 #[test]
 fn test_run_completions_short_circuits_the_scan() {
     let args = Args {
-        dir: PathBuf::from("/nonexistent/directory/that/does/not/exist"),
+        dirs: vec![PathBuf::from("/nonexistent/directory/that/does/not/exist")],
         depth: -1,
         completions: Some(Shell::Bash),
         // Would both be honoured if the scan were reached.
@@ -220,7 +643,7 @@ fn test_run_completions_short_circuits_the_scan() {
     };
 
     let mut out = Vec::new();
-    run(&args, &mut out);
+    run(&args, &mut out).unwrap();
 
     let script = String::from_utf8(out).unwrap();
     assert!(
@@ -250,3 +673,40 @@ fn test_completions_for_every_supported_shell() {
         );
     }
 }
+
+#[cfg(feature = "history")]
+#[test]
+fn test_run_history() {
+    let temp = scan_dir();
+    let state = TempDir::new().unwrap();
+    let db = state.path().join("history.db");
+    let args = Args {
+        dirs: vec![temp.path().to_path_buf()],
+        depth: 1,
+        history: Some(db.clone()),
+        ..Default::default()
+    };
+    run(&args, &mut io::sink()).unwrap();
+    run(&args, &mut io::sink()).unwrap();
+
+    let rows = crate::history::History::open(&db)
+        .unwrap()
+        .report()
+        .unwrap();
+    assert_eq!(rows.len(), 2);
+    assert!(rows.iter().all(|row| row.scans == 2), "both scans recorded");
+
+    let args = Args {
+        history_report: true,
+        ..args
+    };
+    run(&args, &mut io::sink()).unwrap();
+    let rows = crate::history::History::open(&db)
+        .unwrap()
+        .report()
+        .unwrap();
+    assert!(
+        rows.iter().all(|row| row.scans == 2),
+        "the report does not scan"
+    );
+}
@@ -0,0 +1,72 @@
+use std::fs;
+
+use git2::Repository;
+use tempfile::TempDir;
+
+use crate::{
+    gitinfo::repoinfo::RepoListing,
+    manifest::{Manifest, ManifestEntry},
+};
+
+#[test]
+fn test_manifest_round_trip() {
+    let temp = TempDir::new().unwrap();
+    let repo = Repository::init(temp.path().join("team/tool")).unwrap();
+    repo.remote("origin", "https://example.com/team/tool.git")
+        .unwrap();
+    let manifest = Manifest::new(&[RepoListing::new(&repo, "tool", true, temp.path())]);
+    assert_eq!(
+        manifest.repositories,
+        [ManifestEntry {
+            name: "tool".to_owned(),
+            path: "team/tool".to_owned(),
+            remote_url: Some("https://example.com/team/tool.git".to_owned()),
+        }]
+    );
+
+    let path = temp.path().join("manifest.json");
+    manifest.write(&path).unwrap();
+    assert_eq!(Manifest::read(&path).unwrap(), manifest);
+}
+
+#[test]
+fn test_clone_missing() {
+    let temp = TempDir::new().unwrap();
+    let origin = temp.path().join("origin");
+    Repository::init(&origin).unwrap();
+    let workspace = temp.path().join("workspace");
+    fs::create_dir_all(workspace.join("present")).unwrap();
+    let url = origin.display().to_string();
+    let entry = |path: &str, remote_url: Option<&str>| ManifestEntry {
+        name: path.to_owned(),
+        path: path.to_owned(),
+        remote_url: remote_url.map(ToOwned::to_owned),
+    };
+    let manifest = Manifest {
+        repositories: vec![
+            entry("team/tool", Some(&url)),
+            entry("present", Some(&url)),
+            entry("local-only", None),
+            entry("../escaped", Some(&url)),
+        ],
+    };
+
+    let mut out = Vec::new();
+    manifest.clone_missing(&workspace, &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        format!("team/tool: cloned from {url}\n")
+    );
+    assert!(
+        Repository::open(workspace.join("team/tool")).is_ok(),
+        "the missing repository should be cloned to its relative path"
+    );
+    assert!(
+        !temp.path().join("escaped").exists(),
+        "nothing may be cloned outside of the directory"
+    );
+
+    let mut out = Vec::new();
+    manifest.clone_missing(&workspace, &mut out).unwrap();
+    assert!(out.is_empty(), "nothing is missing anymore");
+}
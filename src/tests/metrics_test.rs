@@ -0,0 +1,89 @@
+use std::{path::PathBuf, time::Duration};
+
+use crate::{
+    gitinfo::{repoinfo::RepoInfo, status::Status},
+    metrics::{render, response},
+};
+
+fn repo(repo_path: &str, status: Status, ahead: usize, behind: usize) -> RepoInfo {
+    RepoInfo {
+        name: repo_path.to_owned(),
+        branch: "main".to_owned(),
+        ahead,
+        behind,
+        commits: Some(1),
+        status,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
+        has_unpushed: ahead > 0,
+        remote_url: None,
+        path: PathBuf::from("/path/to").join(repo_path),
+        stash_count: 0,
+        is_local_only: false,
+        fast_forwarded: false,
+        repo_path: repo_path.to_owned(),
+        is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
+    }
+}
+
+#[test]
+fn test_render_gauges() {
+    let repos = [
+        repo("api", Status::Dirty(2), 0, 1),
+        repo("web", Status::Unpushed, 3, 0),
+        repo("tool", Status::Unpushed, 2, 4),
+        repo("docs", Status::Clean, 0, 0),
+    ];
+    let text = render(&repos, 1, Duration::from_millis(1500));
+    let samples: Vec<_> = text.lines().filter(|line| !line.starts_with('#')).collect();
+    assert_eq!(
+        samples,
+        [
+            "git_statuses_repos_total 4",
+            "git_statuses_repos_dirty 1",
+            "git_statuses_repos_behind 2",
+            "git_statuses_repos_failed 1",
+            "git_statuses_commits_unpushed_total 5",
+            "git_statuses_scan_duration_seconds 1.500",
+        ]
+    );
+    assert!(text.starts_with(
+        "# HELP git_statuses_repos_total Number of repositories scanned.\n\
+         # TYPE git_statuses_repos_total gauge\n"
+    ));
+}
+
+#[test]
+fn test_response_serves_only_the_metrics_path() {
+    let ok = response("GET /metrics HTTP/1.1\r\n", "git_statuses_repos_total 4\n");
+    assert!(ok.starts_with("HTTP/1.1 200 OK\r\n"), "{ok}");
+    assert!(ok.contains("Content-Length: 27\r\n"), "{ok}");
+    assert!(ok.ends_with("\r\n\r\ngit_statuses_repos_total 4\n"), "{ok}");
+
+    let missing = response("GET / HTTP/1.1\r\n", "");
+    assert!(
+        missing.starts_with("HTTP/1.1 404 Not Found\r\n"),
+        "{missing}"
+    );
+    let post = response("POST /metrics HTTP/1.1\r\n", "");
+    assert!(post.starts_with("HTTP/1.1 405"), "{post}");
+}
@@ -1,7 +1,42 @@
+mod auth_test;
+mod bulk_test;
 mod cli_test;
+#[cfg(all(unix, feature = "daemon"))]
+mod daemon_test;
+mod diff_test;
+mod disk_usage_test;
+mod forge_test;
 mod gitinfo_test;
+#[cfg(feature = "history")]
+mod history_test;
+mod hooks_test;
+#[cfg(feature = "html")]
+mod html_test;
 mod integration_test;
+mod lint_test;
+mod locks_test;
 mod main_test;
+mod manifest_test;
+#[cfg(feature = "metrics")]
+mod metrics_test;
+mod pick_test;
+mod porcelain_test;
 mod printer_test;
+mod progress_test;
+mod prompt_test;
+mod prune_test;
+mod remotes_test;
+mod report_test;
+mod scanner_test;
+#[cfg(feature = "daemon")]
+mod service_test;
 mod smoke_test;
+#[cfg(feature = "template")]
+mod template_test;
+mod theme_test;
+mod throttle_test;
 mod util_test;
+#[cfg(feature = "fs-events")]
+mod watcher_test;
+mod webhook_test;
+mod workspace_test;
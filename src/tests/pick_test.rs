@@ -0,0 +1,74 @@
+use std::{fs, path::Path};
+
+use git2::Repository;
+use tempfile::TempDir;
+
+use crate::{
+    cli::Args,
+    pick::{self, candidates, picked_repo},
+    run,
+    scanner::Scanner,
+};
+
+/// Lays out the `alpha` and `beta` repositories.
+fn checkouts() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    for name in ["alpha", "beta"] {
+        let path = temp.path().join(name);
+        fs::create_dir_all(&path).unwrap();
+        Repository::init(&path).unwrap();
+    }
+    temp
+}
+
+#[test]
+fn test_candidates_start_with_the_relative_path() {
+    let temp = checkouts();
+    let repos = Scanner::new(temp.path()).scan().repos;
+    let lines = candidates(&repos);
+    let first_fields: Vec<_> = lines
+        .lines()
+        .map(|line| line.split('\t').next().unwrap())
+        .collect();
+    assert_eq!(first_fields, ["alpha", "beta"]);
+}
+
+#[test]
+fn test_picked_repo_is_found_by_its_line() {
+    let temp = checkouts();
+    let repos = Scanner::new(temp.path()).scan().repos;
+    let picked = picked_repo(&repos, "beta\tmaster\tClean\n").unwrap();
+    assert_eq!(picked.repo_path, "beta");
+    assert!(picked_repo(&repos, "").is_none(), "nothing was picked");
+    assert!(
+        picked_repo(&repos, "gamma\n").is_none(),
+        "no such repository"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pick_prints_the_path_of_the_picked_repository() {
+    let temp = checkouts();
+    let args = Args {
+        dirs: vec![temp.path().to_path_buf()],
+        pick: true,
+        picker: "tail -n 1".to_owned(),
+        ..Default::default()
+    };
+    let mut out = Vec::new();
+    run(&args, &mut out).unwrap();
+    let path = String::from_utf8(out).unwrap();
+    assert_eq!(Path::new(path.trim_end()), temp.path().join("beta"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pick_fails_when_the_picker_is_cancelled() {
+    let temp = checkouts();
+    let repos = Scanner::new(temp.path()).scan().repos;
+    let mut out = Vec::new();
+    let error = pick::pick(&repos, "cat > /dev/null; exit 130", &mut out).unwrap_err();
+    assert_eq!(error.to_string(), "No repository was picked");
+    assert!(out.is_empty(), "nothing is printed");
+}
@@ -0,0 +1,96 @@
+use std::fs;
+
+use clap::Parser as _;
+use git2::Repository;
+use serde_json::Value;
+use strum::IntoEnumIterator as _;
+use tempfile::TempDir;
+
+use crate::{
+    cli::Args,
+    gitinfo::{repoinfo::FailedRepo, status::Status},
+    porcelain::{self, PorcelainVersion},
+    run,
+    scanner::Scanner,
+};
+
+/// Returns the names of the properties the schema requires of `def`.
+fn required(schema: &Value, def: &str) -> Vec<String> {
+    let mut names: Vec<String> = schema["$defs"][def]["required"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|name| name.as_str().unwrap().to_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Returns the keys of a JSON object, sorted.
+fn keys(object: &Value) -> Vec<String> {
+    let mut keys: Vec<String> = object.as_object().unwrap().keys().cloned().collect();
+    keys.sort();
+    keys
+}
+
+#[test]
+fn test_porcelain_v1_has_exactly_the_fields_of_its_schema() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join("alpha");
+    fs::create_dir_all(&path).unwrap();
+    Repository::init(&path).unwrap();
+    let result = Scanner::new(temp.path()).scan();
+    let failed = [FailedRepo {
+        name: "broken".to_owned(),
+        path: temp.path().join("broken"),
+        error: "not a repository".to_owned(),
+    }];
+
+    let value = porcelain::value(PorcelainVersion::V1, &result.repos, &failed);
+    let schema = porcelain::schema(PorcelainVersion::V1);
+
+    assert_eq!(value["version"], schema["properties"]["version"]["const"]);
+    assert_eq!(
+        keys(&value["repositories"][0]),
+        required(&schema, "repository")
+    );
+    assert_eq!(keys(&value["failed"][0]), required(&schema, "failed"));
+    assert_eq!(value["repositories"][0]["relative_path"], "alpha");
+}
+
+#[test]
+fn test_porcelain_v1_schema_lists_every_status() {
+    let schema = porcelain::schema(PorcelainVersion::V1);
+    let statuses = schema["$defs"]["repository"]["properties"]["status"]["enum"]
+        .as_array()
+        .unwrap();
+    for status in Status::iter() {
+        let name = porcelain::status_v1(&status);
+        assert!(
+            statuses.iter().any(|s| s == name),
+            "{name} is missing from the schema"
+        );
+    }
+}
+
+#[test]
+fn test_cli_porcelain_defaults_to_v1() {
+    let args = Args::parse_from(["git-statuses", "--porcelain"]);
+    assert_eq!(args.porcelain, Some(PorcelainVersion::V1));
+    let args = Args::parse_from(["git-statuses", "--porcelain=v1"]);
+    assert_eq!(args.porcelain, Some(PorcelainVersion::V1));
+    assert!(
+        Args::try_parse_from(["git-statuses", "--porcelain", "--json"]).is_err(),
+        "--porcelain replaces --json"
+    );
+}
+
+#[test]
+fn test_run_json_schema_prints_the_schema() {
+    let args = Args::parse_from(["git-statuses", "--json-schema"]);
+    assert!(args.is_informational(), "nothing is scanned");
+    let mut out = Vec::new();
+    run(&args, &mut out).unwrap();
+    let schema: Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(schema, porcelain::schema(PorcelainVersion::V1));
+}
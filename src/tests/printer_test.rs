@@ -1,17 +1,96 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
+
+use clap::Parser as _;
+use strum::IntoEnumIterator as _;
 
 use crate::cli::Args;
-use crate::gitinfo::repoinfo::RepoInfo;
+use crate::gitinfo::repoinfo::{FailedRepo, RepoInfo, Signatures, Timing};
 use crate::gitinfo::status::Status;
+use crate::gitinfo::symbol::Symbol;
 use crate::printer::{
-    failed_summary, json_output, json_value, legend, repositories_table, summary,
+    Column, Style, Summary, TableLayout, dot_graph, failed_summary, group_subtotal, json_output,
+    json_value, legend, plain_lines, repositories_table, summary, timing_lines, tmux_line,
+    tree_lines,
 };
+use crate::report::{GroupBy, group};
+
+/// A repository on `main` with one commit and nothing else going on.
+fn repo(name: &str, status: Status) -> RepoInfo {
+    RepoInfo {
+        name: name.to_owned(),
+        branch: "main".to_owned(),
+        ahead: 0,
+        behind: 0,
+        commits: Some(1),
+        status,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
+        has_unpushed: false,
+        remote_url: None,
+        path: PathBuf::from("/path/to").join(name),
+        stash_count: 0,
+        is_local_only: false,
+        fast_forwarded: false,
+        repo_path: name.to_owned(),
+        is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
+    }
+}
+
+#[test]
+fn test_table_layout_fits_the_terminal() {
+    assert_eq!(
+        TableLayout::new(None, false),
+        TableLayout::FULL,
+        "no terminal"
+    );
+    assert_eq!(TableLayout::new(Some(160), false), TableLayout::FULL);
+    assert_eq!(
+        TableLayout::new(Some(90), false),
+        TableLayout {
+            commits: false,
+            short_local: false,
+            max_path: Some(22),
+        }
+    );
+    assert_eq!(
+        TableLayout::new(Some(40), false),
+        TableLayout {
+            commits: false,
+            short_local: true,
+            max_path: Some(16),
+        }
+    );
+    assert_eq!(
+        TableLayout::new(Some(40), true),
+        TableLayout::FULL,
+        "--wide"
+    );
+}
 
 #[test]
 fn test_repositories_table_empty() {
     let repos: Vec<RepoInfo> = Vec::new();
     let args = Args {
-        dir: ".".into(),
+        dirs: vec![".".into()],
         depth: 1,
         ..Default::default()
     };
@@ -26,8 +105,12 @@ fn test_repositories_table_with_data() {
         branch: "main".to_owned(),
         ahead: 1,
         behind: 0,
-        commits: 10,
+        commits: Some(10),
         status: Status::Dirty(2),
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
         has_unpushed: true,
         remote_url: Some("https://example.com/repo1.git".to_owned()),
         path: PathBuf::from("/path/to/repo1"),
@@ -36,9 +119,26 @@ fn test_repositories_table_with_data() {
         fast_forwarded: false,
         repo_path: "repo1".to_owned(),
         is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
     }];
     let args = Args {
-        dir: ".".into(),
+        dirs: vec![".".into()],
         depth: 1,
         remote: true,
         ..Default::default()
@@ -47,9 +147,80 @@ fn test_repositories_table_with_data() {
     // Assert that the table is printed correctly
 }
 
+#[test]
+fn test_style_table_preset() {
+    let mut table = Style {
+        condensed: false,
+        ascii: true,
+        ..Style::default()
+    }
+    .table();
+    table.set_header(["Directory", "Local"]);
+    table.add_row(["tool", "^1 v0"]);
+    let drawn = table.to_string();
+    assert!(drawn.is_ascii(), "{drawn}");
+
+    let mut table = Style::default().table();
+    table.set_header(["Directory"]);
+    assert!(table.to_string().contains('│'));
+    assert_eq!(Style::default().text("↑1"), "↑1");
+}
+
+#[test]
+fn test_selected_columns_follow_the_options() {
+    let args = Args::parse_from(["git-statuses"]);
+    assert_eq!(
+        Column::selected(&args, TableLayout::FULL),
+        [
+            Column::Directory,
+            Column::Branch,
+            Column::Local,
+            Column::Commits,
+            Column::Status
+        ]
+    );
+    assert!(!Column::selected(&args, TableLayout::new(Some(60), false)).contains(&Column::Commits));
+
+    let args = Args::parse_from([
+        "git-statuses",
+        "--remote",
+        "--clones",
+        "--lfs",
+        "--activity",
+        "--maintenance",
+        "--disk-usage",
+        "--show-ignored",
+        "--hidden-files",
+        "--verify-signatures",
+        "--identity",
+        "--github",
+        "--path",
+    ]);
+    assert_eq!(
+        Column::selected(&args, TableLayout::FULL),
+        Column::iter().collect::<Vec<_>>(),
+        "every column the legend lists can be shown"
+    );
+}
+
+#[test]
+fn test_legend_sources_are_complete() {
+    for column in Column::iter() {
+        assert!(!column.title().is_empty());
+        assert!(!column.description().is_empty(), "{column:?}");
+    }
+    for symbol in Symbol::iter() {
+        assert!(
+            symbol.example().contains(symbol.text()),
+            "the example of {symbol:?} shows it"
+        );
+        assert!(!symbol.description().is_empty(), "{symbol:?}");
+    }
+}
+
 #[test]
 fn test_print_legend() {
-    legend(false);
+    legend(Style::default());
     // Assert that the legend is printed correctly
 }
 
@@ -61,8 +232,12 @@ fn test_repositories_table_with_stashes_and_local_only() {
             branch: "main".to_owned(),
             ahead: 0,
             behind: 0,
-            commits: 5,
+            commits: Some(5),
             status: Status::Clean,
+            conflicts: 0,
+            progress: None,
+            stale_locks: Vec::new(),
+            integrity_errors: Vec::new(),
             has_unpushed: false,
             remote_url: None,
             path: PathBuf::from("/path/to/repo-with-stash"),
@@ -71,14 +246,35 @@ fn test_repositories_table_with_stashes_and_local_only() {
             fast_forwarded: false,
             repo_path: "repo-with-stash".to_owned(),
             is_worktree: false,
+            identity: None,
+            fork_behind: None,
+            gone_branches: 0,
+            is_shallow: false,
+            is_partial: false,
+            head_sha: None,
+            lfs: None,
+            user_email: None,
+            forge: None,
+            timing: None,
+            activity: None,
+            housekeeping: None,
+            disk_usage: None,
+            signatures: None,
+            hidden_files: None,
+            ignored: None,
+            root: PathBuf::new(),
         },
         RepoInfo {
             name: "repo-with-upstream".to_owned(),
             branch: "feature".to_owned(),
             ahead: 3,
             behind: 1,
-            commits: 8,
+            commits: Some(8),
             status: Status::Dirty(1),
+            conflicts: 0,
+            progress: None,
+            stale_locks: Vec::new(),
+            integrity_errors: Vec::new(),
             has_unpushed: true,
             remote_url: None,
             path: PathBuf::from("/path/to/repo-with-upstream"),
@@ -87,10 +283,27 @@ fn test_repositories_table_with_stashes_and_local_only() {
             fast_forwarded: false,
             repo_path: "repo-with-upstream".to_owned(),
             is_worktree: false,
+            identity: None,
+            fork_behind: None,
+            gone_branches: 0,
+            is_shallow: false,
+            is_partial: false,
+            head_sha: None,
+            lfs: None,
+            user_email: None,
+            forge: None,
+            timing: None,
+            activity: None,
+            housekeeping: None,
+            disk_usage: None,
+            signatures: None,
+            hidden_files: None,
+            ignored: None,
+            root: PathBuf::new(),
         },
     ];
     let args = Args {
-        dir: ".".into(),
+        dirs: vec![".".into()],
         depth: 1,
         ..Default::default()
     };
@@ -105,8 +318,12 @@ fn test_repositories_table_with_path_option() {
         branch: "main".to_owned(),
         ahead: 0,
         behind: 0,
-        commits: 5,
+        commits: Some(5),
         status: Status::Clean,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
         has_unpushed: false,
         remote_url: None,
         path: PathBuf::from("/very/long/path/to/repository"),
@@ -115,9 +332,26 @@ fn test_repositories_table_with_path_option() {
         fast_forwarded: false,
         repo_path: "test-repo".to_owned(),
         is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
     }];
     let args = Args {
-        dir: ".".into(),
+        dirs: vec![".".into()],
         depth: 1,
         path: true,
         ..Default::default()
@@ -133,8 +367,12 @@ fn test_repositories_table_condensed_layout() {
         branch: "develop".to_owned(),
         ahead: 2,
         behind: 1,
-        commits: 15,
+        commits: Some(15),
         status: Status::Merge,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
         has_unpushed: true,
         remote_url: Some("git@github.com:user/repo.git".to_owned()),
         path: PathBuf::from("/path/to/repo"),
@@ -143,9 +381,26 @@ fn test_repositories_table_condensed_layout() {
         fast_forwarded: false,
         repo_path: "repo".to_owned(),
         is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
     }];
     let args = Args {
-        dir: ".".into(),
+        dirs: vec![".".into()],
         depth: 1,
         condensed: true,
         remote: true,
@@ -164,8 +419,12 @@ fn test_repositories_table_non_clean_filter() {
             branch: "main".to_owned(),
             ahead: 0,
             behind: 0,
-            commits: 5,
+            commits: Some(5),
             status: Status::Clean,
+            conflicts: 0,
+            progress: None,
+            stale_locks: Vec::new(),
+            integrity_errors: Vec::new(),
             has_unpushed: false,
             remote_url: None,
             path: PathBuf::from("/path/to/clean"),
@@ -174,14 +433,35 @@ fn test_repositories_table_non_clean_filter() {
             fast_forwarded: false,
             repo_path: "clean-repo".to_owned(),
             is_worktree: false,
+            identity: None,
+            fork_behind: None,
+            gone_branches: 0,
+            is_shallow: false,
+            is_partial: false,
+            head_sha: None,
+            lfs: None,
+            user_email: None,
+            forge: None,
+            timing: None,
+            activity: None,
+            housekeeping: None,
+            disk_usage: None,
+            signatures: None,
+            hidden_files: None,
+            ignored: None,
+            root: PathBuf::new(),
         },
         RepoInfo {
             name: "dirty-repo".to_owned(),
             branch: "main".to_owned(),
             ahead: 0,
             behind: 0,
-            commits: 5,
+            commits: Some(5),
             status: Status::Dirty(3),
+            conflicts: 0,
+            progress: None,
+            stale_locks: Vec::new(),
+            integrity_errors: Vec::new(),
             has_unpushed: false,
             remote_url: None,
             path: PathBuf::from("/path/to/dirty"),
@@ -190,10 +470,27 @@ fn test_repositories_table_non_clean_filter() {
             fast_forwarded: false,
             repo_path: "dirty-repo".to_owned(),
             is_worktree: false,
+            identity: None,
+            fork_behind: None,
+            gone_branches: 0,
+            is_shallow: false,
+            is_partial: false,
+            head_sha: None,
+            lfs: None,
+            user_email: None,
+            forge: None,
+            timing: None,
+            activity: None,
+            housekeeping: None,
+            disk_usage: None,
+            signatures: None,
+            hidden_files: None,
+            ignored: None,
+            root: PathBuf::new(),
         },
     ];
     let args = Args {
-        dir: ".".into(),
+        dirs: vec![".".into()],
         depth: 1,
         non_clean: true,
         ..Default::default()
@@ -208,58 +505,52 @@ fn test_repositories_table_non_clean_filter() {
 /// already ordered slice. The printer must render the rows in exactly that order.
 #[test]
 fn test_repositories_table_renders_rows_in_given_order() {
-    let repos = vec![
-        RepoInfo {
-            name: "zebra-repo".to_owned(),
-            branch: "main".to_owned(),
-            ahead: 0,
-            behind: 0,
-            commits: 5,
-            status: Status::Clean,
-            has_unpushed: false,
-            remote_url: None,
-            path: PathBuf::from("/path/to/zebra"),
-            stash_count: 0,
-            is_local_only: false,
-            fast_forwarded: false,
-            repo_path: "zebra-repo".to_owned(),
-            is_worktree: false,
-        },
-        RepoInfo {
-            name: "Alpha-Repo".to_owned(), // Capital letter
-            branch: "main".to_owned(),
-            ahead: 0,
-            behind: 0,
-            commits: 5,
-            status: Status::Clean,
-            has_unpushed: false,
-            remote_url: None,
-            path: PathBuf::from("/path/to/alpha"),
-            stash_count: 0,
-            is_local_only: false,
-            fast_forwarded: false,
-            repo_path: "Alpha-Repo".to_owned(),
-            is_worktree: false,
-        },
-        RepoInfo {
-            name: "beta-repo".to_owned(),
-            branch: "main".to_owned(),
-            ahead: 0,
-            behind: 0,
-            commits: 5,
-            status: Status::Clean,
-            has_unpushed: false,
-            remote_url: None,
-            path: PathBuf::from("/path/to/beta"),
-            stash_count: 0,
-            is_local_only: false,
-            fast_forwarded: false,
-            repo_path: "beta-repo".to_owned(),
-            is_worktree: false,
-        },
-    ];
+    let repos: Vec<RepoInfo> = [
+        ("zebra-repo", "zebra"),
+        ("Alpha-Repo", "alpha"), // Capital letter
+        ("beta-repo", "beta"),
+    ]
+    .into_iter()
+    .map(|(name, dir)| RepoInfo {
+        name: name.to_owned(),
+        branch: "main".to_owned(),
+        ahead: 0,
+        behind: 0,
+        commits: Some(5),
+        status: Status::Clean,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
+        has_unpushed: false,
+        remote_url: None,
+        path: PathBuf::from("/path/to").join(dir),
+        stash_count: 0,
+        is_local_only: false,
+        fast_forwarded: false,
+        repo_path: name.to_owned(),
+        is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
+    })
+    .collect();
     let args = Args {
-        dir: ".".into(),
+        dirs: vec![".".into()],
         depth: 1,
         ..Default::default()
     };
@@ -272,58 +563,78 @@ fn test_repositories_table_renders_rows_in_given_order() {
 
 #[test]
 fn test_repositories_table_various_statuses() {
+    let rebase = RepoInfo {
+        name: "rebase-repo".to_owned(),
+        branch: "feature".to_owned(),
+        ahead: 0,
+        behind: 0,
+        commits: Some(5),
+        status: Status::Rebase,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
+        has_unpushed: false,
+        remote_url: None,
+        path: PathBuf::from("/path/to/rebase"),
+        stash_count: 0,
+        is_local_only: false,
+        fast_forwarded: false,
+        repo_path: "rebase-repo".to_owned(),
+        is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
+    };
     let repos = vec![
-        RepoInfo {
-            name: "rebase-repo".to_owned(),
-            branch: "feature".to_owned(),
-            ahead: 0,
-            behind: 0,
-            commits: 5,
-            status: Status::Rebase,
-            has_unpushed: false,
-            remote_url: None,
-            path: PathBuf::from("/path/to/rebase"),
-            stash_count: 0,
-            is_local_only: false,
-            fast_forwarded: false,
-            repo_path: "rebase-repo".to_owned(),
-            is_worktree: false,
-        },
         RepoInfo {
             name: "cherry-repo".to_owned(),
             branch: "hotfix".to_owned(),
             ahead: 1,
-            behind: 0,
-            commits: 8,
+            commits: Some(8),
             status: Status::CherryPick,
             has_unpushed: true,
-            remote_url: None,
             path: PathBuf::from("/path/to/cherry"),
-            stash_count: 0,
-            is_local_only: false,
-            fast_forwarded: false,
             repo_path: "cherry-repo".to_owned(),
-            is_worktree: false,
+            ..rebase.clone()
         },
         RepoInfo {
             name: "bisect-repo".to_owned(),
             branch: "main".to_owned(),
-            ahead: 0,
             behind: 2,
-            commits: 12,
+            commits: Some(12),
             status: Status::Bisect,
-            has_unpushed: false,
-            remote_url: None,
             path: PathBuf::from("/path/to/bisect"),
             stash_count: 1,
-            is_local_only: false,
-            fast_forwarded: false,
             repo_path: "bisect-repo".to_owned(),
-            is_worktree: false,
+            ..rebase.clone()
+        },
+        RepoInfo {
+            name: "locked-repo".to_owned(),
+            status: Status::StaleLock,
+            stale_locks: vec![PathBuf::from("/path/to/locked/.git/index.lock")],
+            path: PathBuf::from("/path/to/locked"),
+            repo_path: "locked-repo".to_owned(),
+            ..rebase.clone()
         },
+        rebase,
     ];
     let args = Args {
-        dir: ".".into(),
+        dirs: vec![".".into()],
         depth: 1,
         ..Default::default()
     };
@@ -333,7 +644,11 @@ fn test_repositories_table_various_statuses() {
 
 #[test]
 fn test_legend_condensed() {
-    legend(true);
+    legend(Style {
+        condensed: true,
+        ascii: true,
+        ..Style::default()
+    });
     // Should print condensed legend format
 }
 
@@ -341,52 +656,24 @@ fn test_legend_condensed() {
 fn test_summary_comprehensive() {
     let repos = vec![
         RepoInfo {
-            name: "clean1".to_owned(),
-            branch: "main".to_owned(),
-            ahead: 0,
-            behind: 0,
-            commits: 5,
-            status: Status::Clean,
-            has_unpushed: false,
-            remote_url: None,
-            path: PathBuf::from("/path/to/clean1"),
-            stash_count: 0,
-            is_local_only: false,
-            fast_forwarded: false,
-            repo_path: "clean1".to_owned(),
-            is_worktree: false,
+            commits: Some(5),
+            ..repo("clean1", Status::Clean)
         },
         RepoInfo {
-            name: "clean2".to_owned(),
-            branch: "main".to_owned(),
-            ahead: 0,
-            behind: 0,
-            commits: 3,
-            status: Status::Clean,
-            has_unpushed: false,
-            remote_url: None,
-            path: PathBuf::from("/path/to/clean2"),
+            commits: Some(3),
             stash_count: 1,      // has stash
             is_local_only: true, // local only
-            fast_forwarded: false,
-            repo_path: "clean2".to_owned(),
-            is_worktree: false,
+            ..repo("clean2", Status::Clean)
         },
         RepoInfo {
-            name: "dirty".to_owned(),
             branch: "feature".to_owned(),
             ahead: 2,
             behind: 1,
-            commits: 8,
-            status: Status::Dirty(3),
+            commits: Some(8),
             has_unpushed: true, // has unpushed
             remote_url: Some("https://example.com".to_owned()),
-            path: PathBuf::from("/path/to/dirty"),
             stash_count: 2, // has stashes
-            is_local_only: false,
-            fast_forwarded: false,
-            repo_path: "dirty".to_owned(),
-            is_worktree: false,
+            ..repo("dirty", Status::Dirty(3))
         },
     ];
 
@@ -404,22 +691,85 @@ fn test_summary_comprehensive() {
 
 #[test]
 fn test_failed_summary_empty() {
-    let failed_repos: Vec<String> = vec![];
+    let failed_repos: Vec<FailedRepo> = vec![];
     failed_summary(&failed_repos);
     // Should not print anything
 }
 
 #[test]
 fn test_failed_summary_multiple() {
-    let failed_repos = vec![
-        "broken-repo-1".to_owned(),
-        "corrupted-repo-2".to_owned(),
-        "invalid-git-dir".to_owned(),
-    ];
+    let failed_repos = ["broken-repo-1", "corrupted-repo-2", "invalid-git-dir"]
+        .map(|name| failed_repo(name, "could not find repository"));
     failed_summary(&failed_repos);
     // Should print warning about failed repos
 }
 
+#[test]
+fn test_summary_totals_and_breakdowns() {
+    let mut behind = repo_named("behind", Status::Clean);
+    behind.behind = 4;
+    behind.remote_url = Some("git@github.com:acme/behind.git".to_owned());
+    let mut unpushed = repo_named("unpushed", Status::Unpushed);
+    unpushed.ahead = 2;
+    unpushed.behind = 1;
+    unpushed.remote_url = Some("https://github.com/acme/unpushed.git".to_owned());
+    let repos = [
+        behind,
+        unpushed,
+        repo_named("dirty", Status::Dirty(3)),
+        repo_named("clean", Status::Clean),
+    ];
+
+    let summary = Summary::new(&repos, 0);
+    assert_eq!((summary.ahead, summary.behind), (2, 5));
+    assert_eq!(
+        summary.statuses.into_iter().collect::<Vec<_>>(),
+        [
+            ("Clean".to_owned(), 2),
+            ("Dirty (N)".to_owned(), 1),
+            ("Unpushed".to_owned(), 1),
+        ]
+    );
+    let groups: Vec<_> = summary
+        .groups
+        .iter()
+        .map(|g| (g.group.as_str(), g.repos, g.clean))
+        .collect();
+    assert_eq!(groups, [("github.com/acme", 2, 1), ("local", 2, 1)]);
+}
+
+#[test]
+fn test_summary_counts_unsigned_repositories() {
+    let repos = [repo("plain", Status::Clean)];
+    assert_eq!(
+        Summary::new(&repos, 0).unsigned,
+        None,
+        "signatures not checked"
+    );
+
+    let signed = Signatures {
+        checked: 3,
+        good: 3,
+        ..Signatures::default()
+    };
+    let repos = [
+        RepoInfo {
+            signatures: Some(signed),
+            ..repo("signed", Status::Clean)
+        },
+        RepoInfo {
+            signatures: Some(Signatures {
+                good: 2,
+                unverified: 1,
+                ..signed
+            }),
+            ..repo("unverified", Status::Clean)
+        },
+        repo("empty", Status::Clean),
+    ];
+    assert_eq!(Summary::new(&repos, 0).unsigned, Some(1));
+}
+
 #[test]
 fn test_summary_edge_cases() {
     // Test with no repos
@@ -435,8 +785,12 @@ fn test_summary_edge_cases() {
         branch: "detached".to_owned(),
         ahead: 0,
         behind: 0,
-        commits: 0,
+        commits: Some(0),
         status: Status::Unknown,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
         has_unpushed: false,
         remote_url: None,
         path: PathBuf::from("/path/to/unknown"),
@@ -445,6 +799,23 @@ fn test_summary_edge_cases() {
         fast_forwarded: false,
         repo_path: "unknown-status".to_owned(),
         is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
     }];
     summary(&edge_repos, 0);
 }
@@ -456,8 +827,12 @@ fn test_repositories_table_marks_worktree_rows() {
         branch: "feature".to_owned(),
         ahead: 0,
         behind: 0,
-        commits: 3,
+        commits: Some(3),
         status: Status::Clean,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
         has_unpushed: false,
         remote_url: None,
         path: PathBuf::from("/path/to/worktree-repo"),
@@ -466,9 +841,26 @@ fn test_repositories_table_marks_worktree_rows() {
         fast_forwarded: false,
         repo_path: "worktree-repo".to_owned(),
         is_worktree: true,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
     }];
     let args = Args {
-        dir: ".".into(),
+        dirs: vec![".".into()],
         depth: 1,
         ..Default::default()
     };
@@ -482,8 +874,12 @@ fn test_json_output_smoke() {
         branch: "main".to_owned(),
         ahead: 0,
         behind: 0,
-        commits: 1,
+        commits: Some(1),
         status: Status::Clean,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
         has_unpushed: false,
         remote_url: None,
         path: PathBuf::from("/path/to/json-repo"),
@@ -492,13 +888,39 @@ fn test_json_output_smoke() {
         fast_forwarded: false,
         repo_path: "json-repo".to_owned(),
         is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
     }];
-    let failed = vec!["broken-repo".to_owned()];
-    json_output(&repos, &failed);
+    let failed = vec![failed_repo("broken-repo", "not a git repository")];
+    json_output(&repos, &failed, None);
 
     let value = json_value(&repos, &failed);
     assert_eq!(value["repositories"][0]["name"], "json-repo");
-    assert_eq!(value["failed"][0], "broken-repo");
+    assert_eq!(value["failed"][0]["name"], "broken-repo");
+    assert_eq!(value["failed"][0]["error"], "not a git repository");
+}
+
+fn failed_repo(name: &str, error: &str) -> FailedRepo {
+    FailedRepo {
+        name: name.to_owned(),
+        path: PathBuf::from("/path/to").join(name),
+        error: error.to_owned(),
+    }
 }
 
 fn repo_named(name: &str, status: Status) -> RepoInfo {
@@ -507,8 +929,12 @@ fn repo_named(name: &str, status: Status) -> RepoInfo {
         branch: "main".to_owned(),
         ahead: 0,
         behind: 0,
-        commits: 1,
+        commits: Some(1),
         status,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
         has_unpushed: false,
         remote_url: None,
         path: PathBuf::from("/path/to").join(name),
@@ -517,6 +943,23 @@ fn repo_named(name: &str, status: Status) -> RepoInfo {
         fast_forwarded: false,
         repo_path: name.to_owned(),
         is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
     }
 }
 
@@ -530,7 +973,7 @@ fn test_non_clean_filter_applies_to_json_output() {
         repo_named("unpushed-repo", Status::Unpushed),
     ];
     let args = Args {
-        dir: ".".into(),
+        dirs: vec![".".into()],
         depth: 1,
         non_clean: true,
         json: true,
@@ -559,6 +1002,36 @@ fn test_non_clean_filter_applies_to_json_output() {
     );
 }
 
+/// The `--min-*` thresholds all have to be met, and combine with `--non-clean`.
+#[test]
+fn test_min_filters() {
+    let mut behind = repo_named("behind", Status::Clean);
+    behind.behind = 25;
+    let mut stashed = repo_named("stashed", Status::Dirty(12));
+    stashed.stash_count = 2;
+    stashed.behind = 3;
+    let mut ahead = repo_named("ahead", Status::Unpushed);
+    ahead.ahead = 1;
+    let repos = vec![behind, stashed, ahead];
+    let names = |args: &Args| -> Vec<String> {
+        args.filter_repos(&repos)
+            .iter()
+            .map(|r| r.name.clone())
+            .collect()
+    };
+
+    let args = Args::parse_from(["git-statuses", "--min-behind", "20"]);
+    assert_eq!(names(&args), ["behind"]);
+    let args = Args::parse_from(["git-statuses", "--min-behind=3", "--min-dirty=10"]);
+    assert_eq!(names(&args), ["stashed"], "every threshold has to be met");
+    let args = Args::parse_from(["git-statuses", "--min-behind=3", "--non-clean"]);
+    assert_eq!(names(&args), ["stashed"], "`behind` is clean");
+    let args = Args::parse_from(["git-statuses", "--min-ahead=1"]);
+    assert_eq!(names(&args), ["ahead"]);
+    let args = Args::parse_from(["git-statuses", "--min-stashes=1"]);
+    assert_eq!(names(&args), ["stashed"]);
+}
+
 /// Without `--non-clean` the filter must be a no-op and must not clone the input.
 #[test]
 fn test_filter_repos_without_non_clean_borrows_everything() {
@@ -567,7 +1040,7 @@ fn test_filter_repos_without_non_clean_borrows_everything() {
         repo_named("dirty-repo", Status::Dirty(1)),
     ];
     let args = Args {
-        dir: ".".into(),
+        dirs: vec![".".into()],
         depth: 1,
         ..Default::default()
     };
@@ -589,7 +1062,7 @@ fn test_non_clean_filter_removing_everything_prints_no_repositories() {
         repo_named("clean-b", Status::Clean),
     ];
     let args = Args {
-        dir: ".".into(),
+        dirs: vec![".".into()],
         depth: 1,
         non_clean: true,
         ..Default::default()
@@ -600,3 +1073,183 @@ fn test_non_clean_filter_removing_everything_prints_no_repositories() {
     // Hits the "No repositories found." branch rather than rendering an empty table.
     repositories_table(&displayed, &args);
 }
+
+#[test]
+fn test_dot_graph_groups_repositories_by_host_and_owner() {
+    let mut hosted = repo_named("hosted", Status::Clean);
+    hosted.remote_url = Some("git@github.com:bircni/hosted.git".to_owned());
+    let local = repo_named("local \"quoted\"", Status::Dirty(1));
+
+    let graph = dot_graph(&[hosted, local]);
+
+    assert!(graph.starts_with("digraph"), "not a dot graph: {graph}");
+    assert!(
+        graph.contains("\"github.com\" -> \"github.com/bircni\";"),
+        "the owner must hang off the host: {graph}"
+    );
+    assert!(
+        graph.contains("\"github.com/bircni\" -> \"/path/to/hosted\";"),
+        "the repository must hang off its owner: {graph}"
+    );
+    assert!(
+        graph.contains("[label=\"hosted: Clean\", fillcolor=palegreen]"),
+        "the repository must be colored by status: {graph}"
+    );
+    assert!(
+        graph.contains("\"local\" -> \"/path/to/local \\\"quoted\\\"\";"),
+        "repositories without a remote are local, with quotes escaped: {graph}"
+    );
+}
+
+#[test]
+fn test_plain_lines_are_tab_separated() {
+    let mut dirty = repo_named("dirty repo", Status::Dirty(2));
+    dirty.ahead = 1;
+    dirty.stash_count = 3;
+    dirty.remote_url = Some("git@github.com:bircni/dirty.git".to_owned());
+    let repos = [repo_named("clean", Status::Clean), dirty];
+
+    assert_eq!(
+        plain_lines(&repos, false, false),
+        [
+            "clean\tmain\tClean\t0\t0\t1\t0",
+            "dirty repo\tmain\tDirty (2)\t1\t0\t1\t3"
+        ]
+    );
+
+    assert_eq!(
+        plain_lines(&repos, true, true),
+        [
+            "clean\tmain\tClean\t0\t0\t1\t0\t/path/to/clean\t-",
+            "dirty repo\tmain\tDirty (2)\t1\t0\t1\t3\t/path/to/dirty repo\tgit@github.com:bircni/dirty.git"
+        ]
+    );
+}
+
+#[test]
+fn test_timing_lines_list_the_slowest_repositories_first() {
+    let timed = |name: &str, revwalk: u64| {
+        let mut repo = repo_named(name, Status::Clean);
+        repo.timing = Some(Timing {
+            open: Duration::from_millis(1),
+            fetch: Duration::ZERO,
+            revwalk: Duration::from_millis(revwalk),
+            status: Duration::from_millis(2),
+        });
+        repo
+    };
+    let repos = [
+        timed("small", 1),
+        repo_named("untimed", Status::Clean),
+        timed("monorepo", 900),
+        timed("medium", 40),
+    ];
+
+    assert_eq!(
+        timing_lines(&repos, 2),
+        [
+            "monorepo: 903.0ms (open 1.0ms, fetch 0.0ns, revwalk 900.0ms, status 2.0ms)",
+            "medium: 43.0ms (open 1.0ms, fetch 0.0ns, revwalk 40.0ms, status 2.0ms)",
+        ]
+    );
+    assert_eq!(
+        json_value(&repos[2..3], &[])["repositories"][0]["timing"]["revwalk"],
+        900.0,
+        "JSON has the timing in milliseconds"
+    );
+}
+
+#[test]
+fn test_prelude_renders_without_the_cli() {
+    use crate::prelude::*;
+
+    let repos = [
+        repo_named("a", Status::Clean),
+        repo_named("b", Status::Dirty(1)),
+    ];
+    assert_eq!(
+        render::plain(&repos, false, false),
+        "a\tmain\tClean\t0\t0\t1\t0\nb\tmain\tDirty (1)\t0\t0\t1\t0\n"
+    );
+    let json: serde_json::Value = serde_json::from_str(&render::json(&repos, &[])).unwrap();
+    assert_eq!(json["repositories"][1]["status"]["Dirty"], 1);
+    assert_eq!(Summary::new(&repos, 0).clean, 1);
+}
+
+#[test]
+fn test_tree_lines_nest_repositories_under_their_directories() {
+    let nested = |repo_path: &str, status: Status| {
+        let mut repo = repo_named(repo_path.rsplit('/').next().unwrap(), status);
+        repo.repo_path = repo_path.to_owned();
+        repo
+    };
+    let mut ahead = nested("work/api", Status::Unpushed);
+    ahead.ahead = 2;
+    let repos = [
+        nested("work/api", Status::Clean),
+        nested("deep/down/below/tool", Status::Dirty(3)),
+        nested("work", Status::Clean),
+        ahead,
+        nested("work/web", Status::Clean),
+    ];
+
+    assert_eq!(
+        tree_lines(&repos[1..]),
+        [
+            "├── deep/down/below/tool  [main] ↑0 ↓0 Dirty (3)",
+            "└── work  [main] ↑0 ↓0 Clean",
+            "    ├── api  [main] ↑2 ↓0 Unpushed",
+            "    └── web  [main] ↑0 ↓0 Clean",
+        ]
+    );
+    assert_eq!(
+        tree_lines(&[repos[0].clone(), repos[4].clone()]),
+        [
+            "└── work/",
+            "    ├── api  [main] ↑0 ↓0 Clean",
+            "    └── web  [main] ↑0 ↓0 Clean",
+        ]
+    );
+    assert_eq!(
+        tree_lines(&repos[..1]),
+        ["└── work/api  [main] ↑0 ↓0 Clean"]
+    );
+}
+
+#[test]
+fn test_group_subtotal() {
+    let mut dirty = repo_named("work/api", Status::Dirty(1));
+    dirty.behind = 2;
+    let repos = [dirty, repo_named("work/web", Status::Clean)];
+    let groups = group(&repos, GroupBy::ParentDir);
+    assert_eq!(
+        group_subtotal(&groups[0]),
+        "2 repositories: 1 dirty, 1 behind, 0 ahead"
+    );
+    let groups = group(&repos[1..], GroupBy::ParentDir);
+    assert_eq!(
+        group_subtotal(&groups[0]),
+        "1 repository: 0 dirty, 0 behind, 0 ahead"
+    );
+}
+
+#[test]
+fn test_tmux_line_counts_what_needs_attention() {
+    let mut unpushed = repo_named("unpushed", Status::Dirty(1));
+    unpushed.ahead = 2;
+    let mut behind = repo_named("behind", Status::Clean);
+    behind.behind = 1;
+    let repos = [
+        unpushed,
+        behind,
+        repo_named("dirty", Status::Dirty(4)),
+        repo_named("clean", Status::Clean),
+    ];
+
+    assert_eq!(
+        tmux_line(&repos, 1),
+        "#[fg=red]2 dirty#[default] · #[fg=yellow]1 unpushed#[default] · \
+         #[fg=blue]1 behind#[default] · #[fg=magenta]1 failed#[default]"
+    );
+    assert_eq!(tmux_line(&repos[3..], 0), "#[fg=green]1 clean#[default]");
+}
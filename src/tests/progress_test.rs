@@ -0,0 +1,35 @@
+use std::{thread, time::Duration};
+
+use crate::progress::Progress;
+
+#[test]
+fn test_progress_line_counts_processed_and_failed() {
+    let progress = Progress::default();
+    assert_eq!(progress.line("fetched", 3), "fetched 0/3 repos, 0 failures");
+
+    progress.processed(false);
+    progress.processed(true);
+    assert_eq!(progress.line("fetched", 3), "fetched 2/3 repos, 1 failures");
+}
+
+#[test]
+fn test_progress_report_while_returns_the_work_result() {
+    let progress = Progress::default();
+    let result = progress.report_while("fetched", 2, Duration::from_millis(5), || {
+        progress.processed(false);
+        thread::sleep(Duration::from_millis(30));
+        progress.processed(true);
+        42
+    });
+    assert_eq!(result, 42);
+    assert_eq!(progress.line("fetched", 2), "fetched 2/2 repos, 1 failures");
+}
+
+/// The reporter must not hold the scan up until its next interval once the work is done.
+#[test]
+fn test_progress_report_while_stops_with_the_work() {
+    let progress = Progress::default();
+    let start = std::time::Instant::now();
+    progress.report_while("fetched", 0, Duration::from_hours(1), || {});
+    assert!(start.elapsed() < Duration::from_mins(1));
+}
@@ -0,0 +1,82 @@
+use std::fs;
+
+use git2::{Repository, Signature};
+use tempfile::TempDir;
+
+use crate::prompt;
+
+fn commit_all(repo: &Repository, message: &str) {
+    let mut index = repo.index().unwrap();
+    index
+        .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+        .unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let parents: Vec<_> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+        .unwrap();
+}
+
+#[test]
+fn test_prompt_line_shows_changes_and_stashes() {
+    let temp = TempDir::new().unwrap();
+    let mut repo = Repository::init(temp.path()).unwrap();
+    repo.set_head("refs/heads/main").unwrap();
+    fs::write(temp.path().join("a.txt"), "a").unwrap();
+    commit_all(&repo, "initial");
+    assert_eq!(prompt::line(&mut repo), "main");
+
+    fs::write(temp.path().join("a.txt"), "changed").unwrap();
+    let sig = Signature::now("Test User", "test@example.com").unwrap();
+    repo.stash_save(&sig, "wip", None).unwrap();
+    fs::write(temp.path().join("b.txt"), "b").unwrap();
+    fs::write(temp.path().join("c.txt"), "c").unwrap();
+    assert_eq!(prompt::line(&mut repo), "main ✚2 ⚑1");
+}
+
+#[test]
+fn test_prompt_line_shows_ahead_and_behind_of_the_upstream() {
+    let temp = TempDir::new().unwrap();
+    let mut repo = Repository::init(temp.path()).unwrap();
+    repo.set_head("refs/heads/main").unwrap();
+    fs::write(temp.path().join("a.txt"), "a").unwrap();
+    commit_all(&repo, "initial");
+    let head = repo.head().unwrap().peel_to_commit().unwrap().id();
+    repo.remote("origin", "https://example.com/tool.git")
+        .unwrap();
+    repo.reference("refs/remotes/origin/main", head, true, "upstream")
+        .unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("branch.main.remote", "origin").unwrap();
+    config
+        .set_str("branch.main.merge", "refs/heads/main")
+        .unwrap();
+    fs::write(temp.path().join("a.txt"), "b").unwrap();
+    commit_all(&repo, "second");
+    assert_eq!(prompt::line(&mut repo), "main ↑1 ↓0");
+
+    let mut out = Vec::new();
+    prompt::print(temp.path(), true, &mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "main ^1 v0\n", "--ascii");
+}
+
+#[test]
+fn test_prompt_prints_the_repository_of_a_subdirectory_and_nothing_outside() {
+    let temp = TempDir::new().unwrap();
+    let repo = Repository::init(temp.path()).unwrap();
+    repo.set_head("refs/heads/main").unwrap();
+    fs::create_dir_all(temp.path().join("src/deep")).unwrap();
+    fs::write(temp.path().join("src/deep/a.txt"), "a").unwrap();
+    commit_all(&repo, "initial");
+
+    let mut out = Vec::new();
+    prompt::print(&temp.path().join("src/deep"), false, &mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "main\n");
+
+    let outside = TempDir::new().unwrap();
+    let mut out = Vec::new();
+    prompt::print(outside.path(), false, &mut out).unwrap();
+    assert!(out.is_empty(), "nothing is printed outside of a repository");
+}
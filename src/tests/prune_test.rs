@@ -0,0 +1,131 @@
+use git2::{BranchType, Repository};
+use tempfile::TempDir;
+
+use crate::{
+    gitinfo::repoinfo::RepoListing,
+    prune::{self, PrunableBranch, PruneReason},
+};
+
+/// Creates a repository on its default branch with the branches `done` (merged), `wip`
+/// (one commit ahead) and `gone` (one commit ahead, tracking a deleted remote branch).
+fn repo_with_branches() -> (TempDir, Repository) {
+    let temp = TempDir::new().unwrap();
+    let repo = Repository::init(temp.path()).unwrap();
+    let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+    let tree = repo
+        .find_tree(repo.index().unwrap().write_tree().unwrap())
+        .unwrap();
+    let root = repo
+        .commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+        .unwrap();
+    let root = repo.find_commit(root).unwrap();
+    repo.branch("done", &root, false).unwrap();
+    for name in ["wip", "gone"] {
+        repo.commit(
+            Some(&format!("refs/heads/{name}")),
+            &sig,
+            &sig,
+            name,
+            &tree,
+            &[&root],
+        )
+        .unwrap();
+    }
+    repo.remote("origin", "https://example.com/acme/tool.git")
+        .unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("branch.gone.remote", "origin").unwrap();
+    config
+        .set_str("branch.gone.merge", "refs/heads/gone")
+        .unwrap();
+    drop((root, tree, config));
+    (temp, repo)
+}
+
+#[test]
+fn test_prunable_branches() {
+    let (_temp, repo) = repo_with_branches();
+    let default = repo.head().unwrap().shorthand().unwrap().to_owned();
+
+    assert_eq!(
+        prune::prunable_branches(&repo),
+        [
+            PrunableBranch {
+                name: "done".to_owned(),
+                reason: PruneReason::Merged(default),
+            },
+            PrunableBranch {
+                name: "gone".to_owned(),
+                reason: PruneReason::Unmerged,
+            },
+        ],
+        "`wip` has work of its own and the default branch stays"
+    );
+}
+
+#[test]
+fn test_prunable_branches_deletes_gone_branches_merged_into_head() {
+    let (_temp, repo) = repo_with_branches();
+    let wip = repo
+        .find_branch("wip", BranchType::Local)
+        .unwrap()
+        .get()
+        .peel_to_commit()
+        .unwrap();
+    repo.branch("landed", &wip, false).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("branch.landed.remote", "origin").unwrap();
+    config
+        .set_str("branch.landed.merge", "refs/heads/landed")
+        .unwrap();
+    repo.set_head("refs/heads/wip").unwrap();
+
+    let reasons: Vec<_> = prune::prunable_branches(&repo)
+        .into_iter()
+        .filter(|branch| branch.name != "done")
+        .map(|branch| (branch.name, branch.reason))
+        .collect();
+    assert_eq!(
+        reasons,
+        [
+            ("gone".to_owned(), PruneReason::Unmerged),
+            ("landed".to_owned(), PruneReason::Gone),
+        ],
+        "HEAD has the commits of `landed` only"
+    );
+}
+
+#[test]
+fn test_prune_merged_only_deletes_with_apply() {
+    let (temp, repo) = repo_with_branches();
+    let listing = RepoListing::new(&repo, "tool", false, temp.path());
+    let repos = [listing];
+
+    let mut out = Vec::new();
+    prune::prune_merged(&repos, false, &mut out).unwrap();
+    let listed = String::from_utf8(out).unwrap();
+    assert!(
+        listed.contains("done (merged into")
+            && listed.contains("gone (upstream gone, unmerged) kept"),
+        "both branches should be listed:\n{listed}"
+    );
+    assert!(
+        repo.find_branch("done", BranchType::Local).is_ok(),
+        "a dry run must not delete anything"
+    );
+
+    let mut out = Vec::new();
+    prune::prune_merged(&repos, true, &mut out).unwrap();
+    let deleted = String::from_utf8(out).unwrap();
+    assert_eq!(deleted.matches(" deleted").count(), 1, "{deleted}");
+    assert!(
+        repo.find_branch("done", BranchType::Local).is_err(),
+        "`done` should be deleted"
+    );
+    for name in ["wip", "gone"] {
+        assert!(
+            repo.find_branch(name, BranchType::Local).is_ok(),
+            "`{name}` has commits of its own and should be kept"
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use git2::Repository;
+use tempfile::TempDir;
+
+use crate::{
+    gitinfo::repoinfo::RepoListing,
+    remotes::{self, Conversion, Protocol},
+};
+
+#[test]
+fn test_protocol_of() {
+    for (url, protocol) in [
+        ("git@github.com:acme/tool.git", Some(Protocol::Ssh)),
+        ("ssh://git@github.com:22/acme/tool.git", Some(Protocol::Ssh)),
+        ("https://github.com/acme/tool.git", Some(Protocol::Https)),
+        ("http://gitlab.local/acme/tool.git", Some(Protocol::Http)),
+        ("git://github.com/acme/tool.git", Some(Protocol::Git)),
+        ("file:///srv/git/tool.git", Some(Protocol::File)),
+        ("/srv/git/tool.git", Some(Protocol::File)),
+        ("./relative/path:with-colon", Some(Protocol::File)),
+        ("ftp://example.com/tool.git", None),
+    ] {
+        assert_eq!(Protocol::of(url), protocol, "{url}");
+    }
+}
+
+#[test]
+fn test_conversion() {
+    let to_https: Conversion = "ssh:https".parse().unwrap();
+    assert_eq!(to_https, "ssh→https".parse().unwrap());
+    assert_eq!(
+        to_https.convert("git@github.com:acme/tool.git").as_deref(),
+        Some("https://github.com/acme/tool.git")
+    );
+    assert_eq!(
+        to_https
+            .convert("ssh://git@github.com:22/acme/tool.git")
+            .as_deref(),
+        Some("https://github.com/acme/tool.git"),
+        "the user and port of SSH do not carry over"
+    );
+    assert_eq!(
+        to_https.convert("https://github.com/acme/tool.git"),
+        None,
+        "only remotes of the source protocol are converted"
+    );
+
+    let to_ssh: Conversion = "https:ssh".parse().unwrap();
+    assert_eq!(
+        to_ssh
+            .convert("https://token@github.com/acme/tool.git")
+            .as_deref(),
+        Some("git@github.com:acme/tool.git")
+    );
+
+    for conversion in ["ssh", "ssh:ssh", "ssh:file", "ssh:ftp"] {
+        assert!(
+            conversion.parse::<Conversion>().is_err(),
+            "`{conversion}` should be rejected"
+        );
+    }
+}
+
+#[test]
+fn test_convert_only_rewrites_with_apply() {
+    let temp = TempDir::new().unwrap();
+    let repo = Repository::init(temp.path().join("tool")).unwrap();
+    repo.remote("origin", "git@github.com:acme/tool.git")
+        .unwrap();
+    repo.remote("mirror", "https://gitlab.com/acme/tool.git")
+        .unwrap();
+    repo.remote_set_pushurl("origin", Some("git@github.com:acme/tool-push.git"))
+        .unwrap();
+    let repos = [RepoListing::new(&repo, "tool", false, temp.path())];
+    let conversion: Conversion = "ssh:https".parse().unwrap();
+
+    let mut out = Vec::new();
+    remotes::audit(&repos, &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "tool: mirror https https://gitlab.com/acme/tool.git\n\
+         tool: origin ssh git@github.com:acme/tool.git\n"
+    );
+
+    let mut out = Vec::new();
+    remotes::convert(&repos, conversion, false, &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "tool: origin git@github.com:acme/tool.git → https://github.com/acme/tool.git\n"
+    );
+    assert_eq!(
+        repo.find_remote("origin").unwrap().url().unwrap(),
+        "git@github.com:acme/tool.git",
+        "a dry run must not rewrite anything"
+    );
+
+    let mut out = Vec::new();
+    remotes::convert(&repos, conversion, true, &mut out).unwrap();
+    assert!(String::from_utf8(out).unwrap().ends_with(" rewritten\n"));
+    let origin = repo.find_remote("origin").unwrap();
+    assert_eq!(origin.url().unwrap(), "https://github.com/acme/tool.git");
+    assert_eq!(
+        origin.pushurl().unwrap(),
+        Some("https://github.com/acme/tool-push.git")
+    );
+}
@@ -0,0 +1,183 @@
+use std::path::PathBuf;
+
+use crate::{
+    gitinfo::{repoinfo::RepoInfo, status::Status},
+    report::{DuplicateCheckout, GroupBy, Rollup, RollupRow, duplicates, group, rollup},
+};
+
+fn repo(repo_path: &str, remote_url: Option<&str>, status: Status, ahead: usize) -> RepoInfo {
+    RepoInfo {
+        name: repo_path.to_owned(),
+        branch: "main".to_owned(),
+        ahead,
+        behind: 0,
+        commits: Some(1),
+        status,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
+        has_unpushed: ahead > 0,
+        remote_url: remote_url.map(str::to_owned),
+        path: PathBuf::from("/path/to").join(repo_path),
+        stash_count: 0,
+        is_local_only: false,
+        fast_forwarded: false,
+        repo_path: repo_path.to_owned(),
+        is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
+    }
+}
+
+fn repos() -> Vec<RepoInfo> {
+    vec![
+        repo("a", Some("git@github.com:team/a.git"), Status::Clean, 0),
+        repo("b", Some("https://github.com/team/b"), Status::Unpushed, 2),
+        repo("c", Some("https://github.com/team/c"), Status::Dirty(5), 1),
+        repo("d", Some("https://github.com/other/d"), Status::Clean, 0),
+        repo("e", None, Status::Dirty(1), 0),
+    ]
+}
+
+#[test]
+fn test_rollup_by_org() {
+    let rows = rollup(&repos(), Rollup::Org);
+    assert_eq!(
+        rows,
+        [
+            RollupRow {
+                group: "github.com/other".to_owned(),
+                repos: 1,
+                clean: 1,
+                clean_percent: 100,
+                unpushed_commits: 0,
+                worst_offender: None,
+            },
+            RollupRow {
+                group: "github.com/team".to_owned(),
+                repos: 3,
+                clean: 1,
+                clean_percent: 33,
+                unpushed_commits: 3,
+                worst_offender: Some("c".to_owned()),
+            },
+            RollupRow {
+                group: "local".to_owned(),
+                repos: 1,
+                clean: 0,
+                clean_percent: 0,
+                unpushed_commits: 0,
+                worst_offender: Some("e".to_owned()),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_rollup_by_host() {
+    let rows = rollup(&repos(), Rollup::Host);
+    let groups = rows
+        .iter()
+        .map(|r| (r.group.as_str(), r.repos, r.clean_percent))
+        .collect::<Vec<_>>();
+    assert_eq!(groups, [("github.com", 4, 50), ("local", 1, 0)]);
+}
+
+#[test]
+fn test_duplicates_group_checkouts_of_the_same_remote() {
+    let mut repos = repos();
+    repos.push(repo(
+        "a-backup",
+        Some("https://GitHub.com/team/a"),
+        Status::Clean,
+        0,
+    ));
+    repos.push(repo(
+        "a-old",
+        Some("ssh://git@github.com/team/a.git"),
+        Status::Unpushed,
+        3,
+    ));
+    repos.push(repo("f", None, Status::Clean, 0));
+
+    let groups = duplicates(&repos);
+    assert_eq!(groups.len(), 1, "only `a` is checked out more than once");
+    assert_eq!(groups[0].remote, "github.com/team/a");
+    assert_eq!(
+        groups[0].checkouts,
+        [
+            DuplicateCheckout {
+                repo_path: "a".to_owned(),
+                branch: "main".to_owned(),
+                status: Status::Clean,
+                ahead: 0,
+                behind: 0,
+                stash_count: 0,
+                disposable: true,
+            },
+            DuplicateCheckout {
+                repo_path: "a-backup".to_owned(),
+                branch: "main".to_owned(),
+                status: Status::Clean,
+                ahead: 0,
+                behind: 0,
+                stash_count: 0,
+                disposable: true,
+            },
+            DuplicateCheckout {
+                repo_path: "a-old".to_owned(),
+                branch: "main".to_owned(),
+                status: Status::Unpushed,
+                ahead: 3,
+                behind: 0,
+                stash_count: 0,
+                disposable: false,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_group_by_parent_dir_counts_every_section() {
+    let mut behind = repo("work/web", None, Status::Clean, 0);
+    behind.behind = 3;
+    let repos = [
+        repo("work/api", None, Status::Dirty(2), 1),
+        repo("oss/lib", None, Status::SubmoduleDirty, 0),
+        repo("scratch", None, Status::Clean, 0),
+        behind,
+        repo("oss/tool", None, Status::Unpushed, 2),
+    ];
+
+    let groups = group(&repos, GroupBy::ParentDir);
+
+    let names: Vec<_> = groups.iter().map(|g| g.name.as_str()).collect();
+    assert_eq!(names, [".", "oss", "work"]);
+    let paths: Vec<_> = groups[2]
+        .repos
+        .iter()
+        .map(|r| r.repo_path.as_str())
+        .collect();
+    assert_eq!(paths, ["work/api", "work/web"]);
+    let totals: Vec<_> = groups
+        .iter()
+        .map(|g| (g.dirty, g.behind, g.ahead))
+        .collect();
+    assert_eq!(totals, [(0, 0, 0), (1, 0, 1), (1, 1, 1)]);
+}
@@ -0,0 +1,413 @@
+use std::{fs, sync::mpsc, time::Duration};
+
+use git2::Repository;
+use tempfile::TempDir;
+
+use crate::{
+    gitinfo::{repoinfo::LfsStatus, status::Status},
+    scanner::{FailureBudget, FetchSelector, IntegrityCheck, ScanEvent, ScanResult, Scanner},
+};
+
+/// Lays out `clean`, `nested/deep` and `node_modules/vendored` repositories and a broken
+/// one, each with a commit.
+fn checkouts() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    for name in ["clean", "nested/deep", "node_modules/vendored"] {
+        let path = temp.path().join(name);
+        fs::create_dir_all(&path).unwrap();
+        let repo = Repository::init(&path).unwrap();
+        let sig = git2::Signature::now("Test User", "test@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+            .unwrap();
+    }
+    let broken = temp.path().join("broken");
+    fs::create_dir_all(&broken).unwrap();
+    fs::write(broken.join(".git"), "not a git directory").unwrap();
+    temp
+}
+
+fn repo_paths(result: &ScanResult) -> Vec<String> {
+    result.repos.iter().map(|r| r.repo_path.clone()).collect()
+}
+
+fn failed_names(result: &ScanResult) -> Vec<String> {
+    result.failed.iter().map(|f| f.name.clone()).collect()
+}
+
+#[test]
+fn test_scanner_defaults_to_direct_subdirectories() {
+    let temp = checkouts();
+    let result = Scanner::new(temp.path()).scan();
+
+    assert_eq!(repo_paths(&result), ["clean"]);
+    assert_eq!(failed_names(&result), ["broken"]);
+    assert_eq!(result.repos[0].status, Status::Unpublished);
+}
+
+#[test]
+fn test_scanner_records_timing_only_when_asked() {
+    let temp = checkouts();
+    let result = Scanner::new(temp.path()).scan();
+    assert!(result.repos[0].timing.is_none());
+
+    let result = Scanner::new(temp.path()).timing(true).scan();
+    let timing = result.repos[0].timing.unwrap();
+    assert!(timing.open > Duration::ZERO, "opening takes some time");
+    assert!(
+        timing.fetch < Duration::from_millis(100),
+        "nothing was fetched, so barely any time went into it"
+    );
+}
+
+#[test]
+fn test_scanner_can_skip_counting_commits() {
+    let temp = checkouts();
+    assert_eq!(Scanner::new(temp.path()).scan().repos[0].commits, Some(1));
+
+    let result = Scanner::new(temp.path()).count_commits(false).scan();
+    assert_eq!(result.repos[0].commits, None);
+    assert_eq!(result.repos[0].format_commits(), "-");
+}
+
+#[test]
+fn test_scanner_can_skip_identifying_repositories() {
+    let temp = checkouts();
+    assert!(Scanner::new(temp.path()).scan().repos[0].identity.is_some());
+
+    let result = Scanner::new(temp.path()).identify(false).scan();
+    assert_eq!(result.repos[0].identity, None);
+}
+
+#[test]
+fn test_scanner_looks_for_lfs_objects_only_when_asked() {
+    let temp = checkouts();
+    fs::write(temp.path().join("clean/.lfsconfig"), "").unwrap();
+    assert_eq!(Scanner::new(temp.path()).scan().repos[0].lfs, None);
+
+    let result = Scanner::new(temp.path()).lfs(true).scan();
+    assert_eq!(result.repos[0].lfs, Some(LfsStatus::default()));
+}
+
+#[test]
+fn test_scanner_compares_forks_only_when_asked() {
+    let temp = checkouts();
+    let repo = Repository::open(temp.path().join("clean")).unwrap();
+    let head = repo.head().unwrap().target().unwrap();
+    for remote in ["origin", "upstream"] {
+        repo.reference(&format!("refs/remotes/{remote}/main"), head, false, "test")
+            .unwrap();
+    }
+    assert_eq!(Scanner::new(temp.path()).scan().repos[0].fork_behind, None);
+
+    let result = Scanner::new(temp.path()).forks(true).scan();
+    assert_eq!(result.repos[0].fork_behind, Some(0));
+}
+
+#[test]
+fn test_scanner_counts_gone_branches_only_when_asked() {
+    let temp = checkouts();
+    let repo = Repository::open(temp.path().join("clean")).unwrap();
+    repo.remote("origin", "https://example.com/acme/tool.git")
+        .unwrap();
+    let branch = repo.head().unwrap().shorthand().unwrap().to_owned();
+    let mut config = repo.config().unwrap();
+    config
+        .set_str(&format!("branch.{branch}.remote"), "origin")
+        .unwrap();
+    config
+        .set_str(
+            &format!("branch.{branch}.merge"),
+            &format!("refs/heads/{branch}"),
+        )
+        .unwrap();
+    assert_eq!(Scanner::new(temp.path()).scan().repos[0].gone_branches, 0);
+
+    let result = Scanner::new(temp.path()).gone_branches(true).scan();
+    assert_eq!(result.repos[0].gone_branches, 1);
+}
+
+#[test]
+fn test_scanner_recognizes_partial_clones_only_when_asked() {
+    let temp = checkouts();
+    let repo = Repository::open(temp.path().join("clean")).unwrap();
+    repo.config()
+        .unwrap()
+        .set_str("extensions.partialclone", "origin")
+        .unwrap();
+    assert!(!Scanner::new(temp.path()).scan().repos[0].is_partial);
+
+    let result = Scanner::new(temp.path()).clones(true).scan();
+    assert!(result.repos[0].is_partial);
+}
+
+#[test]
+fn test_scanner_reads_the_user_email_only_when_asked() {
+    let temp = checkouts();
+    let repo = Repository::open(temp.path().join("clean")).unwrap();
+    repo.config()
+        .unwrap()
+        .set_str("user.email", "dev@acme.com")
+        .unwrap();
+    assert_eq!(Scanner::new(temp.path()).scan().repos[0].user_email, None);
+
+    let result = Scanner::new(temp.path()).user_email(true).scan();
+    assert_eq!(result.repos[0].user_email.as_deref(), Some("dev@acme.com"));
+}
+
+#[test]
+fn test_scanner_reports_corrupt_repositories_when_checking_integrity() {
+    let temp = checkouts();
+    let repo = Repository::open(temp.path().join("clean")).unwrap();
+    let head = repo.head().unwrap().target().unwrap().to_string();
+    let (dir, file) = head.split_at(2);
+    fs::remove_file(repo.path().join("objects").join(dir).join(file)).unwrap();
+
+    let result = Scanner::new(temp.path()).scan();
+    assert_eq!(
+        failed_names(&result),
+        ["broken", "clean"],
+        "without the check, the missing commit fails the scan"
+    );
+
+    let result = Scanner::new(temp.path())
+        .check_integrity(Some(IntegrityCheck::Refs))
+        .scan();
+    assert_eq!(repo_paths(&result), ["clean"]);
+    assert_eq!(failed_names(&result), ["broken"]);
+    let clean = &result.repos[0];
+    assert_eq!(clean.status, Status::Corrupt);
+    assert_eq!(clean.commits, None);
+    assert!(
+        clean
+            .integrity_errors
+            .iter()
+            .any(|e| e.contains(&format!("missing object {head}"))),
+        "{:?}",
+        clean.integrity_errors
+    );
+}
+
+#[test]
+fn test_scanner_depth_and_exclude() {
+    let temp = checkouts();
+
+    let result = Scanner::new(temp.path()).depth(-1).scan();
+    assert_eq!(
+        repo_paths(&result),
+        ["clean", "nested/deep", "node_modules/vendored"]
+    );
+
+    let result = Scanner::new(temp.path())
+        .depth(-1)
+        .exclude(["node_modules"])
+        .scan();
+    assert_eq!(repo_paths(&result), ["clean", "nested/deep"]);
+}
+
+#[test]
+fn test_scanner_list_matches_scan() {
+    let temp = checkouts();
+    let scanner = Scanner::new(temp.path())
+        .depth(3)
+        .max_failures(FailureBudget::Percent(50));
+
+    let listed = scanner.list();
+    let full = scanner.scan();
+    assert_eq!(
+        listed
+            .repos
+            .iter()
+            .map(|r| &r.repo_path)
+            .collect::<Vec<_>>(),
+        full.repos.iter().map(|r| &r.repo_path).collect::<Vec<_>>()
+    );
+    assert_eq!(listed.failed, full.failed);
+}
+
+#[test]
+fn test_scanner_reports_events() {
+    let temp = checkouts();
+    let (sender, receiver) = mpsc::channel();
+    let result = Scanner::new(temp.path()).scan_with_events(|event| {
+        sender.send(event).unwrap();
+    });
+    drop(sender);
+    let events = receiver.iter().collect::<Vec<_>>();
+
+    assert_eq!(
+        events.len(),
+        5,
+        "2 found, 2 processed or failed, 1 finished"
+    );
+    assert!(
+        events[..2]
+            .iter()
+            .all(|e| matches!(e, ScanEvent::RepoFound { .. })),
+        "every repository is found before any is processed"
+    );
+    assert!(
+        events.iter().any(|e| matches!(
+            e,
+            ScanEvent::RepoProcessed(repo) if repo.repo_path == "clean"
+        )),
+        "the clean repository is processed"
+    );
+    assert!(
+        events.iter().any(|e| matches!(
+            e,
+            ScanEvent::RepoFailed(failed) if failed.name == "broken" && !failed.error.is_empty()
+        )),
+        "the broken repository fails with a reason"
+    );
+    assert!(matches!(
+        events.last(),
+        Some(ScanEvent::ScanFinished {
+            repos: 1,
+            failed: 1
+        })
+    ));
+    assert_eq!(failed_names(&result), ["broken"]);
+}
+
+#[test]
+fn test_scanner_with_explicit_repositories_does_not_walk() {
+    let temp = checkouts();
+    let result = Scanner::new(temp.path())
+        .repositories([temp.path().join("nested/deep"), temp.path().join("missing")])
+        .scan();
+
+    assert_eq!(repo_paths(&result), ["nested/deep"]);
+    assert_eq!(failed_names(&result), ["missing"]);
+}
+
+#[test]
+fn test_scanner_lists_a_repository_reached_twice_once() {
+    let temp = checkouts();
+    let result = Scanner::new(temp.path())
+        .repositories([
+            temp.path().join("clean"),
+            temp.path().join("nested/../clean"),
+            temp.path().join("clean/."),
+        ])
+        .scan();
+
+    assert_eq!(
+        repo_paths(&result),
+        ["clean"],
+        "the same repository should be scanned once"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_scanner_does_not_list_a_symlinked_repository_twice() {
+    let temp = checkouts();
+    std::os::unix::fs::symlink(temp.path().join("clean"), temp.path().join("link")).unwrap();
+    let result = Scanner::new(temp.path())
+        .repositories([temp.path().join("clean"), temp.path().join("link")])
+        .scan();
+
+    assert_eq!(
+        repo_paths(&result),
+        ["clean"],
+        "the symlink leads to `clean`"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_scanner_keeps_the_shortest_path_of_a_repository_unless_told_not_to_dedupe() {
+    let temp = checkouts();
+    std::os::unix::fs::symlink(temp.path().join("clean"), temp.path().join("clean-link")).unwrap();
+    let paths = [temp.path().join("clean-link"), temp.path().join("clean")];
+
+    let result = Scanner::new(temp.path()).repositories(&paths).scan();
+    assert_eq!(repo_paths(&result), ["clean"]);
+    assert_eq!(
+        result.repos[0].name, "clean",
+        "the repository is named after its shortest path, not the one found first"
+    );
+
+    let result = Scanner::new(temp.path())
+        .repositories(&paths)
+        .dedupe(false)
+        .scan();
+    assert_eq!(repo_paths(&result), ["clean", "clean-link"]);
+}
+
+#[test]
+fn test_fetch_selector_matches() {
+    let temp = TempDir::new().unwrap();
+    let repo = Repository::init(temp.path()).unwrap();
+    repo.remote("origin", "git@github.com:acme/tool.git")
+        .unwrap();
+
+    assert!(FetchSelector::All.matches(&repo), "all matches everything");
+    assert!(
+        !FetchSelector::Behind.matches(&repo),
+        "a repository without upstream is not behind"
+    );
+    for pattern in ["github.com/acme/*", "*/tool", "github.com/acme/tool"] {
+        assert!(
+            FetchSelector::Remote(pattern.to_owned()).matches(&repo),
+            "`{pattern}` should match"
+        );
+    }
+    for pattern in ["github.com/other/*", "gitlab.com/*", "github.com/acme"] {
+        assert!(
+            !FetchSelector::Remote(pattern.to_owned()).matches(&repo),
+            "`{pattern}` should not match"
+        );
+    }
+}
+
+#[test]
+fn test_fetch_selector_round_trips() {
+    for selector in ["all", "behind", "remote:github.com/acme/*"] {
+        assert_eq!(
+            selector.parse::<FetchSelector>().unwrap().to_string(),
+            selector,
+            "`{selector}` should be written as it is read"
+        );
+    }
+}
+
+#[test]
+fn test_scanner_scans_every_root_under_its_name() {
+    let work = TempDir::new().unwrap();
+    let oss = checkouts();
+    let init = |path: &std::path::Path| {
+        fs::create_dir_all(path).unwrap();
+        Repository::init(path).unwrap();
+    };
+    init(&work.path().join("api"));
+    let work_name = work.path().file_name().unwrap().to_str().unwrap();
+    let oss_name = oss.path().file_name().unwrap().to_str().unwrap();
+
+    let result = Scanner::new(work.path()).root(oss.path()).scan();
+
+    let mut expected = [format!("{work_name}/api"), format!("{oss_name}/clean")];
+    expected.sort_by_key(|path| path.to_lowercase());
+    assert_eq!(repo_paths(&result), expected);
+    for repo in &result.repos {
+        let root = if repo.repo_path.starts_with(work_name) {
+            work.path()
+        } else {
+            oss.path()
+        };
+        assert_eq!(repo.root, root);
+    }
+
+    // A root that is a repository itself is shown under its name alone.
+    let result = Scanner::new(work.path().join("api"))
+        .root(oss.path())
+        .scan();
+    assert!(
+        repo_paths(&result).contains(&"api".to_owned()),
+        "{:?}",
+        repo_paths(&result)
+    );
+}
@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use tempfile::TempDir;
+
+use crate::{
+    cli::{Args, Command, DaemonAction},
+    run,
+    service::{launchd_agent, systemd_unit},
+};
+
+fn command() -> Vec<String> {
+    vec![
+        "/usr/bin/git-statuses".to_owned(),
+        "/home/me/My Projects".to_owned(),
+        "--depth=2".to_owned(),
+        "daemon".to_owned(),
+    ]
+}
+
+#[test]
+fn test_systemd_unit_quotes_arguments() {
+    let unit = systemd_unit(&command());
+    assert!(
+        unit.contains(
+            "ExecStart=/usr/bin/git-statuses \"/home/me/My Projects\" --depth=2 daemon\n"
+        ),
+        "arguments with spaces must be quoted: {unit}"
+    );
+    assert!(unit.contains("WantedBy=default.target"));
+}
+
+#[test]
+fn test_systemd_unit_escapes_specifiers() {
+    let unit = systemd_unit(&["/bin/git-statuses".to_owned(), "/srv/100%".to_owned()]);
+    assert!(unit.contains("ExecStart=/bin/git-statuses /srv/100%%\n"));
+}
+
+#[test]
+fn test_launchd_agent_lists_arguments() {
+    let agent = launchd_agent(&[
+        "/usr/bin/git-statuses".to_owned(),
+        "/Users/me/R&D".to_owned(),
+    ]);
+    assert!(agent.contains("<string>/usr/bin/git-statuses</string>"));
+    assert!(
+        agent.contains("<string>/Users/me/R&amp;D</string>"),
+        "XML special characters must be escaped: {agent}"
+    );
+    assert!(agent.contains("<key>KeepAlive</key>"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_daemon_install_print_carries_scan_options() {
+    let temp = TempDir::new().unwrap();
+    let args = Args {
+        dirs: vec![temp.path().to_path_buf()],
+        depth: -1,
+        remote: true,
+        subdir: Some("checkout".to_owned()),
+        socket: Some(PathBuf::from("/tmp/test.sock")),
+        command: Some(Command::Daemon {
+            interval: 60,
+            action: Some(DaemonAction::Install { print: true }),
+        }),
+        ..Default::default()
+    };
+
+    let mut out = Vec::new();
+    run(&args, &mut out).unwrap();
+    let service = String::from_utf8(out).unwrap();
+
+    let dir = temp.path().canonicalize().unwrap();
+    for expected in [
+        dir.display().to_string(),
+        "--depth=-1".to_owned(),
+        "--subdir=checkout".to_owned(),
+        "--remote".to_owned(),
+        "--socket=/tmp/test.sock".to_owned(),
+        "daemon".to_owned(),
+        "--interval=60".to_owned(),
+    ] {
+        assert!(
+            service.contains(&expected),
+            "`{expected}` missing from the service file:\n{service}"
+        );
+    }
+    assert!(!service.contains("--fetch"), "--fetch was not requested");
+}
+
+#[test]
+fn test_run_daemon_install_requires_an_existing_directory() {
+    let args = Args {
+        dirs: vec![PathBuf::from("/nonexistent/directory/that/does/not/exist")],
+        command: Some(Command::Daemon {
+            interval: 60,
+            action: Some(DaemonAction::Install { print: true }),
+        }),
+        ..Default::default()
+    };
+    assert!(run(&args, &mut Vec::new()).is_err());
+}
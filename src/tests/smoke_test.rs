@@ -97,10 +97,10 @@ fn test_multiple_repos_with_different_remotes_and_statuses() {
     let start = Instant::now();
 
     let args = Args {
-        dir: base_path.to_path_buf(),
+        dirs: vec![base_path.to_path_buf()],
         ..Default::default()
     };
-    _ = args.find_repositories();
+    _ = args.find_repositories().unwrap();
 
     let duration = start.elapsed();
 
@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use crate::{
+    gitinfo::{
+        repoinfo::{FailedRepo, RepoInfo},
+        status::Status,
+    },
+    template,
+};
+
+fn repo(name: &str, status: Status) -> RepoInfo {
+    RepoInfo {
+        name: name.to_owned(),
+        branch: "main".to_owned(),
+        ahead: 0,
+        behind: 0,
+        commits: Some(1),
+        status,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
+        has_unpushed: false,
+        remote_url: None,
+        path: PathBuf::from("/path/to").join(name),
+        stash_count: 0,
+        is_local_only: false,
+        fast_forwarded: false,
+        repo_path: name.to_owned(),
+        is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
+    }
+}
+
+#[test]
+fn test_template_sees_repositories_failed_and_summary() {
+    let failed = FailedRepo {
+        name: "broken".to_owned(),
+        path: PathBuf::from("/path/to/broken"),
+        error: "corrupt".to_owned(),
+    };
+    let rendered = template::render(
+        "report.txt",
+        "{% for repo in repositories %}{{ repo.repo_path }} {{ repo.branch }}\n{% endfor %}\
+         {% for repo in failed %}! {{ repo.name }}: {{ repo.error }}\n{% endfor %}\
+         {{ summary.clean }}/{{ summary.total }} clean, {{ summary.failed }} failed\n",
+        &[repo("a", Status::Clean), repo("b", Status::Dirty(1))],
+        &[failed],
+    )
+    .unwrap();
+    assert_eq!(
+        rendered,
+        "a main\nb main\n! broken: corrupt\n1/2 clean, 1 failed\n"
+    );
+}
+
+#[test]
+fn test_template_escapes_by_file_extension() {
+    let repos = [repo("<b>", Status::Clean)];
+    let source = "{{ repositories[0].name }}";
+    assert_eq!(
+        template::render("report.html", source, &repos, &[]).unwrap(),
+        "&lt;b&gt;"
+    );
+    assert_eq!(
+        template::render("report.txt", source, &repos, &[]).unwrap(),
+        "<b>"
+    );
+}
+
+#[test]
+fn test_template_syntax_error() {
+    assert!(
+        template::render("broken.txt", "{% for %}", &[], &[]).is_err(),
+        "an unfinished tag is an error"
+    );
+}
@@ -0,0 +1,80 @@
+use comfy_table::Color;
+use strum::IntoEnumIterator as _;
+
+use crate::{
+    gitinfo::status::Status,
+    theme::{Palette, StatusColor, Theme},
+};
+
+#[test]
+fn test_status_color_parsing() {
+    let status_color: StatusColor = "dirty=dark-yellow".parse().unwrap();
+    assert_eq!(status_color.status, Status::Dirty(0));
+    assert_eq!(status_color.color, Color::DarkYellow);
+
+    let status_color: StatusColor = "cherry-pick = #E69F00".parse().unwrap();
+    assert_eq!(status_color.status, Status::CherryPick);
+    assert_eq!(
+        status_color.color,
+        Color::Rgb {
+            r: 230,
+            g: 159,
+            b: 0
+        }
+    );
+}
+
+#[test]
+fn test_status_color_parsing_errors() {
+    for (input, error) in [
+        ("dirty", "is not STATUS=COLOR"),
+        ("messy=red", "unknown status `messy`"),
+        ("dirty=rose", "unknown color `rose`"),
+        ("dirty=#e69f", "is not a color"),
+        ("dirty=#e69fzz", "is not a color"),
+    ] {
+        let message = input.parse::<StatusColor>().unwrap_err();
+        assert!(message.contains(error), "{input}: {message}");
+    }
+}
+
+#[test]
+fn test_every_status_has_a_key() {
+    for status in Status::iter() {
+        let parsed: StatusColor = format!("{}=red", status.key()).parse().unwrap();
+        assert_eq!(parsed.status, status);
+    }
+}
+
+#[test]
+fn test_theme_overrides_the_palette() {
+    let overrides = ["dirty=yellow".parse().unwrap()];
+    let theme = Theme::new(Palette::Default, &overrides);
+    assert_eq!(theme.color(&Status::Dirty(3)), Color::Yellow);
+    assert_eq!(theme.color(&Status::Merge), Status::Merge.comfy_color());
+
+    let theme = Theme::new(Palette::Colorblind, &overrides);
+    assert_eq!(theme.color(&Status::Dirty(3)), Color::Yellow);
+    assert_eq!(
+        theme.color(&Status::Merge),
+        Status::Merge.colorblind_color()
+    );
+    assert_eq!(Theme::default().color(&Status::Dirty(1)), Color::Red);
+}
+
+#[test]
+fn test_colorblind_palette_avoids_red_and_green() {
+    for status in Status::iter() {
+        assert!(
+            !matches!(
+                status.colorblind_color(),
+                Color::Red | Color::DarkRed | Color::Green | Color::DarkGreen
+            ),
+            "{status}"
+        );
+    }
+    assert_ne!(
+        Status::Dirty(1).colorblind_color(),
+        Status::Clean.colorblind_color()
+    );
+}
@@ -0,0 +1,63 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::Duration,
+};
+
+use crate::{scanner::HostLimit, throttle::FetchLimits};
+
+#[test]
+fn test_host_limit_parses() {
+    assert_eq!(
+        "GitHub.com=4".parse::<HostLimit>(),
+        Ok(HostLimit {
+            host: "github.com".to_owned(),
+            limit: 4,
+        })
+    );
+    assert_eq!(
+        "*.acme.com=1".parse::<HostLimit>().unwrap().to_string(),
+        "*.acme.com=1"
+    );
+    for invalid in ["github.com", "github.com=0", "=2", "github.com=many"] {
+        assert!(
+            invalid.parse::<HostLimit>().is_err(),
+            "`{invalid}` is no host limit"
+        );
+    }
+}
+
+#[test]
+fn test_fetch_limits_wait_for_a_free_slot() {
+    let limits = FetchLimits::new(
+        Some(2),
+        vec![HostLimit {
+            host: "*github.com".to_owned(),
+            limit: 1,
+        }],
+    );
+    let github = limits.acquire(Some("github.com"));
+    let other = limits.acquire(Some("gitlab.com"));
+    let started = AtomicBool::new(false);
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            let _permit = limits.acquire(Some("GitHub.com"));
+            started.store(true, Ordering::SeqCst);
+        });
+        thread::sleep(Duration::from_millis(100));
+        assert!(
+            !started.load(Ordering::SeqCst),
+            "a second fetch from github.com has to wait"
+        );
+        drop(other);
+        thread::sleep(Duration::from_millis(100));
+        assert!(
+            !started.load(Ordering::SeqCst),
+            "a free slot in total is not enough while the host is at its limit"
+        );
+        drop(github);
+    });
+    assert!(started.load(Ordering::SeqCst));
+
+    let _permits = [limits.acquire(None), limits.acquire(Some("example.com"))];
+}
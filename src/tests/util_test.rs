@@ -2,7 +2,10 @@
 use crate::cli::Args;
 use crate::gitinfo::{repoinfo::RepoInfo, status::Status};
 use crate::printer;
-use crate::util::{GitPathExt, initialize_logger};
+use crate::util::{
+    GitPathExt, format_date, format_size, initialize_logger, normalize_path, path_key,
+    strip_path_prefix, to_ascii, truncate_middle, wildcard_match,
+};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
@@ -16,11 +19,11 @@ fn test_initialize_logger() {
 fn test_find_repositories_empty_dir() {
     let temp = TempDir::new().unwrap();
     let args = Args {
-        dir: temp.path().to_path_buf(),
+        dirs: vec![temp.path().to_path_buf()],
         depth: 1,
         ..Default::default()
     };
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
     assert!(repos.is_empty());
     assert!(failed.is_empty());
 }
@@ -33,8 +36,12 @@ fn test_print_repositories_and_summary() {
         branch: "main".to_owned(),
         ahead: 0,
         behind: 0,
-        commits: 1,
+        commits: Some(1),
         status: Status::Clean,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
         has_unpushed: false,
         remote_url: None,
         path: PathBuf::from("/path/to/dummy"),
@@ -43,9 +50,26 @@ fn test_print_repositories_and_summary() {
         fast_forwarded: false,
         repo_path: "dummy".to_owned(),
         is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
     };
     let args = Args {
-        dir: Path::new(".").to_path_buf(),
+        dirs: vec![Path::new(".").to_path_buf()],
         depth: 1,
         summary: true,
         ..Default::default()
@@ -61,11 +85,11 @@ fn test_find_repositories_with_non_git_dir() {
     let subdir = temp.path().join("foo");
     fs::create_dir_all(&subdir).unwrap();
     let args = Args {
-        dir: temp.path().to_path_buf(),
+        dirs: vec![temp.path().to_path_buf()],
         depth: 1,
         ..Default::default()
     };
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
     assert!(repos.is_empty());
     assert!(failed.is_empty());
 }
@@ -77,8 +101,12 @@ fn test_print_repositories_with_remote() {
         branch: "main".to_owned(),
         ahead: 0,
         behind: 0,
-        commits: 1,
+        commits: Some(1),
         status: Status::Clean,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
         has_unpushed: false,
         remote_url: Some("https://example.com".to_owned()),
         path: PathBuf::from("/path/to/dummy"),
@@ -87,9 +115,26 @@ fn test_print_repositories_with_remote() {
         fast_forwarded: false,
         repo_path: "dummy".to_owned(),
         is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
     };
     let args = Args {
-        dir: Path::new(".").to_path_buf(),
+        dirs: vec![Path::new(".").to_path_buf()],
         depth: 1,
         remote: true,
         ..Default::default()
@@ -163,12 +208,12 @@ fn test_find_repositories_basic_functionality() {
 
     // Test basic find_repositories functionality
     let args = Args {
-        dir: temp.path().to_path_buf(),
+        dirs: vec![temp.path().to_path_buf()],
         depth: 1,
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     // Should complete without error (empty dir)
     assert_eq!(failed.len(), 0);
@@ -181,12 +226,12 @@ fn test_find_repositories_negative_depth() {
 
     // Test unlimited depth behavior
     let args = Args {
-        dir: temp.path().to_path_buf(),
+        dirs: vec![temp.path().to_path_buf()],
         depth: -1,
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     // Should complete without crashing (empty dir, no repos expected)
     assert_eq!(failed.len(), 0);
@@ -199,12 +244,12 @@ fn test_find_repositories_depth_zero() {
 
     // Test depth 0 behavior - should work like depth 1
     let args = Args {
-        dir: temp.path().to_path_buf(),
+        dirs: vec![temp.path().to_path_buf()],
         depth: 0,
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     // Should complete without crashing (empty dir, no repos expected)
     assert_eq!(failed.len(), 0);
@@ -221,16 +266,16 @@ fn test_find_repositories_with_failed_repos() {
     fs::write(fake_git_dir.join(".git"), "this is not a git directory").unwrap();
 
     let args = Args {
-        dir: temp.path().to_path_buf(),
+        dirs: vec![temp.path().to_path_buf()],
         depth: 1,
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     assert_eq!(repos.len(), 0);
     assert_eq!(failed.len(), 1);
-    assert_eq!(failed[0], "fake-repo");
+    assert_eq!(failed[0].name, "fake-repo");
 }
 
 #[test]
@@ -242,15 +287,134 @@ fn test_find_repositories_with_subdir_not_found() {
     fs::create_dir_all(&project_dir).unwrap();
 
     let args = Args {
-        dir: temp.path().to_path_buf(),
+        dirs: vec![temp.path().to_path_buf()],
         depth: 2,
         subdir: Some("nonexistent".to_owned()),
         ..Default::default()
     };
 
-    let (repos, failed) = args.find_repositories();
+    let (repos, failed) = args.find_repositories().unwrap();
 
     // Should find no repos because subdir doesn't exist
     assert_eq!(repos.len(), 0);
     assert_eq!(failed.len(), 0);
 }
+
+#[test]
+fn test_wildcard_match() {
+    for (pattern, text) in [
+        ("", ""),
+        ("abc", "abc"),
+        ("*", "anything/at/all"),
+        ("github.com/*", "github.com/acme/tool"),
+        ("*/tool", "github.com/acme/tool"),
+        ("a*b*c", "aXbYc"),
+        ("a*a", "aa"),
+    ] {
+        assert!(
+            wildcard_match(pattern, text),
+            "`{pattern}` should match `{text}`"
+        );
+    }
+    for (pattern, text) in [
+        ("", "a"),
+        ("abc", "abcd"),
+        ("github.com/*", "gitlab.com/acme"),
+        ("a*a", "a"),
+        ("a*b*c", "aXcYb"),
+    ] {
+        assert!(
+            !wildcard_match(pattern, text),
+            "`{pattern}` should not match `{text}`"
+        );
+    }
+}
+
+#[test]
+fn test_path_key_resolves_dots() {
+    let temp = TempDir::new().unwrap();
+    let repo = temp.path().join("repo");
+    fs::create_dir_all(&repo).unwrap();
+
+    assert_eq!(
+        path_key(&repo.join("..").join("repo")),
+        path_key(&repo),
+        "`..` should not make a different path"
+    );
+    assert_eq!(
+        normalize_path(&temp.path().join("missing")),
+        temp.path().join("missing"),
+        "a missing path should be kept as it is"
+    );
+}
+
+#[test]
+fn test_strip_path_prefix() {
+    assert_eq!(
+        strip_path_prefix(Path::new("/src/a/b"), Path::new("/src")),
+        Some(Path::new("a/b")),
+        "the prefix should be stripped"
+    );
+    assert_eq!(
+        strip_path_prefix(Path::new("/srcs/a"), Path::new("/src")),
+        None,
+        "only whole components are a prefix"
+    );
+    assert_eq!(
+        strip_path_prefix(Path::new("/Src/a"), Path::new("/src")).is_some(),
+        cfg!(any(windows, target_os = "macos")),
+        "case should only be ignored where file systems do"
+    );
+}
+
+#[test]
+fn test_format_date() {
+    assert_eq!(format_date(0), "1970-01-01");
+    assert_eq!(
+        format_date(951_782_400),
+        "2000-02-29",
+        "leap days are counted"
+    );
+    assert_eq!(format_date(1_614_816_000 + 86_399), "2021-03-04");
+    assert_eq!(
+        format_date(-86_400),
+        "1969-12-31",
+        "dates before 1970 work too"
+    );
+}
+
+#[test]
+fn test_truncate_middle() {
+    assert_eq!(truncate_middle("work/tool", 9), "work/tool");
+    assert_eq!(
+        truncate_middle("work/clients/acme/tool", 12),
+        "work/c…/tool"
+    );
+    assert_eq!(
+        truncate_middle("Ärger/ünter", 6),
+        "Ärg…er",
+        "counts characters"
+    );
+    assert_eq!(truncate_middle("work/tool", 1), "…");
+}
+
+#[test]
+fn test_to_ascii() {
+    assert_eq!(to_ascii("↑2 ↓1 (fork ↓5)"), "^2 v1 (fork v5)");
+    assert_eq!(to_ascii("⎇ work/c…/tool"), "@ work/c.../tool");
+    assert_eq!(to_ascii("│   └── tool"), "|   `-- tool");
+    assert_eq!(to_ascii("Ärger"), "Ärger", "names are kept");
+}
+
+#[test]
+fn test_format_size() {
+    assert_eq!(format_size(0), "0 B");
+    assert_eq!(format_size(1023), "1023 B");
+    assert_eq!(format_size(1536), "1.5 KiB");
+    assert_eq!(
+        format_size(3 * 1024 * 1024 * 1024 - 1),
+        "2.9 GiB",
+        "sizes are rounded down"
+    );
+    assert_eq!(format_size(u64::MAX), "16777215.9 TiB");
+}
@@ -0,0 +1,63 @@
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use git2::Repository;
+use tempfile::TempDir;
+
+use crate::{scanner::Scanner, watcher::GitWatcher};
+
+fn create_repo(parent: &Path, name: &str) -> Repository {
+    let repo_path = parent.join(name);
+    fs::create_dir_all(&repo_path).unwrap();
+    Repository::init(&repo_path).unwrap()
+}
+
+/// Stages `file` in `repo`, which rewrites its index.
+fn stage(repo: &Repository, file: &str) {
+    let workdir = repo.workdir().unwrap();
+    fs::write(workdir.join(file), file).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(file)).unwrap();
+    index.write().unwrap();
+}
+
+#[test]
+fn test_watcher_reports_the_repository_whose_index_changed() {
+    let temp = TempDir::new().unwrap();
+    let first = create_repo(temp.path(), "first");
+    create_repo(temp.path(), "second");
+    let result = Scanner::new(temp.path()).scan();
+    let mut watcher = GitWatcher::new();
+    watcher.watch(&result.repos);
+
+    stage(&first, "new.txt");
+    let changed = watcher.wait(Instant::now() + Duration::from_secs(5));
+
+    let first_path = result
+        .repos
+        .iter()
+        .find(|r| r.repo_path == "first")
+        .map(|r| r.path.clone());
+    assert_eq!(changed.into_iter().next(), first_path);
+}
+
+#[test]
+fn test_watcher_scans_do_not_count_as_changes() {
+    let temp = TempDir::new().unwrap();
+    let first = create_repo(temp.path(), "first");
+    stage(&first, "new.txt");
+    let scanner = Scanner::new(temp.path());
+    let mut watcher = GitWatcher::new();
+    watcher.watch(&scanner.scan().repos);
+
+    let deadline = Instant::now() + Duration::from_millis(500);
+    let _ = scanner.scan();
+    assert!(
+        watcher.wait(deadline).is_empty(),
+        "a scan only reads the repository"
+    );
+    assert!(Instant::now() >= deadline, "waits until the deadline");
+}
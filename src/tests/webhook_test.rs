@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use crate::{
+    gitinfo::{repoinfo::RepoInfo, status::Status},
+    webhook::{WebhookFormat, curl_config, payload},
+};
+
+fn repo(repo_path: &str, status: Status, ahead: usize) -> RepoInfo {
+    RepoInfo {
+        name: repo_path.to_owned(),
+        branch: "main".to_owned(),
+        ahead,
+        behind: 0,
+        commits: Some(1),
+        status,
+        conflicts: 0,
+        progress: None,
+        stale_locks: Vec::new(),
+        integrity_errors: Vec::new(),
+        has_unpushed: ahead > 0,
+        remote_url: None,
+        path: PathBuf::from("/path/to").join(repo_path),
+        stash_count: 0,
+        is_local_only: false,
+        fast_forwarded: false,
+        repo_path: repo_path.to_owned(),
+        is_worktree: false,
+        identity: None,
+        fork_behind: None,
+        gone_branches: 0,
+        is_shallow: false,
+        is_partial: false,
+        head_sha: None,
+        lfs: None,
+        user_email: None,
+        forge: None,
+        timing: None,
+        activity: None,
+        housekeeping: None,
+        disk_usage: None,
+        signatures: None,
+        hidden_files: None,
+        ignored: None,
+        root: PathBuf::new(),
+    }
+}
+
+fn repos() -> [RepoInfo; 3] {
+    [
+        repo("api", Status::Dirty(2), 0),
+        repo("web", Status::Unpushed, 3),
+        repo("docs", Status::Clean, 0),
+    ]
+}
+
+#[test]
+fn test_json_payload_holds_the_summary_and_optionally_the_repositories() {
+    let value = payload(&repos(), 1, WebhookFormat::Json, false);
+    assert_eq!(value["summary"]["total"], 3);
+    assert_eq!(value["summary"]["failed"], 1);
+    assert!(value.get("repositories").is_none());
+
+    let value = payload(&repos(), 0, WebhookFormat::Json, true);
+    let directories: Vec<_> = value["repositories"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|repo| repo["directory"].as_str().unwrap())
+        .collect();
+    assert_eq!(directories, ["api", "web"]);
+    assert_eq!(value["repositories"][1]["ahead"], 3);
+}
+
+#[test]
+fn test_slack_payload_is_a_text_message() {
+    let value = payload(&repos(), 1, WebhookFormat::Slack, true);
+    assert_eq!(
+        value["text"],
+        "*git-statuses*: 3 repositories, 1 clean, 1 with changes, 1 with unpushed commits \
+         (3 commits), 1 failed\n\
+         • `api` (main): Dirty (2), ↑0 ↓0\n\
+         • `web` (main): Unpushed, ↑3 ↓0"
+    );
+}
+
+#[test]
+fn test_curl_config_escapes_the_values() {
+    assert_eq!(
+        curl_config("https://hooks.example.com/a\"b", "{\"text\":\"a\\\\b\nc\"}"),
+        "url = \"https://hooks.example.com/a\\\"b\"\n\
+         data-binary = \"{\\\"text\\\":\\\"a\\\\\\\\b\\nc\\\"}\"\n"
+    );
+}
@@ -0,0 +1,72 @@
+use std::fs;
+
+use git2::Repository;
+use serde_json::Value;
+use tempfile::TempDir;
+
+use crate::{
+    cli::Args,
+    run,
+    scanner::Scanner,
+    workspace::{self, Editor},
+};
+
+/// Lays out the `alpha` and `nested/beta` repositories, with a change in `beta`.
+fn checkouts() -> TempDir {
+    let temp = TempDir::new().unwrap();
+    for name in ["alpha", "nested/beta"] {
+        let path = temp.path().join(name);
+        fs::create_dir_all(&path).unwrap();
+        Repository::init(&path).unwrap();
+    }
+    fs::write(temp.path().join("nested/beta/new.txt"), "new").unwrap();
+    temp
+}
+
+#[test]
+fn test_code_workspace_lists_repositories_relative_to_it() {
+    let temp = checkouts();
+    let repos = Scanner::new(temp.path()).depth(2).scan().repos;
+    let file = Editor::Code.default_path(temp.path());
+    let workspace: Value =
+        serde_json::from_str(&workspace::render(Editor::Code, &repos, &file)).unwrap();
+    let paths: Vec<_> = workspace["folders"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|folder| folder["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, ["alpha", "nested/beta"]);
+    assert_eq!(workspace["folders"][1]["name"], "nested/beta");
+}
+
+#[test]
+fn test_idea_workspace_maps_repositories_below_the_project() {
+    let temp = checkouts();
+    let repos = Scanner::new(temp.path()).depth(2).scan().repos;
+    let file = Editor::Idea.default_path(temp.path());
+    assert_eq!(file, temp.path().join(".idea/vcs.xml"));
+    let xml = workspace::render(Editor::Idea, &repos, &file);
+    assert!(
+        xml.contains(r#"<mapping directory="$PROJECT_DIR$/nested/beta" vcs="Git" />"#),
+        "{xml}"
+    );
+}
+
+#[test]
+fn test_emit_workspace_keeps_only_repositories_passing_the_filters() {
+    let temp = checkouts();
+    let file = temp.path().join("in-flight.sublime-project");
+    let args = Args {
+        dirs: vec![temp.path().to_path_buf()],
+        depth: 2,
+        min_dirty: Some(1),
+        emit_workspace: Some(Editor::Sublime),
+        workspace_file: Some(file.clone()),
+        ..Default::default()
+    };
+    run(&args, &mut Vec::new()).unwrap();
+    let workspace: Value = serde_json::from_str(&fs::read_to_string(file).unwrap()).unwrap();
+    assert_eq!(workspace["folders"].as_array().unwrap().len(), 1);
+    assert_eq!(workspace["folders"][0]["path"], "nested/beta");
+}
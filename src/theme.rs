@@ -0,0 +1,123 @@
+use std::{mem, str::FromStr};
+
+use clap::ValueEnum;
+use comfy_table::Color;
+use strum::{EnumCount as _, IntoEnumIterator as _};
+
+use crate::gitinfo::status::Status;
+
+/// The built-in color palettes of the statuses, see `--theme`.
+#[derive(ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Palette {
+    /// Red for changes, and a color of its own for most other statuses
+    #[default]
+    Default,
+    /// The Okabe-Ito colors, which stay apart with red-green color blindness: orange for
+    /// changes, blues and greens for operations in progress
+    Colorblind,
+}
+
+/// A status with the color to show it in instead of the one of the palette, parsed from
+/// `STATUS=COLOR` for `--status-color`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusColor {
+    /// The status, of which only the variant counts, e.g. any `Dirty`.
+    pub status: Status,
+    /// The color to show it in.
+    pub color: Color,
+}
+
+impl FromStr for StatusColor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((key, color)) = s.split_once('=') else {
+            return Err(format!("`{s}` is not STATUS=COLOR, e.g. `dirty=yellow`"));
+        };
+        let status = Status::iter()
+            .find(|status| status.key() == key.trim())
+            .ok_or_else(|| {
+                let keys: Vec<_> = Status::iter().map(|status| status.key()).collect();
+                format!("unknown status `{key}`, use one of {}", keys.join(", "))
+            })?;
+        Ok(Self {
+            status,
+            color: parse_color(color.trim())?,
+        })
+    }
+}
+
+/// Parses a color by its name, e.g. `dark-yellow`, or as `#rrggbb`.
+fn parse_color(s: &str) -> Result<Color, String> {
+    if let Some(hex) = s.strip_prefix('#') {
+        let channel = |range| {
+            hex.get(range)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+        };
+        return match (hex.len(), channel(0..2), channel(2..4), channel(4..6)) {
+            (6, Some(r), Some(g), Some(b)) => Ok(Color::Rgb { r, g, b }),
+            _ => Err(format!("`{s}` is not a color, use `#rrggbb`")),
+        };
+    }
+    Ok(match s.to_ascii_lowercase().as_str() {
+        "reset" | "default" => Color::Reset,
+        "black" => Color::Black,
+        "dark-grey" => Color::DarkGrey,
+        "red" => Color::Red,
+        "dark-red" => Color::DarkRed,
+        "green" => Color::Green,
+        "dark-green" => Color::DarkGreen,
+        "yellow" => Color::Yellow,
+        "dark-yellow" => Color::DarkYellow,
+        "blue" => Color::Blue,
+        "dark-blue" => Color::DarkBlue,
+        "magenta" => Color::Magenta,
+        "dark-magenta" => Color::DarkMagenta,
+        "cyan" => Color::Cyan,
+        "dark-cyan" => Color::DarkCyan,
+        "white" => Color::White,
+        "grey" => Color::Grey,
+        _ => {
+            return Err(format!(
+                "unknown color `{s}`, use a name like `dark-yellow` or `#rrggbb`"
+            ));
+        }
+    })
+}
+
+/// The colors the statuses are shown in on the terminal: those of a palette, some of them
+/// replaced by `--status-color`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    palette: Palette,
+    /// The replaced colors, by the position of the status in `Status::iter`.
+    colors: [Option<Color>; Status::COUNT],
+}
+
+impl Theme {
+    /// Creates the theme of `palette` with the colors of `overrides` in place of its own.
+    #[must_use]
+    pub fn new(palette: Palette, overrides: &[StatusColor]) -> Self {
+        let mut colors = [None; Status::COUNT];
+        for status_color in overrides {
+            colors[index(&status_color.status)] = Some(status_color.color);
+        }
+        Self { palette, colors }
+    }
+
+    /// Returns the color to show `status` in.
+    #[must_use]
+    pub fn color(&self, status: &Status) -> Color {
+        self.colors[index(status)].unwrap_or_else(|| match self.palette {
+            Palette::Default => status.comfy_color(),
+            Palette::Colorblind => status.colorblind_color(),
+        })
+    }
+}
+
+/// Returns the position of the variant of `status` in `Status::iter`.
+fn index(status: &Status) -> usize {
+    Status::iter()
+        .position(|variant| mem::discriminant(&variant) == mem::discriminant(status))
+        .unwrap_or_default()
+}
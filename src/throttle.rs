@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use parking_lot::{Condvar, Mutex};
+
+use crate::{scanner::HostLimit, util::wildcard_match};
+
+/// Limits how many fetches run at the same time, in total and per host, see `--fetch-jobs`
+/// and `--fetch-host-limit`.
+///
+/// A fetch waits for a free slot on the worker thread of its repository, so a limit below
+/// the number of scan threads leaves the other threads waiting rather than scanning.
+#[derive(Debug, Default)]
+pub struct FetchLimits {
+    /// How many fetches may run at the same time, `None` for no limit.
+    total: Option<usize>,
+    /// The limits per host, the first matching one applies.
+    hosts: Vec<HostLimit>,
+    running: Mutex<Running>,
+    /// Signalled whenever a fetch finishes.
+    finished: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct Running {
+    /// Fetches running in total.
+    total: usize,
+    /// Fetches running per host pattern, keyed by the index in `FetchLimits::hosts`.
+    hosts: BTreeMap<usize, usize>,
+}
+
+/// A slot taken by a running fetch, given back when dropped.
+#[must_use = "the slot is given back as soon as the permit is dropped"]
+pub struct FetchPermit<'a> {
+    limits: &'a FetchLimits,
+    host: Option<usize>,
+}
+
+impl FetchLimits {
+    /// Creates the limits for a scan.
+    /// # Arguments
+    /// * `total` - How many fetches may run at the same time, `None` for no limit.
+    /// * `hosts` - The limits per host.
+    #[must_use]
+    pub fn new(total: Option<usize>, hosts: Vec<HostLimit>) -> Self {
+        Self {
+            total,
+            hosts,
+            ..Self::default()
+        }
+    }
+
+    /// Waits until a fetch from `host` is allowed to start, and takes its slot.
+    /// # Arguments
+    /// * `host` - The host of the remote, `None` if it has none, e.g. a local path.
+    /// # Returns
+    /// The permit that gives the slot back when dropped.
+    pub fn acquire(&self, host: Option<&str>) -> FetchPermit<'_> {
+        let limit = host.and_then(|host| {
+            let host = host.to_lowercase();
+            self.hosts
+                .iter()
+                .position(|limit| wildcard_match(&limit.host.to_lowercase(), &host))
+        });
+        let mut running = self.running.lock();
+        while self
+            .total
+            .is_some_and(|total| running.total >= total.max(1))
+            || limit.is_some_and(|index| {
+                running.hosts.get(&index).copied().unwrap_or_default()
+                    >= self.hosts[index].limit.max(1)
+            })
+        {
+            self.finished.wait(&mut running);
+        }
+        running.total += 1;
+        if let Some(index) = limit {
+            *running.hosts.entry(index).or_default() += 1;
+        }
+        drop(running);
+        FetchPermit {
+            limits: self,
+            host: limit,
+        }
+    }
+}
+
+impl Drop for FetchPermit<'_> {
+    fn drop(&mut self) {
+        let mut running = self.limits.running.lock();
+        running.total -= 1;
+        if let Some(index) = self.host
+            && let Some(count) = running.hosts.get_mut(&index)
+        {
+            *count -= 1;
+        }
+        drop(running);
+        self.limits.finished.notify_all();
+    }
+}
@@ -1,4 +1,7 @@
-use std::path::Path;
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use anyhow::Context as _;
 use log::LevelFilter;
@@ -9,6 +12,20 @@ use simplelog::{ColorChoice, ConfigBuilder, TermLogger, TerminalMode};
 /// # Errors
 /// Returns an error if logger initialization fails.
 pub fn initialize_logger() -> anyhow::Result<()> {
+    init_logger(TerminalMode::Mixed)
+}
+
+/// Initializes the logger to write everything to stderr, for when stdout is read by
+/// another program, as with `--pick`.
+///
+/// # Errors
+/// Returns an error if logger initialization fails.
+pub fn initialize_stderr_logger() -> anyhow::Result<()> {
+    init_logger(TerminalMode::Stderr)
+}
+
+/// Initializes the logger, writing to the terminal as `mode` says.
+fn init_logger(mode: TerminalMode) -> anyhow::Result<()> {
     TermLogger::init(
         #[cfg(debug_assertions)]
         LevelFilter::max(),
@@ -17,12 +34,173 @@ pub fn initialize_logger() -> anyhow::Result<()> {
         ConfigBuilder::new()
             .add_filter_allow_str("git_statuses")
             .build(),
-        TerminalMode::Mixed,
+        mode,
         ColorChoice::Auto,
     )
     .context("Failed to initialize logger")
 }
 
+/// Returns the seconds between the Unix epoch and `time`, or 0 for earlier times.
+pub fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+/// Formats seconds since the Unix epoch as a UTC date, e.g. `2021-03-04`.
+pub fn format_date(unix_seconds: i64) -> String {
+    // Howard Hinnant's `civil_from_days`, counting in eras of 400 years from 0000-03-01.
+    let days = unix_seconds.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Formats a size in bytes with binary units and one decimal, e.g. `1.5 GiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut unit = 0;
+    let mut divisor = 1_u64;
+    while bytes / divisor >= 1024 && unit < UNITS.len() - 1 {
+        divisor *= 1024;
+        unit += 1;
+    }
+    if unit == 0 {
+        return format!("{bytes} B");
+    }
+    // Tenths of the unit, rounded down like the whole units are.
+    let tenths = u128::from(bytes) * 10 / u128::from(divisor);
+    format!("{}.{} {}", tenths / 10, tenths % 10, UNITS[unit])
+}
+
+/// Shortens `text` to at most `max` characters by replacing its middle with `…`, keeping
+/// both ends of a path: the directory it is in and its name.
+pub fn truncate_middle(text: &str, max: usize) -> String {
+    let count = text.chars().count();
+    if count <= max {
+        return text.to_owned();
+    }
+    let kept = max.saturating_sub(1);
+    let tail = kept / 2;
+    let head = kept - tail;
+    let start: String = text.chars().take(head).collect();
+    let end: String = text.chars().skip(count - tail).collect();
+    format!("{start}…{end}")
+}
+
+/// The ASCII stand-ins `to_ascii` replaces the symbols of the output with.
+const ASCII_SYMBOLS: [(char, &str); 13] = [
+    ('↑', "^"),
+    ('↓', "v"),
+    ('→', "->"),
+    ('⎇', "@"),
+    ('…', "..."),
+    ('·', "-"),
+    ('•', "*"),
+    ('✚', "+"),
+    ('⚑', "$"),
+    // The lines of `--tree`, drawn like `tree --charset=ascii` does.
+    ('├', "|"),
+    ('└', "`"),
+    ('│', "|"),
+    ('─', "-"),
+];
+
+/// Replaces the arrows, box-drawing characters and other symbols git-statuses prints with
+/// ASCII, for `--ascii`. Other characters, e.g. of repository names, are kept.
+pub fn to_ascii(text: &str) -> String {
+    let mut ascii = String::with_capacity(text.len());
+    for c in text.chars() {
+        match ASCII_SYMBOLS.iter().find(|(symbol, _)| *symbol == c) {
+            Some((_, replacement)) => ascii.push_str(replacement),
+            None => ascii.push(c),
+        }
+    }
+    ascii
+}
+
+/// Whether the file systems of the platform usually ignore case, as they do on Windows and
+/// macOS. There, `~/Src/tool` and `~/src/tool` are the same repository.
+const CASE_INSENSITIVE: bool = cfg!(any(windows, target_os = "macos"));
+
+/// Resolves `path` to the form repository paths are compared and displayed in: absolute,
+/// with symlinks and `..` resolved, and without the `\\?\` prefix Windows adds to resolved
+/// paths. A path that cannot be resolved, e.g. because it does not exist, is kept as it is.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let resolved = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if cfg!(windows)
+        && let Some(local) = resolved.to_str().and_then(|p| p.strip_prefix(r"\\?\"))
+        && !local.starts_with(r"UNC\")
+    {
+        return PathBuf::from(local);
+    }
+    resolved
+}
+
+/// Returns the key under which two paths are the same repository: the normalized path,
+/// lowercased where file systems ignore case.
+pub fn path_key(path: &Path) -> String {
+    let key = normalize_path(path).to_string_lossy().into_owned();
+    if CASE_INSENSITIVE {
+        key.to_lowercase()
+    } else {
+        key
+    }
+}
+
+/// Strips `prefix` from `path` like `Path::strip_prefix`, but ignoring case where file
+/// systems do. Both paths should be normalized.
+pub fn strip_path_prefix<'a>(path: &'a Path, prefix: &Path) -> Option<&'a Path> {
+    if !CASE_INSENSITIVE {
+        return path.strip_prefix(prefix).ok();
+    }
+    let mut rest = path.components();
+    for component in prefix.components() {
+        let next = rest.next()?;
+        if next.as_os_str().to_string_lossy().to_lowercase()
+            != component.as_os_str().to_string_lossy().to_lowercase()
+        {
+            return None;
+        }
+    }
+    Some(rest.as_path())
+}
+
+/// Matches `text` against a pattern in which `*` stands for any run of characters,
+/// including none and including slashes. Everything else has to match literally.
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(first) = parts.next() else {
+        return text.is_empty();
+    };
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    // Without a star the pattern is a literal, and the prefix has to be all of the text.
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
 /// Extension trait for working with Git repository paths.
 pub trait GitPathExt {
     /// Checks if the path is a Git repository directory.
@@ -0,0 +1,178 @@
+#[cfg(feature = "fs-events")]
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::Path,
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+};
+use std::{path::PathBuf, thread, time::Instant};
+
+#[cfg(feature = "fs-events")]
+use notify::{EventKind, RecursiveMode, Watcher as _};
+
+use crate::gitinfo::repoinfo::RepoInfo;
+
+/// The files directly inside a git directory that change the status of the repository.
+#[cfg(feature = "fs-events")]
+const GIT_FILES: [&str; 3] = ["HEAD", "index", "packed-refs"];
+
+/// How long to wait for more changes once one was seen, as a single git command often
+/// writes several files.
+#[cfg(feature = "fs-events")]
+const SETTLE: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// Watches the HEAD, the refs and the index of repositories, so that a repository can be
+/// scanned again as soon as a commit, checkout, fetch or `git add` changes it.
+///
+/// Changes to the working tree alone are not seen, they are left to the next full rescan.
+/// If the platform cannot watch files, `wait` just sleeps until its deadline.
+#[cfg(feature = "fs-events")]
+pub struct GitWatcher {
+    watcher: Option<notify::RecommendedWatcher>,
+    events: Receiver<notify::Result<notify::Event>>,
+    /// The repository of every watched git directory.
+    git_dirs: HashMap<PathBuf, PathBuf>,
+    /// The repositories of every watched refs directory, shared by linked worktrees.
+    ref_dirs: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+#[cfg(feature = "fs-events")]
+impl GitWatcher {
+    /// Creates a watcher that watches no repository yet.
+    pub fn new() -> Self {
+        let (sender, events) = mpsc::channel();
+        let watcher = notify::recommended_watcher(sender)
+            .inspect_err(|e| log::warn!("Failed to watch for changes, only rescanning: {e}"))
+            .ok();
+        Self {
+            watcher,
+            events,
+            git_dirs: HashMap::new(),
+            ref_dirs: HashMap::new(),
+        }
+    }
+
+    /// Watches exactly the given repositories, starting to watch the new ones and
+    /// stopping to watch the ones that are gone.
+    pub fn watch(&mut self, repos: &[RepoInfo]) {
+        let Some(watcher) = &mut self.watcher else {
+            return;
+        };
+        let mut git_dirs = HashMap::new();
+        let mut ref_dirs: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+        for repo in repos {
+            let Ok(repository) = git2::Repository::open(&repo.path) else {
+                continue;
+            };
+            // libgit2 ends the git directory with a separator, which events do not.
+            let git_dir = repository.path().components().collect::<PathBuf>();
+            let refs = repository.commondir().join("refs").components().collect();
+            git_dirs.insert(git_dir, repo.path.clone());
+            ref_dirs.entry(refs).or_default().push(repo.path.clone());
+        }
+
+        for dir in self
+            .git_dirs
+            .keys()
+            .filter(|dir| !git_dirs.contains_key(*dir))
+        {
+            _ = watcher.unwatch(dir);
+        }
+        for dir in self
+            .ref_dirs
+            .keys()
+            .filter(|dir| !ref_dirs.contains_key(*dir))
+        {
+            _ = watcher.unwatch(dir);
+        }
+        let added_git_dirs = git_dirs
+            .keys()
+            .filter(|dir| !self.git_dirs.contains_key(*dir))
+            .map(|dir| (dir, RecursiveMode::NonRecursive));
+        let added_ref_dirs = ref_dirs
+            .keys()
+            .filter(|dir| !self.ref_dirs.contains_key(*dir))
+            .map(|dir| (dir, RecursiveMode::Recursive));
+        for (dir, mode) in added_git_dirs.chain(added_ref_dirs) {
+            if let Err(e) = watcher.watch(dir, mode) {
+                log::debug!("Failed to watch {}: {e}", dir.display());
+            }
+        }
+        self.git_dirs = git_dirs;
+        self.ref_dirs = ref_dirs;
+    }
+
+    /// Waits until a watched repository changes or `deadline` is reached.
+    ///
+    /// # Returns
+    /// The paths of the repositories that changed, sorted, or none if `deadline` was
+    /// reached first.
+    pub fn wait(&self, deadline: Instant) -> Vec<PathBuf> {
+        let mut changed = BTreeSet::new();
+        let mut until = deadline;
+        loop {
+            let timeout = until.saturating_duration_since(Instant::now());
+            match self.events.recv_timeout(timeout) {
+                // Reading a repository, as every scan does, must not count as a change.
+                Ok(Ok(event)) if !matches!(event.kind, EventKind::Access(_)) => {
+                    changed.extend(event.paths.iter().flat_map(|path| self.repos_of(path)));
+                    if !changed.is_empty() {
+                        until = until.min(Instant::now() + SETTLE);
+                    }
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => log::debug!("Failed to watch for changes: {e}"),
+                Err(RecvTimeoutError::Timeout) => return changed.into_iter().collect(),
+                // Nothing is watched, as the watcher could not be created.
+                Err(RecvTimeoutError::Disconnected) => {
+                    thread::sleep(timeout);
+                    return changed.into_iter().collect();
+                }
+            }
+        }
+    }
+
+    /// Returns the repositories a changed file belongs to.
+    fn repos_of(&self, path: &Path) -> Vec<PathBuf> {
+        let in_git_dir = path
+            .file_name()
+            .filter(|name| GIT_FILES.iter().any(|file| name == file))
+            .and_then(|_| path.parent())
+            .and_then(|dir| self.git_dirs.get(dir));
+        let in_refs = path
+            .ancestors()
+            .find_map(|dir| self.ref_dirs.get(dir))
+            .into_iter()
+            .flatten();
+        in_git_dir.into_iter().chain(in_refs).cloned().collect()
+    }
+}
+
+/// Stands in for the watcher without the `fs-events` feature: nothing is watched, and
+/// `wait` just sleeps until its deadline.
+#[cfg(not(feature = "fs-events"))]
+pub struct GitWatcher;
+
+#[cfg(not(feature = "fs-events"))]
+#[expect(
+    clippy::unused_self,
+    clippy::needless_pass_by_ref_mut,
+    reason = "keeps the signatures of the watcher of the `fs-events` feature"
+)]
+impl GitWatcher {
+    /// Creates a watcher that never sees a change.
+    pub const fn new() -> Self {
+        Self
+    }
+
+    /// Does nothing, as nothing can be watched.
+    pub const fn watch(&mut self, _repos: &[RepoInfo]) {}
+
+    /// Sleeps until `deadline`.
+    ///
+    /// # Returns
+    /// No repositories.
+    pub fn wait(&self, deadline: Instant) -> Vec<PathBuf> {
+        thread::sleep(deadline.saturating_duration_since(Instant::now()));
+        Vec::new()
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    fmt::Write as _,
+    io::Write as _,
+    process::{Command, Stdio},
+};
+
+use anyhow::Context as _;
+use clap::ValueEnum;
+use serde_json::{Value, json};
+
+use crate::{
+    gitinfo::{repoinfo::RepoInfo, status::Status},
+    printer::Summary,
+};
+
+/// The payload `--report-webhook` posts.
+#[derive(ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookFormat {
+    /// The `--summary` counts as JSON, under `summary`
+    #[default]
+    Json,
+    /// A Slack message with the counts in its `text`, which Mattermost and Discord's Slack
+    /// compatible webhooks take as well
+    Slack,
+}
+
+/// Builds the payload of `--report-webhook`.
+/// # Arguments
+/// * `repos` - Every repository scanned.
+/// * `failed` - Number of repositories that failed to process.
+/// * `format` - The kind of payload.
+/// * `list_repos` - Whether to list the repositories that are not clean, see
+///   `--webhook-repos`.
+/// # Returns
+/// The JSON to post.
+#[must_use]
+pub fn payload(
+    repos: &[RepoInfo],
+    failed: usize,
+    format: WebhookFormat,
+    list_repos: bool,
+) -> Value {
+    let summary = Summary::new(repos, failed);
+    let not_clean: Vec<&RepoInfo> = if list_repos {
+        repos.iter().filter(|r| r.status != Status::Clean).collect()
+    } else {
+        Vec::new()
+    };
+    match format {
+        WebhookFormat::Json => {
+            let mut payload = json!({ "summary": summary });
+            if list_repos {
+                payload["repositories"] = not_clean
+                    .iter()
+                    .map(|r| {
+                        json!({
+                            "directory": r.repo_path,
+                            "branch": r.branch,
+                            "status": r.status.to_string(),
+                            "ahead": r.ahead,
+                            "behind": r.behind,
+                        })
+                    })
+                    .collect();
+            }
+            payload
+        }
+        WebhookFormat::Slack => {
+            let mut text = format!(
+                "*git-statuses*: {} repositories, {} clean, {} with changes, {} with unpushed \
+                 commits ({} commits)",
+                summary.total, summary.clean, summary.dirty, summary.unpushed, summary.ahead
+            );
+            if failed > 0 {
+                let _ = write!(text, ", {failed} failed");
+            }
+            for repo in not_clean {
+                let _ = write!(
+                    text,
+                    "\n• `{}` ({}): {}, {}",
+                    repo.repo_path,
+                    repo.branch,
+                    repo.status,
+                    repo.format_local_status()
+                );
+            }
+            json!({ "text": text })
+        }
+    }
+}
+
+/// Posts `payload` to `url` with the curl executable, like `forge` queries forges. The
+/// request is handed over as a curl config on stdin, so that the URL, which for Slack is
+/// the secret, does not show up in the process list.
+/// # Errors
+/// Returns an error if curl cannot be run or the request fails.
+pub fn post(url: &str, payload: &Value) -> anyhow::Result<()> {
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location"])
+        .args(["--max-time", "30"])
+        .args(["--header", "Content-Type: application/json"])
+        .args(["--output", if cfg!(windows) { "NUL" } else { "/dev/null" }])
+        .args(["--config", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Failed to run curl")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(curl_config(url, &payload.to_string()).as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to post the report: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Builds the curl config that posts `body` to `url`.
+#[must_use]
+pub fn curl_config(url: &str, body: &str) -> String {
+    format!(
+        "url = {}\ndata-binary = {}\n",
+        config_string(url),
+        config_string(body)
+    )
+}
+
+/// Quotes a value for a curl config file, escaping what curl unescapes in quoted values.
+fn config_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
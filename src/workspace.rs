@@ -0,0 +1,141 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+use clap::ValueEnum;
+use serde_json::json;
+
+use crate::{gitinfo::repoinfo::RepoInfo, util};
+
+/// The editor `--emit-workspace` writes a workspace for.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Editor {
+    /// A VS Code `.code-workspace` file with one folder per repository
+    Code,
+    /// A Sublime Text `.sublime-project` file with one folder per repository
+    Sublime,
+    /// The `.idea/vcs.xml` of a JetBrains IDE, registering every repository as a Git root
+    Idea,
+}
+
+impl Editor {
+    /// Returns where the workspace is written when `--workspace-file` is not given: in
+    /// `dir`, named after it.
+    #[must_use]
+    pub fn default_path(self, dir: &Path) -> PathBuf {
+        let name = util::normalize_path(dir).file_name().map_or_else(
+            || "workspace".to_owned(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        match self {
+            Self::Code => dir.join(format!("{name}.code-workspace")),
+            Self::Sublime => dir.join(format!("{name}.sublime-project")),
+            Self::Idea => dir.join(".idea").join("vcs.xml"),
+        }
+    }
+
+    /// Returns the directory the paths in a workspace written to `file` are relative to.
+    fn base_dir(self, file: &Path) -> PathBuf {
+        let parent = match self {
+            Self::Code | Self::Sublime => file.parent(),
+            // `$PROJECT_DIR$` is the directory holding `.idea`.
+            Self::Idea => file.parent().and_then(Path::parent),
+        };
+        util::normalize_path(
+            parent
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .unwrap_or_else(|| Path::new(".")),
+        )
+    }
+}
+
+/// Renders the workspace of `editor` with the given repositories as folders.
+///
+/// Repositories inside the directory of the workspace are listed relative to it, so the
+/// workspace keeps working when the directory is moved, the others by their absolute path.
+/// # Arguments
+/// * `editor` - The editor to render the workspace for.
+/// * `repos` - The repositories to list, in this order.
+/// * `file` - Where the workspace is going to be written.
+/// # Returns
+/// The content of the workspace file.
+#[must_use]
+pub fn render(editor: Editor, repos: &[RepoInfo], file: &Path) -> String {
+    let base = editor.base_dir(file);
+    let folders = repos.iter().map(|repo| {
+        let path = util::normalize_path(&repo.path);
+        let relative = util::strip_path_prefix(&path, &base).map(|relative| {
+            if relative.as_os_str().is_empty() {
+                PathBuf::from(".")
+            } else {
+                relative.to_path_buf()
+            }
+        });
+        (repo, relative, path)
+    });
+    match editor {
+        Editor::Code | Editor::Sublime => {
+            let folders: Vec<_> = folders
+                .map(|(repo, relative, path)| {
+                    json!({
+                        "name": repo.repo_path,
+                        "path": relative.unwrap_or(path).to_string_lossy(),
+                    })
+                })
+                .collect();
+            format!("{:#}\n", json!({ "folders": folders }))
+        }
+        Editor::Idea => {
+            let mut xml = String::from(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<project version=\"4\">\n  \
+                 <component name=\"VcsDirectoryMappings\">\n",
+            );
+            for (_, relative, path) in folders {
+                let directory = relative.map_or_else(
+                    || path.to_string_lossy().into_owned(),
+                    |relative| {
+                        if relative == Path::new(".") {
+                            "$PROJECT_DIR$".to_owned()
+                        } else {
+                            format!(
+                                "$PROJECT_DIR$/{}",
+                                relative.to_string_lossy().replace('\\', "/")
+                            )
+                        }
+                    },
+                );
+                let _ = writeln!(
+                    xml,
+                    "    <mapping directory=\"{}\" vcs=\"Git\" />",
+                    attribute_escape(&directory)
+                );
+            }
+            xml.push_str("  </component>\n</project>\n");
+            xml
+        }
+    }
+}
+
+/// Writes the workspace of `editor` with the given repositories to `file`, creating the
+/// directory it is in if needed.
+///
+/// # Errors
+/// Returns an error if the file cannot be written.
+pub fn write(editor: Editor, repos: &[RepoInfo], file: &Path) -> anyhow::Result<()> {
+    if let Some(dir) = file.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    fs::write(file, render(editor, repos, file))
+        .with_context(|| format!("Failed to write {}", file.display()))
+}
+
+/// Escapes the characters that are not allowed verbatim in a double-quoted XML attribute.
+fn attribute_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}